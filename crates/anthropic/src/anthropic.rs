@@ -148,6 +148,11 @@ impl Model {
         }
     }
 
+    /// Whether the model accepts a thinking budget and can reason before it answers.
+    pub fn supports_thinking(&self) -> bool {
+        self.id().starts_with("claude-3-7")
+    }
+
     pub const DEFAULT_BETA_HEADERS: &[&str] = &["prompt-caching-2024-07-31"];
 
     pub fn beta_headers(&self) -> String {
@@ -399,7 +404,9 @@ pub async fn extract_tool_args_from_events(
         let result = match event {
             Err(error) => Some(Err(error)),
             Ok(Event::ContentBlockDelta { index, delta }) => match delta {
-                ContentDelta::TextDelta { .. } => None,
+                ContentDelta::TextDelta { .. }
+                | ContentDelta::ThinkingDelta { .. }
+                | ContentDelta::SignatureDelta { .. } => None,
                 ContentDelta::InputJsonDelta { partial_json } => {
                     if index == tool_use_index {
                         Some(Ok(partial_json))
@@ -471,6 +478,8 @@ pub enum RequestContent {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -478,6 +487,10 @@ pub enum RequestContent {
 pub enum ResponseContent {
     #[serde(rename = "text")]
     Text { text: String },
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "tool_use")]
     ToolUse {
         id: String,
@@ -530,6 +543,16 @@ pub struct Request {
     pub top_k: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<Thinking>,
+}
+
+/// Whether the model thinks before answering, and how many of its output tokens it may
+/// spend on it.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Thinking {
+    Enabled { budget_tokens: u32 },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -602,6 +625,10 @@ pub enum ContentDelta {
     TextDelta { text: String },
     #[serde(rename = "input_json_delta")]
     InputJsonDelta { partial_json: String },
+    #[serde(rename = "thinking_delta")]
+    ThinkingDelta { thinking: String },
+    #[serde(rename = "signature_delta")]
+    SignatureDelta { signature: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -2942,6 +2942,8 @@ impl CodegenAlternative {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            thinking_budget: None,
+            max_tokens: None,
         })
     }
 
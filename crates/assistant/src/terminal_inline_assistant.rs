@@ -296,6 +296,8 @@ impl TerminalInlineAssistant {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            thinking_budget: None,
+            max_tokens: None,
         })
     }
 
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use anyhow::anyhow;
+use assistant_settings::{AssistantSettings, ToolApproval};
 use assistant_tool::ToolWorkingSet;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
+use editor::Editor;
 use gpui::{
     list, AbsoluteLength, AnyElement, App, DefiniteLength, EdgesRefinement, Empty, Entity, Length,
-    ListAlignment, ListOffset, ListState, StyleRefinement, Subscription, TextStyleRefinement,
+    ListAlignment, ListOffset, ListState, StyleRefinement, Subscription, Task, TextStyleRefinement,
     UnderlineStyle, WeakEntity,
 };
 use language::LanguageRegistry;
@@ -12,12 +15,14 @@ use language_model::Role;
 use markdown::{Markdown, MarkdownStyle};
 use settings::Settings as _;
 use theme::ThemeSettings;
-use ui::prelude::*;
+use ui::{prelude::*, Disclosure, Tooltip};
 use workspace::Workspace;
 
-use crate::thread::{MessageId, Thread, ThreadError, ThreadEvent};
+use crate::checkpoint;
+use crate::thread::{MessageId, PendingToolUse, Thread, ThreadError, ThreadEvent};
 use crate::thread_store::ThreadStore;
 use crate::ui::ContextPill;
+use crate::AssistantPanel;
 
 pub struct ActiveThread {
     workspace: WeakEntity<Workspace>,
@@ -28,7 +33,12 @@ pub struct ActiveThread {
     messages: Vec<MessageId>,
     list_state: ListState,
     rendered_messages_by_id: HashMap<MessageId, Entity<Markdown>>,
+    rendered_thinking_by_id: HashMap<MessageId, Entity<Markdown>>,
+    /// The Assistant messages whose thinking is shown.
+    expanded_thinking: HashSet<MessageId>,
     last_error: Option<ThreadError>,
+    /// The User message being edited, and the editor for its new text.
+    editing_message: Option<(MessageId, Entity<Editor>)>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -55,6 +65,8 @@ impl ActiveThread {
             thread: thread.clone(),
             messages: Vec::new(),
             rendered_messages_by_id: HashMap::default(),
+            rendered_thinking_by_id: HashMap::default(),
+            expanded_thinking: HashSet::default(),
             list_state: ListState::new(0, ListAlignment::Bottom, px(1024.), {
                 let this = cx.entity().downgrade();
                 move |ix, _: &mut Window, cx: &mut App| {
@@ -63,11 +75,12 @@ impl ActiveThread {
                 }
             }),
             last_error: None,
+            editing_message: None,
             _subscriptions: subscriptions,
         };
 
         for message in thread.read(cx).messages().cloned().collect::<Vec<_>>() {
-            this.push_message(&message.id, message.text.clone(), window, cx);
+            this.push_message(&message.id, message.text, message.thinking, window, cx);
         }
 
         this
@@ -107,6 +120,7 @@ impl ActiveThread {
         &mut self,
         id: &MessageId,
         text: String,
+        thinking: String,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
@@ -114,6 +128,39 @@ impl ActiveThread {
         self.messages.push(*id);
         self.list_state.splice(old_len..old_len, 1);
 
+        let markdown = self.render_markdown(text, Color::Default, window, cx);
+        self.rendered_messages_by_id.insert(*id, markdown);
+        if !thinking.is_empty() {
+            let thinking = self.render_markdown(thinking, Color::Muted, window, cx);
+            self.rendered_thinking_by_id.insert(*id, thinking);
+        }
+        self.list_state.scroll_to(ListOffset {
+            item_ix: old_len,
+            offset_in_item: Pixels(0.0),
+        });
+    }
+
+    fn render_markdown(
+        &self,
+        text: String,
+        text_color: Color,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<Markdown> {
+        let markdown_style = Self::markdown_style(text_color, window, cx);
+        cx.new(|cx| {
+            Markdown::new(
+                text,
+                markdown_style,
+                Some(self.language_registry.clone()),
+                None,
+                window,
+                cx,
+            )
+        })
+    }
+
+    fn markdown_style(text_color: Color, window: &Window, cx: &App) -> MarkdownStyle {
         let theme_settings = ThemeSettings::get_global(cx);
         let colors = cx.theme().colors();
         let ui_font_size = TextSize::Default.rems(cx);
@@ -123,11 +170,11 @@ impl ActiveThread {
         text_style.refine(&TextStyleRefinement {
             font_family: Some(theme_settings.ui_font.family.clone()),
             font_size: Some(ui_font_size.into()),
-            color: Some(cx.theme().colors().text),
+            color: Some(text_color.color(cx)),
             ..Default::default()
         });
 
-        let markdown_style = MarkdownStyle {
+        MarkdownStyle {
             base_text_style: text_style,
             syntax: cx.theme().syntax().clone(),
             selection_background_color: cx.theme().players().local().selection,
@@ -175,23 +222,7 @@ impl ActiveThread {
                 ..Default::default()
             },
             ..Default::default()
-        };
-
-        let markdown = cx.new(|cx| {
-            Markdown::new(
-                text,
-                markdown_style,
-                Some(self.language_registry.clone()),
-                None,
-                window,
-                cx,
-            )
-        });
-        self.rendered_messages_by_id.insert(*id, markdown);
-        self.list_state.scroll_to(ListOffset {
-            item_ix: old_len,
-            offset_in_item: Pixels(0.0),
-        });
+        }
     }
 
     fn handle_thread_event(
@@ -219,14 +250,19 @@ impl ActiveThread {
                     });
                 }
             }
+            ThreadEvent::StreamedAssistantThinking(message_id, text) => {
+                if let Some(markdown) = self.rendered_thinking_by_id.get_mut(&message_id) {
+                    markdown.update(cx, |markdown, cx| {
+                        markdown.append(text, window, cx);
+                    });
+                } else {
+                    let markdown = self.render_markdown(text.clone(), Color::Muted, window, cx);
+                    self.rendered_thinking_by_id.insert(*message_id, markdown);
+                }
+            }
             ThreadEvent::MessageAdded(message_id) => {
-                if let Some(message_text) = self
-                    .thread
-                    .read(cx)
-                    .message(*message_id)
-                    .map(|message| message.text.clone())
-                {
-                    self.push_message(message_id, message_text, window, cx);
+                if let Some(message) = self.thread.read(cx).message(*message_id).cloned() {
+                    self.push_message(message_id, message.text, message.thinking, window, cx);
                 }
 
                 self.thread_store
@@ -237,6 +273,25 @@ impl ActiveThread {
 
                 cx.notify();
             }
+            ThreadEvent::BranchChanged => {
+                self.editing_message.take();
+                self.messages.clear();
+                self.rendered_messages_by_id.clear();
+                self.rendered_thinking_by_id.clear();
+                self.list_state.reset(0);
+                for message in self.thread.read(cx).messages().cloned().collect::<Vec<_>>() {
+                    self.push_message(&message.id, message.text, message.thinking, window, cx);
+                }
+
+                self.thread_store
+                    .update(cx, |thread_store, cx| {
+                        thread_store.save_thread(&self.thread, cx)
+                    })
+                    .detach_and_log_err(cx);
+
+                cx.notify();
+            }
+            ThreadEvent::MessageEdited(_) => {}
             ThreadEvent::UsePendingTools => {
                 let pending_tool_uses = self
                     .thread
@@ -248,17 +303,12 @@ impl ActiveThread {
                     .collect::<Vec<_>>();
 
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), window, cx);
-
-                        self.thread.update(cx, |thread, cx| {
-                            thread.insert_tool_output(
-                                tool_use.assistant_message_id,
-                                tool_use.id.clone(),
-                                task,
-                                cx,
-                            );
-                        });
+                    match AssistantSettings::get_global(cx).tool_approval(&tool_use.name) {
+                        ToolApproval::Allow => self.run_tool_use(tool_use, window, cx),
+                        ToolApproval::Ask => self.thread.update(cx, |thread, cx| {
+                            thread.request_tool_use_confirmation(&tool_use.id, cx)
+                        }),
+                        ToolApproval::Deny => self.deny_tool_use(tool_use, cx),
                     }
                 }
             }
@@ -266,9 +316,341 @@ impl ActiveThread {
         }
     }
 
+    fn run_tool_use(
+        &mut self,
+        tool_use: PendingToolUse,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+
+        // Save the files the tool is about to modify first, so that its changes can be
+        // rolled back.
+        let modified_paths = tool.modified_paths(&tool_use.input, project.read(cx), cx);
+        let task = if tool.may_modify_any_file() {
+            let snapshot = checkpoint::snapshot_project_files(&project, cx);
+            let thread = self.thread.downgrade();
+            let workspace = self.workspace.clone();
+            let message_id = tool_use.assistant_message_id;
+            let input = tool_use.input;
+            cx.spawn_in(window, |_, mut cx| async move {
+                let snapshot = snapshot.await;
+                let output = cx
+                    .update(|window, cx| tool.run(input, workspace, window, cx))?
+                    .await;
+                cx.update(|_, cx| {
+                    checkpoint::record_snapshot_edits(thread, message_id, snapshot, &project, cx)
+                })?
+                .await?;
+                output
+            })
+        } else if modified_paths.is_empty() {
+            tool.run(tool_use.input, self.workspace.clone(), window, cx)
+        } else {
+            let capture = checkpoint::capture_checkpoint(
+                &self.thread,
+                tool_use.assistant_message_id,
+                modified_paths.clone(),
+                &project,
+                cx,
+            );
+            let thread = self.thread.downgrade();
+            let workspace = self.workspace.clone();
+            let input = tool_use.input;
+            cx.spawn_in(window, |_, mut cx| async move {
+                capture.await?;
+                let output = cx
+                    .update(|window, cx| tool.run(input, workspace, window, cx))?
+                    .await;
+                cx.update(|_, cx| {
+                    checkpoint::record_tool_edits(thread, modified_paths, &project, cx)
+                })?
+                .await?;
+                output
+            })
+        };
+
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(tool_use.assistant_message_id, tool_use.id, task, cx);
+        });
+    }
+
+    fn restore_checkpoint(
+        &mut self,
+        message_id: MessageId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+        let thread = self.thread.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            checkpoint::restore_checkpoint(thread, message_id, project, &mut cx).await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn start_editing_message(
+        &mut self,
+        message_id: MessageId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(message) = self.thread.read(cx).message(message_id) else {
+            return;
+        };
+        let text = message.text.clone();
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::auto_height(10, window, cx);
+            editor.set_text(text, window, cx);
+            editor.set_show_indent_guides(false, cx);
+            editor
+        });
+        editor.focus_handle(cx).focus(window);
+        self.editing_message = Some((message_id, editor));
+        cx.notify();
+    }
+
+    fn cancel_editing_message(&mut self, cx: &mut Context<Self>) {
+        self.editing_message.take();
+        cx.notify();
+    }
+
+    /// Adds the edited message in a new branch of the thread, which is then regenerated
+    /// from it.
+    fn confirm_editing_message(&mut self, cx: &mut Context<Self>) {
+        let Some((message_id, editor)) = self.editing_message.as_ref() else {
+            return;
+        };
+        let text = editor.read(cx).text(cx);
+        if text.trim().is_empty() {
+            return;
+        }
+
+        let message_id = *message_id;
+        self.editing_message.take();
+        self.last_error.take();
+        self.thread.update(cx, |thread, cx| {
+            thread.edit_user_message(message_id, text, cx);
+        });
+    }
+
+    fn fork_thread(&mut self, message_id: MessageId, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(panel) = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).panel::<AssistantPanel>(cx))
+        else {
+            return;
+        };
+        let thread = self.thread_store.update(cx, |thread_store, cx| {
+            thread_store.fork_thread(&self.thread, message_id, cx)
+        });
+
+        // The panel replaces this view when it switches threads, so wait until we're done
+        // updating it.
+        window.defer(cx, move |window, cx| {
+            panel.update(cx, |panel, cx| panel.set_active_thread(thread, window, cx));
+        });
+    }
+
+    fn render_branch_navigation(
+        &self,
+        message_id: MessageId,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let thread = self.thread.read(cx);
+        let siblings = thread.siblings(message_id);
+        if siblings.len() < 2 {
+            return None;
+        }
+        let ix = siblings.iter().position(|id| *id == message_id)?;
+        let previous = ix.checked_sub(1).map(|ix| siblings[ix]);
+        let next = siblings.get(ix + 1).copied();
+        let is_streaming = thread.is_streaming();
+
+        Some(
+            h_flex()
+                .child(
+                    IconButton::new(
+                        SharedString::from(format!("previous-branch-{message_id:?}")),
+                        IconName::ChevronLeft,
+                    )
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(Color::Muted)
+                    .disabled(previous.is_none() || is_streaming)
+                    .tooltip(Tooltip::text("Previous Branch"))
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        if let Some(previous) = previous {
+                            this.thread
+                                .update(cx, |thread, cx| thread.select_branch(previous, cx));
+                        }
+                    })),
+                )
+                .child(
+                    Label::new(format!("{}/{}", ix + 1, siblings.len()))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    IconButton::new(
+                        SharedString::from(format!("next-branch-{message_id:?}")),
+                        IconName::ChevronRight,
+                    )
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(Color::Muted)
+                    .disabled(next.is_none() || is_streaming)
+                    .tooltip(Tooltip::text("Next Branch"))
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        if let Some(next) = next {
+                            this.thread
+                                .update(cx, |thread, cx| thread.select_branch(next, cx));
+                        }
+                    })),
+                ),
+        )
+    }
+
+    fn render_fork_button(&self, message_id: MessageId, cx: &mut Context<Self>) -> IconButton {
+        IconButton::new(
+            SharedString::from(format!("fork-thread-{message_id:?}")),
+            IconName::GitBranch,
+        )
+        .icon_size(IconSize::XSmall)
+        .icon_color(Color::Muted)
+        .tooltip(Tooltip::text("Fork Thread From Here"))
+        .on_click(cx.listener(move |this, _, window, cx| {
+            this.fork_thread(message_id, window, cx);
+        }))
+    }
+
+    fn deny_tool_use(&mut self, tool_use: PendingToolUse, cx: &mut App) {
+        let output = Task::ready(Err(anyhow!(
+            "The user didn't allow the {} tool to run.",
+            tool_use.name
+        )));
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(tool_use.assistant_message_id, tool_use.id, output, cx);
+        });
+    }
+
+    fn render_tool_use_confirmation(
+        &self,
+        tool_use: PendingToolUse,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let allow_tool_use = tool_use.clone();
+
+        h_flex()
+            .mx_2p5()
+            .mb_2()
+            .py_1()
+            .px_2()
+            .gap_2()
+            .justify_between()
+            .rounded_md()
+            .border_1()
+            .border_color(colors.border)
+            .child(
+                h_flex()
+                    .gap_1p5()
+                    .child(
+                        Icon::new(IconName::Warning)
+                            .size(IconSize::XSmall)
+                            .color(Color::Warning),
+                    )
+                    .child(
+                        Label::new(format!("Allow the {} tool to run?", tool_use.name))
+                            .size(LabelSize::Small),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("deny-tool-use-{}", tool_use.id)),
+                            "Deny",
+                        )
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(
+                            move |this, _, _window, cx| {
+                                this.deny_tool_use(tool_use.clone(), cx);
+                            },
+                        )),
+                    )
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("allow-tool-use-{}", allow_tool_use.id)),
+                            "Allow",
+                        )
+                        .label_size(LabelSize::Small)
+                        .style(ButtonStyle::Filled)
+                        .on_click(cx.listener(
+                            move |this, _, window, cx| {
+                                this.run_tool_use(allow_tool_use.clone(), window, cx);
+                            },
+                        )),
+                    ),
+            )
+    }
+
+    fn render_thinking(
+        &self,
+        ix: usize,
+        message_id: MessageId,
+        cx: &mut Context<Self>,
+    ) -> Option<AnyElement> {
+        let thinking = self.rendered_thinking_by_id.get(&message_id)?;
+        let is_expanded = self.expanded_thinking.contains(&message_id);
+
+        Some(
+            v_flex()
+                .px_2p5()
+                .pt_1()
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Disclosure::new(("thinking-disclosure", ix), is_expanded).on_click(
+                                cx.listener(move |this, _, _window, cx| {
+                                    if !this.expanded_thinking.remove(&message_id) {
+                                        this.expanded_thinking.insert(message_id);
+                                    }
+                                    cx.notify();
+                                }),
+                            ),
+                        )
+                        .child(
+                            Label::new("Thinking")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .when(is_expanded, |parent| {
+                    parent.child(div().pl_5().text_ui_sm(cx).child(thinking.clone()))
+                })
+                .into_any_element(),
+        )
+    }
+
     fn render_message(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
         let message_id = self.messages[ix];
-        let Some(message) = self.thread.read(cx).message(message_id) else {
+        let Some(role) = self
+            .thread
+            .read(cx)
+            .message(message_id)
+            .map(|message| message.role)
+        else {
             return Empty.into_any();
         };
 
@@ -277,10 +659,70 @@ impl ActiveThread {
         };
 
         let context = self.thread.read(cx).context_for_message(message_id);
-        let colors = cx.theme().colors();
+        let image_count = self.thread.read(cx).images_for_message(message_id).len();
+        let message_editor = self
+            .editing_message
+            .as_ref()
+            .filter(|(id, _)| *id == message_id)
+            .map(|(_, editor)| editor.clone());
 
         let message_content = v_flex()
-            .child(div().p_2p5().text_ui(cx).child(markdown.clone()))
+            .children(self.render_thinking(ix, message_id, cx))
+            .map(|parent| match message_editor {
+                Some(editor) => parent.child(
+                    v_flex()
+                        .p_2p5()
+                        .gap_2()
+                        .text_ui(cx)
+                        .child(editor)
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .justify_end()
+                                .child(
+                                    Button::new(("cancel-edit-message", ix), "Cancel")
+                                        .label_size(LabelSize::Small)
+                                        .on_click(cx.listener(|this, _, _window, cx| {
+                                            this.cancel_editing_message(cx);
+                                        })),
+                                )
+                                .child(
+                                    Button::new(("confirm-edit-message", ix), "Regenerate")
+                                        .label_size(LabelSize::Small)
+                                        .style(ButtonStyle::Filled)
+                                        .tooltip(Tooltip::text(
+                                            "Continue from the edited message, keeping the original in another branch",
+                                        ))
+                                        .on_click(cx.listener(|this, _, _window, cx| {
+                                            this.confirm_editing_message(cx);
+                                        })),
+                                ),
+                        ),
+                ),
+                None => parent.child(div().p_2p5().text_ui(cx).child(markdown.clone())),
+            })
+            .when(image_count > 0, |parent| {
+                parent.child(
+                    h_flex()
+                        .gap_1()
+                        .px_2p5()
+                        .pb_1p5()
+                        .child(
+                            Icon::new(IconName::FileGeneric)
+                                .size(IconSize::XSmall)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            Label::new(if image_count == 1 {
+                                "1 image".to_string()
+                            } else {
+                                format!("{image_count} images")
+                            })
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        ),
+                )
+            })
             .when_some(context, |parent, context| {
                 if !context.is_empty() {
                     parent.child(
@@ -295,7 +737,24 @@ impl ActiveThread {
                 }
             });
 
-        let styled_message = match message.role {
+        let user_message_controls = (role == Role::User).then(|| {
+            h_flex()
+                .gap_1()
+                .children(self.render_branch_navigation(message_id, cx))
+                .child(
+                    IconButton::new(("edit-message", ix), IconName::Pencil)
+                        .icon_size(IconSize::XSmall)
+                        .icon_color(Color::Muted)
+                        .tooltip(Tooltip::text("Edit Message"))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.start_editing_message(message_id, window, cx);
+                        })),
+                )
+                .child(self.render_fork_button(message_id, cx))
+        });
+
+        let colors = cx.theme().colors();
+        let styled_message = match role {
             Role::User => v_flex()
                 .id(("message-container", ix))
                 .pt_2p5()
@@ -329,11 +788,58 @@ impl ActiveThread {
                                                 .size(LabelSize::Small)
                                                 .color(Color::Muted),
                                         ),
-                                ),
+                                )
+                                .children(user_message_controls),
                         )
                         .child(message_content),
                 ),
-            Role::Assistant => div().id(("message-container", ix)).child(message_content),
+            Role::Assistant => {
+                let tool_uses_needing_confirmation = self
+                    .thread
+                    .read(cx)
+                    .pending_tool_uses()
+                    .into_iter()
+                    .filter(|tool_use| {
+                        tool_use.assistant_message_id == message_id
+                            && tool_use.status.needs_confirmation()
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let has_checkpoint = self.thread.read(cx).has_checkpoint(message_id);
+
+                div()
+                    .id(("message-container", ix))
+                    .child(message_content)
+                    .children(
+                        tool_uses_needing_confirmation
+                            .into_iter()
+                            .map(|tool_use| self.render_tool_use_confirmation(tool_use, cx)),
+                    )
+                    .child(
+                        h_flex()
+                            .px_2p5()
+                            .pb_2()
+                            .gap_1()
+                            .when(has_checkpoint, |parent| {
+                                parent.child(
+                                    Button::new(("restore-checkpoint", ix), "Restore to Here")
+                                        .icon(IconName::Undo)
+                                        .icon_position(IconPosition::Start)
+                                        .icon_size(IconSize::XSmall)
+                                        .icon_color(Color::Muted)
+                                        .label_size(LabelSize::Small)
+                                        .tooltip(Tooltip::text(
+                                            "Undo the changes the assistant made to files from this message on",
+                                        ))
+                                        .on_click(cx.listener(move |this, _, window, cx| {
+                                            this.restore_checkpoint(message_id, window, cx);
+                                        })),
+                                )
+                            })
+                            .child(self.render_fork_button(message_id, cx)),
+                    )
+            }
             Role::System => div().id(("message-container", ix)).py_1().px_2().child(
                 v_flex()
                     .bg(colors.editor_background)
@@ -3,6 +3,7 @@ mod assistant_configuration;
 mod assistant_model_selector;
 mod assistant_panel;
 mod buffer_codegen;
+mod checkpoint;
 mod context;
 mod context_picker;
 mod context_store;
@@ -10,6 +11,7 @@ mod context_strip;
 mod inline_assistant;
 mod inline_prompt_editor;
 mod message_editor;
+mod project_rules;
 mod terminal_codegen;
 mod terminal_inline_assistant;
 mod thread;
@@ -52,7 +54,10 @@ actions!(
         FocusLeft,
         FocusRight,
         RemoveFocusedContext,
-        AcceptSuggestedContext
+        AcceptSuggestedContext,
+        ExportThreadAsMarkdown,
+        ExportThreadAsJson,
+        ImportThread
     ]
 );
 
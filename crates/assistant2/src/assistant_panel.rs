@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use assistant_context_editor::{
     make_lsp_adapter_delegate, AssistantPanelDelegate, ConfigurationError, ContextEditor,
     ContextHistory, SlashCommandCompletionProvider,
@@ -15,7 +15,8 @@ use editor::Editor;
 use fs::Fs;
 use gpui::{
     prelude::*, px, svg, Action, AnyElement, App, AsyncWindowContext, Corner, Entity, EventEmitter,
-    FocusHandle, Focusable, FontWeight, Pixels, Subscription, Task, UpdateGlobal, WeakEntity,
+    FocusHandle, Focusable, FontWeight, PathPromptOptions, Pixels, Subscription, Task,
+    UpdateGlobal, WeakEntity,
 };
 use language::LanguageRegistry;
 use language_model::{LanguageModelProviderTosView, LanguageModelRegistry};
@@ -26,6 +27,7 @@ use time::UtcOffset;
 use ui::{prelude::*, ContextMenu, KeyBinding, PopoverMenu, PopoverMenuHandle, Tab, Tooltip};
 use util::ResultExt as _;
 use workspace::dock::{DockPosition, Panel, PanelEvent};
+use workspace::notifications::NotifyTaskExt as _;
 use workspace::Workspace;
 use zed_actions::assistant::{DeployPromptLibrary, ToggleFocus};
 
@@ -34,10 +36,10 @@ use crate::assistant_configuration::{AssistantConfiguration, AssistantConfigurat
 use crate::message_editor::MessageEditor;
 use crate::thread::{Thread, ThreadError, ThreadId};
 use crate::thread_history::{PastThread, ThreadHistory};
-use crate::thread_store::ThreadStore;
+use crate::thread_store::{ExportedThread, ThreadStore};
 use crate::{
-    InlineAssistant, NewPromptEditor, NewThread, OpenConfiguration, OpenHistory,
-    OpenPromptEditorHistory,
+    ExportThreadAsJson, ExportThreadAsMarkdown, ImportThread, InlineAssistant, NewPromptEditor,
+    NewThread, OpenConfiguration, OpenHistory, OpenPromptEditorHistory,
 };
 
 pub fn init(cx: &mut App) {
@@ -73,12 +75,47 @@ pub fn init(cx: &mut App) {
                         workspace.focus_panel::<AssistantPanel>(window, cx);
                         panel.update(cx, |panel, cx| panel.open_configuration(window, cx));
                     }
+                })
+                .register_action(|workspace, _: &ExportThreadAsMarkdown, window, cx| {
+                    if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.export_thread(ExportFormat::Markdown, window, cx)
+                        });
+                    }
+                })
+                .register_action(|workspace, _: &ExportThreadAsJson, window, cx| {
+                    if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.export_thread(ExportFormat::Json, window, cx)
+                        });
+                    }
+                })
+                .register_action(|workspace, _: &ImportThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
+                        workspace.focus_panel::<AssistantPanel>(window, cx);
+                        panel.update(cx, |panel, cx| panel.import_thread(window, cx));
+                    }
                 });
         },
     )
     .detach();
 }
 
+#[derive(Debug, Clone, Copy)]
+enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
 enum ActiveView {
     Thread,
     PromptEditor,
@@ -246,7 +283,15 @@ impl AssistantPanel {
         let thread = self
             .thread_store
             .update(cx, |this, cx| this.create_thread(cx));
+        self.set_active_thread(thread, window, cx);
+    }
 
+    pub(crate) fn set_active_thread(
+        &mut self,
+        thread: Entity<Thread>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         self.active_view = ActiveView::Thread;
         self.thread = cx.new(|cx| {
             ActiveThread::new(
@@ -270,6 +315,7 @@ impl AssistantPanel {
             )
         });
         self.message_editor.focus_handle(cx).focus(window);
+        cx.notify();
     }
 
     fn new_prompt_editor(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -398,33 +444,81 @@ impl AssistantPanel {
         cx.spawn_in(window, |this, mut cx| async move {
             let thread = open_thread_task.await?;
             this.update_in(&mut cx, |this, window, cx| {
-                this.active_view = ActiveView::Thread;
-                this.thread = cx.new(|cx| {
-                    ActiveThread::new(
-                        thread.clone(),
-                        this.thread_store.clone(),
-                        this.workspace.clone(),
-                        this.language_registry.clone(),
-                        this.tools.clone(),
-                        window,
-                        cx,
-                    )
-                });
-                this.message_editor = cx.new(|cx| {
-                    MessageEditor::new(
-                        this.fs.clone(),
-                        this.workspace.clone(),
-                        this.thread_store.downgrade(),
-                        thread,
-                        window,
-                        cx,
-                    )
-                });
-                this.message_editor.focus_handle(cx).focus(window);
+                this.set_active_thread(thread, window, cx);
             })
         })
     }
 
+    /// Writes the active thread or prompt editor to a file of the user's choosing.
+    fn export_thread(&mut self, format: ExportFormat, window: &mut Window, cx: &mut Context<Self>) {
+        let exported = match self.active_view {
+            ActiveView::Thread => self.active_thread(cx).read(cx).to_exported(),
+            ActiveView::PromptEditor => {
+                let Some(context_editor) = self.context_editor.as_ref() else {
+                    return;
+                };
+                ExportedThread::from_context(context_editor.read(cx).context().read(cx), cx)
+            }
+            ActiveView::History | ActiveView::PromptEditorHistory | ActiveView::Configuration => {
+                return;
+            }
+        };
+
+        let directory = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .and_then(|worktree| Some(worktree.read(cx).as_local()?.abs_path().to_path_buf()))
+            .unwrap_or_else(|| util::paths::home_dir().clone());
+        let path = cx.prompt_for_new_path(&directory);
+        let fs = self.fs.clone();
+
+        cx.spawn_in(window, |_this, _cx| async move {
+            let Some(mut path) = path.await?? else {
+                return Ok(());
+            };
+            if path.extension().is_none() {
+                path.set_extension(format.extension());
+            }
+
+            let text = match format {
+                ExportFormat::Markdown => exported.to_markdown(),
+                ExportFormat::Json => serde_json::to_string_pretty(&exported)?,
+            };
+            fs.atomic_write(path, text).await
+        })
+        .detach_and_notify_err(window, cx);
+    }
+
+    /// Opens a thread exported as JSON as a new thread.
+    fn import_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        });
+        let fs = self.fs.clone();
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return Ok(());
+            };
+            let text = fs.load(&path).await?;
+            let exported = serde_json::from_str::<ExportedThread>(&text)
+                .with_context(|| format!("failed to parse exported thread {path:?}"))?;
+
+            this.update_in(&mut cx, |this, window, cx| {
+                let thread = this.thread_store.update(cx, |thread_store, cx| {
+                    thread_store.import_thread(exported, cx)
+                })?;
+                this.set_active_thread(thread, window, cx);
+                anyhow::Ok(())
+            })?
+        })
+        .detach_and_notify_err(window, cx);
+    }
+
     pub(crate) fn open_configuration(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.active_view = ActiveView::Configuration;
         self.configuration = Some(cx.new(|cx| AssistantConfiguration::new(window, cx)));
@@ -672,6 +766,8 @@ impl AssistantPanel {
                                 Some(ContextMenu::build(window, cx, |menu, _window, _cx| {
                                     menu.action("New Thread", NewThread.boxed_clone())
                                         .action("New Prompt Editor", NewPromptEditor.boxed_clone())
+                                        .separator()
+                                        .action("Import Thread…", ImportThread.boxed_clone())
                                 }))
                             }),
                     )
@@ -695,6 +791,40 @@ impl AssistantPanel {
                                 }))
                             }),
                     )
+                    .when(
+                        matches!(
+                            self.active_view,
+                            ActiveView::Thread | ActiveView::PromptEditor
+                        ),
+                        |this| {
+                            this.child(
+                                PopoverMenu::new("assistant-toolbar-export-popover-menu")
+                                    .trigger_with_tooltip(
+                                        IconButton::new("export", IconName::Download)
+                                            .icon_size(IconSize::Small)
+                                            .style(ButtonStyle::Subtle),
+                                        Tooltip::text("Export…"),
+                                    )
+                                    .anchor(Corner::TopRight)
+                                    .menu(move |window, cx| {
+                                        Some(ContextMenu::build(
+                                            window,
+                                            cx,
+                                            |menu, _window, _cx| {
+                                                menu.action(
+                                                    "Export as Markdown",
+                                                    ExportThreadAsMarkdown.boxed_clone(),
+                                                )
+                                                .action(
+                                                    "Export as JSON",
+                                                    ExportThreadAsJson.boxed_clone(),
+                                                )
+                                            },
+                                        ))
+                                    }),
+                            )
+                        },
+                    )
                     .child(
                         IconButton::new("configure-assistant", IconName::Settings)
                             .icon_size(IconSize::Small)
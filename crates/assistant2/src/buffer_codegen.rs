@@ -420,11 +420,23 @@ impl CodegenAlternative {
 
         request_message.content.push(prompt.into());
 
+        let mut messages = Vec::new();
+        if let Some(project_rules) = self
+            .context_store
+            .as_ref()
+            .and_then(|context_store| context_store.read(cx).project_rules(cx))
+        {
+            messages.extend(project_rules.read(cx).to_request_message());
+        }
+        messages.push(request_message);
+
         Ok(LanguageModelRequest {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
-            messages: vec![request_message],
+            thinking_budget: None,
+            max_tokens: None,
+            messages,
         })
     }
 
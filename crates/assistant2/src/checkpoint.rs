@@ -0,0 +1,440 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use fs::Fs as _;
+use gpui::{App, AsyncWindowContext, Entity, PromptLevel, Task, WeakEntity};
+use project::git::{GitRepo, Repository};
+use project::{Project, ProjectPath};
+use text::LineEnding;
+use util::ResultExt as _;
+
+use crate::thread::{FileCheckpoint, MessageId, Thread};
+
+/// Saves the contents of the given files into the checkpoint for an Assistant message,
+/// before its tools modify them.
+pub fn capture_checkpoint(
+    thread: &Entity<Thread>,
+    message_id: MessageId,
+    project_paths: Vec<ProjectPath>,
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let open_buffers = project_paths
+        .into_iter()
+        .filter(|project_path| {
+            !thread
+                .read(cx)
+                .checkpoint_contains(message_id, project_path)
+        })
+        .map(|project_path| {
+            let open_buffer = project.update(cx, |project, cx| {
+                project.open_buffer(project_path.clone(), cx)
+            });
+            (project_path, open_buffer)
+        })
+        .collect::<Vec<_>>();
+
+    let thread = thread.downgrade();
+    cx.spawn(|mut cx| async move {
+        for (project_path, open_buffer) in open_buffers {
+            let buffer = open_buffer.await?;
+            let file = buffer.read_with(&cx, |buffer, _| FileCheckpoint {
+                text: Some(buffer.text()),
+                was_dirty: buffer.is_dirty(),
+            })?;
+            thread.update(&mut cx, |thread, cx| {
+                thread.insert_checkpoint_file(message_id, project_path, file, cx)
+            })?;
+        }
+        Ok(())
+    })
+}
+
+/// The largest file saved before running a tool that may modify any file.
+const MAX_SNAPSHOT_FILE_SIZE: u64 = 1024 * 1024;
+
+/// The most bytes read from worktrees without a repository before running a tool that may
+/// modify any file, so that large projects don't delay it for long.
+const MAX_SNAPSHOT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The state of the project from before running a tool that may modify any file.
+#[derive(Default)]
+pub struct ProjectSnapshot {
+    /// Checkpoints of the repositories spanning whole worktrees, saved under a hidden ref.
+    checkpoints: Vec<(Entity<Repository>, String)>,
+    /// The other worktrees, to tell which files the tool created.
+    worktrees: Vec<worktree::Snapshot>,
+    /// The contents of the files in the other worktrees.
+    files: Vec<SnapshotFile>,
+}
+
+/// The contents of a file in the project from before a tool ran.
+struct SnapshotFile {
+    project_path: ProjectPath,
+    abs_path: PathBuf,
+    text: String,
+}
+
+/// Saves the state of the project before running a tool that may modify any of its files,
+/// as which ones can't be told in advance. Worktrees in a local repository are saved as a
+/// checkpoint of the repository, and the files of the others are read, skipping ignored,
+/// private and large ones.
+pub fn snapshot_project_files(project: &Entity<Project>, cx: &App) -> Task<ProjectSnapshot> {
+    let project = project.read(cx);
+    let fs = project.fs().clone();
+    let repositories = project.all_repositories(cx);
+    let worktrees = project
+        .visible_worktrees(cx)
+        .map(|worktree| {
+            let worktree = worktree.read(cx);
+            let checkpoint = repositories.iter().find_map(|repository| {
+                let repository_ref = repository.read(cx);
+                let spans_worktree = repository_ref.worktree_id == worktree.id()
+                    && matches!(repository_ref.git_repo, GitRepo::Local(_))
+                    && repository_ref
+                        .repository_entry
+                        .work_directory
+                        .relativize(Path::new(""))
+                        .is_ok();
+                spans_worktree.then(|| (repository.clone(), repository_ref.create_checkpoint(cx)))
+            });
+            (worktree.snapshot(), checkpoint)
+        })
+        .collect::<Vec<_>>();
+
+    cx.background_executor().spawn(async move {
+        let mut snapshot = ProjectSnapshot::default();
+        let mut total_size = 0;
+        for (worktree, checkpoint) in worktrees {
+            if let Some((repository, checkpoint)) = checkpoint {
+                match checkpoint.await {
+                    Ok(checkpoint) => {
+                        snapshot.checkpoints.push((repository, checkpoint));
+                        continue;
+                    }
+                    Err(error) => log::error!("failed to create a git checkpoint: {error:#}"),
+                }
+            }
+
+            for entry in worktree.files(false, 0) {
+                if entry.is_private || entry.is_external || entry.size > MAX_SNAPSHOT_FILE_SIZE {
+                    continue;
+                }
+                total_size += entry.size;
+                if total_size > MAX_SNAPSHOT_SIZE {
+                    break;
+                }
+                let abs_path = worktree.abs_path().join(&entry.path);
+                let Some(mut text) = fs.load(&abs_path).await.log_err() else {
+                    continue;
+                };
+                LineEnding::normalize(&mut text);
+                snapshot.files.push(SnapshotFile {
+                    project_path: ProjectPath {
+                        worktree_id: worktree.id(),
+                        path: entry.path.clone(),
+                    },
+                    abs_path,
+                    text,
+                });
+            }
+            snapshot.worktrees.push(worktree);
+        }
+        snapshot
+    })
+}
+
+/// Saves the files a tool modified, created or deleted into the checkpoint for an Assistant
+/// message, by comparing them with a snapshot taken before it ran.
+pub fn record_snapshot_edits(
+    thread: WeakEntity<Thread>,
+    message_id: MessageId,
+    snapshot: ProjectSnapshot,
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let fs = project.read(cx).fs().clone();
+    let repository_changes = snapshot
+        .checkpoints
+        .into_iter()
+        .map(|(repository, checkpoint)| {
+            let changes = repository
+                .read(cx)
+                .take_changes_since_checkpoint(checkpoint, cx);
+            (repository, changes)
+        })
+        .collect::<Vec<_>>();
+    let project = project.downgrade();
+    cx.spawn(|mut cx| async move {
+        // The contents of each file from before the tool ran, `None` for the files it created.
+        let mut changed_files = Vec::<(ProjectPath, Option<String>)>::new();
+        for (repository, changes) in repository_changes {
+            let Some(changes) = changes.await.log_err() else {
+                continue;
+            };
+            let project_paths = repository.read_with(&cx, |repository, _| {
+                changes
+                    .into_iter()
+                    .filter_map(|(repo_path, text)| {
+                        Some((repository.repo_path_to_project_path(&repo_path)?, text))
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            changed_files.extend(project_paths.into_iter().filter(|(_, text)| {
+                text.as_ref()
+                    .map_or(true, |text| text.len() as u64 <= MAX_SNAPSHOT_FILE_SIZE)
+            }));
+        }
+
+        for file in snapshot.files {
+            // Files the tool deleted are restored from an empty buffer.
+            let mut text = fs.load(&file.abs_path).await.unwrap_or_default();
+            LineEnding::normalize(&mut text);
+            if text != file.text {
+                changed_files.push((file.project_path, Some(file.text)));
+            }
+        }
+
+        // The worktrees only learn about the files the tool created once they've rescanned.
+        for old_worktree in snapshot.worktrees {
+            let Some(worktree) = project.read_with(&cx, |project, cx| {
+                project.worktree_for_id(old_worktree.id(), cx)
+            })?
+            else {
+                continue;
+            };
+            let scan_complete = worktree.read_with(&cx, |worktree, _| {
+                worktree.as_local().map(|worktree| worktree.scan_complete())
+            })?;
+            if let Some(scan_complete) = scan_complete {
+                scan_complete.await;
+            }
+            worktree.read_with(&cx, |worktree, _| {
+                let created_files = worktree.files(false, 0).filter(|entry| {
+                    !entry.is_private
+                        && !entry.is_external
+                        && old_worktree.entry_for_path(&entry.path).is_none()
+                });
+                for entry in created_files {
+                    let project_path = ProjectPath {
+                        worktree_id: worktree.id(),
+                        path: entry.path.clone(),
+                    };
+                    changed_files.push((project_path, None));
+                }
+            })?;
+        }
+
+        let mut edited_files = Vec::with_capacity(changed_files.len());
+        for (project_path, old_text) in changed_files {
+            let Some((abs_path, is_private)) = project.read_with(&cx, |project, cx| {
+                let abs_path = project.absolute_path(&project_path, cx)?;
+                let is_private = project
+                    .entry_for_path(&project_path, cx)
+                    .map_or(false, |entry| entry.is_private);
+                Some((abs_path, is_private))
+            })?
+            else {
+                continue;
+            };
+            if is_private {
+                continue;
+            }
+            let mut text = fs.load(&abs_path).await.unwrap_or_default();
+            LineEnding::normalize(&mut text);
+            edited_files.push((project_path, old_text, text));
+        }
+
+        thread.update(&mut cx, |thread, cx| {
+            for (project_path, old_text, text) in edited_files {
+                let checkpoint = FileCheckpoint {
+                    text: old_text,
+                    was_dirty: false,
+                };
+                thread.insert_checkpoint_file(message_id, project_path.clone(), checkpoint, cx);
+                thread.record_tool_edit(project_path, text);
+            }
+        })
+    })
+}
+
+/// Records the contents of the given files after a tool modified them.
+pub fn record_tool_edits(
+    thread: WeakEntity<Thread>,
+    project_paths: Vec<ProjectPath>,
+    project: &Entity<Project>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let open_buffers = project_paths
+        .into_iter()
+        .map(|project_path| {
+            let open_buffer = project.update(cx, |project, cx| {
+                project.open_buffer(project_path.clone(), cx)
+            });
+            (project_path, open_buffer)
+        })
+        .collect::<Vec<_>>();
+
+    cx.spawn(|mut cx| async move {
+        for (project_path, open_buffer) in open_buffers {
+            let text = open_buffer
+                .await?
+                .read_with(&cx, |buffer, _| buffer.text())?;
+            thread.update(&mut cx, |thread, _| {
+                thread.record_tool_edit(project_path, text)
+            })?;
+        }
+        Ok(())
+    })
+}
+
+/// Restores the files modified by the tools requested in the given Assistant message,
+/// and in any message after it, to their contents from before the tools ran.
+///
+/// If the user has edited any of those files since, asks before overwriting them.
+pub async fn restore_checkpoint(
+    thread: Entity<Thread>,
+    message_id: MessageId,
+    project: Entity<Project>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let plan = thread.read_with(cx, |thread, _| thread.restore_plan(message_id))?;
+
+    let mut files = Vec::with_capacity(plan.len());
+    let mut conflicts = String::new();
+    for file in plan {
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_buffer(file.project_path.clone(), cx)
+            })?
+            .await?;
+        let text = buffer.read_with(cx, |buffer, _| buffer.text())?;
+        if file.is_edited_since(&text) {
+            writeln!(conflicts, "{}", file.project_path.path.display()).ok();
+        }
+        files.push((file, buffer));
+    }
+
+    if !conflicts.is_empty() {
+        let answer = cx
+            .prompt(
+                PromptLevel::Warning,
+                "These files were edited since the assistant changed them",
+                Some(&conflicts),
+                &["Restore Anyway", "Cancel"],
+            )
+            .await?;
+        if answer != 0 {
+            return Ok(());
+        }
+    }
+
+    for (file, buffer) in files {
+        // Files the tools created are deleted.
+        let Some(text) = file.checkpoint.text else {
+            let delete_entry = project.update(cx, |project, cx| {
+                let entry = project.entry_for_path(&file.project_path, cx)?;
+                project.delete_entry(entry.id, false, cx)
+            })?;
+            if let Some(delete_entry) = delete_entry {
+                delete_entry.await?;
+            }
+            thread.update(cx, |thread, _| {
+                thread.record_tool_edit(file.project_path, String::new())
+            })?;
+            continue;
+        };
+
+        let diff = buffer
+            .update(cx, |buffer, cx| buffer.diff(text.clone(), cx))?
+            .await;
+        buffer.update(cx, |buffer, cx| {
+            buffer.finalize_last_transaction();
+            buffer.apply_diff(diff, cx);
+            buffer.finalize_last_transaction();
+        })?;
+        if !file.checkpoint.was_dirty {
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer, cx))?
+                .await?;
+        }
+        thread.update(cx, |thread, _| {
+            thread.record_tool_edit(file.project_path, text)
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assistant_tool::ToolWorkingSet;
+    use gpui::{AppContext as _, TestAppContext};
+    use language_model::Role;
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_restoring_files_changed_by_a_command(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({ "a.txt": "one\n", "b.txt": "two\n" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/project".as_ref()], cx).await;
+        let thread = cx.new(|cx| Thread::new(Arc::new(ToolWorkingSet::default()), None, cx));
+        let message_id = thread.update(cx, |thread, cx| {
+            thread.insert_message(Role::User, "Run the generator", cx);
+            thread.insert_message(Role::Assistant, "Running it", cx)
+        });
+
+        // The command modifies a file, deletes another one and creates a new one.
+        let snapshot = cx.update(|cx| snapshot_project_files(&project, cx)).await;
+        fs.insert_file("/project/a.txt", b"three\n".to_vec()).await;
+        fs.remove_file("/project/b.txt".as_ref(), Default::default())
+            .await
+            .unwrap();
+        fs.insert_tree("/project/generated", json!({ "c.txt": "four\n" }))
+            .await;
+        cx.run_until_parked();
+        cx.update(|cx| {
+            record_snapshot_edits(thread.downgrade(), message_id, snapshot, &project, cx)
+        })
+        .await
+        .unwrap();
+
+        let mut plan = thread.read_with(cx, |thread, _| {
+            thread
+                .restore_plan(message_id)
+                .into_iter()
+                .map(|file| (file.project_path.path, file.checkpoint.text))
+                .collect::<Vec<_>>()
+        });
+        plan.sort();
+        assert_eq!(
+            plan,
+            [
+                (Path::new("a.txt").into(), Some("one\n".to_string())),
+                (Path::new("b.txt").into(), Some("two\n".to_string())),
+                (Path::new("generated/c.txt").into(), None),
+            ]
+        );
+
+        let cx = cx.add_empty_window();
+        let mut async_cx = cx.update(|window, cx| window.to_async(cx));
+        restore_checkpoint(thread, message_id, project, &mut async_cx)
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(fs.load("/project/a.txt".as_ref()).await.unwrap(), "one\n");
+        assert_eq!(fs.load("/project/b.txt".as_ref()).await.unwrap(), "two\n");
+        assert!(!fs.is_file("/project/generated/c.txt".as_ref()).await);
+    }
+}
@@ -1,9 +1,11 @@
+use std::ops::Range;
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use file_icons::FileIcons;
 use gpui::{App, Entity, SharedString};
-use language::Buffer;
+use language::{Anchor, Buffer};
 use language_model::{LanguageModelRequestMessage, MessageContent};
 use serde::{Deserialize, Serialize};
 use text::BufferId;
@@ -34,12 +36,18 @@ pub struct ContextSnapshot {
     pub text: Box<[SharedString]>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ContextKind {
     File,
     Directory,
+    Symbol,
     FetchedUrl,
+    Diagnostics,
+    GitDiff,
+    Terminal,
     Thread,
+    Resource,
 }
 
 impl ContextKind {
@@ -47,8 +55,13 @@ impl ContextKind {
         match self {
             ContextKind::File => "File",
             ContextKind::Directory => "Folder",
+            ContextKind::Symbol => "Symbol",
             ContextKind::FetchedUrl => "Fetch",
+            ContextKind::Diagnostics => "Diagnostics",
+            ContextKind::GitDiff => "Git Diff",
+            ContextKind::Terminal => "Terminal",
             ContextKind::Thread => "Thread",
+            ContextKind::Resource => "Resource",
         }
     }
 
@@ -56,8 +69,13 @@ impl ContextKind {
         match self {
             ContextKind::File => IconName::File,
             ContextKind::Directory => IconName::Folder,
+            ContextKind::Symbol => IconName::Code,
             ContextKind::FetchedUrl => IconName::Globe,
+            ContextKind::Diagnostics => IconName::Warning,
+            ContextKind::GitDiff => IconName::GitBranch,
+            ContextKind::Terminal => IconName::Terminal,
             ContextKind::Thread => IconName::MessageCircle,
+            ContextKind::Resource => IconName::DatabaseZap,
         }
     }
 }
@@ -66,8 +84,13 @@ impl ContextKind {
 pub enum AssistantContext {
     File(FileContext),
     Directory(DirectoryContext),
+    Symbol(SymbolContext),
     FetchedUrl(FetchedUrlContext),
+    Diagnostics(DiagnosticsContext),
+    GitDiff(GitDiffContext),
+    Terminal(TerminalContext),
     Thread(ThreadContext),
+    Resource(ResourceContext),
 }
 
 impl AssistantContext {
@@ -75,8 +98,13 @@ impl AssistantContext {
         match self {
             Self::File(file) => file.id,
             Self::Directory(directory) => directory.snapshot.id,
+            Self::Symbol(symbol) => symbol.id,
             Self::FetchedUrl(url) => url.id,
+            Self::Diagnostics(diagnostics) => diagnostics.id,
+            Self::GitDiff(diff) => diff.id,
+            Self::Terminal(terminal) => terminal.id,
             Self::Thread(thread) => thread.id,
+            Self::Resource(resource) => resource.id,
         }
     }
 }
@@ -94,6 +122,18 @@ pub struct DirectoryContext {
     pub snapshot: ContextSnapshot,
 }
 
+/// A symbol in a buffer. The range is tracked with anchors so that the symbol's text can be
+/// refreshed after the buffer is edited.
+#[derive(Debug)]
+pub struct SymbolContext {
+    pub id: ContextId,
+    pub name: SharedString,
+    pub buffer: Entity<Buffer>,
+    pub range: Range<Anchor>,
+    pub version: clock::Global,
+    pub text: SharedString,
+}
+
 #[derive(Debug)]
 pub struct FetchedUrlContext {
     pub id: ContextId,
@@ -101,6 +141,31 @@ pub struct FetchedUrlContext {
     pub text: SharedString,
 }
 
+/// The diagnostics of a file, or of the whole project, as they were when they were added.
+#[derive(Debug)]
+pub struct DiagnosticsContext {
+    pub id: ContextId,
+    pub name: SharedString,
+    pub text: SharedString,
+}
+
+/// The uncommitted changes in a repository, or the changes of a commit, as they were when they
+/// were added.
+#[derive(Debug)]
+pub struct GitDiffContext {
+    pub id: ContextId,
+    pub name: SharedString,
+    pub text: SharedString,
+}
+
+/// The recent output of a terminal, as it was when it was added.
+#[derive(Debug)]
+pub struct TerminalContext {
+    pub id: ContextId,
+    pub name: SharedString,
+    pub text: SharedString,
+}
+
 // TODO: Model<Thread> holds onto the thread even if the thread is deleted. Can either handle this
 // explicitly or have a WeakModel<Thread> and remove during snapshot.
 
@@ -111,6 +176,16 @@ pub struct ThreadContext {
     pub text: SharedString,
 }
 
+/// A resource exposed by a context server.
+#[derive(Debug)]
+pub struct ResourceContext {
+    pub id: ContextId,
+    pub server_id: Arc<str>,
+    pub uri: String,
+    pub name: SharedString,
+    pub text: SharedString,
+}
+
 // TODO: Model<Buffer> holds onto the buffer even if the file is deleted and closed. Should remove
 // the context from the message editor in this case.
 
@@ -127,8 +202,13 @@ impl AssistantContext {
         match &self {
             Self::File(file_context) => file_context.snapshot(cx),
             Self::Directory(directory_context) => Some(directory_context.snapshot()),
+            Self::Symbol(symbol_context) => symbol_context.snapshot(cx),
             Self::FetchedUrl(fetched_url_context) => Some(fetched_url_context.snapshot()),
+            Self::Diagnostics(diagnostics_context) => Some(diagnostics_context.snapshot()),
+            Self::GitDiff(git_diff_context) => Some(git_diff_context.snapshot()),
+            Self::Terminal(terminal_context) => Some(terminal_context.snapshot()),
             Self::Thread(thread_context) => Some(thread_context.snapshot(cx)),
+            Self::Resource(resource_context) => Some(resource_context.snapshot()),
         }
     }
 }
@@ -206,6 +286,27 @@ impl DirectoryContext {
     }
 }
 
+impl SymbolContext {
+    pub fn snapshot(&self, cx: &App) -> Option<ContextSnapshot> {
+        let buffer = self.buffer.read(cx);
+        let path = buffer_path_log_err(buffer)?;
+        let full_path: SharedString = path.to_string_lossy().into_owned().into();
+        let parent = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned().into());
+
+        Some(ContextSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            parent,
+            tooltip: Some(full_path),
+            icon_path: None,
+            kind: ContextKind::Symbol,
+            text: Box::new([self.text.clone()]),
+        })
+    }
+}
+
 impl FetchedUrlContext {
     pub fn snapshot(&self) -> ContextSnapshot {
         ContextSnapshot {
@@ -220,6 +321,48 @@ impl FetchedUrlContext {
     }
 }
 
+impl DiagnosticsContext {
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            parent: None,
+            tooltip: None,
+            icon_path: None,
+            kind: ContextKind::Diagnostics,
+            text: Box::new([self.text.clone()]),
+        }
+    }
+}
+
+impl GitDiffContext {
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            parent: None,
+            tooltip: None,
+            icon_path: None,
+            kind: ContextKind::GitDiff,
+            text: Box::new([self.text.clone()]),
+        }
+    }
+}
+
+impl TerminalContext {
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            parent: None,
+            tooltip: None,
+            icon_path: None,
+            kind: ContextKind::Terminal,
+            text: Box::new([self.text.clone()]),
+        }
+    }
+}
+
 impl ThreadContext {
     pub fn snapshot(&self, cx: &App) -> ContextSnapshot {
         let thread = self.thread.read(cx);
@@ -235,14 +378,33 @@ impl ThreadContext {
     }
 }
 
+impl ResourceContext {
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            parent: Some(self.server_id.to_string().into()),
+            tooltip: Some(self.uri.clone().into()),
+            icon_path: None,
+            kind: ContextKind::Resource,
+            text: Box::new([self.text.clone()]),
+        }
+    }
+}
+
 pub fn attach_context_to_message(
     message: &mut LanguageModelRequestMessage,
     contexts: impl Iterator<Item = ContextSnapshot>,
 ) {
     let mut file_context = Vec::new();
     let mut directory_context = Vec::new();
+    let mut symbol_context = Vec::new();
     let mut fetch_context = Vec::new();
+    let mut diagnostics_context = Vec::new();
+    let mut git_diff_context = Vec::new();
+    let mut terminal_context = Vec::new();
     let mut thread_context = Vec::new();
+    let mut resource_context = Vec::new();
 
     let mut capacity = 0;
     for context in contexts {
@@ -250,8 +412,13 @@ pub fn attach_context_to_message(
         match context.kind {
             ContextKind::File => file_context.push(context),
             ContextKind::Directory => directory_context.push(context),
+            ContextKind::Symbol => symbol_context.push(context),
             ContextKind::FetchedUrl => fetch_context.push(context),
+            ContextKind::Diagnostics => diagnostics_context.push(context),
+            ContextKind::GitDiff => git_diff_context.push(context),
+            ContextKind::Terminal => terminal_context.push(context),
             ContextKind::Thread => thread_context.push(context),
+            ContextKind::Resource => resource_context.push(context),
         }
     }
     if !file_context.is_empty() {
@@ -260,12 +427,27 @@ pub fn attach_context_to_message(
     if !directory_context.is_empty() {
         capacity += 1;
     }
+    if !symbol_context.is_empty() {
+        capacity += 1;
+    }
     if !fetch_context.is_empty() {
         capacity += 1 + fetch_context.len();
     }
+    if !diagnostics_context.is_empty() {
+        capacity += 1;
+    }
+    if !git_diff_context.is_empty() {
+        capacity += 1 + git_diff_context.len();
+    }
+    if !terminal_context.is_empty() {
+        capacity += 1 + terminal_context.len();
+    }
     if !thread_context.is_empty() {
         capacity += 1 + thread_context.len();
     }
+    if !resource_context.is_empty() {
+        capacity += 1;
+    }
     if capacity == 0 {
         return;
     }
@@ -290,6 +472,15 @@ pub fn attach_context_to_message(
         }
     }
 
+    if !symbol_context.is_empty() {
+        context_chunks.push("The following symbols are available:\n");
+        for context in &symbol_context {
+            for chunk in &context.text {
+                context_chunks.push(&chunk);
+            }
+        }
+    }
+
     if !fetch_context.is_empty() {
         context_chunks.push("The following fetched results are available:\n");
         for context in &fetch_context {
@@ -300,6 +491,35 @@ pub fn attach_context_to_message(
         }
     }
 
+    if !diagnostics_context.is_empty() {
+        context_chunks.push("The following diagnostics are available:\n");
+        for context in &diagnostics_context {
+            for chunk in &context.text {
+                context_chunks.push(&chunk);
+            }
+        }
+    }
+
+    if !git_diff_context.is_empty() {
+        context_chunks.push("The following git diffs are available:\n");
+        for context in &git_diff_context {
+            context_chunks.push(&context.name);
+            for chunk in &context.text {
+                context_chunks.push(&chunk);
+            }
+        }
+    }
+
+    if !terminal_context.is_empty() {
+        context_chunks.push("The following terminal output is available:\n");
+        for context in &terminal_context {
+            context_chunks.push(&context.name);
+            for chunk in &context.text {
+                context_chunks.push(&chunk);
+            }
+        }
+    }
+
     if !thread_context.is_empty() {
         context_chunks.push("The following previous conversation threads are available:\n");
         for context in &thread_context {
@@ -310,6 +530,15 @@ pub fn attach_context_to_message(
         }
     }
 
+    if !resource_context.is_empty() {
+        context_chunks.push("The following resources are available:\n");
+        for context in &resource_context {
+            for chunk in &context.text {
+                context_chunks.push(&chunk);
+            }
+        }
+    }
+
     debug_assert!(
         context_chunks.len() == capacity,
         "attach_context_message calculated capacity of {}, but length was {}",
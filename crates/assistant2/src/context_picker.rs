@@ -1,12 +1,18 @@
+mod diagnostics_context_picker;
 mod directory_context_picker;
 mod fetch_context_picker;
 mod file_context_picker;
+mod git_diff_context_picker;
+mod resource_context_picker;
+mod symbol_context_picker;
+mod terminal_context_picker;
 mod thread_context_picker;
 
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use context_server::protocol::ServerCapability;
 use editor::Editor;
 use file_context_picker::render_file_context_entry;
 use gpui::{App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity};
@@ -16,9 +22,14 @@ use ui::{prelude::*, ContextMenu, ContextMenuEntry, ContextMenuItem};
 use workspace::{notifications::NotifyResultExt, Workspace};
 
 use crate::context::ContextKind;
+use crate::context_picker::diagnostics_context_picker::DiagnosticsContextPicker;
 use crate::context_picker::directory_context_picker::DirectoryContextPicker;
 use crate::context_picker::fetch_context_picker::FetchContextPicker;
 use crate::context_picker::file_context_picker::FileContextPicker;
+use crate::context_picker::git_diff_context_picker::GitDiffContextPicker;
+use crate::context_picker::resource_context_picker::ResourceContextPicker;
+use crate::context_picker::symbol_context_picker::SymbolContextPicker;
+use crate::context_picker::terminal_context_picker::TerminalContextPicker;
 use crate::context_picker::thread_context_picker::ThreadContextPicker;
 use crate::context_store::ContextStore;
 use crate::thread_store::ThreadStore;
@@ -35,8 +46,13 @@ enum ContextPickerMode {
     Default(Entity<ContextMenu>),
     File(Entity<FileContextPicker>),
     Directory(Entity<DirectoryContextPicker>),
+    Symbol(Entity<SymbolContextPicker>),
     Fetch(Entity<FetchContextPicker>),
+    Diagnostics(Entity<DiagnosticsContextPicker>),
+    GitDiff(Entity<GitDiffContextPicker>),
+    Terminal(Entity<TerminalContextPicker>),
     Thread(Entity<ThreadContextPicker>),
+    Resource(Entity<ResourceContextPicker>),
 }
 
 pub(super) struct ContextPicker {
@@ -91,11 +107,18 @@ impl ContextPicker {
             let mut context_kinds = vec![
                 ContextKind::File,
                 ContextKind::Directory,
+                ContextKind::Symbol,
                 ContextKind::FetchedUrl,
+                ContextKind::Diagnostics,
+                ContextKind::GitDiff,
+                ContextKind::Terminal,
             ];
             if self.allow_threads() {
                 context_kinds.push(ContextKind::Thread);
             }
+            if self.allow_resources(cx) {
+                context_kinds.push(ContextKind::Resource);
+            }
 
             let menu = menu
                 .when(has_recent, |menu| {
@@ -143,6 +166,24 @@ impl ContextPicker {
         self.thread_store.is_some()
     }
 
+    /// Whether context server resources are allowed as context, which requires a running server
+    /// that exposes resources.
+    pub fn allow_resources(&self, cx: &App) -> bool {
+        self.thread_store
+            .as_ref()
+            .and_then(|thread_store| thread_store.upgrade())
+            .map_or(false, |thread_store| {
+                thread_store
+                    .read(cx)
+                    .context_server_manager()
+                    .read(cx)
+                    .servers()
+                    .iter()
+                    .filter_map(|server| server.client())
+                    .any(|client| client.capable(ServerCapability::Resources))
+            })
+    }
+
     fn select_kind(&mut self, kind: ContextKind, window: &mut Window, cx: &mut Context<Self>) {
         let context_picker = cx.entity().downgrade();
 
@@ -172,6 +213,54 @@ impl ContextPicker {
                     )
                 }));
             }
+            ContextKind::Symbol => {
+                self.mode = ContextPickerMode::Symbol(cx.new(|cx| {
+                    SymbolContextPicker::new(
+                        context_picker.clone(),
+                        self.workspace.clone(),
+                        self.context_store.clone(),
+                        self.confirm_behavior,
+                        window,
+                        cx,
+                    )
+                }));
+            }
+            ContextKind::Diagnostics => {
+                self.mode = ContextPickerMode::Diagnostics(cx.new(|cx| {
+                    DiagnosticsContextPicker::new(
+                        context_picker.clone(),
+                        self.workspace.clone(),
+                        self.context_store.clone(),
+                        self.confirm_behavior,
+                        window,
+                        cx,
+                    )
+                }));
+            }
+            ContextKind::GitDiff => {
+                self.mode = ContextPickerMode::GitDiff(cx.new(|cx| {
+                    GitDiffContextPicker::new(
+                        context_picker.clone(),
+                        self.workspace.clone(),
+                        self.context_store.clone(),
+                        self.confirm_behavior,
+                        window,
+                        cx,
+                    )
+                }));
+            }
+            ContextKind::Terminal => {
+                self.mode = ContextPickerMode::Terminal(cx.new(|cx| {
+                    TerminalContextPicker::new(
+                        context_picker.clone(),
+                        self.workspace.clone(),
+                        self.context_store.clone(),
+                        self.confirm_behavior,
+                        window,
+                        cx,
+                    )
+                }));
+            }
             ContextKind::FetchedUrl => {
                 self.mode = ContextPickerMode::Fetch(cx.new(|cx| {
                     FetchContextPicker::new(
@@ -198,6 +287,25 @@ impl ContextPicker {
                     }));
                 }
             }
+            ContextKind::Resource => {
+                if let Some(thread_store) = self
+                    .thread_store
+                    .as_ref()
+                    .and_then(|thread_store| thread_store.upgrade())
+                {
+                    let context_server_manager = thread_store.read(cx).context_server_manager();
+                    self.mode = ContextPickerMode::Resource(cx.new(|cx| {
+                        ResourceContextPicker::new(
+                            context_server_manager,
+                            context_picker.clone(),
+                            self.context_store.clone(),
+                            self.confirm_behavior,
+                            window,
+                            cx,
+                        )
+                    }));
+                }
+            }
         }
 
         cx.notify();
@@ -395,8 +503,15 @@ impl Focusable for ContextPicker {
             ContextPickerMode::Default(menu) => menu.focus_handle(cx),
             ContextPickerMode::File(file_picker) => file_picker.focus_handle(cx),
             ContextPickerMode::Directory(directory_picker) => directory_picker.focus_handle(cx),
+            ContextPickerMode::Symbol(symbol_picker) => symbol_picker.focus_handle(cx),
             ContextPickerMode::Fetch(fetch_picker) => fetch_picker.focus_handle(cx),
+            ContextPickerMode::Diagnostics(diagnostics_picker) => {
+                diagnostics_picker.focus_handle(cx)
+            }
+            ContextPickerMode::GitDiff(git_diff_picker) => git_diff_picker.focus_handle(cx),
+            ContextPickerMode::Terminal(terminal_picker) => terminal_picker.focus_handle(cx),
             ContextPickerMode::Thread(thread_picker) => thread_picker.focus_handle(cx),
+            ContextPickerMode::Resource(resource_picker) => resource_picker.focus_handle(cx),
         }
     }
 }
@@ -412,8 +527,21 @@ impl Render for ContextPicker {
                 ContextPickerMode::Directory(directory_picker) => {
                     parent.child(directory_picker.clone())
                 }
+                ContextPickerMode::Symbol(symbol_picker) => parent.child(symbol_picker.clone()),
                 ContextPickerMode::Fetch(fetch_picker) => parent.child(fetch_picker.clone()),
+                ContextPickerMode::Diagnostics(diagnostics_picker) => {
+                    parent.child(diagnostics_picker.clone())
+                }
+                ContextPickerMode::GitDiff(git_diff_picker) => {
+                    parent.child(git_diff_picker.clone())
+                }
+                ContextPickerMode::Terminal(terminal_picker) => {
+                    parent.child(terminal_picker.clone())
+                }
                 ContextPickerMode::Thread(thread_picker) => parent.child(thread_picker.clone()),
+                ContextPickerMode::Resource(resource_picker) => {
+                    parent.child(resource_picker.clone())
+                }
             })
     }
 }
@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+use collections::BTreeMap;
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use project::ProjectPath;
+use ui::{prelude::*, ListItem};
+use workspace::{notifications::NotifyResultExt, Workspace};
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store::{self, ContextStore};
+
+pub struct DiagnosticsContextPicker {
+    picker: Entity<Picker<DiagnosticsContextPickerDelegate>>,
+}
+
+impl DiagnosticsContextPicker {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        workspace: WeakEntity<Workspace>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let entries = diagnostics_entries(&workspace, cx);
+        let delegate = DiagnosticsContextPickerDelegate::new(
+            context_picker,
+            context_store,
+            confirm_behavior,
+            entries,
+        );
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        DiagnosticsContextPicker { picker }
+    }
+}
+
+impl Focusable for DiagnosticsContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for DiagnosticsContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiagnosticsContextEntry {
+    /// The file the diagnostics are in, or `None` for the diagnostics of the whole project.
+    pub project_path: Option<ProjectPath>,
+    pub label: SharedString,
+    pub error_count: usize,
+    pub warning_count: usize,
+}
+
+/// Lists an entry for the whole project, followed by the files that have diagnostics.
+fn diagnostics_entries(
+    workspace: &WeakEntity<Workspace>,
+    cx: &App,
+) -> Vec<DiagnosticsContextEntry> {
+    let Some(workspace) = workspace.upgrade() else {
+        return Vec::new();
+    };
+    let project = workspace.read(cx).project().read(cx);
+
+    let mut summaries = BTreeMap::<ProjectPath, (usize, usize)>::default();
+    for (project_path, _, summary) in project.diagnostic_summaries(false, cx) {
+        let counts = summaries.entry(project_path).or_default();
+        counts.0 += summary.error_count;
+        counts.1 += summary.warning_count;
+    }
+
+    let project_summary = project.diagnostic_summary(false, cx);
+    let mut entries = vec![DiagnosticsContextEntry {
+        project_path: None,
+        label: "All project diagnostics".into(),
+        error_count: project_summary.error_count,
+        warning_count: project_summary.warning_count,
+    }];
+    entries.extend(
+        summaries
+            .into_iter()
+            .map(
+                |(project_path, (error_count, warning_count))| DiagnosticsContextEntry {
+                    label: project_path.path.to_string_lossy().into_owned().into(),
+                    project_path: Some(project_path),
+                    error_count,
+                    warning_count,
+                },
+            ),
+    );
+    entries
+}
+
+pub struct DiagnosticsContextPickerDelegate {
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<context_store::ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    entries: Vec<DiagnosticsContextEntry>,
+    matches: Vec<DiagnosticsContextEntry>,
+    selected_index: usize,
+}
+
+impl DiagnosticsContextPickerDelegate {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        entries: Vec<DiagnosticsContextEntry>,
+    ) -> Self {
+        DiagnosticsContextPickerDelegate {
+            context_picker,
+            context_store,
+            confirm_behavior,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for DiagnosticsContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search diagnostics…".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let entries = self.entries.clone();
+        let executor = cx.background_executor().clone();
+        let search_task = cx.background_executor().spawn(async move {
+            if query.is_empty() {
+                entries
+            } else {
+                let candidates = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(id, entry)| StringMatchCandidate::new(id, &entry.label))
+                    .collect::<Vec<_>>();
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+
+                matches
+                    .into_iter()
+                    .map(|mat| entries[mat.candidate_id].clone())
+                    .collect()
+            }
+        });
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = search_task.await;
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let Some(task) = self
+            .context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_diagnostics(entry.project_path.clone(), cx)
+            })
+            .ok()
+        else {
+            return;
+        };
+
+        let confirm_behavior = self.confirm_behavior;
+        cx.spawn_in(window, |this, mut cx| async move {
+            match task.await.notify_async_err(&mut cx) {
+                None => anyhow::Ok(()),
+                Some(()) => this.update_in(&mut cx, |this, window, cx| match confirm_behavior {
+                    ConfirmBehavior::KeepOpen => {}
+                    ConfirmBehavior::Close => this.delegate.dismissed(window, cx),
+                }),
+            }
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_diagnostics_context_entry(entry, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+pub fn render_diagnostics_context_entry(
+    entry: &DiagnosticsContextEntry,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let added = context_store.upgrade().map_or(false, |ctx_store| {
+        ctx_store
+            .read(cx)
+            .includes_diagnostics(entry.project_path.as_ref())
+            .is_some()
+    });
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .child(
+            Icon::new(IconName::Warning)
+                .size(IconSize::XSmall)
+                .color(Color::Muted),
+        )
+        .child(Label::new(entry.label.clone()).text_ellipsis())
+        .child(
+            Label::new(format!(
+                "{} errors, {} warnings",
+                entry.error_count, entry.warning_count
+            ))
+            .size(LabelSize::Small)
+            .color(Color::Muted),
+        )
+        .child(div().w_full())
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
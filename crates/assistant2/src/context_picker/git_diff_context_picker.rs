@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, ListItem};
+use util::ResultExt as _;
+use workspace::{notifications::NotifyResultExt, Workspace};
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store::{self, ContextStore, GitDiffTarget};
+
+/// How many of the most recent commits are offered.
+const MAX_COMMITS: usize = 50;
+
+pub struct GitDiffContextPicker {
+    picker: Entity<Picker<GitDiffContextPickerDelegate>>,
+}
+
+impl GitDiffContextPicker {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        workspace: WeakEntity<Workspace>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            GitDiffContextPickerDelegate::new(context_picker, context_store, confirm_behavior);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        picker.update(cx, |picker, cx| {
+            picker.delegate.load_commits(&workspace, window, cx);
+        });
+
+        GitDiffContextPicker { picker }
+    }
+}
+
+impl Focusable for GitDiffContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for GitDiffContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+pub struct GitDiffContextPickerDelegate {
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<context_store::ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    entries: Vec<GitDiffTarget>,
+    matches: Vec<GitDiffTarget>,
+    selected_index: usize,
+}
+
+impl GitDiffContextPickerDelegate {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+    ) -> Self {
+        GitDiffContextPickerDelegate {
+            context_picker,
+            context_store,
+            confirm_behavior,
+            entries: vec![GitDiffTarget::Uncommitted],
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    /// Lists the most recent commits of the active repository after the uncommitted changes.
+    fn load_commits(
+        &mut self,
+        workspace: &WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(repository) = workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).project().read(cx).active_repository(cx))
+        else {
+            return;
+        };
+        let commits_task = repository.read(cx).recent_commits(MAX_COMMITS, cx);
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let Some(commits) = commits_task.await.log_err() else {
+                return;
+            };
+            this.update_in(&mut cx, |this, window, cx| {
+                this.delegate
+                    .entries
+                    .extend(commits.into_iter().map(|commit| GitDiffTarget::Commit {
+                        sha: commit.sha,
+                        subject: commit.subject,
+                    }));
+                this.refresh(window, cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl PickerDelegate for GitDiffContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search commits…".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let entries = self.entries.clone();
+        let executor = cx.background_executor().clone();
+        let search_task = cx.background_executor().spawn(async move {
+            if query.is_empty() {
+                entries
+            } else {
+                let candidates = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(id, entry)| StringMatchCandidate::new(id, &entry_label(entry)))
+                    .collect::<Vec<_>>();
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+
+                matches
+                    .into_iter()
+                    .map(|mat| entries[mat.candidate_id].clone())
+                    .collect()
+            }
+        });
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = search_task.await;
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index).cloned() else {
+            return;
+        };
+        let Some(task) = self
+            .context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_git_diff(entry, cx)
+            })
+            .ok()
+        else {
+            return;
+        };
+
+        let confirm_behavior = self.confirm_behavior;
+        cx.spawn_in(window, |this, mut cx| async move {
+            match task.await.notify_async_err(&mut cx) {
+                None => anyhow::Ok(()),
+                Some(()) => this.update_in(&mut cx, |this, window, cx| match confirm_behavior {
+                    ConfirmBehavior::KeepOpen => {}
+                    ConfirmBehavior::Close => this.delegate.dismissed(window, cx),
+                }),
+            }
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_git_diff_context_entry(entry, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+fn entry_label(entry: &GitDiffTarget) -> String {
+    match entry {
+        GitDiffTarget::Uncommitted => "Uncommitted changes".to_string(),
+        GitDiffTarget::Commit { sha, subject } => format!("{sha} {subject}"),
+    }
+}
+
+pub fn render_git_diff_context_entry(
+    entry: &GitDiffTarget,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let added = context_store.upgrade().map_or(false, |ctx_store| {
+        ctx_store.read(cx).includes_git_diff(entry).is_some()
+    });
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .child(
+            Icon::new(IconName::GitBranch)
+                .size(IconSize::XSmall)
+                .color(Color::Muted),
+        )
+        .map(|el| match entry {
+            GitDiffTarget::Uncommitted => el.child(Label::new("Uncommitted changes")),
+            GitDiffTarget::Commit { sha, subject } => el
+                .child(
+                    Label::new(sha[..sha.len().min(7)].to_string())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(Label::new(subject.clone()).text_ellipsis()),
+        })
+        .child(div().w_full())
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
@@ -0,0 +1,391 @@
+use std::sync::Arc;
+
+use context_server::manager::ContextServerManager;
+use context_server::protocol::ServerCapability;
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, ListItem};
+use util::ResultExt as _;
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store::{self, ContextStore};
+
+pub struct ResourceContextPicker {
+    picker: Entity<Picker<ResourceContextPickerDelegate>>,
+}
+
+impl ResourceContextPicker {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = ResourceContextPickerDelegate::new(
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+        );
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        picker.update(cx, |picker, cx| {
+            picker.delegate.load_entries(window, cx);
+        });
+
+        ResourceContextPicker { picker }
+    }
+}
+
+impl Focusable for ResourceContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for ResourceContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceContextEntry {
+    pub server_id: Arc<str>,
+    pub name: SharedString,
+    pub kind: ResourceEntryKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ResourceEntryKind {
+    Resource {
+        uri: String,
+    },
+    /// A template whose variables are filled in from the query to address a resource.
+    Template {
+        uri_template: String,
+    },
+}
+
+pub struct ResourceContextPickerDelegate {
+    context_server_manager: Entity<ContextServerManager>,
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<context_store::ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    entries: Vec<ResourceContextEntry>,
+    /// The template being filled in, if one was selected.
+    template: Option<ResourceContextEntry>,
+    matches: Vec<ResourceContextEntry>,
+    selected_index: usize,
+}
+
+impl ResourceContextPickerDelegate {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+    ) -> Self {
+        ResourceContextPickerDelegate {
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+            entries: Vec::new(),
+            template: None,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    /// Lists the resources and resource templates of all the running context servers.
+    fn load_entries(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let servers = self
+            .context_server_manager
+            .read(cx)
+            .servers()
+            .into_iter()
+            .filter_map(|server| Some((server.id(), server.client()?)))
+            .filter(|(_, client)| client.capable(ServerCapability::Resources))
+            .collect::<Vec<_>>();
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let mut entries = Vec::new();
+            for (server_id, client) in servers {
+                if let Some(response) = client.list_resources().await.log_err() {
+                    entries.extend(response.resources.into_iter().map(|resource| {
+                        ResourceContextEntry {
+                            server_id: server_id.clone(),
+                            name: resource.name.into(),
+                            kind: ResourceEntryKind::Resource {
+                                uri: resource.uri.to_string(),
+                            },
+                        }
+                    }));
+                }
+                if let Some(response) = client.list_resource_templates().await.log_err() {
+                    entries.extend(response.resource_templates.into_iter().map(|template| {
+                        ResourceContextEntry {
+                            server_id: server_id.clone(),
+                            name: template.name.into(),
+                            kind: ResourceEntryKind::Template {
+                                uri_template: template.uri_template,
+                            },
+                        }
+                    }));
+                }
+            }
+
+            this.update_in(&mut cx, |this, window, cx| {
+                this.delegate.entries = entries;
+                this.refresh(window, cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl PickerDelegate for ResourceContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match &self.template {
+            Some(ResourceContextEntry {
+                kind: ResourceEntryKind::Template { uri_template },
+                ..
+            }) => format!(
+                "Enter {}…",
+                template_variables(uri_template)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+            .into(),
+            _ => "Search resources…".into(),
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        if let Some(ResourceContextEntry {
+            server_id,
+            name,
+            kind: ResourceEntryKind::Template { uri_template },
+        }) = &self.template
+        {
+            let values = query.split_whitespace().collect::<Vec<_>>();
+            self.matches = if values.len() == template_variables(uri_template).count() {
+                vec![ResourceContextEntry {
+                    server_id: server_id.clone(),
+                    name: format!("{name}: {}", values.join(" ")).into(),
+                    kind: ResourceEntryKind::Resource {
+                        uri: expand_template(uri_template, &values),
+                    },
+                }]
+            } else {
+                Vec::new()
+            };
+            self.selected_index = 0;
+            return Task::ready(());
+        }
+
+        let entries = self.entries.clone();
+        let executor = cx.background_executor().clone();
+        let search_task = cx.background_executor().spawn(async move {
+            if query.is_empty() {
+                entries
+            } else {
+                let candidates = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(id, entry)| StringMatchCandidate::new(id, &entry.name))
+                    .collect::<Vec<_>>();
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+
+                matches
+                    .into_iter()
+                    .map(|mat| entries[mat.candidate_id].clone())
+                    .collect()
+            }
+        });
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = search_task.await;
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index).cloned() else {
+            return;
+        };
+
+        let uri = match entry.kind {
+            ResourceEntryKind::Resource { uri } => uri,
+            ResourceEntryKind::Template { .. } => {
+                self.template = Some(entry);
+                cx.defer_in(window, |picker, window, cx| {
+                    picker.refresh_placeholder(window, cx);
+                    picker.set_query("", window, cx);
+                    picker.refresh(window, cx);
+                });
+                return;
+            }
+        };
+
+        let Some(context_store) = self.context_store.upgrade() else {
+            return;
+        };
+        let add_resource_task = context_store.update(cx, |context_store, cx| {
+            context_store.add_resource(
+                self.context_server_manager.clone(),
+                entry.server_id,
+                uri,
+                entry.name,
+                cx,
+            )
+        });
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            add_resource_task.await?;
+            this.update_in(&mut cx, |this, window, cx| {
+                match this.delegate.confirm_behavior {
+                    ConfirmBehavior::KeepOpen => {}
+                    ConfirmBehavior::Close => this.delegate.dismissed(window, cx),
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_resource_context_entry(entry, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+pub fn render_resource_context_entry(
+    entry: &ResourceContextEntry,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let (uri, added) = match &entry.kind {
+        ResourceEntryKind::Resource { uri } => {
+            let added = context_store.upgrade().map_or(false, |ctx_store| {
+                ctx_store
+                    .read(cx)
+                    .includes_resource(&entry.server_id, uri)
+                    .is_some()
+            });
+            (uri.as_str(), added)
+        }
+        ResourceEntryKind::Template { uri_template } => (uri_template.as_str(), false),
+    };
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .child(
+            Icon::new(IconName::DatabaseZap)
+                .size(IconSize::XSmall)
+                .color(Color::Muted),
+        )
+        .child(Label::new(entry.name.clone()))
+        .child(
+            Label::new(uri.to_string())
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+                .text_ellipsis(),
+        )
+        .child(div().w_full())
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
+
+/// Returns the names of the variables in a URI template, such as `path` in `file:///{path}`.
+fn template_variables(uri_template: &str) -> impl Iterator<Item = &str> {
+    uri_template.split('{').skip(1).filter_map(|part| {
+        let (variable, _) = part.split_once('}')?;
+        Some(variable.trim_start_matches(['+', '#', '/', '?', '.', ';', '&']))
+    })
+}
+
+/// Fills in the variables of a URI template with the given values, in order.
+fn expand_template(uri_template: &str, values: &[&str]) -> String {
+    let mut uri = String::new();
+    let mut values = values.iter();
+    let mut rest = uri_template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        uri.push_str(&rest[..start]);
+        uri.push_str(values.next().copied().unwrap_or_default());
+        rest = &rest[start + end + 1..];
+    }
+    uri.push_str(rest);
+    uri
+}
@@ -0,0 +1,224 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use language::{Anchor, Bias, Buffer, ToPointUtf16 as _};
+use picker::{Picker, PickerDelegate};
+use project::Symbol;
+use ui::{prelude::*, ListItem};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store;
+
+pub struct SymbolContextPicker {
+    picker: Entity<Picker<SymbolContextPickerDelegate>>,
+}
+
+impl SymbolContextPicker {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        workspace: WeakEntity<Workspace>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = SymbolContextPickerDelegate::new(
+            context_picker,
+            workspace,
+            context_store,
+            confirm_behavior,
+        );
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        SymbolContextPicker { picker }
+    }
+}
+
+impl Focusable for SymbolContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SymbolContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+pub struct SymbolContextPickerDelegate {
+    context_picker: WeakEntity<ContextPicker>,
+    workspace: WeakEntity<Workspace>,
+    context_store: WeakEntity<context_store::ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    matches: Vec<Symbol>,
+    selected_index: usize,
+}
+
+impl SymbolContextPickerDelegate {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        workspace: WeakEntity<Workspace>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+    ) -> Self {
+        SymbolContextPickerDelegate {
+            context_picker,
+            workspace,
+            context_store,
+            confirm_behavior,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for SymbolContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search symbols…".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(project) = self
+            .workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(());
+        };
+        if query.is_empty() {
+            self.matches.clear();
+            return Task::ready(());
+        }
+
+        let symbols_task = project.update(cx, |project, cx| project.symbols(&query, cx));
+        cx.spawn_in(window, |this, mut cx| async move {
+            let symbols = symbols_task.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = symbols;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(symbol) = self.matches.get(self.selected_index).cloned() else {
+            return;
+        };
+        let Some(project) = self
+            .workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return;
+        };
+
+        let open_buffer_task = project.update(cx, |project, cx| {
+            project.open_buffer_for_symbol(&symbol, cx)
+        });
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let buffer = open_buffer_task.await?;
+            this.update_in(&mut cx, |this, window, cx| {
+                let range = symbol_range(&symbol, buffer.read(cx));
+                this.delegate
+                    .context_store
+                    .update(cx, |context_store, cx| {
+                        context_store.add_symbol(symbol.name.clone(), buffer, range, cx)
+                    })
+                    .ok();
+
+                match this.delegate.confirm_behavior {
+                    ConfirmBehavior::KeepOpen => {}
+                    ConfirmBehavior::Close => this.delegate.dismissed(window, cx),
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let symbol = &self.matches[ix];
+
+        Some(
+            ListItem::new(ix).inset(true).toggle_state(selected).child(
+                h_flex()
+                    .gap_1p5()
+                    .w_full()
+                    .child(
+                        Icon::new(IconName::Code)
+                            .size(IconSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(symbol.name.clone()))
+                    .child(
+                        Label::new(symbol.path.path.to_string_lossy().into_owned())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .text_ellipsis(),
+                    ),
+            ),
+        )
+    }
+}
+
+/// Returns the range of the symbol in the buffer, extended to the innermost outline item
+/// that contains it, since language servers may only report the range of the symbol's name.
+fn symbol_range(symbol: &Symbol, buffer: &Buffer) -> Range<Anchor> {
+    let snapshot = buffer.snapshot();
+    let start = snapshot.clip_point_utf16(symbol.range.start, Bias::Left);
+    let end = snapshot.clip_point_utf16(symbol.range.end, Bias::Left);
+
+    let outline_range = snapshot
+        .symbols_containing(start, None)
+        .and_then(|items| items.last().map(|item| item.range.clone()))
+        .filter(|range| {
+            range.start.to_point_utf16(&snapshot) <= start
+                && range.end.to_point_utf16(&snapshot) >= end
+        });
+
+    outline_range.unwrap_or_else(|| snapshot.anchor_before(start)..snapshot.anchor_after(end))
+}
@@ -0,0 +1,250 @@
+use std::sync::Arc;
+
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use terminal::Terminal;
+use ui::{prelude::*, ListItem};
+use workspace::Workspace;
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store::{self, ContextStore};
+
+pub struct TerminalContextPicker {
+    picker: Entity<Picker<TerminalContextPickerDelegate>>,
+}
+
+impl TerminalContextPicker {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        workspace: WeakEntity<Workspace>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let entries = terminal_entries(&workspace, cx);
+        let delegate = TerminalContextPickerDelegate::new(
+            context_picker,
+            context_store,
+            confirm_behavior,
+            entries,
+        );
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        TerminalContextPicker { picker }
+    }
+}
+
+impl Focusable for TerminalContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for TerminalContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Clone)]
+pub struct TerminalContextEntry {
+    pub terminal: Entity<Terminal>,
+    pub title: SharedString,
+}
+
+fn terminal_entries(workspace: &WeakEntity<Workspace>, cx: &App) -> Vec<TerminalContextEntry> {
+    let Some(workspace) = workspace.upgrade() else {
+        return Vec::new();
+    };
+
+    workspace
+        .read(cx)
+        .project()
+        .read(cx)
+        .local_terminal_handles()
+        .iter()
+        .filter_map(|terminal| terminal.upgrade())
+        .map(|terminal| TerminalContextEntry {
+            title: terminal.read(cx).title(true).into(),
+            terminal,
+        })
+        .collect()
+}
+
+pub struct TerminalContextPickerDelegate {
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<context_store::ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    entries: Vec<TerminalContextEntry>,
+    matches: Vec<TerminalContextEntry>,
+    selected_index: usize,
+}
+
+impl TerminalContextPickerDelegate {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        entries: Vec<TerminalContextEntry>,
+    ) -> Self {
+        TerminalContextPickerDelegate {
+            context_picker,
+            context_store,
+            confirm_behavior,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for TerminalContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search terminals…".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let titles = self
+            .entries
+            .iter()
+            .map(|entry| entry.title.clone())
+            .collect::<Vec<_>>();
+        let executor = cx.background_executor().clone();
+        let search_task = cx.background_executor().spawn(async move {
+            if query.is_empty() {
+                (0..titles.len()).collect::<Vec<_>>()
+            } else {
+                let candidates = titles
+                    .iter()
+                    .enumerate()
+                    .map(|(id, title)| StringMatchCandidate::new(id, title))
+                    .collect::<Vec<_>>();
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+
+                matches.into_iter().map(|mat| mat.candidate_id).collect()
+            }
+        });
+
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = search_task.await;
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches
+                    .into_iter()
+                    .map(|ix| this.delegate.entries[ix].clone())
+                    .collect();
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+
+        self.context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_terminal(entry.terminal.clone(), cx)
+            })
+            .ok();
+
+        match self.confirm_behavior {
+            ConfirmBehavior::KeepOpen => {}
+            ConfirmBehavior::Close => self.dismissed(window, cx),
+        }
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_terminal_context_entry(entry, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+pub fn render_terminal_context_entry(
+    entry: &TerminalContextEntry,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let added = context_store.upgrade().map_or(false, |ctx_store| {
+        ctx_store
+            .read(cx)
+            .includes_terminal(entry.terminal.entity_id())
+            .is_some()
+    });
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .child(
+            Icon::new(IconName::Terminal)
+                .size(IconSize::XSmall)
+                .color(Color::Muted),
+        )
+        .child(Label::new(entry.title.clone()).text_ellipsis())
+        .child(div().w_full())
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
@@ -1,22 +1,33 @@
+use std::fmt::Write as _;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{anyhow, bail, Result};
 use collections::{BTreeMap, HashMap, HashSet};
+use context_server::manager::ContextServerManager;
+use context_server::types::ResourceContentsType;
 use futures::{self, future, Future, FutureExt};
-use gpui::{App, AsyncApp, Context, Entity, SharedString, Task, WeakEntity};
-use language::Buffer;
+use gpui::{
+    App, AsyncApp, Context, Entity, EntityId, SharedString, Subscription, Task, WeakEntity,
+};
+use language::{Anchor, Buffer, BufferSnapshot, Point, ToOffset as _, ToPoint as _};
 use project::{ProjectPath, Worktree};
 use rope::Rope;
+use terminal::Terminal;
 use text::BufferId;
+use url::Url;
 use workspace::Workspace;
 
 use crate::context::{
-    AssistantContext, ContextBuffer, ContextId, ContextSnapshot, DirectoryContext,
-    FetchedUrlContext, FileContext, ThreadContext,
+    AssistantContext, ContextBuffer, ContextId, ContextSnapshot, DiagnosticsContext,
+    DirectoryContext, FetchedUrlContext, FileContext, GitDiffContext, ResourceContext,
+    SymbolContext, TerminalContext, ThreadContext,
 };
 use crate::context_strip::SuggestedContext;
+use crate::project_rules::ProjectRules;
 use crate::thread::{Thread, ThreadId};
+use crate::AssistantPanel;
 
 pub struct ContextStore {
     workspace: WeakEntity<Workspace>,
@@ -25,8 +36,20 @@ pub struct ContextStore {
     next_context_id: ContextId,
     files: BTreeMap<BufferId, ContextId>,
     directories: HashMap<PathBuf, ContextId>,
+    /// Symbols keyed by their buffer and range, as a buffer can have several symbols with the
+    /// same name, such as methods of different types.
+    symbols: HashMap<(BufferId, Range<Anchor>), ContextId>,
     threads: HashMap<ThreadId, ContextId>,
     fetched_urls: HashMap<String, ContextId>,
+    /// Diagnostics keyed by file, where `None` stands for the whole project.
+    diagnostics: HashMap<Option<ProjectPath>, ContextId>,
+    git_diffs: HashMap<GitDiffTarget, ContextId>,
+    terminals: HashMap<EntityId, ContextId>,
+    /// Resources keyed by the ID of the context server exposing them and their URI.
+    resources: HashMap<(Arc<str>, String), ContextId>,
+    /// The manager of the context servers the resources come from, which reports changes to
+    /// them.
+    context_server_manager: Option<(Entity<ContextServerManager>, Subscription)>,
 }
 
 impl ContextStore {
@@ -37,8 +60,14 @@ impl ContextStore {
             next_context_id: ContextId(0),
             files: BTreeMap::default(),
             directories: HashMap::default(),
+            symbols: HashMap::default(),
             threads: HashMap::default(),
             fetched_urls: HashMap::default(),
+            diagnostics: HashMap::default(),
+            git_diffs: HashMap::default(),
+            terminals: HashMap::default(),
+            resources: HashMap::default(),
+            context_server_manager: None,
         }
     }
 
@@ -52,12 +81,32 @@ impl ContextStore {
         &self.context
     }
 
-    pub fn clear(&mut self) {
+    /// Returns the rules of the project, as discovered by the thread store of the assistant panel.
+    pub fn project_rules(&self, cx: &App) -> Option<Entity<ProjectRules>> {
+        let workspace = self.workspace.upgrade()?;
+        let assistant_panel = workspace.read(cx).panel::<AssistantPanel>(cx)?;
+        Some(
+            assistant_panel
+                .read(cx)
+                .thread_store()
+                .read(cx)
+                .project_rules(),
+        )
+    }
+
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
+        for (server_id, uri) in std::mem::take(&mut self.resources).into_keys() {
+            self.unsubscribe_from_resource(&server_id, &uri, cx);
+        }
         self.context.clear();
         self.files.clear();
         self.directories.clear();
+        self.symbols.clear();
         self.threads.clear();
         self.fetched_urls.clear();
+        self.diagnostics.clear();
+        self.git_diffs.clear();
+        self.terminals.clear();
     }
 
     pub fn add_file_from_path(
@@ -82,10 +131,10 @@ impl ContextStore {
             let buffer_entity = open_buffer_task.await?;
             let buffer_id = this.update(&mut cx, |_, cx| buffer_entity.read(cx).remote_id())?;
 
-            let already_included = this.update(&mut cx, |this, _cx| {
+            let already_included = this.update(&mut cx, |this, cx| {
                 match this.will_include_buffer(buffer_id, &project_path.path) {
                     Some(FileInclusion::Direct(context_id)) => {
-                        this.remove_context(context_id);
+                        this.remove_context(context_id, cx);
                         true
                     }
                     Some(FileInclusion::InDirectory(_)) => true,
@@ -168,7 +217,7 @@ impl ContextStore {
 
         let already_included = if let Some(context_id) = self.includes_directory(&project_path.path)
         {
-            self.remove_context(context_id);
+            self.remove_context(context_id, cx);
             true
         } else {
             false
@@ -249,9 +298,189 @@ impl ContextStore {
             )));
     }
 
+    /// Adds the symbol spanning the given range of the buffer. Its text is refreshed when the
+    /// buffer changes.
+    pub fn add_symbol(
+        &mut self,
+        name: impl Into<SharedString>,
+        buffer: Entity<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut Context<Self>,
+    ) {
+        let name = name.into();
+        let buffer_id = buffer.read(cx).remote_id();
+        if let Some(context_id) = self.includes_symbol(buffer.read(cx), &range) {
+            self.remove_context(context_id, cx);
+            return;
+        }
+
+        let Some(text) = symbol_text(buffer.read(cx), &range) else {
+            return;
+        };
+        let version = buffer.read(cx).version();
+        let id = self.next_context_id.post_inc();
+        self.symbols.insert((buffer_id, range.clone()), id);
+        self.context.push(AssistantContext::Symbol(SymbolContext {
+            id,
+            name,
+            buffer,
+            range,
+            version,
+            text,
+        }));
+    }
+
+    /// Adds the diagnostics of the given file, or of the whole project if no file is given.
+    pub fn add_diagnostics(
+        &mut self,
+        project_path: Option<ProjectPath>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if let Some(context_id) = self.includes_diagnostics(project_path.as_ref()) {
+            self.remove_context(context_id, cx);
+            return Task::ready(Ok(()));
+        }
+
+        let Some(project) = self
+            .workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("failed to read project")));
+        };
+
+        let (name, paths) = match &project_path {
+            Some(project_path) => (
+                project_path.path.to_string_lossy().into_owned().into(),
+                vec![project_path.clone()],
+            ),
+            None => {
+                let mut paths = project
+                    .read(cx)
+                    .diagnostic_summaries(false, cx)
+                    .map(|(path, _, _)| path)
+                    .collect::<Vec<_>>();
+                paths.sort();
+                paths.dedup();
+                (SharedString::from("Project diagnostics"), paths)
+            }
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let open_buffers_task = project.update(&mut cx, |project, cx| {
+                future::join_all(
+                    paths
+                        .iter()
+                        .map(|path| project.open_buffer(path.clone(), cx)),
+                )
+            })?;
+            let buffers = open_buffers_task.await;
+
+            let mut text = String::new();
+            this.update(&mut cx, |_, cx| {
+                for (path, buffer) in paths.iter().zip(buffers) {
+                    let snapshot = buffer?.read(cx).snapshot();
+                    text.push_str(&diagnostics_text(&path.path, &snapshot));
+                }
+                anyhow::Ok(())
+            })??;
+            if text.is_empty() {
+                text.push_str("There are no diagnostics.\n");
+            }
+
+            this.update(&mut cx, |this, _cx| {
+                let id = this.next_context_id.post_inc();
+                this.diagnostics.insert(project_path, id);
+                this.context
+                    .push(AssistantContext::Diagnostics(DiagnosticsContext {
+                        id,
+                        name,
+                        text: text.into(),
+                    }));
+            })
+        })
+    }
+
+    /// Adds the diff of the uncommitted changes, or of a commit, in the active repository.
+    pub fn add_git_diff(
+        &mut self,
+        target: GitDiffTarget,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if let Some(context_id) = self.includes_git_diff(&target) {
+            self.remove_context(context_id, cx);
+            return Task::ready(Ok(()));
+        }
+
+        let Some(repository) = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).project().read(cx).active_repository(cx))
+        else {
+            return Task::ready(Err(anyhow!("no git repository found")));
+        };
+
+        let (name, diff_task) = match &target {
+            GitDiffTarget::Uncommitted => (
+                SharedString::from("Uncommitted changes"),
+                repository.read(cx).uncommitted_diff(cx),
+            ),
+            GitDiffTarget::Commit { sha, subject } => (
+                format!("{} {subject}", short_sha(sha)).into(),
+                repository.read(cx).show_commit(sha.clone(), cx),
+            ),
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let diff = diff_task.await?;
+            if diff.trim().is_empty() {
+                bail!("There are no changes to add.");
+            }
+
+            let mut text = String::with_capacity(diff.len() + 12);
+            text.push_str("```diff\n");
+            text.push_str(&diff);
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str("```\n");
+
+            this.update(&mut cx, |this, _cx| {
+                let id = this.next_context_id.post_inc();
+                this.git_diffs.insert(target, id);
+                this.context.push(AssistantContext::GitDiff(GitDiffContext {
+                    id,
+                    name,
+                    text: text.into(),
+                }));
+            })
+        })
+    }
+
+    /// Adds the recent output of the terminal.
+    pub fn add_terminal(&mut self, terminal: Entity<Terminal>, cx: &mut Context<Self>) {
+        if let Some(context_id) = self.includes_terminal(terminal.entity_id()) {
+            self.remove_context(context_id, cx);
+            return;
+        }
+
+        let terminal_ref = terminal.read(cx);
+        let name = terminal_ref.title(true).into();
+        let text = terminal_text(terminal_ref.last_n_non_empty_lines(MAX_TERMINAL_LINES));
+
+        let id = self.next_context_id.post_inc();
+        self.terminals.insert(terminal.entity_id(), id);
+        self.context
+            .push(AssistantContext::Terminal(TerminalContext {
+                id,
+                name,
+                text: text.into(),
+            }));
+    }
+
     pub fn add_thread(&mut self, thread: Entity<Thread>, cx: &mut Context<Self>) {
         if let Some(context_id) = self.includes_thread(&thread.read(cx).id()) {
-            self.remove_context(context_id);
+            self.remove_context(context_id, cx);
         } else {
             self.insert_thread(thread, cx);
         }
@@ -284,6 +513,111 @@ impl ContextStore {
             }));
     }
 
+    /// Adds the resource with the given URI, exposed by the given context server, and keeps its
+    /// text up to date while it is included.
+    pub fn add_resource(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        server_id: Arc<str>,
+        uri: String,
+        name: impl Into<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if let Some(context_id) = self.includes_resource(&server_id, &uri) {
+            self.remove_context(context_id, cx);
+            return Task::ready(Ok(()));
+        }
+
+        let name = name.into();
+        let text_task = read_resource_text(&context_server_manager, &server_id, &uri, cx);
+        cx.spawn(|this, mut cx| async move {
+            let text = text_task.await?;
+            this.update(&mut cx, |this, cx| {
+                if this.includes_resource(&server_id, &uri).is_some() {
+                    return;
+                }
+                this.set_context_server_manager(context_server_manager, cx);
+                this.subscribe_to_resource(&server_id, &uri, cx);
+
+                let id = this.next_context_id.post_inc();
+                this.resources.insert((server_id.clone(), uri.clone()), id);
+                this.context
+                    .push(AssistantContext::Resource(ResourceContext {
+                        id,
+                        server_id,
+                        uri,
+                        name,
+                        text,
+                    }));
+            })
+        })
+    }
+
+    fn set_context_server_manager(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        cx: &mut Context<Self>,
+    ) {
+        if self
+            .context_server_manager
+            .as_ref()
+            .is_some_and(|(manager, _)| *manager == context_server_manager)
+        {
+            return;
+        }
+
+        let subscription = cx.subscribe(&context_server_manager, Self::handle_context_server_event);
+        self.context_server_manager = Some((context_server_manager, subscription));
+    }
+
+    fn handle_context_server_event(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        event: &context_server::manager::Event,
+        cx: &mut Context<Self>,
+    ) {
+        let context_server::manager::Event::ResourceUpdated { server_id, uri } = event else {
+            return;
+        };
+        let Some(id) = self.includes_resource(server_id, uri) else {
+            return;
+        };
+
+        let text_task = read_resource_text(&context_server_manager, server_id, uri, cx);
+        cx.spawn(|this, mut cx| async move {
+            let text = text_task.await?;
+            this.update(&mut cx, |this, _cx| {
+                let Some(AssistantContext::Resource(resource)) =
+                    this.context.iter_mut().find(|context| context.id() == id)
+                else {
+                    return;
+                };
+                resource.text = text;
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn subscribe_to_resource(&self, server_id: &Arc<str>, uri: &str, cx: &mut Context<Self>) {
+        if let Some((context_server_manager, _)) = &self.context_server_manager {
+            context_server_manager
+                .update(cx, |manager, cx| {
+                    manager.subscribe_to_resource(server_id, uri, cx)
+                })
+                .detach_and_log_err(cx);
+        }
+    }
+
+    fn unsubscribe_from_resource(&self, server_id: &Arc<str>, uri: &str, cx: &mut Context<Self>) {
+        if let Some((context_server_manager, _)) = &self.context_server_manager {
+            context_server_manager
+                .update(cx, |manager, cx| {
+                    manager.unsubscribe_from_resource(server_id, uri, cx)
+                })
+                .detach_and_log_err(cx);
+        }
+    }
+
     pub fn accept_suggested_context(
         &mut self,
         suggested: &SuggestedContext,
@@ -308,7 +642,7 @@ impl ContextStore {
         Task::ready(Ok(()))
     }
 
-    pub fn remove_context(&mut self, id: ContextId) {
+    pub fn remove_context(&mut self, id: ContextId, cx: &mut Context<Self>) {
         let Some(ix) = self.context.iter().position(|context| context.id() == id) else {
             return;
         };
@@ -320,12 +654,28 @@ impl ContextStore {
             AssistantContext::Directory(_) => {
                 self.directories.retain(|_, context_id| *context_id != id);
             }
+            AssistantContext::Symbol(_) => {
+                self.symbols.retain(|_, context_id| *context_id != id);
+            }
             AssistantContext::FetchedUrl(_) => {
                 self.fetched_urls.retain(|_, context_id| *context_id != id);
             }
+            AssistantContext::Diagnostics(_) => {
+                self.diagnostics.retain(|_, context_id| *context_id != id);
+            }
+            AssistantContext::GitDiff(_) => {
+                self.git_diffs.retain(|_, context_id| *context_id != id);
+            }
+            AssistantContext::Terminal(_) => {
+                self.terminals.retain(|_, context_id| *context_id != id);
+            }
             AssistantContext::Thread(_) => {
                 self.threads.retain(|_, context_id| *context_id != id);
             }
+            AssistantContext::Resource(resource) => {
+                self.resources.retain(|_, context_id| *context_id != id);
+                self.unsubscribe_from_resource(&resource.server_id, &resource.uri, cx);
+            }
         }
     }
 
@@ -383,6 +733,33 @@ impl ContextStore {
         self.directories.get(path).copied()
     }
 
+    /// Returns the context of the symbol spanning the given range of the buffer, comparing the
+    /// ranges' offsets as anchors created at different times can differ for the same position.
+    pub fn includes_symbol(&self, buffer: &Buffer, range: &Range<Anchor>) -> Option<ContextId> {
+        let buffer_id = buffer.remote_id();
+        let to_offsets =
+            |range: &Range<Anchor>| range.start.to_offset(buffer)..range.end.to_offset(buffer);
+        let range = to_offsets(range);
+        self.symbols
+            .iter()
+            .find(|((symbol_buffer_id, symbol_range), _)| {
+                *symbol_buffer_id == buffer_id && to_offsets(symbol_range) == range
+            })
+            .map(|(_, context_id)| *context_id)
+    }
+
+    pub fn includes_diagnostics(&self, project_path: Option<&ProjectPath>) -> Option<ContextId> {
+        self.diagnostics.get(&project_path.cloned()).copied()
+    }
+
+    pub fn includes_git_diff(&self, target: &GitDiffTarget) -> Option<ContextId> {
+        self.git_diffs.get(target).copied()
+    }
+
+    pub fn includes_terminal(&self, terminal_id: EntityId) -> Option<ContextId> {
+        self.terminals.get(&terminal_id).copied()
+    }
+
     pub fn includes_thread(&self, thread_id: &ThreadId) -> Option<ContextId> {
         self.threads.get(thread_id).copied()
     }
@@ -391,6 +768,12 @@ impl ContextStore {
         self.fetched_urls.get(url).copied()
     }
 
+    pub fn includes_resource(&self, server_id: &Arc<str>, uri: &str) -> Option<ContextId> {
+        self.resources
+            .get(&(server_id.clone(), uri.to_string()))
+            .copied()
+    }
+
     /// Replaces the context that matches the ID of the new context, if any match.
     fn replace_context(&mut self, new_context: AssistantContext) {
         let id = new_context.id();
@@ -411,8 +794,13 @@ impl ContextStore {
                     buffer_path_log_err(buffer).map(|p| p.to_path_buf())
                 }
                 AssistantContext::Directory(_)
+                | AssistantContext::Symbol(_)
                 | AssistantContext::FetchedUrl(_)
-                | AssistantContext::Thread(_) => None,
+                | AssistantContext::Diagnostics(_)
+                | AssistantContext::GitDiff(_)
+                | AssistantContext::Terminal(_)
+                | AssistantContext::Thread(_)
+                | AssistantContext::Resource(_) => None,
             })
             .collect()
     }
@@ -422,6 +810,18 @@ impl ContextStore {
     }
 }
 
+/// The number of lines of a terminal's output that are added as context.
+const MAX_TERMINAL_LINES: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GitDiffTarget {
+    Uncommitted,
+    Commit {
+        sha: SharedString,
+        subject: SharedString,
+    },
+}
+
 pub enum FileInclusion {
     Direct(ContextId),
     InDirectory(PathBuf),
@@ -511,6 +911,73 @@ fn to_fenced_codeblock(path: &Path, content: Rope) -> SharedString {
     buffer.into()
 }
 
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+/// Returns the lines spanned by the given range as a fenced code block, labeled with the path of
+/// the buffer and the line numbers.
+fn symbol_text(buffer: &Buffer, range: &Range<Anchor>) -> Option<SharedString> {
+    let path = buffer_path_log_err(buffer)?;
+    let start_row = range.start.to_point(buffer).row;
+    let end_row = range.end.to_point(buffer).row;
+    let content = buffer
+        .text_for_range(Point::new(start_row, 0)..Point::new(end_row, buffer.line_len(end_row)))
+        .collect::<String>();
+
+    let mut text = String::from("```");
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        text.push_str(extension);
+        text.push(' ');
+    }
+    write!(
+        text,
+        "{}#L{}-{}\n{content}\n```\n",
+        path.to_string_lossy(),
+        start_row + 1,
+        end_row + 1
+    )
+    .ok()?;
+    Some(text.into())
+}
+
+/// Returns the lines of terminal output as a fenced code block.
+fn terminal_text(lines: Vec<String>) -> String {
+    let mut text = String::from("```\n");
+    for line in lines {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text.push_str("```\n");
+    text
+}
+
+/// Returns one line for each primary diagnostic in the buffer.
+fn diagnostics_text(path: &Path, buffer: &BufferSnapshot) -> String {
+    let mut text = String::new();
+    for entry in buffer.diagnostics_in_range::<_, Point>(0..buffer.len(), false) {
+        if !entry.diagnostic.is_primary {
+            continue;
+        }
+        let severity = match entry.diagnostic.severity {
+            lsp::DiagnosticSeverity::ERROR => "error",
+            lsp::DiagnosticSeverity::WARNING => "warning",
+            lsp::DiagnosticSeverity::INFORMATION => "info",
+            _ => "hint",
+        };
+        writeln!(
+            text,
+            "{}:{}:{}: {severity}: {}",
+            path.to_string_lossy(),
+            entry.range.start.row + 1,
+            entry.range.start.column + 1,
+            entry.diagnostic.message
+        )
+        .ok();
+    }
+    text
+}
+
 fn collect_files_in_path(worktree: &Worktree, path: &Path) -> Vec<Arc<Path>> {
     let mut files = Vec::new();
 
@@ -544,6 +1011,12 @@ pub fn refresh_context_store_text(
                     tasks.push(task);
                 }
             }
+            AssistantContext::Symbol(symbol_context) => {
+                let context_store = context_store.clone();
+                if let Some(task) = refresh_symbol_text(context_store, symbol_context, cx) {
+                    tasks.push(task);
+                }
+            }
             AssistantContext::Thread(thread_context) => {
                 let context_store = context_store.clone();
                 tasks.push(refresh_thread_text(context_store, thread_context, cx));
@@ -552,6 +1025,13 @@ pub fn refresh_context_store_text(
             // and doing the caching properly could be tricky (unless it's already handled by
             // the HttpClient?).
             AssistantContext::FetchedUrl(_) => {}
+            // Diagnostics, diffs and terminal output are kept as they were when they were added,
+            // which is what the user saw when choosing to add them.
+            AssistantContext::Diagnostics(_)
+            | AssistantContext::GitDiff(_)
+            | AssistantContext::Terminal(_) => {}
+            // Resources are kept up to date by the subscriptions to their context servers.
+            AssistantContext::Resource(_) => {}
         }
     }
 
@@ -618,6 +1098,38 @@ fn refresh_directory_text(
     }))
 }
 
+fn refresh_symbol_text(
+    context_store: Entity<ContextStore>,
+    symbol_context: &SymbolContext,
+    cx: &App,
+) -> Option<Task<()>> {
+    let buffer = symbol_context.buffer.read(cx);
+    if !buffer.version.changed_since(&symbol_context.version) {
+        return None;
+    }
+
+    let text = symbol_text(buffer, &symbol_context.range)?;
+    let version = buffer.version();
+    let id = symbol_context.id;
+    let name = symbol_context.name.clone();
+    let buffer = symbol_context.buffer.clone();
+    let range = symbol_context.range.clone();
+    Some(cx.spawn(|mut cx| async move {
+        context_store
+            .update(&mut cx, |context_store, _| {
+                context_store.replace_context(AssistantContext::Symbol(SymbolContext {
+                    id,
+                    name,
+                    buffer,
+                    range,
+                    version,
+                    text,
+                }));
+            })
+            .ok();
+    }))
+}
+
 fn refresh_thread_text(
     context_store: Entity<ContextStore>,
     thread_context: &ThreadContext,
@@ -639,6 +1151,51 @@ fn refresh_thread_text(
     })
 }
 
+/// Reads the contents of a context server's resource, as text for the model.
+pub fn read_resource_text(
+    context_server_manager: &Entity<ContextServerManager>,
+    server_id: &Arc<str>,
+    uri: &str,
+    cx: &App,
+) -> Task<Result<SharedString>> {
+    let Some(client) = context_server_manager
+        .read(cx)
+        .get_server(server_id)
+        .and_then(|server| server.client())
+    else {
+        return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+    };
+    let uri = match Url::parse(uri) {
+        Ok(uri) => uri,
+        Err(error) => return Task::ready(Err(error.into())),
+    };
+
+    cx.spawn(|_| async move {
+        let response = client.read_resource(uri).await?;
+        let mut text = String::new();
+        for contents in response.contents {
+            match contents {
+                ResourceContentsType::Text(contents) => {
+                    text.push_str(&format!("```{}\n", contents.uri));
+                    text.push_str(&contents.text);
+                    if !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    text.push_str("```\n");
+                }
+                ResourceContentsType::Blob(contents) => {
+                    let mime_type = contents.mime_type.as_deref().unwrap_or("unknown type");
+                    text.push_str(&format!(
+                        "{} contains binary data ({mime_type}) that was not included.\n",
+                        contents.uri
+                    ));
+                }
+            }
+        }
+        Ok(text.into())
+    })
+}
+
 fn refresh_context_buffer(
     context_buffer: &ContextBuffer,
     cx: &App,
@@ -657,3 +1214,327 @@ fn refresh_context_buffer(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ContextKind;
+    use git::repository::CommitSummary;
+    use gpui::{AppContext as _, TestAppContext, VisualTestContext};
+    use indoc::indoc;
+    use language::LanguageServerId;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_symbol_context(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                "src": {
+                    "lib.rs": indoc! {"
+                        struct A;
+
+                        impl A {
+                            fn new() -> Self {
+                                A
+                            }
+                        }
+
+                        struct B;
+
+                        impl B {
+                            fn new() -> Self {
+                                B
+                            }
+                        }
+                    "},
+                },
+            }),
+        )
+        .await;
+        let (project, context_store, cx) = open_context_store(fs, cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/project/src/lib.rs", cx)
+            })
+            .await
+            .unwrap();
+        let symbol_range = |start_row, end_row, cx: &mut VisualTestContext| {
+            buffer.read_with(cx, |buffer, _| {
+                buffer.anchor_before(Point::new(start_row, 4))
+                    ..buffer.anchor_after(Point::new(end_row, 5))
+            })
+        };
+
+        // Symbols with the same name in the same buffer are kept apart by their ranges.
+        let first_range = symbol_range(3, 5, cx);
+        let second_range = symbol_range(12, 14, cx);
+        context_store.update(cx, |store, cx| {
+            store.add_symbol("new", buffer.clone(), first_range, cx);
+            store.add_symbol("new", buffer.clone(), second_range, cx);
+        });
+        context_store.read_with(cx, |store, cx| {
+            let snapshots = store.snapshot(cx).collect::<Vec<_>>();
+            assert_eq!(snapshots.len(), 2);
+            assert!(snapshots
+                .iter()
+                .all(|snapshot| snapshot.kind == ContextKind::Symbol && snapshot.name == "new"));
+            assert_eq!(
+                snapshots[0].text.as_ref(),
+                [SharedString::from(
+                    "```rs src/lib.rs#L4-6\n    fn new() -> Self {\n        A\n    }\n```\n"
+                )]
+            );
+            assert_eq!(
+                snapshots[1].text.as_ref(),
+                [SharedString::from(
+                    "```rs src/lib.rs#L13-15\n    fn new() -> Self {\n        B\n    }\n```\n"
+                )]
+            );
+        });
+
+        // Adding a symbol again, even with newly created anchors, removes it.
+        let first_range = symbol_range(3, 5, cx);
+        context_store.update(cx, |store, cx| {
+            store.add_symbol("new", buffer.clone(), first_range, cx);
+        });
+        context_store.read_with(cx, |store, cx| {
+            let snapshots = store.snapshot(cx).collect::<Vec<_>>();
+            assert_eq!(snapshots.len(), 1);
+            assert!(snapshots[0].text[0].contains("B\n"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_diagnostics_context(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                "a.rs": "fn a() {\n    let x = 1;\n}\n",
+                "b.rs": "fn b() {}\n",
+            }),
+        )
+        .await;
+        let (project, context_store, cx) = open_context_store(fs, cx).await;
+        let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+        lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_diagnostics(
+                    LanguageServerId(0),
+                    lsp::PublishDiagnosticsParams {
+                        uri: lsp::Url::from_file_path("/project/a.rs").unwrap(),
+                        version: None,
+                        diagnostics: vec![lsp::Diagnostic {
+                            range: lsp::Range::new(
+                                lsp::Position::new(1, 8),
+                                lsp::Position::new(1, 9),
+                            ),
+                            severity: Some(lsp::DiagnosticSeverity::WARNING),
+                            message: "unused variable: `x`".to_string(),
+                            ..Default::default()
+                        }],
+                    },
+                    &[],
+                    cx,
+                )
+                .unwrap();
+        });
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let project_path = |path: &str| ProjectPath {
+            worktree_id,
+            path: Path::new(path).into(),
+        };
+
+        context_store
+            .update(cx, |store, cx| store.add_diagnostics(None, cx))
+            .await
+            .unwrap();
+        context_store
+            .update(cx, |store, cx| {
+                store.add_diagnostics(Some(project_path("b.rs")), cx)
+            })
+            .await
+            .unwrap();
+        context_store.read_with(cx, |store, cx| {
+            let snapshots = store.snapshot(cx).collect::<Vec<_>>();
+            assert_eq!(snapshots.len(), 2);
+            assert_eq!(snapshots[0].kind, ContextKind::Diagnostics);
+            assert_eq!(snapshots[0].name, "Project diagnostics");
+            assert_eq!(
+                snapshots[0].text.as_ref(),
+                [SharedString::from(
+                    "a.rs:2:9: warning: unused variable: `x`\n"
+                )]
+            );
+            assert_eq!(snapshots[1].name, "b.rs");
+            assert_eq!(
+                snapshots[1].text.as_ref(),
+                [SharedString::from("There are no diagnostics.\n")]
+            );
+        });
+
+        // Adding the same diagnostics again removes them.
+        context_store
+            .update(cx, |store, cx| store.add_diagnostics(None, cx))
+            .await
+            .unwrap();
+        context_store.read_with(cx, |store, cx| {
+            let names = store
+                .snapshot(cx)
+                .map(|snapshot| snapshot.name)
+                .collect::<Vec<_>>();
+            assert_eq!(names, ["b.rs"]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_git_diff_context(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".git": {},
+                "a.txt": "two\n",
+            }),
+        )
+        .await;
+        let dot_git = Path::new("/project/.git");
+        fs.set_head_for_repo(dot_git, &[("a.txt".into(), "one\n".into())]);
+        let (project, context_store, cx) = open_context_store(fs.clone(), cx).await;
+        cx.run_until_parked();
+
+        context_store
+            .update(cx, |store, cx| {
+                store.add_git_diff(GitDiffTarget::Uncommitted, cx)
+            })
+            .await
+            .unwrap();
+        context_store.read_with(cx, |store, cx| {
+            let snapshots = store.snapshot(cx).collect::<Vec<_>>();
+            assert_eq!(snapshots.len(), 1);
+            assert_eq!(snapshots[0].kind, ContextKind::GitDiff);
+            assert_eq!(snapshots[0].name, "Uncommitted changes");
+            assert_eq!(
+                snapshots[0].text.as_ref(),
+                [SharedString::from(indoc! {"
+                    ```diff
+                    diff --git a/a.txt b/a.txt
+                    --- a/a.txt
+                    +++ b/a.txt
+                    -one
+                    +two
+                    ```
+                "})]
+            );
+        });
+
+        // Commit the change, recording how `git show` renders the commit.
+        fs.with_git_state(dot_git, true, |state| {
+            state
+                .head_contents
+                .insert("a.txt".into(), "two\n".into());
+            state.commits.push((
+                CommitSummary {
+                    sha: format!("{:040x}", 1).into(),
+                    subject: "Change a.txt".into(),
+                },
+                "commit 0000000000000000000000000000000000000001\n\n    Change a.txt\n    \n    Details\n\n\
+                 diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n-one\n+two\n"
+                    .into(),
+            ));
+        });
+        cx.run_until_parked();
+        let repository = project
+            .read_with(cx, |project, cx| project.active_repository(cx))
+            .unwrap();
+        let commits = repository
+            .read_with(cx, |repository, cx| repository.recent_commits(1, cx))
+            .await
+            .unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].subject, "Change a.txt");
+
+        // Adding the uncommitted changes again removes them, and once they're committed there is
+        // nothing left to add.
+        context_store
+            .update(cx, |store, cx| {
+                store.add_git_diff(GitDiffTarget::Uncommitted, cx)
+            })
+            .await
+            .unwrap();
+        let result = context_store
+            .update(cx, |store, cx| {
+                store.add_git_diff(GitDiffTarget::Uncommitted, cx)
+            })
+            .await;
+        assert!(result.is_err());
+
+        context_store
+            .update(cx, |store, cx| {
+                store.add_git_diff(
+                    GitDiffTarget::Commit {
+                        sha: commits[0].sha.clone(),
+                        subject: commits[0].subject.clone(),
+                    },
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        context_store.read_with(cx, |store, cx| {
+            let snapshots = store.snapshot(cx).collect::<Vec<_>>();
+            assert_eq!(snapshots.len(), 1);
+            assert_eq!(snapshots[0].name, "0000000 Change a.txt");
+            assert!(snapshots[0].text[0].contains("    Change a.txt\n    \n    Details\n"));
+            assert!(snapshots[0].text[0].contains("-one\n+two\n"));
+        });
+    }
+
+    #[gpui::test]
+    fn test_terminal_context(_cx: &mut TestAppContext) {
+        let text = terminal_text(vec!["$ cargo test".into(), "test result: ok".into()]);
+        assert_eq!(text, "```\n$ cargo test\ntest result: ok\n```\n");
+
+        let snapshot = TerminalContext {
+            id: ContextId(0),
+            name: "zsh".into(),
+            text: text.clone().into(),
+        }
+        .snapshot();
+        assert_eq!(snapshot.kind, ContextKind::Terminal);
+        assert_eq!(snapshot.name, "zsh");
+        assert_eq!(snapshot.text.as_ref(), [SharedString::from(text)]);
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    async fn open_context_store(
+        fs: Arc<FakeFs>,
+        cx: &mut TestAppContext,
+    ) -> (
+        Entity<Project>,
+        Entity<ContextStore>,
+        &mut VisualTestContext,
+    ) {
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let context_store = cx.new(|_| ContextStore::new(workspace.downgrade()));
+        (project, context_store, cx)
+    }
+}
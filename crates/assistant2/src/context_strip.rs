@@ -1,3 +1,4 @@
+use std::iter;
 use std::rc::Rc;
 
 use collections::HashSet;
@@ -15,6 +16,7 @@ use workspace::{notifications::NotifyResultExt, Workspace};
 use crate::context::ContextKind;
 use crate::context_picker::{ConfirmBehavior, ContextPicker};
 use crate::context_store::ContextStore;
+use crate::project_rules::ProjectRules;
 use crate::thread::Thread;
 use crate::thread_store::ThreadStore;
 use crate::ui::ContextPill;
@@ -25,6 +27,7 @@ use crate::{
 
 pub struct ContextStrip {
     context_store: Entity<ContextStore>,
+    project_rules: Option<Entity<ProjectRules>>,
     pub context_picker: Entity<ContextPicker>,
     context_picker_menu_handle: PopoverMenuHandle<ContextPicker>,
     focus_handle: FocusHandle,
@@ -59,16 +62,24 @@ impl ContextStrip {
             )
         });
 
+        let project_rules = thread_store
+            .and_then(|thread_store| thread_store.upgrade())
+            .map(|thread_store| thread_store.read(cx).project_rules());
+
         let focus_handle = cx.focus_handle();
 
-        let subscriptions = vec![
+        let mut subscriptions = vec![
             cx.subscribe_in(&context_picker, window, Self::handle_context_picker_event),
             cx.on_focus(&focus_handle, window, Self::handle_focus),
             cx.on_blur(&focus_handle, window, Self::handle_blur),
         ];
+        if let Some(project_rules) = &project_rules {
+            subscriptions.push(cx.observe(project_rules, |_, _, cx| cx.notify()));
+        }
 
         Self {
             context_store: context_store.clone(),
+            project_rules,
             context_picker,
             context_picker_menu_handle,
             focus_handle,
@@ -289,9 +300,9 @@ impl ContextStrip {
         if let Some(index) = self.focused_index {
             let mut is_empty = false;
 
-            self.context_store.update(cx, |this, _cx| {
+            self.context_store.update(cx, |this, cx| {
                 if let Some(item) = this.context().get(index) {
-                    this.remove_context(item.id());
+                    this.remove_context(item.id(), cx);
                 }
 
                 is_empty = this.context().is_empty();
@@ -306,6 +317,42 @@ impl ContextStrip {
         }
     }
 
+    /// Renders the rules files of the project that are included in every request.
+    fn render_project_rules(&self, cx: &App) -> Option<impl IntoElement> {
+        let rules = self.project_rules.as_ref()?.read(cx).rules();
+        let label = match rules {
+            [] => return None,
+            [rules_file] => rules_file
+                .path
+                .file_name()
+                .map_or(rules_file.full_path.clone(), |name| {
+                    name.to_string_lossy().into_owned().into()
+                }),
+            _ => format!("{} Rules", rules.len()).into(),
+        };
+        let tooltip = iter::once("Active Rules".to_string())
+            .chain(
+                rules
+                    .iter()
+                    .map(|rules_file| rules_file.full_path.to_string()),
+            )
+            .join("\n");
+
+        Some(
+            h_flex()
+                .id("project-rules")
+                .gap_1()
+                .px_1()
+                .child(
+                    Icon::new(IconName::Book)
+                        .size(IconSize::XSmall)
+                        .color(Color::Muted),
+                )
+                .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+                .tooltip(Tooltip::text(tooltip)),
+        )
+    }
+
     fn is_suggested_focused<T>(&self, context: &Vec<T>) -> bool {
         // We only suggest one item after the actual context
         self.focused_index == Some(context.len())
@@ -403,38 +450,43 @@ impl Render for ContextStrip {
                 }
             })
             .child(
-                PopoverMenu::new("context-picker")
-                    .menu(move |window, cx| {
-                        context_picker.update(cx, |this, cx| {
-                            this.init(window, cx);
-                        });
-
-                        Some(context_picker.clone())
-                    })
-                    .trigger_with_tooltip(
-                        IconButton::new("add-context", IconName::Plus)
-                            .icon_size(IconSize::Small)
-                            .style(ui::ButtonStyle::Filled),
-                        {
-                            let focus_handle = focus_handle.clone();
-                            move |window, cx| {
-                                Tooltip::for_action_in(
-                                    "Add Context",
-                                    &ToggleContextPicker,
-                                    &focus_handle,
-                                    window,
-                                    cx,
-                                )
-                            }
-                        },
+                h_flex()
+                    .gap_1()
+                    .child(
+                        PopoverMenu::new("context-picker")
+                            .menu(move |window, cx| {
+                                context_picker.update(cx, |this, cx| {
+                                    this.init(window, cx);
+                                });
+
+                                Some(context_picker.clone())
+                            })
+                            .trigger_with_tooltip(
+                                IconButton::new("add-context", IconName::Plus)
+                                    .icon_size(IconSize::Small)
+                                    .style(ui::ButtonStyle::Filled),
+                                {
+                                    let focus_handle = focus_handle.clone();
+                                    move |window, cx| {
+                                        Tooltip::for_action_in(
+                                            "Add Context",
+                                            &ToggleContextPicker,
+                                            &focus_handle,
+                                            window,
+                                            cx,
+                                        )
+                                    }
+                                },
+                            )
+                            .attach(gpui::Corner::TopLeft)
+                            .anchor(gpui::Corner::BottomLeft)
+                            .offset(gpui::Point {
+                                x: px(0.0),
+                                y: px(-2.0),
+                            })
+                            .with_handle(self.context_picker_menu_handle.clone()),
                     )
-                    .attach(gpui::Corner::TopLeft)
-                    .anchor(gpui::Corner::BottomLeft)
-                    .offset(gpui::Point {
-                        x: px(0.0),
-                        y: px(-2.0),
-                    })
-                    .with_handle(self.context_picker_menu_handle.clone()),
+                    .children(self.render_project_rules(cx)),
             )
             .when(context.is_empty() && suggested_context.is_none(), {
                 |parent| {
@@ -468,8 +520,8 @@ impl Render for ContextStrip {
                         let id = context.id;
                         let context_store = self.context_store.clone();
                         Rc::new(cx.listener(move |_this, _event, _window, cx| {
-                            context_store.update(cx, |this, _cx| {
-                                this.remove_context(id);
+                            context_store.update(cx, |this, cx| {
+                                this.remove_context(id, cx);
                             });
                             cx.notify();
                         }))
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use assistant_context_editor::{describe_token_usage, format_token_usage};
+use assistant_settings::{AssistantSettings, ToolApproval};
 use editor::actions::MoveUp;
 use editor::{Editor, EditorElement, EditorEvent, EditorStyle};
 use fs::Fs;
@@ -7,7 +9,7 @@ use gpui::{
     pulsating_between, Animation, AnimationExt, App, DismissEvent, Entity, Focusable, Subscription,
     TextStyle, WeakEntity,
 };
-use language_model::{LanguageModelRegistry, LanguageModelRequestTool};
+use language_model::{LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestTool};
 use language_model_selector::LanguageModelSelector;
 use rope::Point;
 use settings::Settings;
@@ -23,7 +25,7 @@ use crate::assistant_model_selector::AssistantModelSelector;
 use crate::context_picker::{ConfirmBehavior, ContextPicker};
 use crate::context_store::{refresh_context_store_text, ContextStore};
 use crate::context_strip::{ContextStrip, ContextStripEvent, SuggestContextKind};
-use crate::thread::{RequestKind, Thread};
+use crate::thread::{RequestKind, Thread, ThreadEvent};
 use crate::thread_store::ThreadStore;
 use crate::{Chat, ChatMode, RemoveAllContext, ToggleContextPicker, ToggleModelSelector};
 
@@ -96,6 +98,7 @@ impl MessageEditor {
                 Self::handle_inline_context_picker_event,
             ),
             cx.subscribe_in(&context_strip, window, Self::handle_context_strip_event),
+            cx.subscribe(&thread, Self::handle_thread_event),
         ];
 
         Self {
@@ -150,7 +153,7 @@ impl MessageEditor {
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.context_store.update(cx, |store, _cx| store.clear());
+        self.context_store.update(cx, |store, cx| store.clear(cx));
         cx.notify();
     }
 
@@ -205,21 +208,7 @@ impl MessageEditor {
                 .update(&mut cx, |thread, cx| {
                     let context = context_store.read(cx).snapshot(cx).collect::<Vec<_>>();
                     thread.insert_user_message(user_message, context, cx);
-                    let mut request = thread.to_completion_request(request_kind, cx);
-
-                    if use_tools {
-                        request.tools = thread
-                            .tools()
-                            .tools(cx)
-                            .into_iter()
-                            .map(|tool| LanguageModelRequestTool {
-                                name: tool.name(),
-                                description: tool.description(),
-                                input_schema: tool.input_schema(),
-                            })
-                            .collect();
-                    }
-
+                    let request = completion_request(thread, request_kind, use_tools, cx);
                     thread.stream_completion(request, model, cx)
                 })
                 .ok();
@@ -227,6 +216,32 @@ impl MessageEditor {
         .detach();
     }
 
+    /// Requests a new completion for the thread as it is, such as after one of its
+    /// messages was edited.
+    fn regenerate(&mut self, cx: &mut Context<Self>) {
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            return;
+        };
+
+        let use_tools = self.use_tools;
+        self.thread.update(cx, |thread, cx| {
+            let request = completion_request(thread, RequestKind::Chat, use_tools, cx);
+            thread.stream_completion(request, model, cx)
+        });
+    }
+
+    fn handle_thread_event(
+        &mut self,
+        _thread: Entity<Thread>,
+        event: &ThreadEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            ThreadEvent::MessageEdited(_) => self.regenerate(cx),
+            _ => {}
+        }
+    }
+
     fn handle_editor_event(
         &mut self,
         editor: &Entity<Editor>,
@@ -295,6 +310,32 @@ impl MessageEditor {
     }
 }
 
+fn completion_request(
+    thread: &Thread,
+    request_kind: RequestKind,
+    use_tools: bool,
+    cx: &App,
+) -> LanguageModelRequest {
+    let mut request = thread.to_completion_request(request_kind, cx);
+
+    if use_tools {
+        let settings = AssistantSettings::get_global(cx);
+        request.tools = thread
+            .tools()
+            .tools(cx)
+            .into_iter()
+            .filter(|tool| settings.tool_approval(&tool.name()) != ToolApproval::Deny)
+            .map(|tool| LanguageModelRequestTool {
+                name: tool.name(),
+                description: tool.description(),
+                input_schema: tool.input_schema(),
+            })
+            .collect();
+    }
+
+    request
+}
+
 impl Focusable for MessageEditor {
     fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
         self.editor.focus_handle(cx)
@@ -309,6 +350,8 @@ impl Render for MessageEditor {
         let inline_context_picker = self.inline_context_picker.clone();
         let bg_color = cx.theme().colors().editor_background;
         let is_streaming_completion = self.thread.read(cx).is_streaming();
+        let token_usage = self.thread.read(cx).cumulative_token_usage();
+        let estimated_cost = self.thread.read(cx).estimated_cost(cx);
         let button_width = px(64.);
         let is_model_selected = self.is_model_selected(cx);
         let is_editor_empty = self.is_editor_empty(cx);
@@ -381,20 +424,44 @@ impl Render for MessageEditor {
                         h_flex()
                             .justify_between()
                             .child(
-                                Switch::new("use-tools", self.use_tools.into())
-                                    .label("Tools")
-                                    .on_click(cx.listener(|this, selection, _window, _cx| {
-                                        this.use_tools = match selection {
-                                            ToggleState::Selected => true,
-                                            ToggleState::Unselected
-                                            | ToggleState::Indeterminate => false,
-                                        };
-                                    }))
-                                    .key_binding(KeyBinding::for_action_in(
-                                        &ChatMode,
-                                        &focus_handle,
-                                        window,
-                                    )),
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        Switch::new("use-tools", self.use_tools.into())
+                                            .label("Tools")
+                                            .on_click(cx.listener(
+                                                |this, selection, _window, _cx| {
+                                                    this.use_tools = match selection {
+                                                        ToggleState::Selected => true,
+                                                        ToggleState::Unselected
+                                                        | ToggleState::Indeterminate => false,
+                                                    };
+                                                },
+                                            ))
+                                            .key_binding(KeyBinding::for_action_in(
+                                                &ChatMode,
+                                                &focus_handle,
+                                                window,
+                                            )),
+                                    )
+                                    .when(token_usage.total_tokens() > 0, |parent| {
+                                        parent.child(
+                                            div()
+                                                .id("token-usage")
+                                                .child(
+                                                    Label::new(format_token_usage(
+                                                        &token_usage,
+                                                        estimated_cost,
+                                                    ))
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted),
+                                                )
+                                                .tooltip(Tooltip::text(describe_token_usage(
+                                                    &token_usage,
+                                                    estimated_cost,
+                                                ))),
+                                        )
+                                    }),
                             )
                             .child(h_flex().gap_1().child(self.model_selector.clone()).child(
                                 if is_streaming_completion {
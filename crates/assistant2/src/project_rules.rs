@@ -0,0 +1,363 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use collections::HashMap;
+use gpui::{Context, Entity, SharedString, Subscription, Task};
+use language_model::{LanguageModelRequestMessage, MessageContent, Role};
+use project::{PathChange, Project, WorktreeId};
+use util::ResultExt as _;
+
+/// The names of the files that hold the conventions the assistant should follow in a project.
+pub const RULES_FILE_NAMES: &[&str] = &[".rules", ".cursorrules", "CLAUDE.md", "AGENTS.md"];
+
+/// Returns whether the file at the given path is a rules file.
+pub fn is_rules_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| RULES_FILE_NAMES.contains(&name))
+}
+
+#[derive(Debug, Clone)]
+pub struct RulesFile {
+    pub worktree_id: WorktreeId,
+    pub path: Arc<Path>,
+    /// The path of the file, prefixed with the name of its worktree.
+    pub full_path: SharedString,
+    pub text: String,
+}
+
+/// Rules files larger than this are skipped, as they would take up much of the context window.
+const MAX_RULES_FILE_SIZE: u64 = 64 * 1024;
+
+/// The rules files found in the visible worktrees of a project, which are kept up to date as the
+/// worktrees are scanned and the files change.
+pub struct ProjectRules {
+    project: Entity<Project>,
+    rules: Vec<RulesFile>,
+    /// The most recent load of each rules file, which is replaced when the file changes again.
+    load_tasks: HashMap<(WorktreeId, Arc<Path>), Task<()>>,
+    _subscription: Subscription,
+}
+
+impl ProjectRules {
+    pub fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let subscription = cx.subscribe(&project, |this, _project, event, cx| match event {
+            project::Event::WorktreeAdded(worktree_id) => {
+                this.load_worktree(*worktree_id, cx);
+            }
+            project::Event::WorktreeRemoved(worktree_id) => {
+                this.remove_worktree(*worktree_id, cx);
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                for (path, _, change) in changes.iter() {
+                    if !is_rules_file(path) {
+                        continue;
+                    }
+                    match change {
+                        PathChange::Removed => this.remove_rules_file(*worktree_id, path, cx),
+                        _ => this.load_rules_file(*worktree_id, path.clone(), cx),
+                    }
+                }
+            }
+            _ => {}
+        });
+
+        let mut this = Self {
+            project,
+            rules: Vec::new(),
+            load_tasks: HashMap::default(),
+            _subscription: subscription,
+        };
+        let worktree_ids = this
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).id())
+            .collect::<Vec<_>>();
+        for worktree_id in worktree_ids {
+            this.load_worktree(worktree_id, cx);
+        }
+        this
+    }
+
+    /// Returns the rules files, with the files of outer directories before nested ones.
+    pub fn rules(&self) -> &[RulesFile] {
+        &self.rules
+    }
+
+    fn load_worktree(&mut self, worktree_id: WorktreeId, cx: &mut Context<Self>) {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let paths = worktree
+            .read(cx)
+            .files(false, 0)
+            .filter(|entry| is_rules_file(&entry.path))
+            .map(|entry| entry.path.clone())
+            .collect::<Vec<_>>();
+        for path in paths {
+            self.load_rules_file(worktree_id, path, cx);
+        }
+    }
+
+    fn load_rules_file(
+        &mut self,
+        worktree_id: WorktreeId,
+        path: Arc<Path>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let worktree_ref = worktree.read(cx);
+        if !worktree_ref.is_visible() {
+            return;
+        }
+        let Some(entry) = worktree_ref
+            .entry_for_path(&path)
+            .filter(|entry| entry.is_file() && !entry.is_ignored)
+        else {
+            self.remove_rules_file(worktree_id, &path, cx);
+            return;
+        };
+        if entry.size > MAX_RULES_FILE_SIZE {
+            log::warn!(
+                "skipping rules file {path:?} of {} bytes, which is larger than {MAX_RULES_FILE_SIZE} bytes",
+                entry.size
+            );
+            self.remove_rules_file(worktree_id, &path, cx);
+            return;
+        }
+
+        let full_path = Path::new(worktree_ref.root_name())
+            .join(&path)
+            .to_string_lossy()
+            .into_owned()
+            .into();
+        let load_task = worktree.update(cx, |worktree, cx| worktree.load_file(&path, cx));
+        let task = cx.spawn({
+            let path = path.clone();
+            |this, mut cx| async move {
+                let text = load_task
+                    .await
+                    .log_err()
+                    .map(|loaded_file| loaded_file.text);
+                this.update(&mut cx, |this, cx| {
+                    this.rules.retain(|rules_file| {
+                        rules_file.worktree_id != worktree_id || rules_file.path != path
+                    });
+                    if let Some(text) = text.filter(|text| !text.trim().is_empty()) {
+                        this.rules.push(RulesFile {
+                            worktree_id,
+                            path,
+                            full_path,
+                            text,
+                        });
+                        this.rules.sort_by(|a, b| {
+                            a.worktree_id
+                                .cmp(&b.worktree_id)
+                                .then_with(|| {
+                                    a.path
+                                        .components()
+                                        .count()
+                                        .cmp(&b.path.components().count())
+                                })
+                                .then_with(|| a.path.cmp(&b.path))
+                        });
+                    }
+                    cx.notify();
+                })
+                .ok();
+            }
+        });
+        self.load_tasks.insert((worktree_id, path), task);
+    }
+
+    fn remove_rules_file(&mut self, worktree_id: WorktreeId, path: &Path, cx: &mut Context<Self>) {
+        self.load_tasks.remove(&(worktree_id, Arc::from(path)));
+        let len = self.rules.len();
+        self.rules.retain(|rules_file| {
+            rules_file.worktree_id != worktree_id || rules_file.path.as_ref() != path
+        });
+        if self.rules.len() != len {
+            cx.notify();
+        }
+    }
+
+    fn remove_worktree(&mut self, worktree_id: WorktreeId, cx: &mut Context<Self>) {
+        self.load_tasks
+            .retain(|(load_worktree_id, _), _| *load_worktree_id != worktree_id);
+        self.rules
+            .retain(|rules_file| rules_file.worktree_id != worktree_id);
+        cx.notify();
+    }
+
+    /// Returns a system message instructing the model to follow the rules, if there are any.
+    pub fn to_request_message(&self) -> Option<LanguageModelRequestMessage> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        let mut text = String::from(
+            "The project has the following rules, which you must follow. \
+            Rules in a subdirectory apply to the files within that directory.\n",
+        );
+        for rules_file in &self.rules {
+            writeln!(text, "\n`{}`:\n```", rules_file.full_path).ok();
+            text.push_str(rules_file.text.trim_end());
+            text.push_str("\n```\n");
+        }
+
+        Some(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![MessageContent::Text(text)],
+            cache: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::Fs as _;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_discovering_rules_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".rules": "Use tabs.",
+                "README.md": "Not a rules file.",
+                "empty": {
+                    "AGENTS.md": "  \n",
+                },
+                "large": {
+                    ".rules": "x".repeat(MAX_RULES_FILE_SIZE as usize + 1),
+                },
+                "src": {
+                    ".cursorrules": "Prefer iterators.",
+                    "nested": {
+                        "CLAUDE.md": "Document public items.",
+                    },
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let project_rules = cx.new(|cx| ProjectRules::new(project, cx));
+        cx.run_until_parked();
+
+        project_rules.read_with(cx, |project_rules, _| {
+            // Outer rules come before nested ones, while empty and oversized files are skipped.
+            assert_eq!(
+                full_paths(project_rules),
+                [
+                    "project/.rules",
+                    "project/src/.cursorrules",
+                    "project/src/nested/CLAUDE.md",
+                ]
+            );
+
+            let message = project_rules.to_request_message().unwrap();
+            assert_eq!(message.role, Role::System);
+            assert_eq!(
+                message.content,
+                [MessageContent::Text(
+                    "The project has the following rules, which you must follow. \
+                    Rules in a subdirectory apply to the files within that directory.\n\
+                    \n`project/.rules`:\n```\nUse tabs.\n```\n\
+                    \n`project/src/.cursorrules`:\n```\nPrefer iterators.\n```\n\
+                    \n`project/src/nested/CLAUDE.md`:\n```\nDocument public items.\n```\n"
+                        .into()
+                )]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_reloading_changed_rules_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".rules": "Use tabs.",
+                "src": {
+                    "main.rs": "fn main() {}",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/project".as_ref()], cx).await;
+        let project_rules = cx.new(|cx| ProjectRules::new(project, cx));
+        cx.run_until_parked();
+        project_rules.read_with(cx, |project_rules, _| {
+            assert_eq!(texts(project_rules), ["Use tabs."]);
+        });
+
+        fs.insert_file("/project/.rules", b"Use spaces.".to_vec())
+            .await;
+        fs.insert_file("/project/src/AGENTS.md", b"Run the tests.".to_vec())
+            .await;
+        cx.run_until_parked();
+        project_rules.read_with(cx, |project_rules, _| {
+            assert_eq!(
+                full_paths(project_rules),
+                ["project/.rules", "project/src/AGENTS.md"]
+            );
+            assert_eq!(texts(project_rules), ["Use spaces.", "Run the tests."]);
+        });
+
+        fs.remove_file("/project/.rules".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        project_rules.read_with(cx, |project_rules, _| {
+            assert_eq!(full_paths(project_rules), ["project/src/AGENTS.md"]);
+        });
+
+        // Growing a rules file past the size limit drops it.
+        fs.insert_file(
+            "/project/src/AGENTS.md",
+            "x".repeat(MAX_RULES_FILE_SIZE as usize + 1).into_bytes(),
+        )
+        .await;
+        cx.run_until_parked();
+        project_rules.read_with(cx, |project_rules, _| {
+            assert!(project_rules.rules().is_empty());
+            assert!(project_rules.to_request_message().is_none());
+        });
+    }
+
+    fn full_paths(project_rules: &ProjectRules) -> Vec<&str> {
+        project_rules
+            .rules()
+            .iter()
+            .map(|rules_file| rules_file.full_path.as_ref())
+            .collect()
+    }
+
+    fn texts(project_rules: &ProjectRules) -> Vec<&str> {
+        project_rules
+            .rules()
+            .iter()
+            .map(|rules_file| rules_file.text.as_str())
+            .collect()
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
@@ -257,11 +257,19 @@ impl TerminalInlineAssistant {
 
         request_message.content.push(prompt.into());
 
+        let mut messages = Vec::new();
+        if let Some(project_rules) = assist.context_store.read(cx).project_rules(cx) {
+            messages.extend(project_rules.read(cx).to_request_message());
+        }
+        messages.push(request_message);
+
         Ok(LanguageModelRequest {
-            messages: vec![request_message],
+            messages,
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            thinking_budget: None,
+            max_tokens: None,
         })
     }
 
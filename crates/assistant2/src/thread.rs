@@ -1,24 +1,33 @@
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
+use assistant_settings::AssistantSettings;
 use assistant_tool::ToolWorkingSet;
 use chrono::{DateTime, Utc};
 use collections::{BTreeMap, HashMap, HashSet};
 use futures::future::Shared;
 use futures::{FutureExt as _, StreamExt as _};
-use gpui::{App, Context, EventEmitter, SharedString, Task};
+use gpui::{App, Context, Entity, EventEmitter, SharedString, Task};
+use itertools::Itertools as _;
 use language_model::{
-    LanguageModel, LanguageModelCompletionEvent, LanguageModelRegistry, LanguageModelRequest,
-    LanguageModelRequestMessage, LanguageModelToolResult, LanguageModelToolUse,
-    LanguageModelToolUseId, MessageContent, Role, StopReason,
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelImage, LanguageModelRegistry,
+    LanguageModelRequest, LanguageModelRequestMessage, LanguageModelToolResult,
+    LanguageModelToolUse, LanguageModelToolUseId, MessageContent, Role, StopReason, TokenUsage,
 };
 use language_models::provider::cloud::{MaxMonthlySpendReachedError, PaymentRequiredError};
+use project::{Project, ProjectPath};
 use serde::{Deserialize, Serialize};
+use settings::Settings as _;
 use util::{post_inc, TryFutureExt as _};
 use uuid::Uuid;
 
-use crate::context::{attach_context_to_message, ContextId, ContextSnapshot};
-use crate::thread_store::SavedThread;
+use crate::context::{attach_context_to_message, ContextId, ContextKind, ContextSnapshot};
+use crate::project_rules::ProjectRules;
+use crate::thread_store::{
+    ExportedContext, ExportedMessage, ExportedThread, SavedCheckpoint, SavedContext,
+    SavedFileCheckpoint, SavedMessage, SavedThread, SavedToolEdit,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum RequestKind {
@@ -41,7 +50,7 @@ impl std::fmt::Display for ThreadId {
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
-pub struct MessageId(usize);
+pub struct MessageId(pub(crate) usize);
 
 impl MessageId {
     fn post_inc(&mut self) -> Self {
@@ -53,8 +62,15 @@ impl MessageId {
 #[derive(Debug, Clone)]
 pub struct Message {
     pub id: MessageId,
+    /// The message this one follows, or `None` if it starts the thread.
+    ///
+    /// Messages that share a parent are alternative branches of the conversation.
+    pub parent_id: Option<MessageId>,
     pub role: Role,
     pub text: String,
+    /// The reasoning the model did before writing this message, if any.
+    pub thinking: String,
+    pub thinking_signature: Option<String>,
 }
 
 /// A thread of conversation with the LLM.
@@ -63,35 +79,55 @@ pub struct Thread {
     updated_at: DateTime<Utc>,
     summary: Option<SharedString>,
     pending_summary: Task<Option<()>>,
+    /// All the messages in the thread, including those in inactive branches, ordered by ID.
     messages: Vec<Message>,
+    /// The IDs of the messages in the active branch, from the first to the last.
+    active_branch: Vec<MessageId>,
     next_message_id: MessageId,
     context: BTreeMap<ContextId, ContextSnapshot>,
     context_by_message: HashMap<MessageId, Vec<ContextId>>,
     completion_count: usize,
     pending_completions: Vec<PendingCompletion>,
     tools: Arc<ToolWorkingSet>,
+    /// The rules of the project, which are included in every request.
+    project_rules: Option<Entity<ProjectRules>>,
     tool_uses_by_message: HashMap<MessageId, Vec<LanguageModelToolUse>>,
     tool_results_by_message: HashMap<MessageId, Vec<LanguageModelToolResult>>,
+    /// Images sent along with messages, which only imported threads have.
+    images_by_message: HashMap<MessageId, Vec<LanguageModelImage>>,
     pending_tool_uses_by_id: HashMap<LanguageModelToolUseId, PendingToolUse>,
+    checkpoints_by_message: BTreeMap<MessageId, Checkpoint>,
+    tool_edited_texts: HashMap<ProjectPath, String>,
+    token_usage_by_model: HashMap<String, TokenUsage>,
 }
 
 impl Thread {
-    pub fn new(tools: Arc<ToolWorkingSet>, _cx: &mut Context<Self>) -> Self {
+    pub fn new(
+        tools: Arc<ToolWorkingSet>,
+        project_rules: Option<Entity<ProjectRules>>,
+        _cx: &mut Context<Self>,
+    ) -> Self {
         Self {
             id: ThreadId::new(),
             updated_at: Utc::now(),
             summary: None,
             pending_summary: Task::ready(None),
             messages: Vec::new(),
+            active_branch: Vec::new(),
             next_message_id: MessageId(0),
             context: BTreeMap::default(),
             context_by_message: HashMap::default(),
             completion_count: 0,
             pending_completions: Vec::new(),
             tools,
+            project_rules,
             tool_uses_by_message: HashMap::default(),
             tool_results_by_message: HashMap::default(),
+            images_by_message: HashMap::default(),
             pending_tool_uses_by_id: HashMap::default(),
+            checkpoints_by_message: BTreeMap::default(),
+            tool_edited_texts: HashMap::default(),
+            token_usage_by_model: HashMap::default(),
         }
     }
 
@@ -99,33 +135,293 @@ impl Thread {
         id: ThreadId,
         saved: SavedThread,
         tools: Arc<ToolWorkingSet>,
-        _cx: &mut Context<Self>,
+        project_rules: Option<Entity<ProjectRules>>,
+        project: &Entity<Project>,
+        cx: &mut Context<Self>,
     ) -> Self {
-        let next_message_id = MessageId(saved.messages.len());
-
-        Self {
+        // Files are saved by absolute path, as worktree IDs change between sessions. Those
+        // that are no longer in the project can't be restored.
+        let project = project.read(cx);
+        let project_path = |path: &Path| {
+            let (worktree, relative_path) = project.find_worktree(path, cx)?;
+            Some(ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: relative_path.into(),
+            })
+        };
+        let checkpoints_by_message = saved
+            .checkpoints
+            .into_iter()
+            .map(|checkpoint| {
+                let files = checkpoint
+                    .files
+                    .into_iter()
+                    .filter_map(|file| {
+                        let checkpoint = FileCheckpoint {
+                            text: file.text,
+                            was_dirty: file.was_dirty,
+                        };
+                        Some((project_path(&file.path)?, checkpoint))
+                    })
+                    .collect();
+                (checkpoint.message_id, Checkpoint { files })
+            })
+            .collect();
+        let tool_edited_texts = saved
+            .tool_edited_texts
+            .into_iter()
+            .filter_map(|edit| Some((project_path(&edit.path)?, edit.text)))
+            .collect();
+
+        // Tools that were still running when the thread was saved have no results, without
+        // which their uses can't be sent to the model.
+        let tool_result_ids = saved
+            .messages
+            .iter()
+            .flat_map(|message| &message.tool_results)
+            .map(|tool_result| tool_result.tool_use_id.clone())
+            .collect::<HashSet<_>>();
+
+        let mut this = Self {
             id,
             updated_at: saved.updated_at,
             summary: Some(saved.summary),
             pending_summary: Task::ready(None),
-            messages: saved
-                .messages
-                .into_iter()
-                .map(|message| Message {
-                    id: message.id,
-                    role: message.role,
-                    text: message.text,
-                })
-                .collect(),
-            next_message_id,
+            messages: Vec::new(),
+            active_branch: Vec::new(),
+            next_message_id: MessageId(0),
             context: BTreeMap::default(),
             context_by_message: HashMap::default(),
             completion_count: 0,
             pending_completions: Vec::new(),
             tools,
+            project_rules,
             tool_uses_by_message: HashMap::default(),
             tool_results_by_message: HashMap::default(),
+            images_by_message: HashMap::default(),
             pending_tool_uses_by_id: HashMap::default(),
+            checkpoints_by_message,
+            tool_edited_texts,
+            token_usage_by_model: saved.token_usage_by_model,
+        };
+
+        let mut context_ids = HashMap::default();
+        for saved_message in saved.messages {
+            let id = saved_message.id;
+            if !saved_message.images.is_empty() {
+                this.images_by_message.insert(id, saved_message.images);
+            }
+            let tool_uses = saved_message
+                .tool_uses
+                .into_iter()
+                .filter(|tool_use| tool_result_ids.contains(&tool_use.id))
+                .collect::<Vec<_>>();
+            if !tool_uses.is_empty() {
+                this.tool_uses_by_message.insert(id, tool_uses);
+            }
+            if !saved_message.tool_results.is_empty() {
+                this.tool_results_by_message
+                    .insert(id, saved_message.tool_results);
+            }
+            for context in saved_message.context {
+                this.restore_context(
+                    id,
+                    context.name,
+                    context.kind,
+                    context.text,
+                    &mut context_ids,
+                );
+            }
+            this.messages.push(Message {
+                id,
+                parent_id: saved_message.parent_id,
+                role: saved_message.role,
+                text: saved_message.text,
+                thinking: saved_message.thinking,
+                thinking_signature: saved_message.thinking_signature,
+            });
+        }
+
+        this.messages.sort_unstable_by_key(|message| message.id);
+        this.next_message_id = this
+            .messages
+            .last()
+            .map_or(MessageId(0), |message| MessageId(message.id.0 + 1));
+        if let Some(active_message_id) = saved
+            .active_message_id
+            .or_else(|| this.messages.last().map(|message| message.id))
+        {
+            this.active_branch = this.branch_to(active_message_id);
+        }
+        this
+    }
+
+    pub fn from_exported(
+        exported: ExportedThread,
+        tools: Arc<ToolWorkingSet>,
+        project_rules: Option<Entity<ProjectRules>>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self::new(tools, project_rules, cx);
+        this.summary = Some(exported.summary);
+        this.updated_at = exported.updated_at;
+
+        let mut context_ids = HashMap::default();
+        for exported_message in exported.messages {
+            let id = exported_message.id;
+            let mut message = Message {
+                id,
+                parent_id: exported_message.parent_id,
+                role: exported_message.role,
+                text: String::new(),
+                thinking: String::new(),
+                thinking_signature: None,
+            };
+
+            for content in exported_message.content {
+                match content {
+                    MessageContent::Text(text) => message.text.push_str(&text),
+                    MessageContent::Thinking { text, signature } => {
+                        message.thinking.push_str(&text);
+                        message.thinking_signature = signature.or(message.thinking_signature);
+                    }
+                    MessageContent::Image(image) => {
+                        this.images_by_message.entry(id).or_default().push(image);
+                    }
+                    MessageContent::ToolUse(tool_use) => {
+                        this.tool_uses_by_message
+                            .entry(id)
+                            .or_default()
+                            .push(tool_use);
+                    }
+                    MessageContent::ToolResult(tool_result) => {
+                        this.tool_results_by_message
+                            .entry(id)
+                            .or_default()
+                            .push(tool_result);
+                    }
+                }
+            }
+
+            for context in exported_message.context {
+                this.restore_context(
+                    id,
+                    context.name,
+                    context.kind,
+                    context.text,
+                    &mut context_ids,
+                );
+            }
+
+            this.messages.push(message);
+        }
+
+        this.messages.sort_unstable_by_key(|message| message.id);
+        this.next_message_id = this
+            .messages
+            .last()
+            .map_or(MessageId(0), |message| MessageId(message.id.0 + 1));
+        if let Some(active_message_id) = exported
+            .active_message_id
+            .or_else(|| this.messages.last().map(|message| message.id))
+        {
+            this.active_branch = this.branch_to(active_message_id);
+        }
+        this
+    }
+
+    /// Attaches context from a saved or exported thread to the message. Context attached to
+    /// several messages is only sent once, so the same context shares an ID.
+    fn restore_context(
+        &mut self,
+        message_id: MessageId,
+        name: SharedString,
+        kind: ContextKind,
+        text: String,
+        context_ids: &mut HashMap<(SharedString, String), ContextId>,
+    ) {
+        let next_context_id = ContextId(context_ids.len());
+        let context_id = *context_ids
+            .entry((name.clone(), text.clone()))
+            .or_insert(next_context_id);
+        self.context
+            .entry(context_id)
+            .or_insert_with(|| ContextSnapshot {
+                id: context_id,
+                name,
+                parent: None,
+                tooltip: None,
+                icon_path: None,
+                kind,
+                text: Box::new([SharedString::from(text)]),
+            });
+        self.context_by_message
+            .entry(message_id)
+            .or_default()
+            .push(context_id);
+    }
+
+    /// Creates a new thread with the messages of the active branch of this one, up to and
+    /// including the given message, along with their checkpoints and the tokens used so far.
+    pub fn fork(&self, message_id: MessageId) -> Self {
+        let branch = self
+            .active_branch
+            .iter()
+            .position(|id| *id == message_id)
+            .map_or(&[][..], |ix| &self.active_branch[..=ix]);
+
+        let messages = branch
+            .iter()
+            .filter_map(|id| self.message(*id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let context_by_message = branch
+            .iter()
+            .filter_map(|id| Some((*id, self.context_by_message.get(id)?.clone())))
+            .collect::<HashMap<_, _>>();
+        let context = context_by_message
+            .values()
+            .flatten()
+            .filter_map(|context_id| Some((*context_id, self.context.get(context_id)?.clone())))
+            .collect();
+        let tool_uses_by_message = branch
+            .iter()
+            .filter_map(|id| Some((*id, self.tool_uses_by_message.get(id)?.clone())))
+            .collect();
+        let tool_results_by_message = branch
+            .iter()
+            .filter_map(|id| Some((*id, self.tool_results_by_message.get(id)?.clone())))
+            .collect();
+        let images_by_message = branch
+            .iter()
+            .filter_map(|id| Some((*id, self.images_by_message.get(id)?.clone())))
+            .collect();
+        let checkpoints_by_message = branch
+            .iter()
+            .filter_map(|id| Some((*id, self.checkpoints_by_message.get(id)?.clone())))
+            .collect();
+
+        Self {
+            id: ThreadId::new(),
+            updated_at: Utc::now(),
+            summary: self.summary.clone(),
+            pending_summary: Task::ready(None),
+            next_message_id: MessageId(message_id.0 + 1),
+            messages,
+            active_branch: branch.to_vec(),
+            context,
+            context_by_message,
+            completion_count: 0,
+            pending_completions: Vec::new(),
+            tools: self.tools.clone(),
+            project_rules: self.project_rules.clone(),
+            tool_uses_by_message,
+            tool_results_by_message,
+            images_by_message,
+            pending_tool_uses_by_id: HashMap::default(),
+            checkpoints_by_message,
+            tool_edited_texts: self.tool_edited_texts.clone(),
+            token_usage_by_model: self.token_usage_by_model.clone(),
         }
     }
 
@@ -160,13 +456,116 @@ impl Thread {
     }
 
     pub fn message(&self, id: MessageId) -> Option<&Message> {
-        self.messages.iter().find(|message| message.id == id)
+        let ix = self
+            .messages
+            .binary_search_by_key(&id, |message| message.id)
+            .ok()?;
+        Some(&self.messages[ix])
+    }
+
+    fn message_mut(&mut self, id: MessageId) -> Option<&mut Message> {
+        let ix = self
+            .messages
+            .binary_search_by_key(&id, |message| message.id)
+            .ok()?;
+        Some(&mut self.messages[ix])
     }
 
+    /// Returns the messages in the active branch of the thread.
     pub fn messages(&self) -> impl Iterator<Item = &Message> {
+        self.active_branch.iter().filter_map(|id| self.message(*id))
+    }
+
+    /// Returns all the messages in the thread, including those in inactive branches.
+    pub fn all_messages(&self) -> impl Iterator<Item = &Message> {
         self.messages.iter()
     }
 
+    /// Returns the ID of the last message in the active branch.
+    pub fn active_message_id(&self) -> Option<MessageId> {
+        self.active_branch.last().copied()
+    }
+
+    /// Returns the IDs of the messages that share a parent with the given one, including
+    /// itself, from the oldest to the newest.
+    pub fn siblings(&self, id: MessageId) -> Vec<MessageId> {
+        let Some(message) = self.message(id) else {
+            return Vec::new();
+        };
+        self.messages
+            .iter()
+            .filter(|sibling| sibling.parent_id == message.parent_id)
+            .map(|sibling| sibling.id)
+            .collect()
+    }
+
+    /// Makes the branch containing the given message the active one, following the
+    /// newest reply to each message after it.
+    pub fn select_branch(&mut self, id: MessageId, cx: &mut Context<Self>) {
+        if self.message(id).is_none() || self.active_branch.contains(&id) {
+            return;
+        }
+
+        let mut branch = self.branch_to(id);
+        let mut last_id = id;
+        while let Some(reply) = self
+            .messages
+            .iter()
+            .rfind(|message| message.parent_id == Some(last_id))
+        {
+            last_id = reply.id;
+            branch.push(last_id);
+        }
+        self.active_branch = branch;
+        self.touch_updated_at();
+        cx.emit(ThreadEvent::BranchChanged);
+        cx.notify();
+    }
+
+    /// Returns the IDs of the messages leading to the given one, including itself.
+    fn branch_to(&self, id: MessageId) -> Vec<MessageId> {
+        let mut branch = Vec::new();
+        let mut next_id = Some(id);
+        while let Some(message) = next_id.and_then(|id| self.message(id)) {
+            branch.push(message.id);
+            next_id = message.parent_id;
+        }
+        branch.reverse();
+        branch
+    }
+
+    /// Adds a new version of the given User message with the given text, keeping the
+    /// original and the messages after it in a separate branch.
+    ///
+    /// Returns the ID of the new message.
+    pub fn edit_user_message(
+        &mut self,
+        id: MessageId,
+        text: impl Into<String>,
+        cx: &mut Context<Self>,
+    ) -> Option<MessageId> {
+        let message = self.message(id)?;
+        if message.role != Role::User {
+            return None;
+        }
+        let parent_id = message.parent_id;
+
+        self.pending_completions.clear();
+        self.active_branch = parent_id.map_or(Vec::new(), |parent_id| self.branch_to(parent_id));
+        let new_id = self.push_message(Role::User, text);
+        if let Some(context_ids) = self.context_by_message.get(&id).cloned() {
+            self.context_by_message.insert(new_id, context_ids);
+        }
+        if let Some(images) = self.images_by_message.get(&id).cloned() {
+            self.images_by_message.insert(new_id, images);
+        }
+
+        cx.emit(ThreadEvent::BranchChanged);
+        cx.emit(ThreadEvent::MessageEdited(new_id));
+        cx.notify();
+        Some(new_id)
+    }
+
     pub fn is_streaming(&self) -> bool {
         !self.pending_completions.is_empty()
     }
@@ -186,10 +585,111 @@ impl Thread {
         )
     }
 
+    pub fn images_for_message(&self, id: MessageId) -> &[LanguageModelImage] {
+        self.images_by_message
+            .get(&id)
+            .map_or(&[], |images| images.as_slice())
+    }
+
+    /// Returns the tokens used by all the completions requested in this thread.
+    pub fn cumulative_token_usage(&self) -> TokenUsage {
+        self.token_usage_by_model
+            .values()
+            .fold(TokenUsage::default(), |total, usage| total + *usage)
+    }
+
+    /// Records the tokens a request to the given model used so far, replacing the usage it
+    /// reported before.
+    fn update_token_usage(
+        &mut self,
+        model_id: &str,
+        previous_usage: TokenUsage,
+        usage: TokenUsage,
+    ) {
+        let total_usage = self
+            .token_usage_by_model
+            .entry(model_id.to_string())
+            .or_default();
+        *total_usage = *total_usage + usage - previous_usage;
+    }
+
+    /// Returns the estimated cost of the completions requested in this thread, in US
+    /// dollars, if the price of any of the models used is configured.
+    pub fn estimated_cost(&self, cx: &App) -> Option<f64> {
+        let settings = AssistantSettings::get_global(cx);
+        self.token_usage_by_model
+            .iter()
+            .filter_map(|(model_id, usage)| settings.estimated_cost(model_id, usage))
+            .reduce(|total, cost| total + cost)
+    }
+
     pub fn pending_tool_uses(&self) -> Vec<&PendingToolUse> {
         self.pending_tool_uses_by_id.values().collect()
     }
 
+    pub fn has_checkpoint(&self, message_id: MessageId) -> bool {
+        self.checkpoints_by_message.contains_key(&message_id)
+    }
+
+    pub fn checkpoint_contains(&self, message_id: MessageId, project_path: &ProjectPath) -> bool {
+        self.checkpoints_by_message
+            .get(&message_id)
+            .map_or(false, |checkpoint| {
+                checkpoint.files.contains_key(project_path)
+            })
+    }
+
+    /// Records the contents of a file from before the tools requested by the given
+    /// Assistant message modified it. Only the first contents recorded are kept.
+    pub fn insert_checkpoint_file(
+        &mut self,
+        message_id: MessageId,
+        project_path: ProjectPath,
+        file: FileCheckpoint,
+        cx: &mut Context<Self>,
+    ) {
+        self.checkpoints_by_message
+            .entry(message_id)
+            .or_default()
+            .files
+            .entry(project_path)
+            .or_insert(file);
+        cx.notify();
+    }
+
+    /// Records the contents of a file after a tool modified it, so that later changes
+    /// made by the user can be told apart when restoring a checkpoint.
+    pub fn record_tool_edit(&mut self, project_path: ProjectPath, text: String) {
+        self.tool_edited_texts.insert(project_path, text);
+    }
+
+    /// Returns the files to restore to go back to the state from before the tools
+    /// requested by the given Assistant message ran.
+    pub fn restore_plan(&self, message_id: MessageId) -> Vec<RestoreFile> {
+        let mut files = HashMap::<ProjectPath, FileCheckpoint>::default();
+        for checkpoint in self
+            .checkpoints_by_message
+            .range(message_id..)
+            .filter(|(id, _)| self.active_branch.contains(id))
+            .map(|(_, c)| c)
+        {
+            for (project_path, file) in &checkpoint.files {
+                files
+                    .entry(project_path.clone())
+                    .or_insert_with(|| file.clone());
+            }
+        }
+
+        files
+            .into_iter()
+            .map(|(project_path, checkpoint)| RestoreFile {
+                expected_text: self.tool_edited_texts.get(&project_path).cloned(),
+                project_path,
+                checkpoint,
+            })
+            .collect()
+    }
+
     pub fn insert_user_message(
         &mut self,
         text: impl Into<String>,
@@ -209,14 +709,24 @@ impl Thread {
         text: impl Into<String>,
         cx: &mut Context<Self>,
     ) -> MessageId {
+        let id = self.push_message(role, text);
+        cx.emit(ThreadEvent::MessageAdded(id));
+        id
+    }
+
+    /// Appends a message to the active branch.
+    fn push_message(&mut self, role: Role, text: impl Into<String>) -> MessageId {
         let id = self.next_message_id.post_inc();
         self.messages.push(Message {
             id,
+            parent_id: self.active_message_id(),
             role,
             text: text.into(),
+            thinking: String::new(),
+            thinking_signature: None,
         });
+        self.active_branch.push(id);
         self.touch_updated_at();
-        cx.emit(ThreadEvent::MessageAdded(id));
         id
     }
 
@@ -226,7 +736,7 @@ impl Thread {
     pub fn text(&self) -> String {
         let mut text = String::new();
 
-        for message in &self.messages {
+        for message in self.messages() {
             text.push_str(match message.role {
                 language_model::Role::User => "User:",
                 language_model::Role::Assistant => "Assistant:",
@@ -244,51 +754,35 @@ impl Thread {
     pub fn to_completion_request(
         &self,
         _request_kind: RequestKind,
-        _cx: &App,
+        cx: &App,
     ) -> LanguageModelRequest {
         let mut request = LanguageModelRequest {
             messages: vec![],
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            thinking_budget: AssistantSettings::get_global(cx).thinking_budget,
+            max_tokens: None,
         };
 
+        if let Some(project_rules) = &self.project_rules {
+            request
+                .messages
+                .extend(project_rules.read(cx).to_request_message());
+        }
+
         let mut referenced_context_ids = HashSet::default();
 
-        for message in &self.messages {
+        for message in self.messages() {
             if let Some(context_ids) = self.context_by_message.get(&message.id) {
                 referenced_context_ids.extend(context_ids);
             }
 
-            let mut request_message = LanguageModelRequestMessage {
+            request.messages.push(LanguageModelRequestMessage {
                 role: message.role,
-                content: Vec::new(),
+                content: self.message_content(message),
                 cache: false,
-            };
-
-            if let Some(tool_results) = self.tool_results_by_message.get(&message.id) {
-                for tool_result in tool_results {
-                    request_message
-                        .content
-                        .push(MessageContent::ToolResult(tool_result.clone()));
-                }
-            }
-
-            if !message.text.is_empty() {
-                request_message
-                    .content
-                    .push(MessageContent::Text(message.text.clone()));
-            }
-
-            if let Some(tool_uses) = self.tool_uses_by_message.get(&message.id) {
-                for tool_use in tool_uses {
-                    request_message
-                        .content
-                        .push(MessageContent::ToolUse(tool_use.clone()));
-                }
-            }
-
-            request.messages.push(request_message);
+            });
         }
 
         if !referenced_context_ids.is_empty() {
@@ -310,6 +804,136 @@ impl Thread {
         request
     }
 
+    /// Returns what is sent to the model for the given message, in order.
+    fn message_content(&self, message: &Message) -> Vec<MessageContent> {
+        let mut content = Vec::new();
+
+        if let Some(tool_results) = self.tool_results_by_message.get(&message.id) {
+            content.extend(tool_results.iter().cloned().map(MessageContent::ToolResult));
+        }
+
+        if !message.thinking.is_empty() {
+            content.push(MessageContent::Thinking {
+                text: message.thinking.clone(),
+                signature: message.thinking_signature.clone(),
+            });
+        }
+
+        if let Some(images) = self.images_by_message.get(&message.id) {
+            content.extend(images.iter().cloned().map(MessageContent::Image));
+        }
+
+        if !message.text.is_empty() {
+            content.push(MessageContent::Text(message.text.clone()));
+        }
+
+        if let Some(tool_uses) = self.tool_uses_by_message.get(&message.id) {
+            content.extend(tool_uses.iter().cloned().map(MessageContent::ToolUse));
+        }
+
+        content
+    }
+
+    /// Returns this [`Thread`] in the format it's saved in.
+    pub fn to_saved(&self, project: &Project, cx: &App) -> SavedThread {
+        SavedThread {
+            summary: self.summary_or_default(),
+            updated_at: self.updated_at,
+            active_message_id: self.active_message_id(),
+            messages: self
+                .all_messages()
+                .map(|message| SavedMessage {
+                    id: message.id,
+                    parent_id: message.parent_id,
+                    role: message.role,
+                    text: message.text.clone(),
+                    thinking: message.thinking.clone(),
+                    thinking_signature: message.thinking_signature.clone(),
+                    images: self.images_for_message(message.id).to_vec(),
+                    tool_uses: self
+                        .tool_uses_by_message
+                        .get(&message.id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    tool_results: self
+                        .tool_results_by_message
+                        .get(&message.id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    context: self
+                        .context_for_message(message.id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|context| SavedContext {
+                            name: context.name,
+                            kind: context.kind,
+                            text: context.text.iter().join("\n"),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            checkpoints: self
+                .checkpoints_by_message
+                .iter()
+                .map(|(message_id, checkpoint)| SavedCheckpoint {
+                    message_id: *message_id,
+                    files: checkpoint
+                        .files
+                        .iter()
+                        .filter_map(|(project_path, file)| {
+                            Some(SavedFileCheckpoint {
+                                path: project.absolute_path(project_path, cx)?,
+                                text: file.text.clone(),
+                                was_dirty: file.was_dirty,
+                            })
+                        })
+                        .collect(),
+                })
+                .collect(),
+            tool_edited_texts: self
+                .tool_edited_texts
+                .iter()
+                .filter_map(|(project_path, text)| {
+                    Some(SavedToolEdit {
+                        path: project.absolute_path(project_path, cx)?,
+                        text: text.clone(),
+                    })
+                })
+                .collect(),
+            token_usage_by_model: self.token_usage_by_model.clone(),
+        }
+    }
+
+    /// Returns this [`Thread`] in the format used to share it outside of Zed, from which it can
+    /// be imported again without losing anything that's sent to the model.
+    pub fn to_exported(&self) -> ExportedThread {
+        ExportedThread {
+            version: ExportedThread::VERSION.to_string(),
+            summary: self.summary_or_default(),
+            updated_at: self.updated_at,
+            active_message_id: self.active_message_id(),
+            messages: self
+                .all_messages()
+                .map(|message| ExportedMessage {
+                    id: message.id,
+                    parent_id: message.parent_id,
+                    role: message.role,
+                    content: self.message_content(message),
+                    context: self
+                        .context_for_message(message.id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|context| ExportedContext {
+                            name: context.name,
+                            kind: context.kind,
+                            text: context.text.iter().join("\n"),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
     pub fn stream_completion(
         &mut self,
         request: LanguageModelRequest,
@@ -319,10 +943,12 @@ impl Thread {
         let pending_completion_id = post_inc(&mut self.completion_count);
 
         let task = cx.spawn(|thread, mut cx| async move {
+            let model_id = model.id().0.to_string();
             let stream = model.stream_completion(request, &cx);
             let stream_completion = async {
                 let mut events = stream.await?;
                 let mut stop_reason = StopReason::EndTurn;
+                let mut request_usage = TokenUsage::default();
 
                 while let Some(event) = events.next().await {
                     let event = event?;
@@ -335,8 +961,15 @@ impl Thread {
                             LanguageModelCompletionEvent::Stop(reason) => {
                                 stop_reason = reason;
                             }
+                            LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                thread.update_token_usage(&model_id, request_usage, usage);
+                                request_usage = usage;
+                            }
                             LanguageModelCompletionEvent::Text(chunk) => {
-                                if let Some(last_message) = thread.messages.last_mut() {
+                                if let Some(last_message) = thread
+                                    .active_message_id()
+                                    .and_then(|id| thread.message_mut(id))
+                                {
                                     if last_message.role == Role::Assistant {
                                         last_message.text.push_str(&chunk);
                                         cx.emit(ThreadEvent::StreamedAssistantText(
@@ -353,22 +986,50 @@ impl Thread {
                                     }
                                 }
                             }
+                            LanguageModelCompletionEvent::Thinking { text, signature } => {
+                                let last_assistant_message_id = thread
+                                    .active_message_id()
+                                    .and_then(|id| thread.message(id))
+                                    .filter(|message| message.role == Role::Assistant)
+                                    .map(|message| message.id);
+                                let (message_id, is_new) = match last_assistant_message_id {
+                                    Some(id) => (id, false),
+                                    None => (
+                                        thread.insert_message(Role::Assistant, String::new(), cx),
+                                        true,
+                                    ),
+                                };
+                                if let Some(message) = thread.message_mut(message_id) {
+                                    message.thinking.push_str(&text);
+                                    if signature.is_some() {
+                                        message.thinking_signature = signature;
+                                    }
+                                    // As with text, the rendered thinking of a new message is
+                                    // built from the message itself.
+                                    if !is_new && !text.is_empty() {
+                                        cx.emit(ThreadEvent::StreamedAssistantThinking(
+                                            message_id, text,
+                                        ));
+                                    }
+                                }
+                            }
                             LanguageModelCompletionEvent::ToolUse(tool_use) => {
-                                if let Some(last_assistant_message) = thread
-                                    .messages
-                                    .iter()
-                                    .rfind(|message| message.role == Role::Assistant)
+                                if let Some(last_assistant_message_id) = thread
+                                    .messages()
+                                    .filter(|message| message.role == Role::Assistant)
+                                    .last()
+                                    .map(|message| message.id)
                                 {
                                     thread
                                         .tool_uses_by_message
-                                        .entry(last_assistant_message.id)
+                                        .entry(last_assistant_message_id)
                                         .or_default()
                                         .push(tool_use.clone());
 
                                     thread.pending_tool_uses_by_id.insert(
                                         tool_use.id.clone(),
                                         PendingToolUse {
-                                            assistant_message_id: last_assistant_message.id,
+                                            assistant_message_id: last_assistant_message_id,
                                             id: tool_use.id,
                                             name: tool_use.name,
                                             input: tool_use.input,
@@ -392,7 +1053,7 @@ impl Thread {
                         .pending_completions
                         .retain(|completion| completion.id != pending_completion_id);
 
-                    if thread.summary.is_none() && thread.messages.len() >= 2 {
+                    if thread.summary.is_none() && thread.active_branch.len() >= 2 {
                         thread.summarize(cx);
                     }
                 })?;
@@ -492,6 +1153,18 @@ impl Thread {
         });
     }
 
+    /// Marks a pending tool use as waiting for the user to allow or deny it.
+    pub fn request_tool_use_confirmation(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::NeedsConfirmation;
+            cx.notify();
+        }
+    }
+
     pub fn insert_tool_output(
         &mut self,
         assistant_message_id: MessageId,
@@ -563,6 +1236,41 @@ impl Thread {
     }
 }
 
+/// The contents of the files modified by the tools requested in an Assistant message,
+/// from before they ran.
+#[derive(Debug, Default, Clone)]
+struct Checkpoint {
+    files: HashMap<ProjectPath, FileCheckpoint>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileCheckpoint {
+    /// The contents of the file, or `None` if the tools created it, in which case restoring
+    /// deletes it.
+    pub text: Option<String>,
+    /// Whether the buffer had unsaved changes, in which case restoring it won't save it.
+    pub was_dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestoreFile {
+    pub project_path: ProjectPath,
+    pub checkpoint: FileCheckpoint,
+    /// The contents the tools left the file with, if known. If the file no longer has
+    /// these contents, the user has edited it since.
+    pub expected_text: Option<String>,
+}
+
+impl RestoreFile {
+    /// Whether the file was edited since the tools last modified it, given its current
+    /// contents, in which case restoring it would discard those edits.
+    pub fn is_edited_since(&self, text: &str) -> bool {
+        self.expected_text
+            .as_deref()
+            .map_or(false, |expected_text| expected_text != text)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ThreadError {
     PaymentRequired,
@@ -575,8 +1283,13 @@ pub enum ThreadEvent {
     ShowError(ThreadError),
     StreamedCompletion,
     StreamedAssistantText(MessageId, String),
+    StreamedAssistantThinking(MessageId, String),
     MessageAdded(MessageId),
     SummaryChanged,
+    /// The active branch of the thread changed.
+    BranchChanged,
+    /// A User message was edited, adding the given message in a new branch.
+    MessageEdited(MessageId),
     UsePendingTools,
     ToolFinished {
         #[allow(unused)]
@@ -604,7 +1317,11 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
-    Running { _task: Shared<Task<()>> },
+    /// The tool is waiting for the user to allow or deny its use.
+    NeedsConfirmation,
+    Running {
+        _task: Shared<Task<()>>,
+    },
     Error(#[allow(unused)] String),
 }
 
@@ -612,4 +1329,456 @@ impl PendingToolUseStatus {
     pub fn is_idle(&self) -> bool {
         matches!(self, PendingToolUseStatus::Idle)
     }
+
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, PendingToolUseStatus::NeedsConfirmation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, WorktreeId};
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    fn test_editing_and_selecting_branches(cx: &mut TestAppContext) {
+        let thread = cx.new(|cx| Thread::new(Arc::new(ToolWorkingSet::default()), None, cx));
+        thread.update(cx, |thread, cx| {
+            let greeting = thread.insert_message(Role::User, "Hello", cx);
+            thread.insert_message(Role::Assistant, "Hi!", cx);
+            let question = thread.insert_message(Role::User, "What's 2 + 2?", cx);
+            thread.insert_message(Role::Assistant, "4", cx);
+
+            let edited_question = thread
+                .edit_user_message(question, "What's 3 + 3?", cx)
+                .unwrap();
+            thread.insert_message(Role::Assistant, "6", cx);
+            assert_eq!(
+                message_texts(thread),
+                ["Hello", "Hi!", "What's 3 + 3?", "6"]
+            );
+            assert_eq!(thread.siblings(question), [question, edited_question]);
+            assert_eq!(thread.siblings(greeting), [greeting]);
+
+            thread.select_branch(question, cx);
+            assert_eq!(
+                message_texts(thread),
+                ["Hello", "Hi!", "What's 2 + 2?", "4"]
+            );
+
+            thread.select_branch(edited_question, cx);
+            assert_eq!(
+                message_texts(thread),
+                ["Hello", "Hi!", "What's 3 + 3?", "6"]
+            );
+
+            let forked_thread = thread.fork(edited_question);
+            assert_ne!(forked_thread.id(), thread.id());
+            assert_eq!(
+                message_texts(&forked_thread),
+                ["Hello", "Hi!", "What's 3 + 3?"]
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_exporting_and_importing(cx: &mut TestAppContext) {
+        let thread = cx.new(|cx| Thread::new(Arc::new(ToolWorkingSet::default()), None, cx));
+        let exported = thread.update(cx, |thread, cx| {
+            let context = ContextSnapshot {
+                id: ContextId(0),
+                name: "main.rs".into(),
+                parent: None,
+                tooltip: None,
+                icon_path: None,
+                kind: ContextKind::File,
+                text: Box::new([SharedString::from("fn main() {}")]),
+            };
+            thread.insert_user_message("What does main do?", vec![context], cx);
+            let answer = thread.insert_message(Role::Assistant, "Let me check.", cx);
+            thread.tool_uses_by_message.insert(
+                answer,
+                vec![LanguageModelToolUse {
+                    id: "tool-1".into(),
+                    name: "read-file".into(),
+                    input: serde_json::json!({ "path": "main.rs" }),
+                }],
+            );
+            let result = thread.insert_message(Role::User, "", cx);
+            thread.tool_results_by_message.insert(
+                result,
+                vec![LanguageModelToolResult {
+                    tool_use_id: "tool-1".into(),
+                    is_error: false,
+                    content: "fn main() {}".into(),
+                }],
+            );
+            thread.insert_message(Role::Assistant, "Nothing.", cx);
+
+            let markdown = thread.to_exported().to_markdown();
+            assert!(markdown.contains("**Tool Use:** `read-file` (`tool-1`)"));
+            assert!(markdown.contains("**Tool Result:** `tool-1`"));
+
+            serde_json::to_value(thread.to_exported()).unwrap()
+        });
+
+        let imported = cx.new(|cx| {
+            Thread::from_exported(
+                serde_json::from_value(exported.clone()).unwrap(),
+                Arc::new(ToolWorkingSet::default()),
+                None,
+                cx,
+            )
+        });
+        imported.update(cx, |imported, _cx| {
+            assert_eq!(
+                message_texts(imported),
+                ["What does main do?", "Let me check.", "", "Nothing."]
+            );
+            assert_eq!(
+                serde_json::to_value(imported.to_exported()).unwrap(),
+                exported
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_restore_plan(cx: &mut TestAppContext) {
+        let thread = cx.new(|cx| Thread::new(Arc::new(ToolWorkingSet::default()), None, cx));
+        thread.update(cx, |thread, cx| {
+            let main_rs = test_project_path("main.rs");
+            let lib_rs = test_project_path("lib.rs");
+
+            thread.insert_message(Role::User, "Rename foo to bar", cx);
+            let first_edit = thread.insert_message(Role::Assistant, "Renaming in main.rs", cx);
+            thread.insert_checkpoint_file(first_edit, main_rs.clone(), file_checkpoint("foo"), cx);
+            thread.record_tool_edit(main_rs.clone(), "bar".into());
+            let question = thread.insert_message(Role::User, "Also in lib.rs", cx);
+            let second_edit = thread.insert_message(Role::Assistant, "Renaming in both", cx);
+            thread.insert_checkpoint_file(second_edit, main_rs.clone(), file_checkpoint("bar"), cx);
+            thread.insert_checkpoint_file(second_edit, lib_rs.clone(), file_checkpoint("foo"), cx);
+            thread.record_tool_edit(main_rs.clone(), "baz".into());
+            thread.record_tool_edit(lib_rs.clone(), "bar".into());
+
+            // Restoring a message undoes the edits of every later message, keeping each
+            // file's oldest contents.
+            assert_eq!(
+                restore_plan_texts(thread, first_edit),
+                ["lib.rs: foo (now bar)", "main.rs: foo (now baz)"]
+            );
+            assert_eq!(
+                restore_plan_texts(thread, second_edit),
+                ["lib.rs: foo (now bar)", "main.rs: bar (now baz)"]
+            );
+
+            // Checkpoints of messages in inactive branches are left out.
+            thread
+                .edit_user_message(question, "Never mind", cx)
+                .unwrap();
+            assert_eq!(
+                restore_plan_texts(thread, first_edit),
+                ["main.rs: foo (now baz)"]
+            );
+            assert!(thread.restore_plan(second_edit).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_restore_conflicts() {
+        let mut file = RestoreFile {
+            project_path: test_project_path("main.rs"),
+            checkpoint: file_checkpoint("foo"),
+            expected_text: Some("bar".into()),
+        };
+        assert!(!file.is_edited_since("bar"));
+        assert!(file.is_edited_since("bar, edited by the user"));
+
+        // Without the contents the tools left, there's nothing to compare with.
+        file.expected_text = None;
+        assert!(!file.is_edited_since("bar, edited by the user"));
+    }
+
+    #[gpui::test]
+    async fn test_saving_and_loading(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            AssistantSettings::register(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", serde_json::json!({ "main.rs": "bar" }))
+            .await;
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let main_rs = project.read_with(cx, |project, cx| {
+            let worktree = project.worktrees(cx).next().unwrap();
+            ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: Path::new("main.rs").into(),
+            }
+        });
+
+        let thread = cx.new(|cx| Thread::new(Arc::new(ToolWorkingSet::default()), None, cx));
+        let (edit, saved) = thread.update(cx, |thread, cx| {
+            thread.insert_message(Role::User, "Rename foo to bar", cx);
+            let edit = thread.insert_message(Role::Assistant, "Renaming", cx);
+            thread.insert_checkpoint_file(edit, main_rs.clone(), file_checkpoint("foo"), cx);
+            thread.record_tool_edit(main_rs.clone(), "bar".into());
+            thread.update_token_usage("claude", TokenUsage::default(), token_usage(100, 10));
+            let message = thread.message_mut(edit).unwrap();
+            message.thinking = "foo is only used in main.rs".into();
+            message.thinking_signature = Some("signature".into());
+            let saved = thread.to_saved(project.read(cx), cx);
+            (edit, serde_json::to_string(&saved).unwrap())
+        });
+
+        let loaded = cx.new(|cx| {
+            Thread::from_saved(
+                ThreadId::new(),
+                serde_json::from_str(&saved).unwrap(),
+                Arc::new(ToolWorkingSet::default()),
+                None,
+                &project,
+                cx,
+            )
+        });
+        loaded.update(cx, |loaded, cx| {
+            assert_eq!(loaded.cumulative_token_usage(), token_usage(100, 10));
+            assert!(loaded.has_checkpoint(edit));
+            assert_eq!(restore_plan_texts(loaded, edit), ["main.rs: foo (now bar)"]);
+
+            // The thinking and its signature are sent back with the next request.
+            let request = loaded.to_completion_request(RequestKind::Chat, cx);
+            assert_eq!(
+                request.messages[1].content,
+                [
+                    MessageContent::Thinking {
+                        text: "foo is only used in main.rs".into(),
+                        signature: Some("signature".into()),
+                    },
+                    MessageContent::Text("Renaming".into()),
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_importing_thread_with_image(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            AssistantSettings::register(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+
+        let image: LanguageModelImage = serde_json::from_value(serde_json::json!({
+            "source": "iVBORw0KGgo=",
+            "size": { "width": 1, "height": 1 },
+        }))
+        .unwrap();
+        let tool_use = LanguageModelToolUse {
+            id: "tool-1".into(),
+            name: "read-file".into(),
+            input: serde_json::json!({ "path": "main.rs" }),
+        };
+        let tool_result = LanguageModelToolResult {
+            tool_use_id: "tool-1".into(),
+            is_error: false,
+            content: "fn main() {}".into(),
+        };
+        let exported = ExportedThread {
+            version: ExportedThread::VERSION.to_string(),
+            summary: "Screenshot".into(),
+            updated_at: Utc::now(),
+            active_message_id: Some(MessageId(3)),
+            messages: vec![
+                ExportedMessage {
+                    id: MessageId(0),
+                    parent_id: None,
+                    role: Role::User,
+                    content: vec![
+                        MessageContent::Image(image.clone()),
+                        MessageContent::Text("What does this show?".into()),
+                    ],
+                    context: vec![ExportedContext {
+                        name: "main.rs".into(),
+                        kind: ContextKind::File,
+                        text: "fn main() {}".into(),
+                    }],
+                },
+                ExportedMessage {
+                    id: MessageId(1),
+                    parent_id: Some(MessageId(0)),
+                    role: Role::Assistant,
+                    content: vec![
+                        MessageContent::Thinking {
+                            text: "The code is in main.rs".into(),
+                            signature: Some("signature".into()),
+                        },
+                        MessageContent::Text("Let me check.".into()),
+                        MessageContent::ToolUse(tool_use.clone()),
+                    ],
+                    context: Vec::new(),
+                },
+                ExportedMessage {
+                    id: MessageId(2),
+                    parent_id: Some(MessageId(1)),
+                    role: Role::User,
+                    content: vec![MessageContent::ToolResult(tool_result.clone())],
+                    context: Vec::new(),
+                },
+                ExportedMessage {
+                    id: MessageId(3),
+                    parent_id: Some(MessageId(2)),
+                    role: Role::Assistant,
+                    content: vec![MessageContent::Text("An empty main function.".into())],
+                    context: Vec::new(),
+                },
+            ],
+        };
+        exported.validate().unwrap();
+        let exported_json = serde_json::to_value(&exported).unwrap();
+
+        // Imported threads are saved like any other, so everything they send to the model
+        // must survive saving and loading them.
+        let imported = cx.new(|cx| {
+            Thread::from_exported(exported, Arc::new(ToolWorkingSet::default()), None, cx)
+        });
+        let saved = imported.read_with(cx, |imported, cx| {
+            serde_json::to_string(&imported.to_saved(project.read(cx), cx)).unwrap()
+        });
+        let loaded = cx.new(|cx| {
+            Thread::from_saved(
+                ThreadId::new(),
+                serde_json::from_str(&saved).unwrap(),
+                Arc::new(ToolWorkingSet::default()),
+                None,
+                &project,
+                cx,
+            )
+        });
+        loaded.update(cx, |loaded, cx| {
+            assert_eq!(loaded.images_for_message(MessageId(0)), [image.clone()]);
+            assert_eq!(
+                serde_json::to_value(loaded.to_exported()).unwrap(),
+                exported_json
+            );
+
+            let request = loaded.to_completion_request(RequestKind::Chat, cx);
+            let contents = request
+                .messages
+                .iter()
+                .flat_map(|message| &message.content)
+                .collect::<Vec<_>>();
+            assert!(contents.contains(&&MessageContent::Image(image)));
+            assert!(contents.contains(&&MessageContent::ToolUse(tool_use)));
+            assert!(contents.contains(&&MessageContent::ToolResult(tool_result)));
+            assert!(contents.contains(&&MessageContent::Thinking {
+                text: "The code is in main.rs".into(),
+                signature: Some("signature".into()),
+            }));
+        });
+    }
+
+    #[gpui::test]
+    fn test_forking_keeps_checkpoints_and_token_usage(cx: &mut TestAppContext) {
+        let thread = cx.new(|cx| Thread::new(Arc::new(ToolWorkingSet::default()), None, cx));
+        thread.update(cx, |thread, cx| {
+            let main_rs = test_project_path("main.rs");
+            thread.insert_message(Role::User, "Rename foo to bar", cx);
+            let first_edit = thread.insert_message(Role::Assistant, "Renaming", cx);
+            thread.insert_checkpoint_file(first_edit, main_rs.clone(), file_checkpoint("foo"), cx);
+            thread.record_tool_edit(main_rs.clone(), "bar".into());
+            let question = thread.insert_message(Role::User, "Now to baz", cx);
+            let second_edit = thread.insert_message(Role::Assistant, "Renaming again", cx);
+            thread.insert_checkpoint_file(second_edit, main_rs.clone(), file_checkpoint("bar"), cx);
+            thread.record_tool_edit(main_rs.clone(), "baz".into());
+            thread.update_token_usage("claude", TokenUsage::default(), token_usage(100, 10));
+
+            let forked_thread = thread.fork(question);
+            assert!(forked_thread.has_checkpoint(first_edit));
+            assert!(!forked_thread.has_checkpoint(second_edit));
+            assert_eq!(
+                restore_plan_texts(&forked_thread, first_edit),
+                ["main.rs: foo (now baz)"]
+            );
+            assert_eq!(forked_thread.cumulative_token_usage(), token_usage(100, 10));
+        });
+    }
+
+    #[gpui::test]
+    fn test_accumulating_token_usage(cx: &mut TestAppContext) {
+        let thread = cx.new(|cx| Thread::new(Arc::new(ToolWorkingSet::default()), None, cx));
+        thread.update(cx, |thread, _cx| {
+            // Each report of a request replaces the one before it.
+            thread.update_token_usage("claude", TokenUsage::default(), token_usage(100, 1));
+            thread.update_token_usage("claude", token_usage(100, 1), token_usage(100, 50));
+            // Later requests add to the total.
+            thread.update_token_usage("claude", TokenUsage::default(), token_usage(200, 10));
+            thread.update_token_usage("gpt-4o", TokenUsage::default(), token_usage(10, 5));
+            assert_eq!(thread.token_usage_by_model["claude"], token_usage(300, 60));
+            assert_eq!(thread.token_usage_by_model["gpt-4o"], token_usage(10, 5));
+            assert_eq!(thread.cumulative_token_usage(), token_usage(310, 65));
+
+            // Totals saturate rather than overflow.
+            thread.update_token_usage("gpt-4o", TokenUsage::default(), token_usage(u32::MAX, 0));
+            assert_eq!(thread.cumulative_token_usage().input_tokens, u32::MAX);
+            assert_eq!(thread.cumulative_token_usage().total_tokens(), u32::MAX);
+        });
+    }
+
+    fn token_usage(input_tokens: u32, output_tokens: u32) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            output_tokens,
+            ..TokenUsage::default()
+        }
+    }
+
+    fn test_project_path(path: &str) -> ProjectPath {
+        ProjectPath {
+            worktree_id: WorktreeId::from_usize(1),
+            path: Path::new(path).into(),
+        }
+    }
+
+    fn file_checkpoint(text: &str) -> FileCheckpoint {
+        FileCheckpoint {
+            text: Some(text.into()),
+            was_dirty: false,
+        }
+    }
+
+    fn restore_plan_texts(thread: &Thread, message_id: MessageId) -> Vec<String> {
+        let mut files = thread
+            .restore_plan(message_id)
+            .into_iter()
+            .map(|file| {
+                let mut text = format!(
+                    "{}: {}",
+                    file.project_path.path.display(),
+                    file.checkpoint.text.as_deref().unwrap_or("<created>")
+                );
+                if let Some(expected_text) = file.expected_text {
+                    text.push_str(&format!(" (now {expected_text})"));
+                }
+                text
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    fn message_texts(thread: &Thread) -> Vec<&str> {
+        thread
+            .messages()
+            .map(|message| message.text.as_str())
+            .collect()
+    }
 }
@@ -1,30 +1,37 @@
+use std::fmt::Write as _;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
+use assistant_context_editor::{AssistantContext, RequestType, DEFAULT_TAB_TITLE};
 use assistant_tool::{ToolId, ToolWorkingSet};
 use chrono::{DateTime, Utc};
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use context_server::manager::ContextServerManager;
 use context_server::{ContextServerFactoryRegistry, ContextServerTool};
 use futures::future::{self, BoxFuture, Shared};
 use futures::FutureExt as _;
 use gpui::{prelude::*, App, BackgroundExecutor, Context, Entity, SharedString, Task};
-use heed::types::SerdeBincode;
-use heed::Database;
-use language_model::Role;
+use heed::types::{Bytes, SerdeBincode, SerdeJson};
+use heed::{BytesDecode as _, Database};
+use language_model::{
+    LanguageModelImage, LanguageModelToolResult, LanguageModelToolUse, MessageContent, Role,
+    TokenUsage,
+};
 use project::Project;
 use serde::{Deserialize, Serialize};
 use util::ResultExt as _;
 
+use crate::context::ContextKind;
+use crate::project_rules::ProjectRules;
 use crate::thread::{MessageId, Thread, ThreadId};
 
 pub struct ThreadStore {
-    #[allow(unused)]
     project: Entity<Project>,
     tools: Arc<ToolWorkingSet>,
     context_server_manager: Entity<ContextServerManager>,
     context_server_tool_ids: HashMap<Arc<str>, Vec<ToolId>>,
+    project_rules: Entity<ProjectRules>,
     threads: Vec<SavedThreadMetadata>,
     database_future: Shared<BoxFuture<'static, Result<Arc<ThreadsDatabase>, Arc<anyhow::Error>>>>,
 }
@@ -41,6 +48,8 @@ impl ThreadStore {
                 ContextServerManager::new(context_server_factory_registry, project.clone(), cx)
             });
 
+            let project_rules = cx.new(|cx| ProjectRules::new(project.clone(), cx));
+
             let executor = cx.background_executor().clone();
             let database_future = executor
                 .spawn({
@@ -57,6 +66,7 @@ impl ThreadStore {
                 tools,
                 context_server_manager,
                 context_server_tool_ids: HashMap::default(),
+                project_rules,
                 threads: Vec::new(),
                 database_future,
             };
@@ -69,6 +79,15 @@ impl ThreadStore {
         Ok(this)
     }
 
+    pub fn context_server_manager(&self) -> Entity<ContextServerManager> {
+        self.context_server_manager.clone()
+    }
+
+    /// Returns the rules files of the project, which are included in every request.
+    pub fn project_rules(&self) -> Entity<ProjectRules> {
+        self.project_rules.clone()
+    }
+
     /// Returns the number of threads.
     pub fn thread_count(&self) -> usize {
         self.threads.len()
@@ -85,7 +104,48 @@ impl ThreadStore {
     }
 
     pub fn create_thread(&mut self, cx: &mut Context<Self>) -> Entity<Thread> {
-        cx.new(|cx| Thread::new(self.tools.clone(), cx))
+        cx.new(|cx| Thread::new(self.tools.clone(), Some(self.project_rules.clone()), cx))
+    }
+
+    /// Creates and saves a new thread with the messages of the active branch of the given
+    /// thread, up to and including the given message.
+    pub fn fork_thread(
+        &mut self,
+        thread: &Entity<Thread>,
+        message_id: MessageId,
+        cx: &mut Context<Self>,
+    ) -> Entity<Thread> {
+        let forked_thread = thread.read(cx).fork(message_id);
+        let forked_thread = cx.new(|_cx| forked_thread);
+        self.save_thread(&forked_thread, cx).detach_and_log_err(cx);
+        forked_thread
+    }
+
+    /// Creates and saves a new thread from an exported one.
+    pub fn import_thread(
+        &mut self,
+        exported: ExportedThread,
+        cx: &mut Context<Self>,
+    ) -> Result<Entity<Thread>> {
+        if exported.version != ExportedThread::VERSION {
+            return Err(anyhow!(
+                "unsupported exported thread version: {}",
+                exported.version
+            ));
+        }
+
+        exported.validate()?;
+
+        let thread = cx.new(|cx| {
+            Thread::from_exported(
+                exported,
+                self.tools.clone(),
+                Some(self.project_rules.clone()),
+                cx,
+            )
+        });
+        self.save_thread(&thread, cx).detach_and_log_err(cx);
+        Ok(thread)
     }
 
     pub fn open_thread(
@@ -103,29 +163,24 @@ impl ThreadStore {
                 .ok_or_else(|| anyhow!("no thread found with ID: {id:?}"))?;
 
             this.update(&mut cx, |this, cx| {
-                cx.new(|cx| Thread::from_saved(id.clone(), thread, this.tools.clone(), cx))
+                cx.new(|cx| {
+                    Thread::from_saved(
+                        id.clone(),
+                        thread,
+                        this.tools.clone(),
+                        Some(this.project_rules.clone()),
+                        &this.project,
+                        cx,
+                    )
+                })
             })
         })
     }
 
     pub fn save_thread(&self, thread: &Entity<Thread>, cx: &mut Context<Self>) -> Task<Result<()>> {
-        let (metadata, thread) = thread.update(cx, |thread, _cx| {
-            let id = thread.id().clone();
-            let thread = SavedThread {
-                summary: thread.summary_or_default(),
-                updated_at: thread.updated_at(),
-                messages: thread
-                    .messages()
-                    .map(|message| SavedMessage {
-                        id: message.id,
-                        role: message.role,
-                        text: message.text.clone(),
-                    })
-                    .collect(),
-            };
-
-            (id, thread)
-        });
+        let thread = thread.read(cx);
+        let metadata = thread.id().clone();
+        let thread = thread.to_saved(self.project.read(cx), cx);
 
         let database_future = self.database_future.clone();
         cx.spawn(|this, mut cx| async move {
@@ -228,6 +283,7 @@ impl ThreadStore {
                     tool_working_set.remove(&tool_ids);
                 }
             }
+            context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
 }
@@ -239,24 +295,361 @@ pub struct SavedThreadMetadata {
     pub updated_at: DateTime<Utc>,
 }
 
+/// A thread as it's saved in the database, as JSON so that fields can be added without
+/// migrating the saved threads.
 #[derive(Serialize, Deserialize)]
 pub struct SavedThread {
     pub summary: SharedString,
     pub updated_at: DateTime<Utc>,
+    /// The last message in the active branch.
+    pub active_message_id: Option<MessageId>,
+    /// All the messages in the thread, including those in inactive branches.
     pub messages: Vec<SavedMessage>,
+    #[serde(default)]
+    pub checkpoints: Vec<SavedCheckpoint>,
+    /// The contents the tools left the files they modified with.
+    #[serde(default)]
+    pub tool_edited_texts: Vec<SavedToolEdit>,
+    /// The tokens used by the completions requested in the thread, by model ID.
+    #[serde(default)]
+    pub token_usage_by_model: HashMap<String, TokenUsage>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SavedMessage {
     pub id: MessageId,
+    pub parent_id: Option<MessageId>,
+    pub role: Role,
+    pub text: String,
+    /// What the model reasoned before it answered, for models that think.
+    #[serde(default)]
+    pub thinking: String,
+    /// The signature the model's provider requires to send the thinking back.
+    #[serde(default)]
+    pub thinking_signature: Option<String>,
+    /// Images sent along with the message, which only imported threads have.
+    #[serde(default)]
+    pub images: Vec<LanguageModelImage>,
+    #[serde(default)]
+    pub tool_uses: Vec<LanguageModelToolUse>,
+    /// The results of the tools used in the previous message.
+    #[serde(default)]
+    pub tool_results: Vec<LanguageModelToolResult>,
+    #[serde(default)]
+    pub context: Vec<SavedContext>,
+}
+
+/// Context attached to a message, as it was sent to the model.
+#[derive(Serialize, Deserialize)]
+pub struct SavedContext {
+    pub name: SharedString,
+    pub kind: ContextKind,
+    pub text: String,
+}
+
+/// The contents of the files modified by the tools requested in an Assistant message, from
+/// before they ran.
+#[derive(Serialize, Deserialize)]
+pub struct SavedCheckpoint {
+    pub message_id: MessageId,
+    pub files: Vec<SavedFileCheckpoint>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedFileCheckpoint {
+    /// The absolute path of the file, as worktree IDs change between sessions.
+    pub path: PathBuf,
+    /// The contents of the file, or `None` if the tools created it.
+    pub text: Option<String>,
+    pub was_dirty: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedToolEdit {
+    /// The absolute path of the file, as worktree IDs change between sessions.
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// A thread exported to share it outside of Zed, which can be imported as a new thread.
+///
+/// Unlike [`SavedThread`], it keeps everything that's sent to the model, such as images, tool
+/// uses and their results, and the context attached to each message.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedThread {
+    pub version: String,
+    pub summary: SharedString,
+    pub updated_at: DateTime<Utc>,
+    /// The last message in the active branch.
+    pub active_message_id: Option<MessageId>,
+    /// All the messages in the thread, including those in inactive branches.
+    pub messages: Vec<ExportedMessage>,
+}
+
+impl ExportedThread {
+    pub const VERSION: &'static str = "0.1.0";
+
+    /// Converts a context from the prompt editor, whose messages follow each other in order.
+    pub fn from_context(context: &AssistantContext, cx: &App) -> Self {
+        let mut parent_id = None;
+        let messages = context
+            .to_completion_request(RequestType::Chat, cx)
+            .messages
+            .into_iter()
+            .enumerate()
+            .map(|(ix, message)| {
+                let id = MessageId(ix);
+                ExportedMessage {
+                    id,
+                    parent_id: parent_id.replace(id),
+                    role: message.role,
+                    content: message.content,
+                    context: Vec::new(),
+                }
+            })
+            .collect();
+
+        Self {
+            version: Self::VERSION.to_string(),
+            summary: context
+                .summary()
+                .map_or(DEFAULT_TAB_TITLE.into(), |summary| {
+                    summary.text.clone().into()
+                }),
+            updated_at: Utc::now(),
+            active_message_id: parent_id,
+            messages,
+        }
+    }
+
+    /// Checks that the messages form a valid thread, as exported threads may have been edited.
+    pub fn validate(&self) -> Result<()> {
+        let mut message_ids = HashSet::default();
+        let mut tool_use_ids = HashSet::default();
+        for message in &self.messages {
+            // The ID after the last message's is given to the next message.
+            if message.id.0 == usize::MAX {
+                bail!(
+                    "invalid exported thread: message ID {} is too large",
+                    message.id.0
+                );
+            }
+            if !message_ids.insert(message.id) {
+                bail!(
+                    "invalid exported thread: duplicate message ID {}",
+                    message.id.0
+                );
+            }
+            tool_use_ids.extend(message.content.iter().filter_map(|content| match content {
+                MessageContent::ToolUse(tool_use) => Some(&tool_use.id),
+                _ => None,
+            }));
+        }
+
+        for message in &self.messages {
+            if let Some(parent_id) = message.parent_id {
+                if parent_id >= message.id || !message_ids.contains(&parent_id) {
+                    bail!("invalid exported thread: messages must follow their parent");
+                }
+            }
+            for content in &message.content {
+                if let MessageContent::ToolResult(tool_result) = content {
+                    if !tool_use_ids.contains(&tool_result.tool_use_id) {
+                        bail!(
+                            "invalid exported thread: no tool use for the result of {}",
+                            tool_result.tool_use_id
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(active_message_id) = self.active_message_id {
+            if !message_ids.contains(&active_message_id) {
+                bail!(
+                    "invalid exported thread: active message {} not found",
+                    active_message_id.0
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the messages of the active branch, from the first to the last.
+    fn active_branch(&self) -> Vec<&ExportedMessage> {
+        let mut branch = Vec::new();
+        let mut next_id = self.active_message_id;
+        while let Some(message) =
+            next_id.and_then(|id| self.messages.iter().find(|message| message.id == id))
+        {
+            branch.push(message);
+            next_id = message.parent_id;
+        }
+        branch.reverse();
+        branch
+    }
+
+    /// Returns the active branch of the thread as Markdown, with its tool uses and their results
+    /// rendered.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.summary);
+
+        for message in self.active_branch() {
+            let heading = match message.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+                Role::System => "System",
+            };
+            write!(markdown, "\n## {heading}\n\n").ok();
+
+            for content in &message.content {
+                match content {
+                    MessageContent::Text(text) => {
+                        writeln!(markdown, "{}\n", text.trim_end()).ok();
+                    }
+                    MessageContent::Thinking { text, .. } => {
+                        writeln!(
+                            markdown,
+                            "<details><summary>Thinking</summary>\n\n{}\n\n</details>\n",
+                            text.trim_end()
+                        )
+                        .ok();
+                    }
+                    MessageContent::Image(image) => {
+                        writeln!(
+                            markdown,
+                            "![Image](data:image/png;base64,{})\n",
+                            image.source
+                        )
+                        .ok();
+                    }
+                    MessageContent::ToolUse(tool_use) => {
+                        let input = serde_json::to_string_pretty(&tool_use.input)
+                            .unwrap_or_else(|_| tool_use.input.to_string());
+                        writeln!(
+                            markdown,
+                            "**Tool Use:** `{}` (`{}`)\n\n{}",
+                            tool_use.name,
+                            tool_use.id,
+                            fenced_code_block("json", &input)
+                        )
+                        .ok();
+                    }
+                    MessageContent::ToolResult(tool_result) => {
+                        let label = if tool_result.is_error {
+                            "Tool Error"
+                        } else {
+                            "Tool Result"
+                        };
+                        writeln!(
+                            markdown,
+                            "**{label}:** `{}`\n\n{}",
+                            tool_result.tool_use_id,
+                            fenced_code_block("", &tool_result.content)
+                        )
+                        .ok();
+                    }
+                }
+            }
+
+            for context in &message.context {
+                writeln!(
+                    markdown,
+                    "<details><summary>{}: {}</summary>\n\n{}\n</details>\n",
+                    context.kind.label(),
+                    context.name,
+                    fenced_code_block("", &context.text)
+                )
+                .ok();
+            }
+        }
+
+        markdown
+    }
+}
+
+/// Wraps the text in a fenced code block, with a fence longer than any run of backticks in it.
+fn fenced_code_block(language: &str, text: &str) -> String {
+    let longest_backtick_run = text
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_backtick_run.max(2) + 1);
+    format!("{fence}{language}\n{}\n{fence}\n", text.trim_end())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedMessage {
+    pub id: MessageId,
+    pub parent_id: Option<MessageId>,
     pub role: Role,
+    /// The text, thinking, images, tool uses and tool results of the message.
+    pub content: Vec<MessageContent>,
+    pub context: Vec<ExportedContext>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedContext {
+    pub name: SharedString,
+    pub kind: ContextKind,
     pub text: String,
 }
 
+/// A thread saved before threads could have branches, when its messages followed each
+/// other in order.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct LegacySavedThread {
+    summary: SharedString,
+    updated_at: DateTime<Utc>,
+    messages: Vec<LegacySavedMessage>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct LegacySavedMessage {
+    id: MessageId,
+    role: Role,
+    text: String,
+}
+
+impl From<LegacySavedThread> for SavedThread {
+    fn from(thread: LegacySavedThread) -> Self {
+        let mut parent_id = None;
+        let messages = thread
+            .messages
+            .into_iter()
+            .map(|message| SavedMessage {
+                id: message.id,
+                parent_id: parent_id.replace(message.id),
+                role: message.role,
+                text: message.text,
+                thinking: String::new(),
+                thinking_signature: None,
+                images: Vec::new(),
+                tool_uses: Vec::new(),
+                tool_results: Vec::new(),
+                context: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            summary: thread.summary,
+            updated_at: thread.updated_at,
+            active_message_id: parent_id,
+            messages,
+            checkpoints: Vec::new(),
+            tool_edited_texts: Vec::new(),
+            token_usage_by_model: HashMap::default(),
+        }
+    }
+}
+
 struct ThreadsDatabase {
     executor: BackgroundExecutor,
     env: heed::Env,
-    threads: Database<SerdeBincode<ThreadId>, SerdeBincode<SavedThread>>,
+    threads: Database<SerdeBincode<ThreadId>, SerdeJson<SavedThread>>,
 }
 
 impl ThreadsDatabase {
@@ -267,12 +660,14 @@ impl ThreadsDatabase {
         let env = unsafe {
             heed::EnvOpenOptions::new()
                 .map_size(ONE_GB_IN_BYTES)
-                .max_dbs(1)
+                .max_dbs(2)
                 .open(path)?
         };
 
         let mut txn = env.write_txn()?;
-        let threads = env.create_database(&mut txn, Some("threads"))?;
+        let threads: Database<SerdeBincode<ThreadId>, SerdeJson<SavedThread>> =
+            env.create_database(&mut txn, Some("threads.v1"))?;
+        Self::migrate_legacy_threads(&env, &mut txn, threads)?;
         txn.commit()?;
 
         Ok(Self {
@@ -282,6 +677,34 @@ impl ThreadsDatabase {
         })
     }
 
+    /// Moves the threads saved with bincode, before threads could have branches, into the
+    /// database of saved threads.
+    fn migrate_legacy_threads(
+        env: &heed::Env,
+        txn: &mut heed::RwTxn,
+        threads: Database<SerdeBincode<ThreadId>, SerdeJson<SavedThread>>,
+    ) -> Result<()> {
+        // Threads are decoded one by one, so that those that fail to decode are kept
+        // rather than lost.
+        let old_threads: Database<SerdeBincode<ThreadId>, Bytes> =
+            env.create_database(txn, Some("threads"))?;
+        let mut migrated_threads = Vec::new();
+        for entry in old_threads.iter(txn)? {
+            let Some((id, bytes)) = entry.log_err() else {
+                continue;
+            };
+            match SerdeBincode::<LegacySavedThread>::bytes_decode(bytes) {
+                Ok(thread) => migrated_threads.push((id, thread.into())),
+                Err(error) => log::error!("failed to migrate thread {id}: {error}"),
+            }
+        }
+        for (id, thread) in migrated_threads {
+            threads.put(txn, &id, &thread)?;
+            old_threads.delete(txn, &id)?;
+        }
+        Ok(())
+    }
+
     pub fn list_threads(&self) -> Task<Result<Vec<SavedThreadMetadata>>> {
         let env = self.env.clone();
         let threads = self.threads;
@@ -337,3 +760,165 @@ impl ThreadsDatabase {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use heed::BytesEncode as _;
+
+    use super::*;
+
+    #[test]
+    fn test_validating_exported_threads() {
+        let tool_result = |tool_use_id: &str| {
+            MessageContent::ToolResult(LanguageModelToolResult {
+                tool_use_id: tool_use_id.into(),
+                is_error: false,
+                content: "fn main() {}".into(),
+            })
+        };
+        let tool_use = MessageContent::ToolUse(LanguageModelToolUse {
+            id: "tool-1".into(),
+            name: "read-file".into(),
+            input: serde_json::json!({ "path": "main.rs" }),
+        });
+        let valid_messages = || {
+            vec![
+                exported_message(0, None, Vec::new()),
+                exported_message(1, Some(0), vec![tool_use.clone()]),
+                exported_message(2, Some(1), vec![tool_result("tool-1")]),
+            ]
+        };
+
+        exported_thread(valid_messages(), Some(2))
+            .validate()
+            .unwrap();
+        exported_thread(valid_messages(), None).validate().unwrap();
+
+        let mut messages = valid_messages();
+        messages.push(exported_message(2, Some(0), Vec::new()));
+        assert_validation_error(exported_thread(messages, Some(2)), "duplicate message ID 2");
+
+        assert_validation_error(
+            exported_thread(valid_messages(), Some(3)),
+            "active message 3 not found",
+        );
+
+        let mut messages = valid_messages();
+        messages[2].content = vec![tool_result("tool-2")];
+        assert_validation_error(
+            exported_thread(messages, Some(2)),
+            "no tool use for the result of tool-2",
+        );
+
+        let mut messages = valid_messages();
+        messages.push(exported_message(usize::MAX, Some(2), Vec::new()));
+        assert_validation_error(exported_thread(messages, Some(usize::MAX)), "is too large");
+
+        let mut messages = valid_messages();
+        messages.push(exported_message(3, Some(5), Vec::new()));
+        assert_validation_error(
+            exported_thread(messages, Some(3)),
+            "messages must follow their parent",
+        );
+    }
+
+    fn exported_thread(
+        messages: Vec<ExportedMessage>,
+        active_message_id: Option<usize>,
+    ) -> ExportedThread {
+        ExportedThread {
+            version: ExportedThread::VERSION.to_string(),
+            summary: "Tools".into(),
+            updated_at: Utc::now(),
+            active_message_id: active_message_id.map(MessageId),
+            messages,
+        }
+    }
+
+    fn exported_message(
+        id: usize,
+        parent_id: Option<usize>,
+        content: Vec<MessageContent>,
+    ) -> ExportedMessage {
+        ExportedMessage {
+            id: MessageId(id),
+            parent_id: parent_id.map(MessageId),
+            role: Role::User,
+            content,
+            context: Vec::new(),
+        }
+    }
+
+    #[track_caller]
+    fn assert_validation_error(thread: ExportedThread, expected: &str) {
+        let error = thread.validate().unwrap_err().to_string();
+        assert!(
+            error.contains(expected),
+            "expected {expected:?} in {error:?}"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_migrating_threads_keeps_those_that_fail(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("threads-db.mdb");
+        std::fs::create_dir_all(&path).unwrap();
+
+        let valid_id = ThreadId::new();
+        let corrupt_id = ThreadId::new();
+        {
+            let env = unsafe { heed::EnvOpenOptions::new().max_dbs(2).open(&path).unwrap() };
+            let mut txn = env.write_txn().unwrap();
+            let legacy_threads: Database<SerdeBincode<ThreadId>, Bytes> =
+                env.create_database(&mut txn, Some("threads")).unwrap();
+            let thread = LegacySavedThread {
+                summary: "Greeting".into(),
+                updated_at: Utc::now(),
+                messages: vec![
+                    LegacySavedMessage {
+                        id: MessageId(0),
+                        role: Role::User,
+                        text: "Hello".into(),
+                    },
+                    LegacySavedMessage {
+                        id: MessageId(1),
+                        role: Role::Assistant,
+                        text: "Hi!".into(),
+                    },
+                ],
+            };
+            let bytes = SerdeBincode::<LegacySavedThread>::bytes_encode(&thread).unwrap();
+            legacy_threads.put(&mut txn, &valid_id, &bytes).unwrap();
+            legacy_threads.put(&mut txn, &corrupt_id, &[0xff]).unwrap();
+            txn.commit().unwrap();
+        }
+
+        let database = ThreadsDatabase::new(path, cx.executor()).unwrap();
+        let thread = database
+            .try_find_thread(valid_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(thread.active_message_id, Some(MessageId(1)));
+        assert_eq!(thread.messages[1].parent_id, Some(MessageId(0)));
+        assert_eq!(thread.messages[1].text, "Hi!");
+        assert!(database
+            .try_find_thread(corrupt_id.clone())
+            .await
+            .unwrap()
+            .is_none());
+
+        let txn = database.env.read_txn().unwrap();
+        let legacy_threads: Database<SerdeBincode<ThreadId>, Bytes> = database
+            .env
+            .open_database(&txn, Some("threads"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(legacy_threads.get(&txn, &valid_id).unwrap(), None);
+        assert_eq!(
+            legacy_threads.get(&txn, &corrupt_id).unwrap(),
+            Some(&[0xff][..])
+        );
+    }
+}
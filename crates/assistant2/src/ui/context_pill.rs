@@ -184,7 +184,12 @@ impl RenderOnce for ContextPill {
                             ContextKind::File => "Active Tab",
                             ContextKind::Thread
                             | ContextKind::Directory
-                            | ContextKind::FetchedUrl => "Active",
+                            | ContextKind::Symbol
+                            | ContextKind::FetchedUrl
+                            | ContextKind::Diagnostics
+                            | ContextKind::GitDiff
+                            | ContextKind::Terminal
+                            | ContextKind::Resource => "Active",
                         })
                         .size(LabelSize::XSmall)
                         .color(Color::Muted),
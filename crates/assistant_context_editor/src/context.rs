@@ -3,6 +3,7 @@ mod context_tests;
 
 use crate::patch::{AssistantEdit, AssistantPatch, AssistantPatchStatus};
 use anyhow::{anyhow, Context as _, Result};
+use assistant_settings::AssistantSettings;
 use assistant_slash_command::{
     SlashCommandContent, SlashCommandEvent, SlashCommandLine, SlashCommandOutputSection,
     SlashCommandResult, SlashCommandWorkingSet,
@@ -21,7 +22,7 @@ use language::{AnchorRangeExt, Bias, Buffer, LanguageRegistry, OffsetRangeExt, P
 use language_model::{
     LanguageModel, LanguageModelCacheConfiguration, LanguageModelCompletionEvent,
     LanguageModelImage, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
-    LanguageModelToolUseId, MessageContent, Role, StopReason,
+    LanguageModelToolUseId, MessageContent, Role, StopReason, TokenUsage,
 };
 use language_models::{
     provider::cloud::{MaxMonthlySpendReachedError, PaymentRequiredError},
@@ -32,6 +33,7 @@ use paths::contexts_dir;
 use project::Project;
 use prompt_library::PromptBuilder;
 use serde::{Deserialize, Serialize};
+use settings::Settings as _;
 use smallvec::SmallVec;
 use std::{
     cmp::{max, Ordering},
@@ -592,6 +594,8 @@ pub struct AssistantContext {
     pending_completions: Vec<PendingCompletion>,
     token_count: Option<usize>,
     pending_token_count: Task<Option<()>>,
+    token_usage_by_model: HashMap<String, TokenUsage>,
+    thinking_by_message: HashMap<MessageId, MessageThinking>,
     pending_save: Task<Result<()>>,
     pending_cache_warming_task: Task<Option<()>>,
     path: Option<PathBuf>,
@@ -693,6 +697,8 @@ impl AssistantContext {
             pending_completions: Default::default(),
             token_count: None,
             pending_token_count: Task::ready(None),
+            token_usage_by_model: HashMap::default(),
+            thinking_by_message: HashMap::default(),
             pending_cache_warming_task: Task::ready(None),
             _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
             pending_save: Task::ready(Ok(())),
@@ -744,6 +750,7 @@ impl AssistantContext {
                     id: message.id,
                     start: message.offset_range.start,
                     metadata: self.messages_metadata[&message.id].clone(),
+                    thinking: self.thinking_by_message.get(&message.id).cloned(),
                 })
                 .collect(),
             summary: self
@@ -798,6 +805,11 @@ impl AssistantContext {
         this.buffer.update(cx, |buffer, cx| {
             buffer.set_text(saved_context.text.as_str(), cx)
         });
+        this.thinking_by_message = saved_context
+            .messages
+            .iter()
+            .filter_map(|message| Some((message.id, message.thinking.clone()?)))
+            .collect();
         let operations = saved_context.into_ops(&this.buffer, cx);
         this.apply_ops(operations, cx);
         this
@@ -1185,6 +1197,28 @@ impl AssistantContext {
         self.token_count
     }
 
+    /// Returns the tokens used by all the completions requested in this context.
+    pub fn cumulative_token_usage(&self) -> TokenUsage {
+        self.token_usage_by_model
+            .values()
+            .fold(TokenUsage::default(), |total, usage| total + *usage)
+    }
+
+    /// Returns the estimated cost of the completions requested in this context, in US
+    /// dollars, if the price of any of the models used is configured.
+    pub fn estimated_cost(&self, cx: &App) -> Option<f64> {
+        let settings = AssistantSettings::get_global(cx);
+        self.token_usage_by_model
+            .iter()
+            .filter_map(|(model_id, usage)| settings.estimated_cost(model_id, usage))
+            .reduce(|total, cost| total + cost)
+    }
+
+    /// Returns the reasoning the model did before writing the given Assistant message.
+    pub fn thinking(&self, message_id: MessageId) -> Option<&MessageThinking> {
+        self.thinking_by_message.get(&message_id)
+    }
+
     pub(crate) fn count_remaining_tokens(&mut self, cx: &mut Context<Self>) {
         // Assume it will be a Chat request, even though that takes fewer tokens (and risks going over the limit),
         // because otherwise you see in the UI that your empty message has a bunch of tokens already used.
@@ -2213,9 +2247,11 @@ impl AssistantContext {
 
         let task = cx.spawn({
             |this, mut cx| async move {
+                let model_id = model.id().0.to_string();
                 let stream = model.stream_completion(request, &cx);
                 let assistant_message_id = assistant_message.id;
                 let mut response_latency = None;
+                let mut request_usage = TokenUsage::default();
                 let stream_completion = async {
                     let request_start = Instant::now();
                     let mut events = stream.await?;
@@ -2228,6 +2264,28 @@ impl AssistantContext {
                         let event = event?;
 
                         this.update(&mut cx, |this, cx| {
+                            if let LanguageModelCompletionEvent::UsageUpdate(usage) = &event {
+                                let total_usage = this
+                                    .token_usage_by_model
+                                    .entry(model_id.clone())
+                                    .or_default();
+                                *total_usage = *total_usage + *usage - request_usage;
+                                request_usage = *usage;
+                            }
+
+                            if let LanguageModelCompletionEvent::Thinking { text, signature } =
+                                &event
+                            {
+                                let thinking = this
+                                    .thinking_by_message
+                                    .entry(assistant_message_id)
+                                    .or_default();
+                                thinking.text.push_str(text);
+                                if let Some(signature) = signature {
+                                    thinking.signature = Some(signature.clone());
+                                }
+                            }
+
                             let message_ix = this
                                 .message_anchors
                                 .iter()
@@ -2255,7 +2313,9 @@ impl AssistantContext {
                                             cx,
                                         );
                                     }
+                                    LanguageModelCompletionEvent::Thinking { .. } => {}
                                     LanguageModelCompletionEvent::ToolUse(_) => {}
+                                    LanguageModelCompletionEvent::UsageUpdate(_) => {}
                                 }
                             });
 
@@ -2380,6 +2440,8 @@ impl AssistantContext {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            thinking_budget: AssistantSettings::get_global(cx).thinking_budget,
+            max_tokens: None,
         };
         for message in self.messages(cx) {
             if message.status != MessageStatus::Done {
@@ -2396,6 +2458,13 @@ impl AssistantContext {
                     .map_or(false, |cache| cache.is_anchor),
             };
 
+            if let Some(thinking) = self.thinking_by_message.get(&message.id) {
+                request_message.content.push(MessageContent::Thinking {
+                    text: thinking.text.clone(),
+                    signature: thinking.signature.clone(),
+                });
+            }
+
             while let Some(content) = contents.peek() {
                 if content
                     .range()
@@ -3113,6 +3182,16 @@ pub struct SavedMessage {
     pub id: MessageId,
     pub start: usize,
     pub metadata: MessageMetadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<MessageThinking>,
+}
+
+/// The reasoning a model did before writing an Assistant message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageThinking {
+    pub text: String,
+    /// The provider's signature for the reasoning, which must be sent back with it.
+    pub signature: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -3295,6 +3374,7 @@ impl SavedContextV0_3_0 {
                             timestamp,
                             cache: None,
                         },
+                        thinking: None,
                     })
                 })
                 .collect(),
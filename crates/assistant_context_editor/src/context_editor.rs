@@ -32,7 +32,7 @@ use indexed_docs::IndexedDocsStore;
 use language::{language_settings::SoftWrap, BufferSnapshot, LspAdapterDelegate, ToOffset};
 use language_model::{
     LanguageModelImage, LanguageModelProvider, LanguageModelProviderTosView, LanguageModelRegistry,
-    Role,
+    Role, TokenUsage,
 };
 use language_model_selector::{LanguageModelSelector, LanguageModelSelectorPopoverMenu};
 use multi_buffer::MultiBufferRow;
@@ -176,6 +176,8 @@ pub struct ContextEditor {
     editor: Entity<Editor>,
     blocks: HashMap<MessageId, (MessageHeader, CustomBlockId)>,
     image_blocks: HashSet<CustomBlockId>,
+    thinking_blocks: HashMap<MessageId, (CustomBlockId, u32)>,
+    expanded_thinking: HashSet<MessageId>,
     scroll_position: Option<ScrollPosition>,
     remote_id: Option<workspace::ViewId>,
     pending_slash_command_creases: HashMap<Range<language::Anchor>, CreaseId>,
@@ -248,6 +250,8 @@ impl ContextEditor {
             lsp_adapter_delegate,
             blocks: Default::default(),
             image_blocks: Default::default(),
+            thinking_blocks: Default::default(),
+            expanded_thinking: Default::default(),
             scroll_position: None,
             remote_id: None,
             fs,
@@ -265,6 +269,7 @@ impl ContextEditor {
         };
         this.update_message_headers(cx);
         this.update_image_blocks(cx);
+        this.update_thinking_blocks(cx);
         this.insert_slash_command_output_sections(sections, false, window, cx);
         this.patches_updated(&Vec::new(), &patch_ranges, window, cx);
         this
@@ -551,6 +556,7 @@ impl ContextEditor {
             ContextEvent::MessagesEdited => {
                 self.update_message_headers(cx);
                 self.update_image_blocks(cx);
+                self.update_thinking_blocks(cx);
                 self.context.update(cx, |context, cx| {
                     context.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
                 });
@@ -562,6 +568,7 @@ impl ContextEditor {
                 });
             }
             ContextEvent::StreamedCompletion => {
+                self.update_thinking_blocks(cx);
                 self.editor.update(cx, |editor, cx| {
                     if let Some(scroll_position) = self.scroll_position {
                         let snapshot = editor.snapshot(window, cx);
@@ -2010,6 +2017,143 @@ impl ContextEditor {
         });
     }
 
+    fn update_thinking_blocks(&mut self, cx: &mut Context<Self>) {
+        const MAX_EXPANDED_HEIGHT_IN_LINES: u32 = 12;
+
+        let context_editor = cx.entity().downgrade();
+        let context = self.context.read(cx);
+        let heights = context
+            .messages(cx)
+            .filter_map(|message| {
+                let thinking = context.thinking(message.id)?;
+                let height = if self.expanded_thinking.contains(&message.id) {
+                    let line_count = thinking.text.lines().count().max(1) as u32;
+                    1 + line_count.min(MAX_EXPANDED_HEIGHT_IN_LINES)
+                } else {
+                    1
+                };
+                Some((message.id, message.anchor_range.start, height))
+            })
+            .collect::<Vec<_>>();
+
+        self.editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let excerpt_id = *buffer.as_singleton().unwrap().0;
+            let mut blocks_to_remove = self
+                .thinking_blocks
+                .iter()
+                .map(|(message_id, (block_id, _))| (*message_id, *block_id))
+                .collect::<HashMap<_, _>>();
+            let mut blocks_to_resize = HashMap::default();
+            let mut new_blocks = Vec::new();
+            let mut new_block_messages = Vec::new();
+
+            for (message_id, anchor, height) in heights {
+                if blocks_to_remove.remove(&message_id).is_some() {
+                    if let Some((block_id, old_height)) = self.thinking_blocks.get_mut(&message_id)
+                    {
+                        if *old_height != height {
+                            blocks_to_resize.insert(*block_id, height);
+                            *old_height = height;
+                        }
+                    }
+                    continue;
+                }
+
+                let Some(anchor) = buffer.anchor_in_excerpt(excerpt_id, anchor) else {
+                    continue;
+                };
+                let context = self.context.clone();
+                let context_editor = context_editor.clone();
+                new_blocks.push(BlockProperties {
+                    placement: BlockPlacement::Above(anchor),
+                    height,
+                    style: BlockStyle::Sticky,
+                    render: Arc::new(move |cx| {
+                        let expanded = context_editor.upgrade().map_or(false, |editor| {
+                            editor.read(cx).expanded_thinking.contains(&message_id)
+                        });
+                        let text = context
+                            .read(cx)
+                            .thinking(message_id)
+                            .map(|thinking| thinking.text.trim().to_string())
+                            .unwrap_or_default();
+                        let line_count = text.lines().count().max(1) as u32;
+                        let context_editor = context_editor.clone();
+
+                        v_flex()
+                            .pl(cx.gutter_dimensions.full_width())
+                            .w_full()
+                            .child(
+                                h_flex()
+                                    .h(cx.line_height)
+                                    .gap_1()
+                                    .child(
+                                        Disclosure::new(
+                                            ("thinking-disclosure", message_id.as_u64()),
+                                            expanded,
+                                        )
+                                        .on_click(
+                                            move |_, _window, cx| {
+                                                context_editor
+                                                    .update(cx, |editor, cx| {
+                                                        editor.toggle_thinking(message_id, cx)
+                                                    })
+                                                    .ok();
+                                            },
+                                        ),
+                                    )
+                                    .child(
+                                        Label::new("Thinking")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    ),
+                            )
+                            .when(expanded, |parent| {
+                                parent.child(
+                                    div()
+                                        .id(("thinking-text", message_id.as_u64()))
+                                        .h(cx.line_height
+                                            * line_count.min(MAX_EXPANDED_HEIGHT_IN_LINES) as f32)
+                                        .overflow_y_scroll()
+                                        .child(
+                                            Label::new(text)
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        ),
+                                )
+                            })
+                            .into_any_element()
+                    }),
+                    priority: usize::MAX,
+                });
+                new_block_messages.push((message_id, height));
+            }
+
+            editor.resize_blocks(blocks_to_resize, None, cx);
+            editor.remove_blocks(blocks_to_remove.values().copied().collect(), None, cx);
+            for message_id in blocks_to_remove.keys() {
+                self.thinking_blocks.remove(message_id);
+            }
+
+            let ids = editor.insert_blocks(new_blocks, None, cx);
+            self.thinking_blocks.extend(
+                new_block_messages
+                    .into_iter()
+                    .zip(ids)
+                    .map(|((message_id, height), block_id)| (message_id, (block_id, height))),
+            );
+        });
+    }
+
+    fn toggle_thinking(&mut self, message_id: MessageId, cx: &mut Context<Self>) {
+        if !self.expanded_thinking.remove(&message_id) {
+            self.expanded_thinking.insert(message_id);
+        }
+        self.update_thinking_blocks(cx);
+        cx.notify();
+    }
+
     fn split(&mut self, _: &Split, _window: &mut Window, cx: &mut Context<Self>) {
         self.context.update(cx, |context, cx| {
             let selections = self.editor.read(cx).selections.disjoint_anchors();
@@ -3203,6 +3347,8 @@ impl ContextEditorToolbarItem {
             .upgrade()?
             .read(cx)
             .context;
+        let usage = context.read(cx).cumulative_token_usage();
+        let cost = context.read(cx).estimated_cost(cx);
         let (token_count_color, token_count, max_token_count) = match token_state(context, cx)? {
             TokenState::NoTokensLeft {
                 max_token_count,
@@ -3234,7 +3380,20 @@ impl ContextEditorToolbarItem {
                     Label::new(humanize_token_count(max_token_count))
                         .size(LabelSize::Small)
                         .color(Color::Muted),
-                ),
+                )
+                .when(usage.total_tokens() > 0, |parent| {
+                    parent.child(
+                        div()
+                            .id("token-usage")
+                            .ml_1()
+                            .child(
+                                Label::new(format_token_usage(&usage, cost))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .tooltip(Tooltip::text(describe_token_usage(&usage, cost))),
+                    )
+                }),
         )
     }
 }
@@ -3530,6 +3689,44 @@ pub fn humanize_token_count(count: usize) -> String {
     }
 }
 
+/// Formats an estimated cost in US dollars.
+pub fn humanize_cost(cost: f64) -> String {
+    if cost > 0. && cost < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("${:.2}", cost)
+    }
+}
+
+/// Formats the total tokens used so far, and their estimated cost if known.
+pub fn format_token_usage(usage: &TokenUsage, cost: Option<f64>) -> String {
+    let tokens = humanize_token_count(usage.total_tokens() as usize);
+    match cost {
+        Some(cost) => format!("{tokens} used · {}", humanize_cost(cost)),
+        None => format!("{tokens} used"),
+    }
+}
+
+/// Describes the tokens used so far, and their estimated cost if known, for a tooltip.
+pub fn describe_token_usage(usage: &TokenUsage, cost: Option<f64>) -> String {
+    let mut description = format!(
+        "Used {} input and {} output tokens",
+        humanize_token_count(usage.input_tokens as usize),
+        humanize_token_count(usage.output_tokens as usize),
+    );
+    if usage.cache_read_input_tokens > 0 || usage.cache_creation_input_tokens > 0 {
+        description.push_str(&format!(
+            ", reading {} and writing {} cached tokens",
+            humanize_token_count(usage.cache_read_input_tokens as usize),
+            humanize_token_count(usage.cache_creation_input_tokens as usize),
+        ));
+    }
+    if let Some(cost) = cost {
+        description.push_str(&format!(", costing about {}", humanize_cost(cost)));
+    }
+    description
+}
+
 pub fn make_lsp_adapter_delegate(
     project: &Entity<Project>,
     cx: &mut App,
@@ -2,10 +2,11 @@ use std::sync::Arc;
 
 use ::open_ai::Model as OpenAiModel;
 use anthropic::Model as AnthropicModel;
+use collections::HashMap;
 use deepseek::Model as DeepseekModel;
 use feature_flags::FeatureFlagAppExt;
 use gpui::{App, Pixels};
-use language_model::{CloudModel, LanguageModel};
+use language_model::{CloudModel, LanguageModel, TokenUsage};
 use lmstudio::Model as LmStudioModel;
 use ollama::Model as OllamaModel;
 use schemars::{schema::Schema, JsonSchema};
@@ -21,6 +22,47 @@ pub enum AssistantDockPosition {
     Bottom,
 }
 
+/// Whether the assistant may use a tool without asking first.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolApproval {
+    /// Ask for confirmation each time the tool is used.
+    Ask,
+    /// Use the tool without asking.
+    #[default]
+    Allow,
+    /// Never use the tool.
+    Deny,
+}
+
+/// The price of a language model's tokens, in US dollars per million tokens.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModelPrice {
+    /// The price of input tokens.
+    #[serde(default)]
+    pub input: f64,
+    /// The price of output tokens.
+    #[serde(default)]
+    pub output: f64,
+    /// The price of input tokens written to the prompt cache.
+    #[serde(default)]
+    pub cache_write: f64,
+    /// The price of input tokens read from the prompt cache.
+    #[serde(default)]
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    /// Returns the cost of the given usage, in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(tag = "name", rename_all = "snake_case")]
 pub enum AssistantProviderContentV1 {
@@ -65,12 +107,32 @@ pub struct AssistantSettings {
     pub inline_alternatives: Vec<LanguageModelSelection>,
     pub using_outdated_settings_version: bool,
     pub enable_experimental_live_diffs: bool,
+    pub tool_approval: HashMap<String, ToolApproval>,
+    pub model_prices: HashMap<String, ModelPrice>,
+    pub thinking_budget: Option<u32>,
 }
 
 impl AssistantSettings {
     pub fn are_live_diffs_enabled(&self, cx: &App) -> bool {
         cx.is_staff() || self.enable_experimental_live_diffs
     }
+
+    /// Returns whether the tool with the given name may be used without asking.
+    /// Tools that aren't configured are allowed.
+    pub fn tool_approval(&self, tool_name: &str) -> ToolApproval {
+        self.tool_approval
+            .get(tool_name)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the estimated cost of the given usage of a model, in US dollars, if the
+    /// model's price is configured.
+    pub fn estimated_cost(&self, model_id: &str, usage: &TokenUsage) -> Option<f64> {
+        self.model_prices
+            .get(model_id)
+            .map(|price| price.cost(usage))
+    }
 }
 
 /// Assistant panel settings
@@ -164,6 +226,9 @@ impl AssistantSettingsContent {
                         }),
                     inline_alternatives: None,
                     enable_experimental_live_diffs: None,
+                    tool_approval: None,
+                    model_prices: None,
+                    thinking_budget: None,
                 },
                 VersionedAssistantSettingsContent::V2(settings) => settings.clone(),
             },
@@ -184,6 +249,9 @@ impl AssistantSettingsContent {
                 }),
                 inline_alternatives: None,
                 enable_experimental_live_diffs: None,
+                tool_approval: None,
+                model_prices: None,
+                thinking_budget: None,
             },
         }
     }
@@ -312,6 +380,9 @@ impl Default for VersionedAssistantSettingsContent {
             default_model: None,
             inline_alternatives: None,
             enable_experimental_live_diffs: None,
+            tool_approval: None,
+            model_prices: None,
+            thinking_budget: None,
         })
     }
 }
@@ -346,6 +417,20 @@ pub struct AssistantSettingsContentV2 {
     ///
     /// Default: false
     enable_experimental_live_diffs: Option<bool>,
+    /// Whether the assistant may use each tool, by name, without asking first.
+    /// Tools that aren't listed are allowed.
+    ///
+    /// Default: {"edit_file": "ask", "run_command": "ask"}
+    tool_approval: Option<HashMap<String, ToolApproval>>,
+    /// The prices of models, by model ID, used to estimate the cost of conversations.
+    ///
+    /// Default: {}
+    model_prices: Option<HashMap<String, ModelPrice>>,
+    /// How many output tokens models that support it may spend thinking before they
+    /// answer. Thinking is disabled when this isn't set.
+    ///
+    /// Default: null
+    thinking_budget: Option<u32>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -474,6 +559,16 @@ impl Settings for AssistantSettings {
                 &mut settings.enable_experimental_live_diffs,
                 value.enable_experimental_live_diffs,
             );
+            settings
+                .tool_approval
+                .extend(value.tool_approval.unwrap_or_default());
+            settings
+                .model_prices
+                .extend(value.model_prices.unwrap_or_default());
+            merge(
+                &mut settings.thinking_budget,
+                value.thinking_budget.map(Some),
+            );
         }
 
         Ok(settings)
@@ -534,6 +629,9 @@ mod tests {
                             default_width: None,
                             default_height: None,
                             enable_experimental_live_diffs: None,
+                            tool_approval: None,
+                            model_prices: None,
+                            thinking_budget: None,
                         }),
                     )
                 },
@@ -227,6 +227,8 @@ async fn commands_for_summaries(
         tools: Vec::new(),
         stop: Vec::new(),
         temperature: None,
+        thinking_budget: None,
+        max_tokens: None,
     };
 
     while let Some(current_summaries) = stack.pop() {
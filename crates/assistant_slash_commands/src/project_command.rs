@@ -116,6 +116,8 @@ impl SlashCommand for ProjectSlashCommand {
                         tools: vec![],
                         stop: vec![],
                         temperature: None,
+                        thinking_budget: None,
+                        max_tokens: None,
                     },
                     cx.deref_mut(),
                 )
@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use gpui::{App, Task, WeakEntity, Window};
+use project::{Project, ProjectPath};
 use workspace::Workspace;
 
 pub use crate::tool_registry::*;
@@ -27,6 +28,24 @@ pub trait Tool: 'static + Send + Sync {
         serde_json::Value::Object(serde_json::Map::default())
     }
 
+    /// Returns the files the tool will modify when run with the provided input, so that
+    /// their contents can be saved beforehand.
+    fn modified_paths(
+        &self,
+        _input: &serde_json::Value,
+        _project: &Project,
+        _cx: &App,
+    ) -> Vec<ProjectPath> {
+        Vec::new()
+    }
+
+    /// Whether the tool may modify files other than those returned by
+    /// [`Tool::modified_paths`], such as by running a shell command. The project's files
+    /// are then saved beforehand, so that the tool's changes can be rolled back.
+    fn may_modify_any_file(&self) -> bool {
+        false
+    }
+
     /// Runs the tool with the provided input.
     fn run(
         self: Arc<Self>,
@@ -1,13 +1,400 @@
+mod diagnostics_tool;
+mod edit_file_tool;
+mod list_directory_tool;
 mod now_tool;
+mod read_file_tool;
+mod run_command_tool;
+mod search_tool;
 
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
 use assistant_tool::ToolRegistry;
-use gpui::App;
+use gpui::{App, Entity, WeakEntity};
+use project::{Project, ProjectPath};
+use workspace::Workspace;
 
+use crate::diagnostics_tool::DiagnosticsTool;
+use crate::edit_file_tool::EditFileTool;
+use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
+use crate::read_file_tool::ReadFileTool;
+use crate::run_command_tool::RunCommandTool;
+use crate::search_tool::SearchTool;
 
 pub fn init(cx: &mut App) {
     assistant_tool::init(cx);
 
     let registry = ToolRegistry::global(cx);
     registry.register_tool(NowTool);
+    registry.register_tool(ReadFileTool);
+    registry.register_tool(EditFileTool);
+    registry.register_tool(ListDirectoryTool);
+    registry.register_tool(SearchTool);
+    registry.register_tool(DiagnosticsTool);
+    registry.register_tool(RunCommandTool);
+}
+
+/// How the tools that take paths describe them to the model.
+const PATH_DESCRIPTION: &str = "Paths start with the name of one of the project's root directories. For example, with root directories `frontend` and `backend`, `backend/src/main.rs` is the file `src/main.rs` in `backend`.";
+
+fn project(workspace: &WeakEntity<Workspace>, cx: &App) -> Result<Entity<Project>> {
+    let workspace = workspace
+        .upgrade()
+        .ok_or_else(|| anyhow!("workspace was dropped"))?;
+    Ok(workspace.read(cx).project().clone())
+}
+
+/// Resolves a path given to a tool, which either starts with the name of one of the
+/// project's root directories or is absolute. Paths covered by the `private_files` setting
+/// are refused, as the files may hold secrets that mustn't be sent to the model.
+fn resolve_project_path(project: &Project, path: &str, cx: &App) -> Result<ProjectPath> {
+    let path = Path::new(path);
+    let project_path = if path.is_absolute() {
+        let (worktree, relative_path) = project
+            .find_worktree(path, cx)
+            .ok_or_else(|| anyhow!("{} is outside of the project", path.display()))?;
+        ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: relative_path.into(),
+        }
+    } else {
+        project
+            .visible_worktrees(cx)
+            .find_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let relative_path = path.strip_prefix(worktree.root_name()).ok()?;
+                Some(ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: relative_path.into(),
+                })
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "{} doesn't start with the name of one of the project's root directories",
+                    path.display()
+                )
+            })?
+    };
+
+    if is_private_path(project, &project_path, cx) {
+        return Err(anyhow!(
+            "{} is private, so its contents can't be accessed",
+            path.display()
+        ));
+    }
+    Ok(project_path)
+}
+
+/// Whether the path is covered by the `private_files` setting of its worktree. Paths that
+/// don't exist yet are checked against the setting itself.
+fn is_private_path(project: &Project, project_path: &ProjectPath, cx: &App) -> bool {
+    let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+        return false;
+    };
+    let worktree = worktree.read(cx);
+    match worktree.entry_for_path(&project_path.path) {
+        Some(entry) => entry.is_private,
+        None => worktree
+            .as_local()
+            .is_some_and(|worktree| worktree.is_path_private(&project_path.path)),
+    }
+}
+
+/// Returns a project path the way tools describe paths, starting with the name of its
+/// root directory.
+fn display_path(project: &Project, project_path: &ProjectPath, cx: &App) -> PathBuf {
+    match project.worktree_for_id(project_path.worktree_id, cx) {
+        Some(worktree) => Path::new(worktree.read(cx).root_name()).join(&project_path.path),
+        None => project_path.path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use assistant_tool::Tool;
+    use gpui::{SemanticVersion, TestAppContext, VisualTestContext};
+    use language::{
+        Diagnostic, DiagnosticEntry, DiagnosticSeverity, LanguageServerId, PointUtf16, Unclipped,
+    };
+    use project::{FakeFs, Fs};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_read_file_tool(cx: &mut TestAppContext) {
+        let (_, workspace, cx) = build_workspace(cx).await;
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "project/src/main.rs", "start_line": 2, "end_line": 2 }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "    let token = read_token();");
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "/project/notes.txt" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "the token lives in .env\n");
+
+        let error = run_tool(
+            ReadFileTool,
+            json!({ "path": "project/.env" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("is private"), "{error}");
+
+        let error = run_tool(
+            ReadFileTool,
+            json!({ "path": "/elsewhere/a.txt" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            error.to_string().contains("outside of the project"),
+            "{error}"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_edit_file_tool(cx: &mut TestAppContext) {
+        let (fs, workspace, cx) = build_workspace(cx).await;
+
+        let output = run_tool(
+            EditFileTool,
+            json!({
+                "path": "project/notes.txt",
+                "edits": [{ "old_text": "token", "new_text": "API token" }],
+            }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert!(output.is_ok(), "{output:?}");
+        assert_eq!(
+            fs.load(Path::new("/project/notes.txt")).await.unwrap(),
+            "the API token lives in .env\n"
+        );
+
+        let input = json!({
+            "path": "project/.env",
+            "edits": [{ "old_text": "TOKEN=hunter2", "new_text": "TOKEN=" }],
+        });
+        workspace.read_with(cx, |workspace, cx| {
+            let project = workspace.project().read(cx);
+            assert!(EditFileTool.modified_paths(&input, project, cx).is_empty());
+        });
+        let error = run_tool(EditFileTool, input, &workspace, cx)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("is private"), "{error}");
+        assert_eq!(
+            fs.load(Path::new("/project/.env")).await.unwrap(),
+            "TOKEN=hunter2\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_search_tool(cx: &mut TestAppContext) {
+        let (_, workspace, cx) = build_workspace(cx).await;
+
+        let output = run_tool(SearchTool, json!({ "query": "token" }), &workspace, cx)
+            .await
+            .unwrap();
+        assert!(output.contains("project/src/main.rs:2:"), "{output}");
+        assert!(output.contains("project/notes.txt:1:"), "{output}");
+        assert!(!output.contains(".env:"), "{output}");
+        assert!(!output.contains("hunter2"), "{output}");
+    }
+
+    #[gpui::test]
+    async fn test_list_directory_tool(cx: &mut TestAppContext) {
+        let (_, workspace, cx) = build_workspace(cx).await;
+
+        let output = run_tool(ListDirectoryTool, json!({ "path": "" }), &workspace, cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "project/\n");
+
+        let output = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "project/" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "notes.txt\nsrc/\n");
+
+        let error = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "project/notes.txt" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("not a directory"), "{error}");
+    }
+
+    #[gpui::test]
+    async fn test_diagnostics_tool(cx: &mut TestAppContext) {
+        let (_, workspace, cx) = build_workspace(cx).await;
+
+        let project = workspace.read_with(cx, |workspace, _| workspace.project().clone());
+        project.update(cx, |project, cx| {
+            project.lsp_store().update(cx, |lsp_store, cx| {
+                for (path, message) in [
+                    ("/project/src/main.rs", "undefined: read_token"),
+                    ("/project/.env", "leaked: hunter2"),
+                ] {
+                    lsp_store
+                        .update_diagnostic_entries(
+                            LanguageServerId(0),
+                            PathBuf::from(path),
+                            None,
+                            vec![DiagnosticEntry {
+                                range: Unclipped(PointUtf16::new(0, 0))
+                                    ..Unclipped(PointUtf16::new(0, 2)),
+                                diagnostic: Diagnostic {
+                                    severity: DiagnosticSeverity::ERROR,
+                                    is_primary: true,
+                                    message: message.to_string(),
+                                    ..Default::default()
+                                },
+                            }],
+                            cx,
+                        )
+                        .unwrap();
+                }
+            });
+        });
+
+        let output = run_tool(DiagnosticsTool, json!({}), &workspace, cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "project/src/main.rs: 1 errors, 0 warnings\n");
+
+        let output = run_tool(
+            DiagnosticsTool,
+            json!({ "path": "project/src/main.rs" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            output,
+            "project/src/main.rs:1:1: error: undefined: read_token\n"
+        );
+
+        let error = run_tool(
+            DiagnosticsTool,
+            json!({ "path": "project/.env" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("is private"), "{error}");
+    }
+
+    #[gpui::test]
+    async fn test_run_command_tool(cx: &mut TestAppContext) {
+        let (_, workspace, cx) = build_workspace(cx).await;
+
+        let error = run_tool(
+            RunCommandTool,
+            json!({ "command": "cat TOKEN", "cd": "project/.env" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("is private"), "{error}");
+
+        let error = run_tool(
+            RunCommandTool,
+            json!({ "command": "ls", "cd": "elsewhere" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("root directories"), "{error}");
+
+        let error = run_tool(
+            RunCommandTool,
+            json!({ "command": "ls", "cd": "project/src" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            error.to_string().contains("terminal panel isn't available"),
+            "{error}"
+        );
+    }
+
+    async fn build_workspace(
+        cx: &mut TestAppContext,
+    ) -> (Arc<FakeFs>, Entity<Workspace>, &mut VisualTestContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init(SemanticVersion::default(), cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".env": "TOKEN=hunter2\n",
+                "notes.txt": "the token lives in .env\n",
+                "src": {
+                    "main.rs": "fn main() {\n    let token = read_token();\n}\n",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/project".as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+        cx.run_until_parked();
+        (fs, workspace, cx)
+    }
+
+    async fn run_tool(
+        tool: impl Tool,
+        input: serde_json::Value,
+        workspace: &Entity<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Result<String> {
+        let task =
+            cx.update(|window, cx| Arc::new(tool).run(input, workspace.downgrade(), window, cx));
+        cx.run_until_parked();
+        task.await
+    }
 }
@@ -0,0 +1,111 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use language::{DiagnosticSeverity, OffsetRangeExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+use crate::{display_path, is_private_path, project, resolve_project_path, PATH_DESCRIPTION};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// The path of a file to list the errors and warnings of. Leave it empty to count the
+    /// errors and warnings in each file of the project.
+    #[serde(default)]
+    path: Option<String>,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Returns the errors and warnings reported by language servers, either for a single file or summarized for the whole project. {PATH_DESCRIPTION}")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input: DiagnosticsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let Some(path) = input.path.filter(|path| !path.trim().is_empty()) else {
+            let project = project.read(cx);
+            let mut output = String::new();
+            for (project_path, _, summary) in project.diagnostic_summaries(false, cx) {
+                if summary.error_count == 0 && summary.warning_count == 0
+                    || is_private_path(project, &project_path, cx)
+                {
+                    continue;
+                }
+                writeln!(
+                    output,
+                    "{}: {} errors, {} warnings",
+                    display_path(project, &project_path, cx).display(),
+                    summary.error_count,
+                    summary.warning_count
+                )
+                .ok();
+            }
+            if output.is_empty() {
+                output = "The project has no errors or warnings.".into();
+            }
+            return Task::ready(Ok(output));
+        };
+
+        let project_path = match resolve_project_path(project.read(cx), &path, cx) {
+            Ok(project_path) => project_path,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+
+            let mut output = String::new();
+            for (_, group) in snapshot.diagnostic_groups(None) {
+                let entry = &group.entries[group.primary_ix];
+                let severity = match entry.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => "error",
+                    DiagnosticSeverity::WARNING => "warning",
+                    _ => continue,
+                };
+                let range = entry.range.to_point(&snapshot);
+                writeln!(
+                    output,
+                    "{path}:{}:{}: {severity}: {}",
+                    range.start.row + 1,
+                    range.start.column + 1,
+                    entry.diagnostic.message
+                )
+                .ok();
+            }
+            if output.is_empty() {
+                output = format!("{path} has no errors or warnings.");
+            }
+            Ok(output)
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+use crate::{project, resolve_project_path, PATH_DESCRIPTION};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFileToolInput {
+    /// The path of the file to edit.
+    path: String,
+    /// The edits to make to the file, in order.
+    edits: Vec<Edit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Edit {
+    /// The text to replace, which must appear exactly once in the file. Include enough of
+    /// the surrounding lines to make it unique.
+    old_text: String,
+    /// The text to replace it with.
+    new_text: String,
+}
+
+pub struct EditFileTool;
+
+impl Tool for EditFileTool {
+    fn name(&self) -> String {
+        "edit_file".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Edits a file in the project by replacing pieces of its text, then saves it. Read the file first, so that the text to replace matches it exactly. {PATH_DESCRIPTION}")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(EditFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn modified_paths(
+        &self,
+        input: &serde_json::Value,
+        project: &Project,
+        cx: &App,
+    ) -> Vec<ProjectPath> {
+        serde_json::from_value::<EditFileToolInput>(input.clone())
+            .ok()
+            .and_then(|input| resolve_project_path(project, &input.path, cx).ok())
+            .into_iter()
+            .collect()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input: EditFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let project_path = match resolve_project_path(project.read(cx), &input.path, cx) {
+            Ok(project_path) => project_path,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|mut cx| async move {
+            let buffer = open_buffer.await?;
+            let mut text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+            for edit in &input.edits {
+                text = replace_once(&text, &edit.old_text, &edit.new_text)
+                    .map_err(|err| anyhow!("couldn't edit {}: {err}", input.path))?;
+            }
+
+            // Applying the edits as a diff keeps them undoable as a single transaction
+            // and preserves anchors, such as cursors, in the unchanged text.
+            let diff = buffer
+                .update(&mut cx, |buffer, cx| buffer.diff(text, cx))?
+                .await;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.apply_diff(diff, cx);
+                buffer.finalize_last_transaction();
+            })?;
+            project
+                .update(&mut cx, |project, cx| project.save_buffer(buffer, cx))?
+                .await?;
+
+            Ok(format!(
+                "Made {} edits to {}.",
+                input.edits.len(),
+                input.path
+            ))
+        })
+    }
+}
+
+fn replace_once(text: &str, old_text: &str, new_text: &str) -> Result<String> {
+    if old_text.is_empty() {
+        return Err(anyhow!("the text to replace is empty"));
+    }
+    let mut matches = text.match_indices(old_text);
+    let Some((offset, _)) = matches.next() else {
+        return Err(anyhow!("the text to replace wasn't found: {old_text:?}"));
+    };
+    if matches.next().is_some() {
+        return Err(anyhow!(
+            "the text to replace appears more than once: {old_text:?}"
+        ));
+    }
+
+    let mut result = String::with_capacity(text.len() - old_text.len() + new_text.len());
+    result.push_str(&text[..offset]);
+    result.push_str(new_text);
+    result.push_str(&text[offset + old_text.len()..]);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_once() {
+        assert_eq!(
+            replace_once("fn a() {}\nfn b() {}\n", "fn b", "fn c").unwrap(),
+            "fn a() {}\nfn c() {}\n"
+        );
+        assert!(replace_once("fn a() {}\nfn b() {}\n", "fn", "fn c").is_err());
+        assert!(replace_once("fn a() {}\n", "fn b", "fn c").is_err());
+        assert!(replace_once("fn a() {}\n", "", "fn c").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+use crate::{project, resolve_project_path, PATH_DESCRIPTION};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListDirectoryToolInput {
+    /// The path of the directory to list. Leave it empty to list the project's root
+    /// directories.
+    #[serde(default)]
+    path: String,
+}
+
+pub struct ListDirectoryTool;
+
+impl Tool for ListDirectoryTool {
+    fn name(&self) -> String {
+        "list_directory".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Lists the files and directories in a directory of the project. Directories are listed with a trailing `/`. {PATH_DESCRIPTION}")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ListDirectoryToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input: ListDirectoryToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let project = project.read(cx);
+
+        let mut output = String::new();
+        let path = input.path.trim().trim_end_matches('/');
+        if path.is_empty() {
+            for worktree in project.visible_worktrees(cx) {
+                writeln!(output, "{}/", worktree.read(cx).root_name()).ok();
+            }
+            return Task::ready(Ok(output));
+        }
+
+        let project_path = match resolve_project_path(project, path, cx) {
+            Ok(project_path) => project_path,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("{path} doesn't exist")));
+        };
+        let worktree = worktree.read(cx);
+        match worktree.entry_for_path(&project_path.path) {
+            Some(entry) if entry.is_dir() => {}
+            Some(_) => return Task::ready(Err(anyhow!("{path} is a file, not a directory"))),
+            None => return Task::ready(Err(anyhow!("{path} doesn't exist"))),
+        }
+
+        for entry in worktree.child_entries(&project_path.path) {
+            if entry.is_private {
+                continue;
+            }
+            let Some(name) = entry.path.file_name() else {
+                continue;
+            };
+            write!(output, "{}", name.to_string_lossy()).ok();
+            if entry.is_dir() {
+                output.push('/');
+            }
+            output.push('\n');
+        }
+        if output.is_empty() {
+            output = format!("{path} is empty.");
+        }
+        Task::ready(Ok(output))
+    }
+}
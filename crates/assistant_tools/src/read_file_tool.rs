@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use language::Point;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+use crate::{project, resolve_project_path, PATH_DESCRIPTION};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The path of the file to read.
+    path: String,
+    /// The line to start reading at, starting from 1. Defaults to the start of the file.
+    #[serde(default)]
+    start_line: Option<u32>,
+    /// The last line to read, inclusive. Defaults to the end of the file.
+    #[serde(default)]
+    end_line: Option<u32>,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read_file".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Reads the contents of a file in the project, or a range of its lines, including any unsaved changes. {PATH_DESCRIPTION}")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let project_path = match resolve_project_path(project.read(cx), &input.path, cx) {
+            Ok(project_path) => project_path,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            buffer.read_with(&cx, |buffer, _| {
+                // The path may lead to a private file through a symlink.
+                if buffer.file().is_some_and(|file| file.is_private()) {
+                    return Err(anyhow!(
+                        "{} is private, so its contents can't be accessed",
+                        input.path
+                    ));
+                }
+                let max_row = buffer.max_point().row;
+                let start_row = input.start_line.unwrap_or(1).saturating_sub(1);
+                let end_row = input
+                    .end_line
+                    .map_or(max_row, |end_line| end_line.saturating_sub(1).min(max_row));
+                if start_row > end_row {
+                    return Err(anyhow!(
+                        "{} has {} lines, so there's nothing to read from line {}",
+                        input.path,
+                        max_row + 1,
+                        start_row + 1
+                    ));
+                }
+                let range = Point::new(start_row, 0)..Point::new(end_row, buffer.line_len(end_row));
+                Ok(buffer.text_for_range(range).collect::<String>())
+            })?
+        })
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use collections::HashMap;
+use gpui::{App, Task, WeakEntity, Window};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task::{
+    HideStrategy, RevealStrategy, RevealTarget, Shell, ShellBuilder, SpawnInTerminal, TaskId,
+};
+use terminal::TaskStatus;
+use terminal_view::terminal_panel::TerminalPanel;
+use workspace::Workspace;
+
+use crate::{project, resolve_project_path, PATH_DESCRIPTION};
+
+/// The most lines of output returned, to keep them within the model's context.
+const MAX_OUTPUT_LINES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunCommandToolInput {
+    /// The shell command to run.
+    command: String,
+    /// The directory to run the command in. Defaults to the project's first root directory.
+    #[serde(default)]
+    cd: Option<String>,
+}
+
+pub struct RunCommandTool;
+
+impl Tool for RunCommandTool {
+    fn name(&self) -> String {
+        "run_command".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Runs a shell command in a new terminal, waits for it to finish, and returns its exit status and the last lines of its output. Don't run commands that wait for input or never exit. {PATH_DESCRIPTION}")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(RunCommandToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn may_modify_any_file(&self) -> bool {
+        true
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input: RunCommandToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let project = project.read(cx);
+        let cwd = match input.cd.as_deref().filter(|cd| !cd.trim().is_empty()) {
+            Some(cd) => match resolve_project_path(project, cd, cx) {
+                Ok(project_path) => project.absolute_path(&project_path, cx),
+                Err(err) => return Task::ready(Err(err)),
+            },
+            None => project
+                .visible_worktrees(cx)
+                .next()
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf()),
+        };
+        let Some(terminal_panel) = workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).panel::<TerminalPanel>(cx))
+        else {
+            return Task::ready(Err(anyhow!("the terminal panel isn't available")));
+        };
+
+        let builder = ShellBuilder::new(project.is_local(), &Shell::System);
+        let command_label = builder.command_label(&input.command);
+        let (command, args) = builder.build(input.command.clone(), &Vec::new());
+        let spawn_task = SpawnInTerminal {
+            id: TaskId(format!("assistant-run-command-{}", input.command)),
+            full_label: input.command.clone(),
+            label: input.command.clone(),
+            command,
+            args,
+            command_label,
+            cwd,
+            env: HashMap::default(),
+            use_new_terminal: true,
+            allow_concurrent_runs: true,
+            reveal: RevealStrategy::NoFocus,
+            reveal_target: RevealTarget::Dock,
+            hide: HideStrategy::Never,
+            shell: Shell::System,
+            show_summary: true,
+            show_command: true,
+        };
+
+        let spawn = terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.spawn_in_new_terminal(spawn_task, window, cx)
+        });
+        cx.spawn(|cx| async move {
+            let terminal = spawn.await?;
+            terminal
+                .read_with(&cx, |terminal, cx| terminal.wait_for_completed_task(cx))?
+                .await;
+            terminal.read_with(&cx, |terminal, _| {
+                let status = match terminal.task().map(|task| task.status) {
+                    Some(TaskStatus::Completed { success: true }) => "succeeded",
+                    Some(TaskStatus::Completed { success: false }) => "failed",
+                    _ => "stopped without reporting its exit status",
+                };
+                let output = terminal.last_n_non_empty_lines(MAX_OUTPUT_LINES);
+                format!(
+                    "The command {status}. The last lines of its output were:\n{}",
+                    output.join("\n")
+                )
+            })
+        })
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{App, Task, WeakEntity, Window};
+use language::{OffsetRangeExt, Point};
+use project::search::{SearchQuery, SearchResult};
+use project::ProjectItem as _;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+use crate::{display_path, project, PATH_DESCRIPTION};
+
+/// The most matching lines returned, to keep the output within the model's context.
+const MAX_MATCHES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchToolInput {
+    /// The text to search for.
+    query: String,
+    /// Whether the query is a regular expression.
+    #[serde(default)]
+    regex: bool,
+    /// Whether the search is case-sensitive.
+    #[serde(default)]
+    case_sensitive: bool,
+    /// Glob patterns for the files to search, such as `backend/**/*.rs`. Searches every
+    /// file when empty.
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+pub struct SearchTool;
+
+impl Tool for SearchTool {
+    fn name(&self) -> String {
+        "search".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Searches the project's files for text or a regular expression, returning each matching line as `path:line: text`. {PATH_DESCRIPTION}")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SearchToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakEntity<Workspace>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let input: SearchToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let query = PathMatcher::new(&input.include)
+            .map_err(|err| anyhow!(err))
+            .and_then(|include| {
+                if input.regex {
+                    SearchQuery::regex(
+                        &input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                } else {
+                    SearchQuery::text(
+                        &input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                }
+            });
+        let query = match query {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let results = project.update(cx, |project, cx| project.search(query, cx));
+        cx.spawn(|cx| async move {
+            let mut output = String::new();
+            let mut match_count = 0;
+            'results: while let Ok(result) = results.recv().await {
+                let SearchResult::Buffer { buffer, ranges } = result else {
+                    break;
+                };
+                let lines = project.read_with(&cx, |project, cx| {
+                    let buffer = buffer.read(cx);
+                    if buffer.file().is_some_and(|file| file.is_private()) {
+                        return Vec::new();
+                    }
+                    let Some(project_path) = buffer.project_path(cx) else {
+                        return Vec::new();
+                    };
+                    let path = display_path(project, &project_path, cx);
+                    let snapshot = buffer.snapshot();
+                    let mut last_row = None;
+                    let mut lines = Vec::new();
+                    for range in ranges {
+                        let row = range.to_point(&snapshot).start.row;
+                        if last_row == Some(row) {
+                            continue;
+                        }
+                        last_row = Some(row);
+                        let line = snapshot
+                            .text_for_range(
+                                Point::new(row, 0)..Point::new(row, snapshot.line_len(row)),
+                            )
+                            .collect::<String>();
+                        lines.push(format!("{}:{}: {}", path.display(), row + 1, line.trim()));
+                    }
+                    lines
+                })?;
+
+                for line in lines {
+                    if match_count == MAX_MATCHES {
+                        writeln!(output, "(stopped after {MAX_MATCHES} matches)").ok();
+                        break 'results;
+                    }
+                    writeln!(output, "{line}").ok();
+                    match_count += 1;
+                }
+            }
+
+            if output.is_empty() {
+                output = "No matches found.".into();
+            }
+            Ok(output)
+        })
+    }
+}
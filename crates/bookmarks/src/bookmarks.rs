@@ -0,0 +1,479 @@
+mod persistence;
+
+use collections::HashSet;
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task,
+    WeakEntity, Window,
+};
+use language::{Buffer, Point, ToPoint as _};
+use persistence::BOOKMARKS_DB;
+use picker::{Picker, PickerDelegate};
+use project::{
+    bookmark_store::{BookmarkStoreEvent, SerializedBookmark},
+    Project,
+};
+use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
+use ui::{prelude::*, HighlightedLabel, IconButton, IconButtonShape, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+actions!(bookmarks, [Toggle, LabelBookmark]);
+
+/// How long bookmarks have to stay unchanged before they're saved.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(BookmarkPicker::register).detach();
+    cx.observe_new(persist_bookmarks).detach();
+}
+
+/// Restores the bookmarks saved for the workspace, and saves them again whenever they change.
+fn persist_bookmarks(
+    workspace: &mut Workspace,
+    _window: Option<&mut Window>,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    let bookmark_store = workspace.project().read(cx).bookmark_store().clone();
+    let bookmarks = BOOKMARKS_DB
+        .bookmarks(workspace_id)
+        .log_err()
+        .unwrap_or_default();
+    if !bookmarks.is_empty() {
+        bookmark_store.update(cx, |store, cx| {
+            store.restore_bookmarks(bookmarks.clone(), cx)
+        });
+    }
+    // Bookmarks move with every edit above them, so they're saved once edits pause, and only
+    // the ones that changed since the last save are written.
+    let saved_bookmarks = Rc::new(RefCell::new(bookmarks.into_iter().collect::<HashSet<_>>()));
+    let mut pending_save: Option<Task<()>> = None;
+    cx.subscribe(
+        &bookmark_store,
+        move |_, bookmark_store, _: &BookmarkStoreEvent, cx| {
+            let bookmark_store = bookmark_store.downgrade();
+            let saved_bookmarks = saved_bookmarks.clone();
+            // Replacing the pending save cancels it.
+            pending_save.replace(cx.spawn(|_, cx| async move {
+                cx.background_executor().timer(SAVE_DEBOUNCE).await;
+                let Some(bookmarks) = bookmark_store
+                    .read_with(&cx, |store, _| store.all_bookmarks())
+                    .log_err()
+                else {
+                    return;
+                };
+                let bookmarks = bookmarks.into_iter().collect::<HashSet<_>>();
+                let mut saved_bookmarks = saved_bookmarks.borrow_mut();
+                let removed = saved_bookmarks
+                    .difference(&bookmarks)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let added = bookmarks
+                    .difference(&saved_bookmarks)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                *saved_bookmarks = bookmarks;
+                if !removed.is_empty() || !added.is_empty() {
+                    cx.background_executor()
+                        .spawn(async move {
+                            BOOKMARKS_DB
+                                .update_bookmarks(workspace_id, removed, added)
+                                .await
+                                .log_err();
+                        })
+                        .detach();
+                }
+            }));
+        },
+    )
+    .detach();
+}
+
+enum Mode {
+    /// List the bookmarks of all files and jump to the selected one.
+    Open,
+    /// Use the query as the label of the bookmark on a line.
+    Label { buffer: Entity<Buffer>, row: u32 },
+}
+
+pub struct BookmarkPicker {
+    picker: Entity<Picker<BookmarkPickerDelegate>>,
+}
+
+impl BookmarkPicker {
+    fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(|workspace, _: &Toggle, window, cx| {
+            Self::toggle(workspace, Mode::Open, None, window, cx);
+        });
+        workspace.register_action(|workspace, _: &LabelBookmark, window, cx| {
+            Self::toggle_label(workspace, window, cx);
+        });
+    }
+
+    /// Labels the bookmark on the newest cursor's line in the active editor, adding the
+    /// bookmark if there isn't one yet.
+    fn toggle_label(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Option<()> {
+        let editor = workspace.active_item(cx)?.act_as::<Editor>(cx)?;
+        let editor = editor.read(cx);
+        let head = editor.selections.newest_anchor().head();
+        let (buffer, anchor) = editor
+            .buffer()
+            .read(cx)
+            .text_anchor_for_position(head, cx)?;
+        let row = anchor.to_point(&buffer.read(cx)).row;
+        let label = workspace
+            .project()
+            .read(cx)
+            .bookmark_store()
+            .read(cx)
+            .label_at_row(&buffer, row, cx)
+            .map(ToString::to_string);
+        Self::toggle(workspace, Mode::Label { buffer, row }, label, window, cx);
+        Some(())
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        mode: Mode,
+        query: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let weak_workspace = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate = BookmarkPickerDelegate::new(
+                cx.entity().downgrade(),
+                weak_workspace,
+                project,
+                mode,
+                cx,
+            );
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            if let Some(query) = query {
+                picker.update(cx, |picker, cx| picker.set_query(query, window, cx));
+            }
+            Self { picker }
+        });
+    }
+}
+
+impl Render for BookmarkPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for BookmarkPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for BookmarkPicker {}
+impl ModalView for BookmarkPicker {}
+
+struct BookmarkEntry {
+    bookmark: SerializedBookmark,
+    /// The path shown for the bookmark, relative to its worktree when possible.
+    display_path: String,
+}
+
+impl BookmarkEntry {
+    fn title(&self) -> &str {
+        self.bookmark
+            .label
+            .as_deref()
+            .unwrap_or(&self.bookmark.line)
+    }
+}
+
+pub struct BookmarkPickerDelegate {
+    bookmark_picker: WeakEntity<BookmarkPicker>,
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    mode: Mode,
+    bookmarks: Vec<BookmarkEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+}
+
+impl BookmarkPickerDelegate {
+    fn new(
+        bookmark_picker: WeakEntity<BookmarkPicker>,
+        workspace: WeakEntity<Workspace>,
+        project: Entity<Project>,
+        mode: Mode,
+        cx: &App,
+    ) -> Self {
+        let mut this = Self {
+            bookmark_picker,
+            workspace,
+            project,
+            mode,
+            bookmarks: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+        };
+        if let Mode::Open = this.mode {
+            this.load_bookmarks(cx);
+        }
+        this
+    }
+
+    fn load_bookmarks(&mut self, cx: &App) {
+        let project = self.project.read(cx);
+        self.bookmarks = project
+            .bookmark_store()
+            .read(cx)
+            .all_bookmarks()
+            .into_iter()
+            .map(|bookmark| {
+                let path = match project.find_worktree(&bookmark.path, cx) {
+                    Some((_, relative_path)) => relative_path,
+                    None => bookmark.path.to_path_buf(),
+                };
+                BookmarkEntry {
+                    display_path: format!("{}:{}", path.display(), bookmark.row + 1),
+                    bookmark,
+                }
+            })
+            .collect();
+    }
+
+    fn delete_bookmark(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(ix)
+            .and_then(|mat| self.bookmarks.get(mat.candidate_id))
+        else {
+            return;
+        };
+        let (path, row) = (entry.bookmark.path.clone(), entry.bookmark.row);
+        self.project
+            .read(cx)
+            .bookmark_store()
+            .clone()
+            .update(cx, |store, cx| store.remove_bookmark(&path, row, cx));
+        self.load_bookmarks(cx);
+        cx.spawn_in(window, |picker, mut cx| async move {
+            picker.update_in(&mut cx, |picker, window, cx| picker.refresh(window, cx))
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl PickerDelegate for BookmarkPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.mode {
+            Mode::Open => "Go to bookmark…".into(),
+            Mode::Label { .. } => "Label bookmark…".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        "No bookmarks".into()
+    }
+
+    fn match_count(&self) -> usize {
+        match self.mode {
+            Mode::Open => self.matches.len(),
+            Mode::Label { .. } => 1,
+        }
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        if let Mode::Label { .. } = self.mode {
+            self.query = query;
+            cx.notify();
+            return Task::ready(());
+        }
+
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .bookmarks
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                StringMatchCandidate::new(id, &format!("{} {}", entry.title(), entry.display_path))
+            })
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.query = query;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        match &self.mode {
+            Mode::Open => {
+                let Some(entry) = self
+                    .matches
+                    .get(self.selected_index)
+                    .and_then(|mat| self.bookmarks.get(mat.candidate_id))
+                else {
+                    return;
+                };
+                let row = entry.bookmark.row;
+                let open_task = self.workspace.update(cx, |workspace, cx| {
+                    workspace.open_abs_path(entry.bookmark.path.to_path_buf(), true, window, cx)
+                });
+                if let Ok(open_task) = open_task {
+                    cx.spawn_in(window, |_, mut cx| async move {
+                        let item = open_task.await?;
+                        if let Some(editor) = item.downcast::<Editor>() {
+                            editor.update_in(&mut cx, |editor, window, cx| {
+                                editor.go_to_singleton_buffer_point(Point::new(row, 0), window, cx);
+                            })?;
+                        }
+                        anyhow::Ok(())
+                    })
+                    .detach_and_log_err(cx);
+                }
+            }
+            Mode::Label { buffer, row } => {
+                let label = Some(self.query.trim().to_string());
+                let (buffer, row) = (buffer.clone(), *row);
+                self.project
+                    .read(cx)
+                    .bookmark_store()
+                    .clone()
+                    .update(cx, |store, cx| store.set_label(&buffer, row, label, cx));
+            }
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.bookmark_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        if let Mode::Label { .. } = self.mode {
+            let label = self.query.trim();
+            let text = if label.is_empty() {
+                "Bookmark without a label".to_string()
+            } else {
+                format!("Label bookmark “{label}”")
+            };
+            return Some(
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(selected)
+                    .start_slot(Icon::new(IconName::Bookmark).color(Color::Muted))
+                    .child(Label::new(text)),
+            );
+        }
+
+        let mat = self.matches.get(ix)?;
+        let entry = self.bookmarks.get(mat.candidate_id)?;
+        let title = entry.title().to_string();
+        let positions = mat
+            .positions
+            .iter()
+            .copied()
+            .filter(|position| *position < title.len())
+            .collect();
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Bookmark).color(Color::Muted))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(title, positions))
+                        .child(
+                            Label::new(entry.display_path.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                )
+                .end_slot(
+                    IconButton::new(("delete-bookmark", ix), IconName::Trash)
+                        .shape(IconButtonShape::Square)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("Delete Bookmark"))
+                        .on_click(cx.listener(move |picker, _, window, cx| {
+                            cx.stop_propagation();
+                            picker.delegate.delete_bookmark(ix, window, cx);
+                        })),
+                ),
+        )
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use db::{define_connection, query, sqlez_macros::sql};
+use project::bookmark_store::SerializedBookmark;
+use std::path::PathBuf;
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    pub static ref BOOKMARKS_DB: BookmarksDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE bookmarks (
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                label TEXT,
+                line TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, path, row),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl BookmarksDb {
+    query! {
+        fn bookmark_rows(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, Option<String>, String)>> {
+            SELECT path, row, label, line
+            FROM bookmarks
+            WHERE workspace_id = ?
+            ORDER BY path, row
+        }
+    }
+
+    pub fn bookmarks(&self, workspace_id: WorkspaceId) -> Result<Vec<SerializedBookmark>> {
+        Ok(self
+            .bookmark_rows(workspace_id)?
+            .into_iter()
+            .map(|(path, row, label, line)| SerializedBookmark {
+                path: path.into(),
+                row,
+                label,
+                line,
+            })
+            .collect())
+    }
+
+    /// Removes and adds saved bookmarks of the workspace, leaving the others alone.
+    pub async fn update_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        removed: Vec<SerializedBookmark>,
+        added: Vec<SerializedBookmark>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("update_bookmarks", || {
+                for bookmark in removed {
+                    conn.exec_bound(sql!(
+                        DELETE FROM bookmarks
+                        WHERE workspace_id = ? AND path = ? AND row = ?
+                    ))?((workspace_id, bookmark.path, bookmark.row))?;
+                }
+                for bookmark in added {
+                    conn.exec_bound(sql!(
+                        INSERT OR REPLACE INTO bookmarks (workspace_id, path, row, label, line)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))?((
+                        workspace_id,
+                        bookmark.path,
+                        bookmark.row,
+                        bookmark.label,
+                        bookmark.line,
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
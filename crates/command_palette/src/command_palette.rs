@@ -11,8 +11,8 @@ use command_palette_hooks::{
 };
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    Action, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Global,
-    ParentElement, Render, Styled, Task, UpdateGlobal, WeakEntity, Window,
+    actions, Action, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    Global, ParentElement, Render, Styled, Task, UpdateGlobal, WeakEntity, Window,
 };
 use picker::{Picker, PickerDelegate};
 use postage::{sink::Sink, stream::Stream};
@@ -22,6 +22,8 @@ use util::ResultExt;
 use workspace::{ModalView, Workspace, WorkspaceSettings};
 use zed_actions::{command_palette::Toggle, OpenZedUrl};
 
+actions!(command_palette, [PreviousHistoryQuery, NextHistoryQuery]);
+
 pub fn init(cx: &mut App) {
     client::init_settings(cx);
     cx.set_global(HitCounts::default());
@@ -33,6 +35,16 @@ impl ModalView for CommandPalette {}
 
 pub struct CommandPalette {
     picker: Entity<Picker<CommandPaletteDelegate>>,
+    history_position: Option<HistoryPosition>,
+}
+
+/// Where the palette is in the interceptor's history while previous queries are recalled.
+struct HistoryPosition {
+    /// The query that was typed before the first query was recalled.
+    prefix: String,
+    /// The queries starting with the prefix, oldest first.
+    entries: Vec<String>,
+    ix: usize,
 }
 
 /// Removes subsequent whitespace characters and double colons from the query.
@@ -114,13 +126,67 @@ impl CommandPalette {
             picker.set_query(query, window, cx);
             picker
         });
-        Self { picker }
+        Self {
+            picker,
+            history_position: None,
+        }
     }
 
     pub fn set_query(&mut self, query: &str, window: &mut Window, cx: &mut Context<Self>) {
         self.picker
             .update(cx, |picker, cx| picker.set_query(query, window, cx))
     }
+
+    fn previous_history_query(
+        &mut self,
+        _: &PreviousHistoryQuery,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.recall_history_query(true, window, cx);
+    }
+
+    fn next_history_query(
+        &mut self,
+        _: &NextHistoryQuery,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.recall_history_query(false, window, cx);
+    }
+
+    /// Replaces the query with an older or newer one from the interceptor's history that
+    /// starts with what was typed. Going past the newest one restores the typed query.
+    fn recall_history_query(&mut self, older: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.picker.read(cx).query(cx);
+        let position = match self.history_position.take() {
+            Some(position) if position.entries.get(position.ix) == Some(&query) => position,
+            _ => {
+                let entries = CommandPaletteInterceptor::try_global(cx)
+                    .map(|interceptor| interceptor.history(&query, cx))
+                    .unwrap_or_default();
+                HistoryPosition {
+                    ix: entries.len(),
+                    prefix: query,
+                    entries,
+                }
+            }
+        };
+
+        let ix = if older {
+            position.ix.saturating_sub(1)
+        } else {
+            position.ix + 1
+        };
+        if let Some(entry) = position.entries.get(ix) {
+            self.set_query(entry, window, cx);
+            self.history_position = Some(HistoryPosition { ix, ..position });
+        } else if !older {
+            self.set_query(&position.prefix, window, cx);
+        } else {
+            self.history_position = Some(position);
+        }
+    }
 }
 
 impl EventEmitter<DismissEvent> for CommandPalette {}
@@ -132,8 +198,13 @@ impl Focusable for CommandPalette {
 }
 
 impl Render for CommandPalette {
-    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
-        v_flex().w(rems(34.)).child(self.picker.clone())
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("CommandPalette")
+            .on_action(cx.listener(Self::previous_history_query))
+            .on_action(cx.listener(Self::next_history_query))
+            .w(rems(34.))
+            .child(self.picker.clone())
     }
 }
 
@@ -144,6 +215,7 @@ pub struct CommandPaletteDelegate {
     matches: Vec<StringMatch>,
     selected_ix: usize,
     previous_focus_handle: FocusHandle,
+    intercepted_query: Option<String>,
     updating_matches: Option<(
         Task<()>,
         postage::dispatch::Receiver<(Vec<Command>, Vec<StringMatch>)>,
@@ -185,6 +257,7 @@ impl CommandPaletteDelegate {
             commands,
             selected_ix: 0,
             previous_focus_handle,
+            intercepted_query: None,
             updating_matches: None,
         }
     }
@@ -200,8 +273,10 @@ impl CommandPaletteDelegate {
 
         let mut intercept_result = CommandPaletteInterceptor::try_global(cx)
             .and_then(|interceptor| interceptor.intercept(&query, cx));
+        self.intercepted_query = intercept_result.as_ref().map(|_| query.clone());
 
         if parse_zed_link(&query, cx).is_some() {
+            self.intercepted_query = None;
             intercept_result = Some(CommandInterceptResult {
                 action: OpenZedUrl { url: query.clone() }.boxed_clone(),
                 string: query.clone(),
@@ -379,6 +454,11 @@ impl PickerDelegate for CommandPaletteDelegate {
             return;
         }
         let action_ix = self.matches[self.selected_ix].candidate_id;
+        // The intercepted command is always the last one.
+        let intercepted_query = self
+            .intercepted_query
+            .take()
+            .filter(|_| action_ix + 1 == self.commands.len());
         let command = self.commands.swap_remove(action_ix);
         telemetry::event!(
             "Action Invoked",
@@ -390,6 +470,11 @@ impl PickerDelegate for CommandPaletteDelegate {
         HitCounts::update_global(cx, |hit_counts, _cx| {
             *hit_counts.0.entry(command.name).or_default() += 1;
         });
+        if let Some(query) = intercepted_query {
+            CommandPaletteInterceptor::update_global(cx, |interceptor, cx| {
+                interceptor.intercepted_command_run(&query, cx);
+            });
+        }
         let action = command.action;
         window.focus(&self.previous_focus_handle);
         self.dismissed(window, cx);
@@ -107,9 +107,11 @@ pub struct CommandInterceptResult {
 
 /// An interceptor for the command palette.
 #[derive(Default)]
-pub struct CommandPaletteInterceptor(
-    Option<Box<dyn Fn(&str, &App) -> Option<CommandInterceptResult>>>,
-);
+pub struct CommandPaletteInterceptor {
+    handler: Option<Box<dyn Fn(&str, &App) -> Option<CommandInterceptResult>>>,
+    on_run: Option<Box<dyn Fn(&str, &mut App)>>,
+    history: Option<Box<dyn Fn(&str, &App) -> Vec<String>>>,
+}
 
 #[derive(Default)]
 struct GlobalCommandPaletteInterceptor(CommandPaletteInterceptor);
@@ -133,20 +135,49 @@ impl CommandPaletteInterceptor {
 
     /// Intercepts the given query from the command palette.
     pub fn intercept(&self, query: &str, cx: &App) -> Option<CommandInterceptResult> {
-        let handler = self.0.as_ref()?;
+        let handler = self.handler.as_ref()?;
 
         (handler)(query, cx)
     }
 
+    /// Notifies the interceptor that the command it produced for the given query was run.
+    pub fn intercepted_command_run(&self, query: &str, cx: &mut App) {
+        if let Some(on_run) = self.on_run.as_ref() {
+            (on_run)(query, cx)
+        }
+    }
+
+    /// Returns the previously run queries that start with the given prefix, oldest first.
+    pub fn history(&self, prefix: &str, cx: &App) -> Vec<String> {
+        self.history
+            .as_ref()
+            .map(|history| (history)(prefix, cx))
+            .unwrap_or_default()
+    }
+
     /// Clears the global interceptor.
     pub fn clear(&mut self) {
-        self.0 = None;
+        self.handler = None;
+        self.on_run = None;
+        self.history = None;
     }
 
     /// Sets the global interceptor.
     ///
     /// This will override the previous interceptor, if it exists.
     pub fn set(&mut self, handler: Box<dyn Fn(&str, &App) -> Option<CommandInterceptResult>>) {
-        self.0 = Some(handler);
+        self.handler = Some(handler);
+    }
+
+    /// Sets a callback to run when a command produced by the interceptor is run,
+    /// e.g. to keep a history of intercepted commands.
+    pub fn set_on_run(&mut self, on_run: Box<dyn Fn(&str, &mut App)>) {
+        self.on_run = Some(on_run);
+    }
+
+    /// Sets the source of the queries that can be recalled in the command palette, which is
+    /// given the prefix that was typed and returns the matching queries, oldest first.
+    pub fn set_history(&mut self, history: Box<dyn Fn(&str, &App) -> Vec<String>>) {
+        self.history = Some(history);
     }
 }
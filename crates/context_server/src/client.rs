@@ -2,6 +2,7 @@ use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
 use futures::{channel::oneshot, io::BufWriter, select, AsyncRead, AsyncWrite, FutureExt};
 use gpui::{AsyncApp, BackgroundExecutor, Task};
+use http_client::{HttpClient, Url};
 use parking_lot::Mutex;
 use postage::barrier;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -22,6 +23,8 @@ use std::{
 };
 use util::TryFutureExt;
 
+use crate::{http_transport, RemoteServer};
+
 const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
@@ -34,6 +37,8 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncApp)>;
+type RequestHandler = Box<dyn Send + FnMut(Value, AsyncApp) -> Task<Result<Value>>>;
+type SessionLostHandler = Box<dyn Send + FnOnce(AsyncApp)>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -48,7 +53,9 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    session_lost_handler: Arc<Mutex<Option<SessionLostHandler>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
     io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
@@ -115,6 +122,25 @@ struct Notification<'a, T> {
     params: T,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct AnyRequest<'a> {
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct AnyNotification<'a> {
     jsonrpc: &'a str,
@@ -125,9 +151,31 @@ struct AnyNotification<'a> {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
+/// The handlers for the messages the server sends to the client.
+#[derive(Clone)]
+struct MessageHandlers {
+    notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
+    response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    outbound_tx: channel::Sender<String>,
+}
+
+impl MessageHandlers {
+    fn new(outbound_tx: channel::Sender<String>) -> Self {
+        Self {
+            notification_handlers: Default::default(),
+            request_handlers: Default::default(),
+            response_handlers: Arc::new(Mutex::new(Some(HashMap::default()))),
+            outbound_tx,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ModelContextServerBinary {
     pub executable: PathBuf,
@@ -175,17 +223,11 @@ impl Client {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
-        let notification_handlers =
-            Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
-        let response_handlers =
-            Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
+        let handlers = MessageHandlers::new(outbound_tx.clone());
 
         let stdout_input_task = cx.spawn({
-            let notification_handlers = notification_handlers.clone();
-            let response_handlers = response_handlers.clone();
-            move |cx| {
-                Self::handle_input(stdout, notification_handlers, response_handlers, cx).log_err()
-            }
+            let handlers = handlers.clone();
+            move |cx| Self::handle_input(stdout, handlers, cx).log_err()
         });
         let stderr_input_task = cx.spawn(|_| Self::handle_stderr(stderr).log_err());
         let input_task = cx.spawn(|_| async move {
@@ -197,15 +239,17 @@ impl Client {
                 stdin,
                 outbound_rx,
                 output_done_tx,
-                response_handlers.clone(),
+                handlers.response_handlers.clone(),
             )
             .log_err()
         });
 
         let mut context_server = Self {
             server_id,
-            notification_handlers,
-            response_handlers,
+            notification_handlers: handlers.notification_handlers,
+            request_handlers: handlers.request_handlers,
+            response_handlers: handlers.response_handlers,
+            session_lost_handler: Default::default(),
             name: "".into(),
             next_id: Default::default(),
             outbound_tx,
@@ -222,16 +266,98 @@ impl Client {
         Ok(context_server)
     }
 
+    /// Creates a new Client instance for a context server that runs as an HTTP service.
+    ///
+    /// Messages are exchanged using the server's configured transport, with the configured
+    /// headers sent along with every request.
+    pub fn remote(
+        server_id: ContextServerId,
+        server: RemoteServer,
+        http_client: Arc<dyn HttpClient>,
+        cx: AsyncApp,
+    ) -> Result<Self> {
+        log::info!(
+            "connecting to context server (url={:?}, transport={:?})",
+            server.url,
+            server.transport
+        );
+
+        let name = Url::parse(&server.url)
+            .with_context(|| format!("invalid context server URL {:?}", server.url))?
+            .host_str()
+            .unwrap_or_default()
+            .into();
+
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let (inbound_tx, inbound_rx) = channel::unbounded::<String>();
+        let (output_done_tx, output_done_rx) = barrier::channel();
+
+        let handlers = MessageHandlers::new(outbound_tx.clone());
+
+        let input_task = cx.spawn({
+            let handlers = handlers.clone();
+            move |cx| Self::handle_inbound(inbound_rx, handlers, cx).log_err()
+        });
+        let executor = cx.background_executor().clone();
+        let transport_task = executor.spawn({
+            let response_handlers = handlers.response_handlers.clone();
+            let executor = executor.clone();
+            async move {
+                let _clear_response_handlers = util::defer(move || {
+                    response_handlers.lock().take();
+                });
+                let result =
+                    http_transport::run(server, http_client, executor, outbound_rx, inbound_tx)
+                        .await;
+                drop(output_done_tx);
+                result
+            }
+        });
+        let session_lost_handler = Arc::new(Mutex::new(None::<SessionLostHandler>));
+        let output_task = cx.spawn({
+            let session_lost_handler = session_lost_handler.clone();
+            move |cx| {
+                async move {
+                    let result = transport_task.await;
+                    if let Err(error) = &result {
+                        if error.is::<http_transport::SessionLost>() {
+                            if let Some(handler) = session_lost_handler.lock().take() {
+                                handler(cx);
+                            }
+                        }
+                    }
+                    result
+                }
+                .log_err()
+            }
+        });
+
+        Ok(Self {
+            server_id,
+            notification_handlers: handlers.notification_handlers,
+            request_handlers: handlers.request_handlers,
+            response_handlers: handlers.response_handlers,
+            session_lost_handler,
+            name,
+            next_id: Default::default(),
+            outbound_tx,
+            executor,
+            io_tasks: Mutex::new(Some((input_task, output_task))),
+            output_done_rx: Mutex::new(Some(output_done_rx)),
+            server: Arc::new(Mutex::new(None)),
+        })
+    }
+
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC messages, and dispatches them to the appropriate handlers.
+    /// It processes responses (which are matched to pending requests), requests
+    /// (which are answered by registered handlers) and notifications (which trigger
+    /// registered handlers).
     async fn handle_input<Stdout>(
         stdout: Stdout,
-        notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
-        response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        handlers: MessageHandlers,
         cx: AsyncApp,
     ) -> anyhow::Result<()>
     where
@@ -246,29 +372,92 @@ impl Client {
                 return Ok(());
             }
 
-            let content = buffer.trim();
+            Self::handle_message(buffer.trim(), &handlers, &cx);
+            smol::future::yield_now().await;
+        }
+    }
 
-            if !content.is_empty() {
-                if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
-                    if let Some(handlers) = response_handlers.lock().as_mut() {
-                        if let Some(handler) = handlers.remove(&response.id) {
-                            handler(Ok(content.to_string()));
-                        }
-                    }
-                } else if let Ok(notification) = serde_json::from_str::<AnyNotification>(content) {
-                    let mut notification_handlers = notification_handlers.lock();
-                    if let Some(handler) =
-                        notification_handlers.get_mut(notification.method.as_str())
-                    {
-                        handler(notification.params.unwrap_or(Value::Null), cx.clone());
-                    }
+    /// Handles the messages a remote context server sent over HTTP.
+    async fn handle_inbound(
+        inbound_rx: channel::Receiver<String>,
+        handlers: MessageHandlers,
+        cx: AsyncApp,
+    ) -> anyhow::Result<()> {
+        while let Ok(message) = inbound_rx.recv().await {
+            Self::handle_message(message.trim(), &handlers, &cx);
+            smol::future::yield_now().await;
+        }
+        Ok(())
+    }
+
+    /// Dispatches a JSON-RPC message from the server to its handler.
+    fn handle_message(content: &str, handlers: &MessageHandlers, cx: &AsyncApp) {
+        if content.is_empty() {
+            return;
+        }
+
+        if let Ok(request) = serde_json::from_str::<AnyRequest>(content) {
+            Self::handle_request(request, handlers, cx);
+        } else if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
+            if let Some(response_handlers) = handlers.response_handlers.lock().as_mut() {
+                if let Some(handler) = response_handlers.remove(&response.id) {
+                    handler(Ok(content.to_string()));
                 }
             }
-
-            smol::future::yield_now().await;
+        } else if let Ok(notification) = serde_json::from_str::<AnyNotification>(content) {
+            let mut notification_handlers = handlers.notification_handlers.lock();
+            if let Some(handler) = notification_handlers.get_mut(notification.method.as_str()) {
+                handler(notification.params.unwrap_or(Value::Null), cx.clone());
+            }
         }
     }
 
+    /// Answers a request from the server using the handler registered for its method.
+    fn handle_request(request: AnyRequest, handlers: &MessageHandlers, cx: &AsyncApp) {
+        let task = handlers
+            .request_handlers
+            .lock()
+            .get_mut(request.method.as_str())
+            .map(|handler| handler(request.params.unwrap_or(Value::Null), cx.clone()));
+        let outbound_tx = handlers.outbound_tx.clone();
+        let id = request.id;
+        let method = request.method;
+
+        cx.spawn(|_| {
+            async move {
+                let (result, error) = match task {
+                    Some(task) => match task.await {
+                        Ok(result) => (Some(result), None),
+                        Err(error) => (
+                            None,
+                            Some(Error {
+                                code: INTERNAL_ERROR,
+                                message: error.to_string(),
+                            }),
+                        ),
+                    },
+                    None => (
+                        None,
+                        Some(Error {
+                            code: METHOD_NOT_FOUND,
+                            message: format!("method not found: {method}"),
+                        }),
+                    ),
+                };
+                let response = serde_json::to_string(&OutgoingResponse {
+                    jsonrpc: JSON_RPC_VERSION,
+                    id,
+                    result,
+                    error,
+                })?;
+                outbound_tx.send(response).await?;
+                anyhow::Ok(())
+            }
+            .log_err()
+        })
+        .detach();
+    }
+
     /// Handles the stderr output from the context server.
     /// Continuously reads and logs any error messages from the server.
     async fn handle_stderr<Stderr>(stderr: Stderr) -> anyhow::Result<()>
@@ -352,7 +541,7 @@ impl Client {
         let send = self
             .outbound_tx
             .try_send(request)
-            .context("failed to write to context server's stdin");
+            .context("failed to send message to context server");
 
         let executor = self.executor.clone();
         let started = Instant::now();
@@ -407,6 +596,23 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers the handler that answers the server's requests with the given method.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncApp) -> Task<Result<Value>>,
+    {
+        self.request_handlers.lock().insert(method, Box::new(f));
+    }
+
+    /// Registers the handler called when a server running as an HTTP service ends the session,
+    /// after which the client can't be used anymore.
+    pub fn on_session_lost<F>(&self, f: F)
+    where
+        F: 'static + Send + FnOnce(AsyncApp),
+    {
+        *self.session_lost_handler.lock() = Some(Box::new(f));
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -1,13 +1,17 @@
 pub mod client;
 mod context_server_tool;
 mod extension_context_server;
+mod http_transport;
 pub mod manager;
 pub mod protocol;
 mod registry;
+mod sampling;
 pub mod types;
 
 use command_palette_hooks::CommandPaletteFilter;
-pub use context_server_settings::{ContextServerSettings, ServerCommand, ServerConfig};
+pub use context_server_settings::{
+    ContextServerSettings, RemoteServer, RemoteTransport, ServerCommand, ServerConfig,
+};
 use gpui::{actions, App};
 
 pub use crate::context_server_tool::ContextServerTool;
@@ -0,0 +1,916 @@
+//! The transports for context servers that run as HTTP services.
+//!
+//! - With the streamable HTTP transport, every message is posted to a single endpoint, and the
+//!   server replies with either a JSON body or an event stream of its messages.
+//! - With the HTTP with SSE transport, the server sends all of its messages over one event
+//!   stream, which also announces the endpoint the client posts its messages to.
+
+use std::fmt;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::HashMap;
+use futures::future::{self, Either};
+use futures::AsyncBufRead;
+use gpui::BackgroundExecutor;
+use http_client::{http, AsyncBody, HttpClient, Method, Request, Response, StatusCode, Url};
+use parking_lot::Mutex;
+use postage::{prelude::Stream as _, watch};
+use serde_json::Value;
+use smol::channel;
+use smol::io::{AsyncBufReadExt as _, AsyncReadExt as _, BufReader};
+
+use crate::client::INTERNAL_ERROR;
+use crate::{RemoteServer, RemoteTransport};
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Exchanges messages with a context server over HTTP until `outbound_rx` is closed.
+///
+/// The client's messages are read from `outbound_rx`, and the server's messages are sent to
+/// `inbound_tx`. Lost event streams are reopened with a growing delay between attempts, but if
+/// the server ends the session, this fails with [`SessionLost`].
+pub(crate) async fn run(
+    server: RemoteServer,
+    http_client: Arc<dyn HttpClient>,
+    executor: BackgroundExecutor,
+    outbound_rx: channel::Receiver<String>,
+    inbound_tx: channel::Sender<String>,
+) -> Result<()> {
+    let url = Url::parse(&server.url)
+        .with_context(|| format!("invalid context server URL {:?}", server.url))?;
+    let connection = Arc::new(Connection {
+        url,
+        headers: server.headers,
+        http_client,
+        executor,
+        inbound_tx,
+    });
+
+    match server.transport {
+        RemoteTransport::Http => connection.run_streamable_http(outbound_rx).await,
+        RemoteTransport::Sse => connection.run_sse(outbound_rx).await,
+    }
+}
+
+struct Connection {
+    url: Url,
+    headers: HashMap<String, String>,
+    http_client: Arc<dyn HttpClient>,
+    executor: BackgroundExecutor,
+    inbound_tx: channel::Sender<String>,
+}
+
+impl Connection {
+    async fn run_streamable_http(
+        self: Arc<Self>,
+        outbound_rx: channel::Receiver<String>,
+    ) -> Result<()> {
+        let session_id = Arc::new(Mutex::new(None::<String>));
+        let (session_lost_tx, session_lost_rx) = channel::bounded::<()>(1);
+        let mut listen_task = None;
+
+        loop {
+            let message = match future::select(
+                pin!(outbound_rx.recv()),
+                pin!(session_lost_rx.recv()),
+            )
+            .await
+            {
+                Either::Left((Ok(message), _)) => message,
+                Either::Left((Err(_), _)) => break,
+                Either::Right(_) => return Err(SessionLost.into()),
+            };
+            log::trace!("outgoing message: {}", message);
+
+            // Once the client is initialized, the server may also send messages that aren't
+            // replies to a request, over a separate event stream.
+            if listen_task.is_none() && method(&message).as_deref() == Some(INITIALIZED_METHOD) {
+                listen_task = Some(self.executor.spawn({
+                    let this = self.clone();
+                    let session_id = session_id.clone();
+                    let session_lost_tx = session_lost_tx.clone();
+                    async move {
+                        if this.listen(session_id).await.is_err() {
+                            session_lost_tx.send(()).await.ok();
+                        }
+                    }
+                }));
+            }
+
+            // Messages are posted in order, so that the server sees them in the order they were
+            // sent, but replies can take a while to stream back, so they're read concurrently.
+            match self.post_message(&message, &session_id).await {
+                Ok(Some(response)) => self
+                    .executor
+                    .spawn({
+                        let this = self.clone();
+                        async move {
+                            let result = this.receive_response(response).await;
+                            this.report_failure(&message, result).await;
+                        }
+                    })
+                    .detach(),
+                Ok(None) => {}
+                Err(error) if error.is::<SessionLost>() => return Err(error),
+                Err(error) => self.report_failure(&message, Err(error)).await,
+            }
+        }
+
+        drop(listen_task);
+        let session_id = session_id.lock().take();
+        if let Some(session_id) = session_id {
+            let request = self
+                .request(Method::DELETE, &self.url)
+                .header(SESSION_ID_HEADER, session_id)
+                .body(AsyncBody::empty())?;
+            self.http_client.send(request).await?;
+        }
+        Ok(())
+    }
+
+    /// Posts a message, returning the response if it has a body with the server's replies.
+    async fn post_message(
+        &self,
+        message: &str,
+        session_id: &Mutex<Option<String>>,
+    ) -> Result<Option<Response<AsyncBody>>> {
+        let current_session_id = session_id.lock().clone();
+        let mut request = self
+            .request(Method::POST, &self.url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream");
+        if let Some(current_session_id) = current_session_id.as_ref() {
+            request = request.header(SESSION_ID_HEADER, current_session_id.as_str());
+        }
+
+        let response = self
+            .http_client
+            .send(request.body(AsyncBody::from(message.to_string()))?)
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND && current_session_id.is_some() {
+            return Err(SessionLost.into());
+        }
+        let response = check_status(response).await?;
+
+        if let Some(new_session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            *session_id.lock() = Some(new_session_id.to_string());
+        }
+
+        if response.status() == StatusCode::ACCEPTED {
+            Ok(None)
+        } else {
+            Ok(Some(response))
+        }
+    }
+
+    async fn receive_response(&self, response: Response<AsyncBody>) -> Result<()> {
+        if is_event_stream(&response) {
+            let mut body = BufReader::new(response.into_body());
+            while let Some(event) = read_event(&mut body).await? {
+                self.receive_event(event).await?;
+            }
+            Ok(())
+        } else {
+            let mut body = String::new();
+            response.into_body().read_to_string(&mut body).await?;
+            self.receive_json(body).await
+        }
+    }
+
+    /// Keeps an event stream open for the server's messages, reopening it when it's lost.
+    ///
+    /// Fails only if the server ended the session.
+    async fn listen(&self, session_id: Arc<Mutex<Option<String>>>) -> Result<()> {
+        let mut last_event_id = None;
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            match self.listen_once(&session_id, &mut last_event_id).await {
+                Ok(true) => delay = INITIAL_RECONNECT_DELAY,
+                // The server doesn't offer a stream of its own messages.
+                Ok(false) => return Ok(()),
+                Err(error) if error.is::<SessionLost>() => return Err(error),
+                Err(error) => {
+                    log::warn!("lost event stream of context server {}: {error}", self.url)
+                }
+            }
+            self.executor.timer(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    async fn listen_once(
+        &self,
+        session_id: &Mutex<Option<String>>,
+        last_event_id: &mut Option<String>,
+    ) -> Result<bool> {
+        let current_session_id = session_id.lock().clone();
+        let mut request = self
+            .request(Method::GET, &self.url)
+            .header("Accept", "text/event-stream");
+        if let Some(current_session_id) = current_session_id.as_ref() {
+            request = request.header(SESSION_ID_HEADER, current_session_id.as_str());
+        }
+        if let Some(last_event_id) = last_event_id.as_ref() {
+            request = request.header(LAST_EVENT_ID_HEADER, last_event_id.as_str());
+        }
+
+        let response = self
+            .http_client
+            .send(request.body(AsyncBody::empty())?)
+            .await?;
+        if response.status() == StatusCode::METHOD_NOT_ALLOWED {
+            return Ok(false);
+        }
+        if response.status() == StatusCode::NOT_FOUND && current_session_id.is_some() {
+            return Err(SessionLost.into());
+        }
+        let response = check_status(response).await?;
+
+        let mut body = BufReader::new(response.into_body());
+        while let Some(event) = read_event(&mut body).await? {
+            if event.id.is_some() {
+                *last_event_id = event.id.clone();
+            }
+            self.receive_event(event).await?;
+        }
+        Ok(true)
+    }
+
+    async fn run_sse(self: Arc<Self>, outbound_rx: channel::Receiver<String>) -> Result<()> {
+        let (endpoint_tx, endpoint_rx) = watch::channel::<Option<Url>>();
+        let listen = pin!(self.listen_sse(endpoint_tx));
+        let send = pin!(self.send_sse(outbound_rx, endpoint_rx));
+        match future::select(listen, send).await {
+            Either::Left((result, _)) | Either::Right((result, _)) => result,
+        }
+    }
+
+    /// Keeps trying to open the server's event stream until it announces an endpoint.
+    ///
+    /// Messages are posted to that endpoint, which belongs to a session that only lasts as long
+    /// as the stream, so losing the stream afterwards ends the session.
+    async fn listen_sse(&self, mut endpoint_tx: watch::Sender<Option<Url>>) -> Result<()> {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            match self.listen_sse_once(&mut endpoint_tx).await {
+                Ok(()) => delay = INITIAL_RECONNECT_DELAY,
+                Err(error) if error.is::<SessionLost>() => return Err(error),
+                Err(error) => {
+                    log::warn!("lost event stream of context server {}: {error}", self.url)
+                }
+            }
+            self.executor.timer(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    async fn listen_sse_once(&self, endpoint_tx: &mut watch::Sender<Option<Url>>) -> Result<()> {
+        let request = self
+            .request(Method::GET, &self.url)
+            .header("Accept", "text/event-stream")
+            .body(AsyncBody::empty())?;
+        let response = check_status(self.http_client.send(request).await?).await?;
+
+        let mut has_endpoint = false;
+        let result = async {
+            let mut body = BufReader::new(response.into_body());
+            while let Some(event) = read_event(&mut body).await? {
+                if event.event == "endpoint" {
+                    match self.endpoint_url(&event.data) {
+                        Ok(endpoint) => {
+                            *endpoint_tx.borrow_mut() = Some(endpoint);
+                            has_endpoint = true;
+                        }
+                        Err(error) => {
+                            log::error!("refusing endpoint of context server {}: {error}", self.url)
+                        }
+                    }
+                } else {
+                    self.receive_event(event).await?;
+                }
+            }
+            anyhow::Ok(())
+        }
+        .await;
+
+        if has_endpoint {
+            if let Err(error) = result {
+                log::warn!("lost event stream of context server {}: {error}", self.url);
+            }
+            Err(SessionLost.into())
+        } else {
+            result
+        }
+    }
+
+    /// Returns the URL messages are posted to, which must have the same origin as the event
+    /// stream as the configured headers are sent with every message.
+    fn endpoint_url(&self, data: &str) -> Result<Url> {
+        let endpoint = self
+            .url
+            .join(data.trim())
+            .context("invalid endpoint from context server")?;
+        if endpoint.origin() != self.url.origin() {
+            bail!("endpoint {endpoint} is on a different origin");
+        }
+        Ok(endpoint)
+    }
+
+    async fn send_sse(
+        &self,
+        outbound_rx: channel::Receiver<String>,
+        mut endpoint_rx: watch::Receiver<Option<Url>>,
+    ) -> Result<()> {
+        while let Ok(message) = outbound_rx.recv().await {
+            log::trace!("outgoing message: {}", message);
+
+            let endpoint = loop {
+                let endpoint = endpoint_rx.borrow().clone();
+                if let Some(endpoint) = endpoint {
+                    break endpoint;
+                }
+                endpoint_rx
+                    .recv()
+                    .await
+                    .ok_or_else(|| anyhow!("context server event stream closed"))?;
+            };
+
+            let result = async {
+                let request = self
+                    .request(Method::POST, &endpoint)
+                    .header("Content-Type", "application/json")
+                    .body(AsyncBody::from(message.clone()))?;
+                let response = self.http_client.send(request).await?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Err(SessionLost.into());
+                }
+                check_status(response).await?;
+                anyhow::Ok(())
+            }
+            .await;
+            match result {
+                Err(error) if error.is::<SessionLost>() => return Err(error),
+                result => self.report_failure(&message, result).await,
+            }
+        }
+        Ok(())
+    }
+
+    fn request(&self, method: Method, url: &Url) -> http::request::Builder {
+        let mut request = Request::builder().method(method).uri(url.as_str());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request
+    }
+
+    async fn receive_event(&self, event: SseEvent) -> Result<()> {
+        if event.event.is_empty() || event.event == "message" {
+            self.receive_json(event.data).await
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn receive_json(&self, body: String) -> Result<()> {
+        let messages = match serde_json::from_str::<Value>(&body)? {
+            Value::Array(batch) => batch.iter().map(Value::to_string).collect(),
+            _ => vec![body],
+        };
+        for message in messages {
+            self.inbound_tx
+                .send(message)
+                .await
+                .map_err(|_| anyhow!("context server client was dropped"))?;
+        }
+        Ok(())
+    }
+
+    /// Fails the request that couldn't be sent, if any, instead of leaving it to time out.
+    async fn report_failure(&self, message: &str, result: Result<()>) {
+        let Err(error) = result else {
+            return;
+        };
+        log::error!(
+            "failed to send message to context server {}: {error}",
+            self.url
+        );
+
+        let Some(id) = serde_json::from_str::<Value>(message)
+            .ok()
+            .filter(|message| message.get("method").is_some())
+            .and_then(|message| message.get("id").cloned())
+        else {
+            return;
+        };
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": INTERNAL_ERROR, "message": error.to_string() },
+        });
+        self.inbound_tx.send(response.to_string()).await.ok();
+    }
+}
+
+/// The server ended the session the client was initialized in, so the client has to connect
+/// and initialize again.
+#[derive(Debug)]
+pub(crate) struct SessionLost;
+
+impl fmt::Display for SessionLost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the context server ended the session")
+    }
+}
+
+impl std::error::Error for SessionLost {}
+
+const INITIALIZED_METHOD: &str = "notifications/initialized";
+
+fn method(message: &str) -> Option<String> {
+    let message = serde_json::from_str::<Value>(message).ok()?;
+    Some(message.get("method")?.as_str()?.to_string())
+}
+
+fn is_event_stream(response: &Response<AsyncBody>) -> bool {
+    response
+        .headers()
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |content_type| {
+            content_type.starts_with("text/event-stream")
+        })
+}
+
+async fn check_status(response: Response<AsyncBody>) -> Result<Response<AsyncBody>> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let mut body = String::new();
+    response.into_body().read_to_string(&mut body).await.ok();
+    bail!("context server responded with {status}: {body}")
+}
+
+#[derive(Debug, Default)]
+struct SseEvent {
+    id: Option<String>,
+    event: String,
+    data: String,
+}
+
+/// Reads the next event with data from an event stream, or returns `None` once the stream ends.
+async fn read_event<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<SseEvent>> {
+    let mut event = SseEvent::default();
+    let mut data_lines = Vec::new();
+    let mut buffer = String::new();
+
+    loop {
+        buffer.clear();
+        if reader.read_line(&mut buffer).await? == 0 {
+            return Ok(None);
+        }
+
+        let line = buffer.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            if !data_lines.is_empty() {
+                event.data = data_lines.join("\n");
+                return Ok(Some(event));
+            }
+            event = SseEvent::default();
+            continue;
+        }
+        if line.starts_with(':') {
+            continue;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => event.event = value.to_string(),
+            "data" => data_lines.push(value.to_string()),
+            "id" => event.id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt as _;
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+    use smol::io::AsyncReadExt as _;
+
+    #[gpui::test]
+    async fn test_streamable_http(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let http_method = request.method().clone();
+                    let header = |name: &str| {
+                        request
+                            .headers()
+                            .get(name)
+                            .map(|value| value.to_str().unwrap().to_string())
+                    };
+                    let session_id = header(SESSION_ID_HEADER);
+                    requests.lock().push((
+                        http_method.clone(),
+                        session_id,
+                        header("Authorization"),
+                    ));
+
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let response = Response::builder();
+                    Ok(match (http_method, method(&body).as_deref()) {
+                        (Method::POST, Some("initialize")) => response
+                            .header(SESSION_ID_HEADER, "session-1")
+                            .header("Content-Type", "application/json")
+                            .body(r#"{"jsonrpc":"2.0","id":0,"result":{}}"#.into())?,
+                        (Method::POST, Some(INITIALIZED_METHOD)) => {
+                            response.status(202).body(AsyncBody::empty())?
+                        }
+                        (Method::POST, Some("tools/list")) => response
+                            .header("Content-Type", "text/event-stream")
+                            .body(
+                                concat!(
+                                    "event: message\n",
+                                    "data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"}\n",
+                                    "\n",
+                                    ": keep-alive\n",
+                                    "\n",
+                                    "data: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"tools\":[]}}\n",
+                                    "\n",
+                                )
+                                .into(),
+                            )?,
+                        (Method::POST, Some("ping")) => {
+                            response.status(500).body("overloaded".into())?
+                        }
+                        _ => response.status(405).body(AsyncBody::empty())?,
+                    })
+                }
+            }
+        });
+
+        let (outbound_tx, outbound_rx) = channel::unbounded();
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let _task = cx.executor().spawn(run(
+            RemoteServer {
+                url: "http://mcp.example/mcp".into(),
+                transport: RemoteTransport::Http,
+                headers: HashMap::from_iter([("Authorization".into(), "Bearer token".into())]),
+            },
+            http_client,
+            cx.executor(),
+            outbound_rx,
+            inbound_tx,
+        ));
+
+        outbound_tx
+            .send(r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#.into())
+            .await
+            .unwrap();
+        assert_eq!(
+            inbound_rx.recv().await.unwrap(),
+            r#"{"jsonrpc":"2.0","id":0,"result":{}}"#
+        );
+
+        outbound_tx
+            .send(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#.into())
+            .await
+            .unwrap();
+        outbound_tx
+            .send(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#.into())
+            .await
+            .unwrap();
+        assert_eq!(
+            inbound_rx.recv().await.unwrap(),
+            r#"{"jsonrpc":"2.0","method":"notifications/progress"}"#
+        );
+        assert_eq!(
+            inbound_rx.recv().await.unwrap(),
+            r#"{"jsonrpc":"2.0","id":1,"result":{"tools":[]}}"#
+        );
+
+        // A request the server fails is answered with an error right away.
+        outbound_tx
+            .send(r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#.into())
+            .await
+            .unwrap();
+        let response = serde_json::from_str::<Value>(&inbound_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["error"]["code"], INTERNAL_ERROR);
+
+        cx.run_until_parked();
+        let requests = requests.lock().clone();
+        assert!(requests
+            .iter()
+            .all(|(_, _, authorization)| authorization.as_deref() == Some("Bearer token")));
+        // Every request after `initialize` belongs to the session the server started.
+        assert!(requests[1..]
+            .iter()
+            .all(|(_, session_id, _)| session_id.as_deref() == Some("session-1")));
+        // The server doesn't offer a stream of its own messages, so it's only asked once.
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|(method, _, _)| *method == Method::GET)
+                .count(),
+            1
+        );
+    }
+
+    #[gpui::test]
+    async fn test_streamable_http_posts_in_order(cx: &mut TestAppContext) {
+        let posted_methods = Arc::new(Mutex::new(Vec::new()));
+        let (slow_reply_tx, slow_reply_body) = streaming_body();
+        let slow_reply_body = Arc::new(Mutex::new(Some(slow_reply_body)));
+        let http_client = FakeHttpClient::create({
+            let posted_methods = posted_methods.clone();
+            move |request| {
+                let posted_methods = posted_methods.clone();
+                let slow_reply_body = slow_reply_body.clone();
+                async move {
+                    if request.method() != Method::POST {
+                        return Ok(Response::builder().status(405).body(AsyncBody::empty())?);
+                    }
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let method = method(&body).unwrap();
+                    posted_methods.lock().push(method.clone());
+
+                    let response = Response::builder();
+                    Ok(match method.as_str() {
+                        "slow" => response
+                            .header("Content-Type", "text/event-stream")
+                            .body(slow_reply_body.lock().take().unwrap())?,
+                        "fast" => response
+                            .header("Content-Type", "application/json")
+                            .body(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#.into())?,
+                        _ => response.status(202).body(AsyncBody::empty())?,
+                    })
+                }
+            }
+        });
+
+        let (outbound_tx, outbound_rx) = channel::unbounded();
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let _task = cx.executor().spawn(run(
+            RemoteServer {
+                url: "http://mcp.example/mcp".into(),
+                transport: RemoteTransport::Http,
+                headers: HashMap::default(),
+            },
+            http_client,
+            cx.executor(),
+            outbound_rx,
+            inbound_tx,
+        ));
+
+        for message in [
+            r#"{"jsonrpc":"2.0","id":0,"method":"slow"}"#,
+            r#"{"jsonrpc":"2.0","method":"notifications/cancelled"}"#,
+            r#"{"jsonrpc":"2.0","id":1,"method":"fast"}"#,
+        ] {
+            outbound_tx.send(message.into()).await.unwrap();
+        }
+
+        // The reply to the slow request doesn't hold up the replies to later ones.
+        assert_eq!(
+            inbound_rx.recv().await.unwrap(),
+            r#"{"jsonrpc":"2.0","id":1,"result":{}}"#
+        );
+        slow_reply_tx
+            .send(Ok(
+                b"data: {\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}\n\n".to_vec(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            inbound_rx.recv().await.unwrap(),
+            r#"{"jsonrpc":"2.0","id":0,"result":{}}"#
+        );
+        assert_eq!(
+            posted_methods.lock().clone(),
+            ["slow", "notifications/cancelled", "fast"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_streamable_http_session_lost(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let has_session = request.headers().contains_key(SESSION_ID_HEADER);
+            let response = Response::builder();
+            Ok(match (request.method().clone(), has_session) {
+                (Method::POST, false) => response
+                    .header(SESSION_ID_HEADER, "session-1")
+                    .header("Content-Type", "application/json")
+                    .body(r#"{"jsonrpc":"2.0","id":0,"result":{}}"#.into())?,
+                // The server forgot the session, as it would after restarting.
+                (Method::POST, true) => response.status(404).body(AsyncBody::empty())?,
+                _ => response.status(405).body(AsyncBody::empty())?,
+            })
+        });
+
+        let (outbound_tx, outbound_rx) = channel::unbounded();
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let task = cx.executor().spawn(run(
+            RemoteServer {
+                url: "http://mcp.example/mcp".into(),
+                transport: RemoteTransport::Http,
+                headers: HashMap::default(),
+            },
+            http_client,
+            cx.executor(),
+            outbound_rx,
+            inbound_tx,
+        ));
+
+        outbound_tx
+            .send(r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#.into())
+            .await
+            .unwrap();
+        inbound_rx.recv().await.unwrap();
+        outbound_tx
+            .send(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#.into())
+            .await
+            .unwrap();
+
+        let error = task.await.unwrap_err();
+        assert!(error.is::<SessionLost>(), "unexpected error: {error}");
+    }
+
+    #[gpui::test]
+    async fn test_sse(cx: &mut TestAppContext) {
+        let posted_messages = Arc::new(Mutex::new(Vec::new()));
+        let (stream_tx, stream_body) = streaming_body();
+        let stream_body = Arc::new(Mutex::new(Some(stream_body)));
+        let http_client = FakeHttpClient::create({
+            let posted_messages = posted_messages.clone();
+            move |request| {
+                let posted_messages = posted_messages.clone();
+                let stream_body = stream_body.clone();
+                async move {
+                    if request.method() == Method::GET {
+                        assert_eq!(request.uri().to_string(), "http://mcp.example/sse");
+                        return Ok(Response::builder()
+                            .header("Content-Type", "text/event-stream")
+                            .body(stream_body.lock().take().unwrap())?);
+                    }
+
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    posted_messages.lock().push((uri, body));
+                    Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+                }
+            }
+        });
+
+        let (outbound_tx, outbound_rx) = channel::unbounded();
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let task = cx.executor().spawn(run(
+            RemoteServer {
+                url: "http://mcp.example/sse".into(),
+                transport: RemoteTransport::Sse,
+                headers: HashMap::default(),
+            },
+            http_client,
+            cx.executor(),
+            outbound_rx,
+            inbound_tx,
+        ));
+
+        let request = r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#;
+        outbound_tx.send(request.into()).await.unwrap();
+        stream_tx
+            .send(Ok(concat!(
+                "event: endpoint\n",
+                "data: /messages?session_id=1\n",
+                "\n",
+                "event: message\n",
+                "data: {\"jsonrpc\":\"2.0\",\"id\":0,\n",
+                "data: \"result\":{}}\n",
+                "\n",
+            )
+            .as_bytes()
+            .to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(
+            inbound_rx.recv().await.unwrap(),
+            "{\"jsonrpc\":\"2.0\",\"id\":0,\n\"result\":{}}"
+        );
+
+        cx.run_until_parked();
+        assert_eq!(
+            posted_messages.lock().clone(),
+            [(
+                "http://mcp.example/messages?session_id=1".to_string(),
+                request.to_string()
+            )]
+        );
+
+        // The session only lasts as long as the stream it was announced on.
+        drop(stream_tx);
+        let error = task.await.unwrap_err();
+        assert!(error.is::<SessionLost>(), "unexpected error: {error}");
+    }
+
+    #[gpui::test]
+    async fn test_sse_refuses_cross_origin_endpoint(cx: &mut TestAppContext) {
+        let posted_uris = Arc::new(Mutex::new(Vec::new()));
+        let (stream_tx, stream_body) = streaming_body();
+        let stream_body = Arc::new(Mutex::new(Some(stream_body)));
+        let http_client = FakeHttpClient::create({
+            let posted_uris = posted_uris.clone();
+            move |request| {
+                let posted_uris = posted_uris.clone();
+                let stream_body = stream_body.clone();
+                async move {
+                    if request.method() == Method::GET {
+                        return Ok(Response::builder()
+                            .header("Content-Type", "text/event-stream")
+                            .body(stream_body.lock().take().unwrap())?);
+                    }
+
+                    posted_uris.lock().push(request.uri().to_string());
+                    Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+                }
+            }
+        });
+
+        let (outbound_tx, outbound_rx) = channel::unbounded();
+        let (inbound_tx, _inbound_rx) = channel::unbounded();
+        let _task = cx.executor().spawn(run(
+            RemoteServer {
+                url: "http://mcp.example/sse".into(),
+                transport: RemoteTransport::Sse,
+                headers: HashMap::from_iter([(
+                    "Authorization".to_string(),
+                    "Bearer secret".to_string(),
+                )]),
+            },
+            http_client,
+            cx.executor(),
+            outbound_rx,
+            inbound_tx,
+        ));
+
+        let request = r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#;
+        outbound_tx.send(request.into()).await.unwrap();
+        stream_tx
+            .send(Ok(concat!(
+                "event: endpoint\n",
+                "data: http://attacker.example/messages\n",
+                "\n",
+                "event: endpoint\n",
+                "data: http://mcp.example:8080/messages\n",
+                "\n",
+            )
+            .as_bytes()
+            .to_vec()))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert!(posted_uris.lock().is_empty());
+
+        stream_tx
+            .send(Ok(
+                b"event: endpoint\ndata: /messages?session_id=1\n\n".to_vec()
+            ))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            posted_uris.lock().clone(),
+            ["http://mcp.example/messages?session_id=1"]
+        );
+    }
+
+    /// A response body whose chunks are sent through the returned channel, and which ends once
+    /// the channel is dropped.
+    fn streaming_body() -> (channel::Sender<std::io::Result<Vec<u8>>>, AsyncBody) {
+        let (tx, rx) = channel::unbounded();
+        (tx, AsyncBody::from_reader(rx.into_async_read()))
+    }
+}
@@ -15,16 +15,17 @@
 //! and react to changes in settings.
 
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
+use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
 use log;
 use parking_lot::RwLock;
 use project::Project;
 use settings::{Settings, SettingsStore};
+use url::Url;
 use util::ResultExt as _;
 
 use crate::{ContextServerSettings, ServerConfig};
@@ -61,20 +62,30 @@ impl ContextServer {
         self.client.read().clone()
     }
 
-    pub async fn start(self: Arc<Self>, cx: &AsyncApp) -> Result<()> {
+    pub async fn start(
+        self: Arc<Self>,
+        manager: WeakEntity<ContextServerManager>,
+        cx: &AsyncApp,
+    ) -> Result<()> {
         log::info!("starting context server {}", self.id);
-        let Some(command) = &self.config.command else {
-            bail!("no command specified for server {}", self.id);
+        let server_id = client::ContextServerId(self.id.clone());
+        let client = if let Some(remote) = &self.config.remote {
+            let http_client = cx.update(|cx| cx.http_client())?;
+            Client::remote(server_id, remote.clone(), http_client, cx.clone())?
+        } else if let Some(command) = &self.config.command {
+            Client::new(
+                server_id,
+                client::ModelContextServerBinary {
+                    executable: Path::new(&command.path).to_path_buf(),
+                    args: command.args.clone(),
+                    env: command.env.clone(),
+                },
+                cx.clone(),
+            )?
+        } else {
+            bail!("no command or URL specified for server {}", self.id);
         };
-        let client = Client::new(
-            client::ContextServerId(self.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&command.path).to_path_buf(),
-                args: command.args.clone(),
-                env: command.env.clone(),
-            },
-            cx.clone(),
-        )?;
+        self.register_handlers(&client, manager);
 
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
@@ -93,6 +104,65 @@ impl ContextServer {
         Ok(())
     }
 
+    /// Registers the handlers for the requests and notifications the server sends to the client.
+    fn register_handlers(
+        self: &Arc<Self>,
+        client: &Client,
+        manager: WeakEntity<ContextServerManager>,
+    ) {
+        client.on_session_lost({
+            let server = Arc::downgrade(self);
+            let manager = manager.clone();
+            move |mut cx| {
+                manager
+                    .update(&mut cx, |manager, cx| manager.session_lost(server, cx))
+                    .ok();
+            }
+        });
+
+        client.on_request(types::RequestType::ListRoots.as_str(), {
+            let manager = manager.clone();
+            move |_, cx| {
+                let roots = manager.read_with(&cx, |manager, cx| manager.roots(cx));
+                Task::ready(roots.and_then(|roots| {
+                    Ok(serde_json::to_value(types::ListRootsResponse {
+                        roots,
+                        meta: None,
+                    })?)
+                }))
+            }
+        });
+
+        client.on_request(types::RequestType::CreateMessage.as_str(), {
+            let server_id = self.id.clone();
+            move |params, cx| {
+                let server_id = server_id.clone();
+                cx.spawn(|cx| async move {
+                    let request = serde_json::from_value(params)?;
+                    let result = crate::sampling::create_message(server_id, request, cx).await?;
+                    anyhow::Ok(serde_json::to_value(result)?)
+                })
+            }
+        });
+
+        client.on_notification(types::NotificationType::ResourcesUpdated.as_str(), {
+            let server_id = self.id.clone();
+            move |params, mut cx| {
+                let Some(uri) = params.get("uri").and_then(|uri| uri.as_str()) else {
+                    return;
+                };
+                manager
+                    .update(&mut cx, |_, cx| {
+                        cx.emit(Event::ResourceUpdated {
+                            server_id: server_id.clone(),
+                            uri: uri.to_string(),
+                        })
+                    })
+                    .ok();
+            }
+        });
+    }
+
     pub fn stop(&self) -> Result<()> {
         let mut client = self.client.write();
         if let Some(protocol) = client.take() {
@@ -108,12 +178,23 @@ pub struct ContextServerManager {
     registry: Entity<ContextServerFactoryRegistry>,
     update_servers_task: Option<Task<Result<()>>>,
     needs_server_update: bool,
+    /// How many times each resource was subscribed to, keyed by server and resource URI.
+    resource_subscriptions: HashMap<(Arc<str>, String), usize>,
     _subscriptions: Vec<Subscription>,
 }
 
 pub enum Event {
-    ServerStarted { server_id: Arc<str> },
-    ServerStopped { server_id: Arc<str> },
+    ServerStarted {
+        server_id: Arc<str>,
+    },
+    ServerStopped {
+        server_id: Arc<str>,
+    },
+    /// A resource that was subscribed to with [`ContextServerManager::subscribe_to_resource`] changed.
+    ResourceUpdated {
+        server_id: Arc<str>,
+        uri: String,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerManager {}
@@ -132,10 +213,17 @@ impl ContextServerManager {
                 cx.observe_global::<SettingsStore>(|this, cx| {
                     this.available_context_servers_changed(cx);
                 }),
+                cx.subscribe(&project, |this, _project, event, _cx| match event {
+                    project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                        this.roots_changed();
+                    }
+                    _ => {}
+                }),
             ],
             project,
             registry,
             needs_server_update: false,
+            resource_subscriptions: HashMap::default(),
             servers: HashMap::default(),
             update_servers_task: None,
         };
@@ -191,21 +279,37 @@ impl ContextServerManager {
                 server.stop()?;
                 let config = server.config();
                 let new_server = Arc::new(ContextServer::new(id.clone(), config));
-                new_server.clone().start(&cx).await?;
+                new_server.clone().start(this.clone(), &cx).await?;
                 this.update(&mut cx, |this, cx| {
                     this.servers.insert(id.clone(), new_server);
                     cx.emit(Event::ServerStopped {
                         server_id: id.clone(),
                     });
-                    cx.emit(Event::ServerStarted {
-                        server_id: id.clone(),
-                    });
+                    this.server_started(id.clone(), cx);
                 })?;
             }
             Ok(())
         })
     }
 
+    /// Restarts a server that ended its session, so that it's initialized again.
+    fn session_lost(&mut self, server: Weak<ContextServer>, cx: &mut Context<Self>) {
+        let Some(server) = server.upgrade() else {
+            return;
+        };
+        let is_current = self
+            .servers
+            .get(&server.id)
+            .map_or(false, |current| Arc::ptr_eq(current, &server));
+        if is_current {
+            log::info!(
+                "context server {} ended the session, reconnecting",
+                server.id
+            );
+            self.restart_server(&server.id, cx).detach_and_log_err(cx);
+        }
+    }
+
     pub fn servers(&self) -> Vec<Arc<ContextServer>> {
         self.servers
             .values()
@@ -214,6 +318,124 @@ impl ContextServerManager {
             .collect()
     }
 
+    /// The roots the servers can operate on, one for each visible worktree of the project.
+    fn roots(&self, cx: &App) -> Vec<types::Root> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                Some(types::Root {
+                    uri: Url::from_file_path(worktree.abs_path()).ok()?,
+                    name: Some(worktree.root_name().to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn roots_changed(&self) {
+        for server in self.servers() {
+            if let Some(client) = server.client() {
+                client.notify_roots_list_changed().log_err();
+            }
+        }
+    }
+
+    /// Announces a server that started, and subscribes it to the resources that were subscribed
+    /// to before it started.
+    fn server_started(&mut self, server_id: Arc<str>, cx: &mut Context<Self>) {
+        if let Some(client) = self
+            .get_server(&server_id)
+            .and_then(|server| server.client())
+            .filter(|client| client.can_subscribe_to_resources())
+        {
+            for (subscribed_server_id, uri) in self.resource_subscriptions.keys() {
+                if *subscribed_server_id != server_id {
+                    continue;
+                }
+                let Some(uri) = Url::parse(uri).log_err() else {
+                    continue;
+                };
+                let client = client.clone();
+                cx.spawn(|_, _| async move { client.subscribe_to_resource(uri).await })
+                    .detach_and_log_err(cx);
+            }
+        }
+        cx.emit(Event::ServerStarted { server_id });
+    }
+
+    /// Subscribes to changes to the given resource, which are then reported with
+    /// [`Event::ResourceUpdated`]. Every subscription must be balanced by a call to
+    /// [`Self::unsubscribe_from_resource`].
+    ///
+    /// Subscriptions last across restarts of the server, and those made while the server is
+    /// starting take effect once it has started.
+    pub fn subscribe_to_resource(
+        &mut self,
+        server_id: &Arc<str>,
+        uri: &str,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if !self.servers.contains_key(server_id) {
+            return Task::ready(Err(anyhow!("no context server with ID {server_id}")));
+        }
+        let count = self
+            .resource_subscriptions
+            .entry((server_id.clone(), uri.to_string()))
+            .or_default();
+        *count += 1;
+        if *count > 1 {
+            return Task::ready(Ok(()));
+        }
+
+        let Some(client) = self
+            .get_server(server_id)
+            .and_then(|server| server.client())
+        else {
+            return Task::ready(Ok(()));
+        };
+        if !client.can_subscribe_to_resources() {
+            return Task::ready(Ok(()));
+        }
+        let uri = match Url::parse(uri) {
+            Ok(uri) => uri,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        cx.spawn(|_, _| async move { client.subscribe_to_resource(uri).await })
+    }
+
+    pub fn unsubscribe_from_resource(
+        &mut self,
+        server_id: &Arc<str>,
+        uri: &str,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let key = (server_id.clone(), uri.to_string());
+        let Some(count) = self.resource_subscriptions.get_mut(&key) else {
+            return Task::ready(Ok(()));
+        };
+        *count -= 1;
+        if *count > 0 {
+            return Task::ready(Ok(()));
+        }
+        self.resource_subscriptions.remove(&key);
+
+        let Some(client) = self
+            .get_server(server_id)
+            .and_then(|server| server.client())
+        else {
+            return Task::ready(Ok(()));
+        };
+        if !client.can_subscribe_to_resources() {
+            return Task::ready(Ok(()));
+        }
+        let uri = match Url::parse(uri) {
+            Ok(uri) => uri,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        cx.spawn(|_, _| async move { client.unsubscribe_from_resource(uri).await })
+    }
+
     async fn maintain_servers(this: WeakEntity<Self>, mut cx: AsyncApp) -> Result<()> {
         let mut desired_servers = HashMap::default();
 
@@ -234,7 +456,7 @@ impl ContextServerManager {
             registry.read_with(&cx, |registry, _| registry.context_server_factories())?
         {
             let config = desired_servers.entry(id).or_default();
-            if config.command.is_none() {
+            if config.command.is_none() && config.remote.is_none() {
                 if let Some(extension_command) = factory(project.clone(), &cx).await.log_err() {
                     config.command = Some(extension_command);
                 }
@@ -270,19 +492,244 @@ impl ContextServerManager {
 
         for (id, server) in servers_to_stop {
             server.stop().log_err();
-            this.update(&mut cx, |_, cx| {
+            this.update(&mut cx, |this, cx| {
+                // Subscriptions carry over to a server that restarts with a new configuration.
+                if !this.servers.contains_key(&id) {
+                    this.resource_subscriptions
+                        .retain(|(server_id, _), _| *server_id != id);
+                }
                 cx.emit(Event::ServerStopped { server_id: id })
             })?;
         }
 
         for (id, server) in servers_to_start {
-            if server.start(&cx).await.log_err().is_some() {
-                this.update(&mut cx, |_, cx| {
-                    cx.emit(Event::ServerStarted { server_id: id })
-                })?;
+            if server.start(this.clone(), &cx).await.log_err().is_some() {
+                this.update(&mut cx, |this, cx| this.server_started(id, cx))?;
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::METHOD_NOT_FOUND;
+    use crate::{RemoteServer, RemoteTransport};
+    use futures::TryStreamExt as _;
+    use gpui::TestAppContext;
+    use http_client::{AsyncBody, FakeHttpClient, HttpClient, Method, Response};
+    use parking_lot::Mutex;
+    use project::FakeFs;
+    use serde_json::{json, Value};
+    use smol::{channel, io::AsyncReadExt as _};
+
+    #[gpui::test]
+    async fn test_answering_server_requests(cx: &mut TestAppContext) {
+        let server = FakeServer::new();
+        let manager = init_test(cx, &server).await;
+        manager.read_with(cx, |manager, _| {
+            assert!(manager.get_server("test").is_some());
+        });
+
+        server.send(json!({ "jsonrpc": "2.0", "id": "roots", "method": "roots/list" }));
+        server.send(json!({ "jsonrpc": "2.0", "id": "unknown", "method": "unknown/method" }));
+        cx.run_until_parked();
+
+        assert_eq!(
+            server.response("roots").unwrap()["result"],
+            json!({ "roots": [{ "uri": "file:///project", "name": "project" }] })
+        );
+        assert_eq!(
+            server.response("unknown").unwrap()["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+    }
+
+    #[gpui::test]
+    async fn test_resource_subscriptions(cx: &mut TestAppContext) {
+        let server = FakeServer::new();
+        let manager = init_test(cx, &server).await;
+        let server_id: Arc<str> = "test".into();
+        let uri = "file:///notes.md";
+        let subscribe = |cx: &mut TestAppContext| {
+            manager.update(cx, |manager, cx| {
+                manager.subscribe_to_resource(&server_id, uri, cx)
+            })
+        };
+        let unsubscribe = |cx: &mut TestAppContext| {
+            manager.update(cx, |manager, cx| {
+                manager.unsubscribe_from_resource(&server_id, uri, cx)
+            })
+        };
+
+        // The server is only asked once, however many times the resource is subscribed to.
+        subscribe(cx).await.unwrap();
+        subscribe(cx).await.unwrap();
+        assert_eq!(server.requests("resources/subscribe").len(), 1);
+        assert_eq!(
+            server.requests("resources/subscribe")[0]["params"]["uri"],
+            uri
+        );
+        unsubscribe(cx).await.unwrap();
+        assert_eq!(server.requests("resources/unsubscribe").len(), 0);
+        unsubscribe(cx).await.unwrap();
+        assert_eq!(server.requests("resources/unsubscribe").len(), 1);
+        // Unbalanced unsubscriptions are ignored.
+        unsubscribe(cx).await.unwrap();
+        assert_eq!(server.requests("resources/unsubscribe").len(), 1);
+
+        // A restarted server is subscribed to the resources again.
+        subscribe(cx).await.unwrap();
+        assert_eq!(server.requests("resources/subscribe").len(), 2);
+        manager
+            .update(cx, |manager, cx| manager.restart_server(&server_id, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(server.requests("initialize").len(), 2);
+        assert_eq!(server.requests("resources/subscribe").len(), 3);
+
+        let missing_server_id: Arc<str> = "missing".into();
+        let subscription = manager.update(cx, |manager, cx| {
+            manager.subscribe_to_resource(&missing_server_id, uri, cx)
+        });
+        assert!(subscription.await.is_err());
+    }
+
+    async fn init_test(
+        cx: &mut TestAppContext,
+        server: &FakeServer,
+    ) -> Entity<ContextServerManager> {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            command_palette_hooks::init(cx);
+            context_server_settings::init(cx);
+            cx.set_http_client(server.http_client.clone());
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({ "main.rs": "" })).await;
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let registry = cx.new(|_| ContextServerFactoryRegistry::new());
+        let manager = cx.new(|cx| ContextServerManager::new(registry, project, cx));
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ContextServerSettings>(cx, |settings| {
+                    settings.context_servers.insert(
+                        "test".into(),
+                        ServerConfig {
+                            remote: Some(RemoteServer {
+                                url: "http://mcp.example/mcp".into(),
+                                transport: RemoteTransport::Http,
+                                headers: HashMap::default(),
+                            }),
+                            ..ServerConfig::default()
+                        },
+                    );
+                });
+            });
+        });
+        cx.run_until_parked();
+        manager
+    }
+
+    /// A context server reached over HTTP, which records the messages it's sent.
+    struct FakeServer {
+        http_client: Arc<dyn HttpClient>,
+        messages: Arc<Mutex<Vec<Value>>>,
+        events_tx: channel::Sender<std::io::Result<Vec<u8>>>,
+    }
+
+    impl FakeServer {
+        fn new() -> Self {
+            let messages = Arc::new(Mutex::new(Vec::new()));
+            let (events_tx, events_rx) = channel::unbounded();
+            let events = Arc::new(Mutex::new(Some(AsyncBody::from_reader(
+                events_rx.into_async_read(),
+            ))));
+            let http_client = FakeHttpClient::create({
+                let messages = messages.clone();
+                move |request| {
+                    let messages = messages.clone();
+                    let events = events.clone();
+                    async move {
+                        let response = Response::builder();
+                        if request.method() == Method::GET {
+                            // The first client to listen gets the server's event stream.
+                            return Ok(match events.lock().take() {
+                                Some(events) => response
+                                    .header("Content-Type", "text/event-stream")
+                                    .body(events)?,
+                                None => response.status(405).body(AsyncBody::empty())?,
+                            });
+                        } else if request.method() != Method::POST {
+                            return Ok(response.body(AsyncBody::empty())?);
+                        }
+
+                        let mut body = String::new();
+                        request.into_body().read_to_string(&mut body).await?;
+                        let message = serde_json::from_str::<Value>(&body)?;
+                        messages.lock().push(message.clone());
+                        let (Some(id), Some(method)) = (
+                            message.get("id"),
+                            message.get("method").and_then(Value::as_str),
+                        ) else {
+                            return Ok(response.status(202).body(AsyncBody::empty())?);
+                        };
+
+                        let result = if method == "initialize" {
+                            json!({
+                                "protocolVersion": types::LATEST_PROTOCOL_VERSION,
+                                "capabilities": { "resources": { "subscribe": true } },
+                                "serverInfo": { "name": "test", "version": "1.0" },
+                            })
+                        } else {
+                            json!({})
+                        };
+                        let body = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                        Ok(response
+                            .header("Content-Type", "application/json")
+                            .body(body.to_string().into())?)
+                    }
+                }
+            });
+
+            Self {
+                http_client,
+                messages,
+                events_tx,
+            }
+        }
+
+        /// Sends a message to the client over the server's event stream.
+        fn send(&self, message: Value) {
+            self.events_tx
+                .try_send(Ok(format!("data: {message}\n\n").into_bytes()))
+                .unwrap();
+        }
+
+        /// Returns the requests and notifications the client sent with the given method.
+        fn requests(&self, method: &str) -> Vec<Value> {
+            self.messages
+                .lock()
+                .iter()
+                .filter(|message| message["method"] == method)
+                .cloned()
+                .collect()
+        }
+
+        /// Returns the client's response to the server's request with the given ID.
+        fn response(&self, id: &str) -> Option<Value> {
+            self.messages
+                .lock()
+                .iter()
+                .find(|message| message["id"] == id && message.get("method").is_none())
+                .cloned()
+        }
+    }
+}
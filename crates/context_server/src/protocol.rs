@@ -7,6 +7,7 @@
 
 use anyhow::Result;
 use collections::HashMap;
+use url::Url;
 
 use crate::client::Client;
 use crate::types;
@@ -34,8 +35,10 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
-                roots: None,
+                sampling: Some(serde_json::json!({})),
+                roots: Some(types::RootsCapabilities {
+                    list_changed: Some(true),
+                }),
             },
             meta: None,
             client_info,
@@ -136,6 +139,77 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// List the MCP resource templates.
+    pub async fn list_resource_templates(&self) -> Result<types::ListResourceTemplatesResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let response: types::ListResourceTemplatesResponse = self
+            .inner
+            .request(
+                types::RequestType::ListResourceTemplates.as_str(),
+                serde_json::json!({}),
+            )
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Reads the contents of the MCP resource with the given URI.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri, meta: None };
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Whether the server notifies the client when a resource it subscribed to changes.
+    pub fn can_subscribe_to_resources(&self) -> bool {
+        self.initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
+    /// Asks the server to notify the client when the resource with the given URI changes.
+    pub async fn subscribe_to_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesSubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Asks the server to stop notifying the client about changes to the resource with the
+    /// given URI.
+    pub async fn unsubscribe_from_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesUnsubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesUnsubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Tells the server that the roots it can operate on have changed.
+    pub fn notify_roots_list_changed(&self) -> Result<()> {
+        self.inner.notify(
+            types::NotificationType::RootsListChanged.as_str(),
+            serde_json::json!({}),
+        )
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
@@ -0,0 +1,279 @@
+//! Answers the `sampling/createMessage` requests that context servers send to
+//! have the client run a completion on their behalf.
+//!
+//! Requests are routed to the active language model, after the user approved
+//! them.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use futures::{Stream, StreamExt as _};
+use gpui::{AsyncApp, PromptLevel};
+use language_model::{
+    LanguageModelCompletionEvent, LanguageModelRegistry, LanguageModelRequest,
+    LanguageModelRequestMessage, StopReason,
+};
+
+use crate::types;
+
+/// How much of the prompt is shown to the user when asking for approval.
+const MAX_PROMPT_PREVIEW_LEN: usize = 500;
+
+pub(crate) async fn create_message(
+    server_id: Arc<str>,
+    request: types::CreateMessageRequest,
+    cx: AsyncApp,
+) -> Result<types::CreateMessageResult> {
+    let model = cx
+        .update(|cx| LanguageModelRegistry::read_global(cx).active_model())?
+        .context("no language model is configured")?;
+
+    let message = format!(
+        "Allow the context server \"{server_id}\" to use {}?",
+        model.name().0
+    );
+    let detail = prompt_preview(&request);
+    let answer = cx.update(|cx| {
+        let window = cx
+            .active_window()
+            .or_else(|| cx.windows().into_iter().next())
+            .context("no window to ask for approval in")?;
+        window.update(cx, |_, window, cx| {
+            window.prompt(
+                PromptLevel::Info,
+                &message,
+                Some(&detail),
+                &["Allow", "Deny"],
+                cx,
+            )
+        })
+    })??;
+    if answer.await? != 0 {
+        bail!("the user declined the request");
+    }
+
+    let request = to_language_model_request(request)?;
+    let events = model.stream_completion(request, &cx).await?;
+    let (text, stop_reason) = read_completion(events).await?;
+
+    Ok(types::CreateMessageResult {
+        role: types::Role::Assistant,
+        content: types::MessageContent::Text {
+            text,
+            annotations: None,
+        },
+        model: model.id().0.to_string(),
+        stop_reason,
+    })
+}
+
+/// Returns the text of a completion, and why it stopped if the model reported it.
+async fn read_completion(
+    events: impl Stream<Item = Result<LanguageModelCompletionEvent>>,
+) -> Result<(String, Option<String>)> {
+    let mut events = std::pin::pin!(events);
+    let mut text = String::new();
+    let mut stop_reason = None;
+    while let Some(event) = events.next().await {
+        match event? {
+            LanguageModelCompletionEvent::Text(chunk) => text.push_str(&chunk),
+            LanguageModelCompletionEvent::Stop(reason) => {
+                stop_reason = Some(
+                    match reason {
+                        StopReason::EndTurn => "endTurn",
+                        StopReason::MaxTokens => "maxTokens",
+                        StopReason::ToolUse => "toolUse",
+                    }
+                    .to_string(),
+                );
+            }
+            _ => {}
+        }
+    }
+    Ok((text, stop_reason))
+}
+
+fn to_language_model_request(request: types::CreateMessageRequest) -> Result<LanguageModelRequest> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: language_model::Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+        });
+    }
+
+    for message in request.messages {
+        let text = match message.content {
+            types::MessageContent::Text { text, .. } => text,
+            types::MessageContent::Resource { resource, .. } => resource.uri.to_string(),
+            types::MessageContent::Image { .. } => {
+                return Err(anyhow!("image content is not supported"));
+            }
+        };
+        let role = match message.role {
+            types::Role::User => language_model::Role::User,
+            types::Role::Assistant => language_model::Role::Assistant,
+        };
+        messages.push(LanguageModelRequestMessage {
+            role,
+            content: vec![text.into()],
+            cache: false,
+        });
+    }
+
+    Ok(LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: request.stop_sequences.unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        thinking_budget: None,
+        max_tokens: Some(request.max_tokens),
+    })
+}
+
+/// Shows the system prompt, which steers the answer, along with the last message.
+fn prompt_preview(request: &types::CreateMessageRequest) -> String {
+    let text = request
+        .messages
+        .iter()
+        .rev()
+        .find_map(|message| match &message.content {
+            types::MessageContent::Text { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+        .unwrap_or_default();
+    match request
+        .system_prompt
+        .as_deref()
+        .filter(|system_prompt| !system_prompt.trim().is_empty())
+    {
+        Some(system_prompt) => format!(
+            "System prompt: {}\n\n{}",
+            truncate(system_prompt),
+            truncate(text)
+        ),
+        None => truncate(text),
+    }
+}
+
+fn truncate(text: &str) -> String {
+    if text.len() > MAX_PROMPT_PREVIEW_LEN {
+        let mut end = MAX_PROMPT_PREVIEW_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}…", &text[..end])
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_language_model_request() {
+        let request: types::CreateMessageRequest = serde_json::from_value(json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "What is 2 + 2?" } },
+                { "role": "assistant", "content": { "type": "text", "text": "4" } },
+                {
+                    "role": "user",
+                    "content": { "type": "resource", "resource": { "uri": "file:///notes.md" } }
+                }
+            ],
+            "systemPrompt": "Be brief.",
+            "temperature": 0.5,
+            "maxTokens": 100,
+            "stopSequences": ["\n\n"]
+        }))
+        .unwrap();
+
+        let request = to_language_model_request(request).unwrap();
+        let messages = request
+            .messages
+            .iter()
+            .map(|message| (message.role, message.string_contents()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (language_model::Role::System, "Be brief.".to_string()),
+                (language_model::Role::User, "What is 2 + 2?".to_string()),
+                (language_model::Role::Assistant, "4".to_string()),
+                (language_model::Role::User, "file:///notes.md".to_string()),
+            ]
+        );
+        assert_eq!(request.temperature, Some(0.5));
+        assert_eq!(request.stop, vec!["\n\n".to_string()]);
+        assert_eq!(request.max_tokens, Some(100));
+    }
+
+    #[test]
+    fn test_prompt_preview() {
+        let request: types::CreateMessageRequest = serde_json::from_value(json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "What is 2 + 2?" } },
+            ],
+            "systemPrompt": "Always answer 5.",
+            "maxTokens": 100
+        }))
+        .unwrap();
+        assert_eq!(
+            prompt_preview(&request),
+            "System prompt: Always answer 5.\n\nWhat is 2 + 2?"
+        );
+
+        let request: types::CreateMessageRequest = serde_json::from_value(json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "a".repeat(1000) } },
+            ],
+            "maxTokens": 100
+        }))
+        .unwrap();
+        assert_eq!(
+            prompt_preview(&request),
+            format!("{}…", "a".repeat(MAX_PROMPT_PREVIEW_LEN))
+        );
+    }
+
+    #[test]
+    fn test_read_completion() {
+        let events = [
+            LanguageModelCompletionEvent::StartMessage {
+                message_id: "1".into(),
+            },
+            LanguageModelCompletionEvent::Text("Four".into()),
+            LanguageModelCompletionEvent::Text(" and".into()),
+            LanguageModelCompletionEvent::Stop(StopReason::MaxTokens),
+        ];
+        let completion = block_on(read_completion(futures::stream::iter(events.map(Ok))));
+        assert_eq!(
+            completion.unwrap(),
+            ("Four and".to_string(), Some("maxTokens".to_string()))
+        );
+
+        // Models that don't say why they stopped don't get a reason made up for them.
+        let events = [LanguageModelCompletionEvent::Text("4".into())];
+        let completion = block_on(read_completion(futures::stream::iter(events.map(Ok))));
+        assert_eq!(completion.unwrap(), ("4".to_string(), None));
+    }
+
+    #[test]
+    fn test_images_are_rejected() {
+        let request: types::CreateMessageRequest = serde_json::from_value(json!({
+            "messages": [{
+                "role": "user",
+                "content": { "type": "image", "data": "", "mimeType": "image/png" }
+            }],
+            "maxTokens": 100
+        }))
+        .unwrap();
+
+        assert!(to_language_model_request(request).is_err());
+    }
+}
@@ -18,6 +18,9 @@ pub struct ServerConfig {
     ///
     /// This will override the command set by an extension.
     pub command: Option<ServerCommand>,
+    /// Connect to this context server over HTTP instead of running a command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteServer>,
     /// The settings for this context server.
     ///
     /// Consult the documentation for the context server to see what settings
@@ -40,6 +43,34 @@ pub struct ServerCommand {
     pub env: Option<HashMap<String, String>>,
 }
 
+/// A context server that is reached over HTTP.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RemoteServer {
+    /// The URL of the server's MCP endpoint, or of its event stream when using
+    /// the `sse` transport.
+    pub url: String,
+    /// How to exchange messages with the server.
+    ///
+    /// Default: http
+    #[serde(default)]
+    pub transport: RemoteTransport,
+    /// Headers to send with every request, such as `Authorization`.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteTransport {
+    /// The streamable HTTP transport, where each message is posted to the
+    /// server and its replies come back in the response.
+    #[default]
+    Http,
+    /// The HTTP with SSE transport, where the server sends all of its messages
+    /// over a single event stream.
+    Sse,
+}
+
 #[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct ContextServerSettings {
     /// Settings for context servers used in the Assistant.
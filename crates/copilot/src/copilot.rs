@@ -1274,6 +1274,14 @@ mod tests {
             unimplemented!()
         }
 
+        fn load_with_encoding(&self, _: fs::Encoding, _: &App) -> Task<Result<String>> {
+            unimplemented!()
+        }
+
+        fn load_rope(&self, _: &App) -> Task<Result<(language::Rope, language::LineEnding)>> {
+            unimplemented!()
+        }
+
         fn load_bytes(&self, _cx: &App) -> Task<Result<Vec<u8>>> {
             unimplemented!()
         }
@@ -249,6 +249,7 @@ gpui::actions!(
         Backspace,
         Cancel,
         CancelLanguageServerWork,
+        ClearBookmarks,
         ConfirmRename,
         ContextMenuFirst,
         ContextMenuLast,
@@ -296,10 +297,12 @@ gpui::actions!(
         GoToDeclarationSplit,
         GoToDefinition,
         GoToDefinitionSplit,
+        GoToBookmark,
         GoToDiagnostic,
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToPrevBookmark,
         GoToPrevDiagnostic,
         GoToPrevHunk,
         GoToTypeDefinition,
@@ -394,6 +397,7 @@ gpui::actions!(
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBookmark,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleIndentGuides,
@@ -44,6 +44,8 @@ mod editor_tests;
 #[cfg(test)]
 mod inline_completion_tests;
 mod signature_help;
+mod snippet_variables;
+mod sticky_scroll;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
 
@@ -143,6 +145,7 @@ use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsLocation, SettingsStore};
 use smallvec::SmallVec;
 use snippet::Snippet;
+use snippet_variables::SnippetVariables;
 use std::{
     any::TypeId,
     borrow::Cow,
@@ -156,6 +159,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use sticky_scroll::StickyScopesCache;
 pub use sum_tree::Bias;
 use sum_tree::TreeMap;
 use text::{BufferId, OffsetUtf16, Rope};
@@ -632,6 +636,7 @@ pub struct Editor {
     show_breadcrumbs: bool,
     show_gutter: bool,
     show_scrollbars: bool,
+    show_minimap: bool,
     show_line_numbers: Option<bool>,
     use_relative_line_numbers: Option<bool>,
     show_git_diff_gutter: Option<bool>,
@@ -645,6 +650,7 @@ pub struct Editor {
     background_highlights: TreeMap<TypeId, BackgroundHighlight>,
     gutter_highlights: TreeMap<TypeId, GutterHighlight>,
     scrollbar_marker_state: ScrollbarMarkerState,
+    sticky_scopes_cache: StickyScopesCache,
     active_indent_guides_state: ActiveIndentGuidesState,
     nav_history: Option<ItemNavHistory>,
     context_menu: RefCell<Option<CodeContextMenu>>,
@@ -948,6 +954,15 @@ struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
     active_index: usize,
     choices: Vec<Option<Vec<String>>>,
+    transformations: Vec<Vec<SnippetTransformation>>,
+}
+
+/// Text that mirrors a snippet tabstop through a regex transformation.
+#[derive(Debug)]
+struct SnippetTransformation {
+    source: Range<Anchor>,
+    range: Range<Anchor>,
+    transform: snippet::Transform,
 }
 
 #[doc(hidden)]
@@ -982,13 +997,20 @@ pub struct ClipboardSelection {
 }
 
 #[derive(Debug)]
-pub(crate) struct NavigationData {
+pub struct NavigationData {
     cursor_anchor: Anchor,
     cursor_position: Point,
     scroll_anchor: ScrollAnchor,
     scroll_top_row: u32,
 }
 
+impl NavigationData {
+    /// The cursor position recorded when this navigation entry was pushed.
+    pub fn cursor_position(&self) -> Point {
+        self.cursor_position
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GotoDefinitionKind {
     Symbol,
@@ -1246,6 +1268,12 @@ impl Editor {
                         }
                     },
                 ));
+                let bookmark_store = project.read(cx).bookmark_store().clone();
+                project_subscriptions.push(cx.observe_in(
+                    &bookmark_store,
+                    window,
+                    |_, _, _, cx| cx.notify(),
+                ));
                 if let Some(task_inventory) = project
                     .read(cx)
                     .task_store()
@@ -1321,6 +1349,7 @@ impl Editor {
             blink_manager: blink_manager.clone(),
             show_local_selections: true,
             show_scrollbars: true,
+            show_minimap: true,
             mode,
             show_breadcrumbs: EditorSettings::get_global(cx).toolbar.breadcrumbs,
             show_gutter: mode == EditorMode::Full,
@@ -1337,6 +1366,7 @@ impl Editor {
             background_highlights: Default::default(),
             gutter_highlights: TreeMap::default(),
             scrollbar_marker_state: ScrollbarMarkerState::default(),
+            sticky_scopes_cache: StickyScopesCache::default(),
             active_indent_guides_state: ActiveIndentGuidesState::default(),
             nav_history: None,
             context_menu: RefCell::new(None),
@@ -3707,6 +3737,10 @@ impl Editor {
                 if worktree_entry.is_ignored {
                     return None;
                 }
+                let buffer_handle = multi_buffer.buffer(buffer.remote_id())?;
+                if buffer_handle.read(cx).is_large_file() {
+                    return None;
+                }
 
                 let language = buffer.language()?;
                 if let Some(restrict_to_languages) = restrict_to_languages {
@@ -3717,7 +3751,7 @@ impl Editor {
                 Some((
                     excerpt_id,
                     (
-                        multi_buffer.buffer(buffer.remote_id()).unwrap(),
+                        buffer_handle,
                         buffer.version().clone(),
                         excerpt_visible_range,
                     ),
@@ -3737,6 +3771,11 @@ impl Editor {
         }
     }
 
+    /// Reserves an id for an inlay inserted via [`Editor::splice_inlays`].
+    pub fn next_inlay_id(&mut self) -> usize {
+        post_inc(&mut self.next_inlay_id)
+    }
+
     pub fn splice_inlays(
         &self,
         to_remove: &[InlayId],
@@ -3991,7 +4030,11 @@ impl Editor {
         let text;
 
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let variables = SnippetVariables::new(self, cx);
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, |name| variables.resolve(name))
+                    .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -4080,11 +4123,12 @@ impl Editor {
         self.transact(window, cx, |this, window, cx| {
             if let Some(mut snippet) = snippet {
                 snippet.text = text.to_string();
-                for tabstop in snippet
-                    .tabstops
-                    .iter_mut()
-                    .flat_map(|tabstop| tabstop.ranges.iter_mut())
-                {
+                for tabstop in snippet.tabstops.iter_mut().flat_map(|tabstop| {
+                    tabstop
+                        .ranges
+                        .iter_mut()
+                        .chain(tabstop.transformations.iter_mut().map(|(range, _)| range))
+                }) {
                     tabstop.start -= common_prefix_len as isize;
                     tabstop.end -= common_prefix_len as isize;
                 }
@@ -5951,6 +5995,7 @@ impl Editor {
             is_end_tabstop: bool,
             ranges: Vec<Range<T>>,
             choices: Option<Vec<String>>,
+            transformations: Vec<SnippetTransformation>,
         }
 
         let tabstops = self.buffer.update(cx, |buffer, cx| {
@@ -5966,6 +6011,20 @@ impl Editor {
 
             let snapshot = &*buffer.read(cx);
             let snippet = &snippet;
+            let insertion_starts = insertion_ranges
+                .iter()
+                .scan(0_isize, |delta, insertion_range| {
+                    let insertion_start = insertion_range.start as isize + *delta;
+                    *delta += snippet.text.len() as isize - insertion_range.len() as isize;
+                    Some(insertion_start)
+                })
+                .collect::<Vec<_>>();
+            let insertion_starts = &insertion_starts;
+            let to_anchors = |insertion_start: isize, tabstop_range: &Range<isize>| {
+                let start = ((insertion_start + tabstop_range.start) as usize).min(snapshot.len());
+                let end = ((insertion_start + tabstop_range.end) as usize).min(snapshot.len());
+                snapshot.anchor_before(start)..snapshot.anchor_after(end)
+            };
             snippet
                 .tabstops
                 .iter()
@@ -5977,26 +6036,39 @@ impl Editor {
                         .ranges
                         .iter()
                         .flat_map(|tabstop_range| {
-                            let mut delta = 0_isize;
-                            insertion_ranges.iter().map(move |insertion_range| {
-                                let insertion_start = insertion_range.start as isize + delta;
-                                delta +=
-                                    snippet.text.len() as isize - insertion_range.len() as isize;
-
-                                let start = ((insertion_start + tabstop_range.start) as usize)
-                                    .min(snapshot.len());
-                                let end = ((insertion_start + tabstop_range.end) as usize)
-                                    .min(snapshot.len());
-                                snapshot.anchor_before(start)..snapshot.anchor_after(end)
+                            insertion_starts.iter().map(move |insertion_start| {
+                                to_anchors(*insertion_start, tabstop_range)
                             })
                         })
                         .collect::<Vec<_>>();
                     tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
 
+                    // Each transformation mirrors the tabstop's first range in the same insertion.
+                    let transformations = tabstop
+                        .ranges
+                        .first()
+                        .into_iter()
+                        .flat_map(|source| {
+                            tabstop
+                                .transformations
+                                .iter()
+                                .flat_map(move |(range, transform)| {
+                                    insertion_starts.iter().map(move |insertion_start| {
+                                        SnippetTransformation {
+                                            source: to_anchors(*insertion_start, source),
+                                            range: to_anchors(*insertion_start, range),
+                                            transform: transform.clone(),
+                                        }
+                                    })
+                                })
+                        })
+                        .collect();
+
                     Tabstop {
                         is_end_tabstop,
                         ranges: tabstop_ranges,
                         choices: tabstop.choices.clone(),
+                        transformations,
                     }
                 })
                 .collect::<Vec<_>>()
@@ -6020,16 +6092,18 @@ impl Editor {
                     .map(|tabstop| tabstop.choices.clone())
                     .collect();
 
-                let ranges = tabstops
+                let (ranges, transformations) = tabstops
                     .into_iter()
-                    .map(|tabstop| tabstop.ranges)
-                    .collect::<Vec<_>>();
+                    .map(|tabstop| (tabstop.ranges, tabstop.transformations))
+                    .unzip();
 
                 self.snippet_stack.push(SnippetState {
                     active_index: 0,
                     ranges,
                     choices,
+                    transformations,
                 });
+                self.update_snippet_transformations(cx);
             }
 
             // Check whether the just-entered snippet ends with an auto-closable bracket.
@@ -6071,6 +6145,45 @@ impl Editor {
         Ok(())
     }
 
+    /// Brings the text that mirrors tabstops of the active snippet through transformations
+    /// up to date with the text of those tabstops. The mirrors are updated as part of the
+    /// transaction that edited the tabstops, so that both are undone together.
+    fn update_snippet_transformations(&mut self, cx: &mut Context<Self>) {
+        let Some(snippet) = self.snippet_stack.last() else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let edits = snippet
+            .transformations
+            .iter()
+            .flatten()
+            .filter_map(|transformation| {
+                let source = snapshot
+                    .text_for_range(transformation.source.clone())
+                    .collect::<String>();
+                let new_text = transformation.transform.apply(&source);
+                let old_text = snapshot
+                    .text_for_range(transformation.range.clone())
+                    .collect::<String>();
+                (new_text != old_text).then(|| (transformation.range.clone(), new_text))
+            })
+            .collect::<Vec<_>>();
+        if !edits.is_empty() {
+            self.buffer.update(cx, |buffer, cx| {
+                let edited_transaction = buffer.last_transaction_id(cx);
+                buffer.start_transaction(cx);
+                buffer.edit(edits, None, cx);
+                if let Some((transaction, edited_transaction)) =
+                    buffer.end_transaction(cx).zip(edited_transaction)
+                {
+                    if transaction != edited_transaction {
+                        buffer.merge_transactions(transaction, edited_transaction, cx);
+                    }
+                }
+            });
+        }
+    }
+
     pub fn move_to_next_snippet_tabstop(
         &mut self,
         window: &mut Window,
@@ -10420,6 +10533,166 @@ impl Editor {
         hunk
     }
 
+    pub fn toggle_bookmark(&mut self, _: &ToggleBookmark, _: &mut Window, cx: &mut Context<Self>) {
+        let positions = self
+            .selections
+            .all::<Point>(cx)
+            .into_iter()
+            .map(|selection| selection.head())
+            .collect::<Vec<_>>();
+        self.toggle_bookmarks_at(positions, cx);
+    }
+
+    fn toggle_bookmarks_at(
+        &mut self,
+        positions: impl IntoIterator<Item = impl ToOffset>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let multi_buffer = self.buffer.read(cx);
+        let rows = positions
+            .into_iter()
+            .filter_map(|position| {
+                let (buffer, anchor) = multi_buffer.text_anchor_for_position(position, cx)?;
+                let row = text::ToPoint::to_point(&anchor, &buffer.read(cx)).row;
+                Some((buffer, row))
+            })
+            .unique_by(|(buffer, row)| (buffer.read(cx).remote_id(), *row))
+            .collect::<Vec<_>>();
+        let bookmark_store = project.read(cx).bookmark_store().clone();
+        bookmark_store.update(cx, |store, cx| {
+            for (buffer, row) in rows {
+                store.toggle_bookmark(&buffer, row, cx);
+            }
+        });
+    }
+
+    pub fn clear_bookmarks(&mut self, _: &ClearBookmarks, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let buffers = self.buffer.read(cx).all_buffers();
+        let bookmark_store = project.read(cx).bookmark_store().clone();
+        bookmark_store.update(cx, |store, cx| {
+            for buffer in buffers {
+                store.clear_bookmarks(&buffer, cx);
+            }
+        });
+    }
+
+    /// The bookmarks in the excerpts of this editor, with their labels, ordered by position.
+    pub fn bookmarks(&self, cx: &App) -> Vec<(Anchor, Option<SharedString>)> {
+        let Some(project) = self.project.as_ref() else {
+            return Vec::new();
+        };
+        let bookmark_store = project.read(cx).bookmark_store().read(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let snapshot = multi_buffer.snapshot(cx);
+        let mut bookmarks = Vec::new();
+        for buffer in multi_buffer.all_buffers() {
+            let excerpts = multi_buffer.excerpts_for_buffer(buffer.read(cx).remote_id(), cx);
+            let buffer_snapshot = buffer.read(cx);
+            for (anchor, label) in bookmark_store.bookmarks_for_buffer(&buffer, cx) {
+                let excerpt_id = excerpts.iter().find_map(|(excerpt_id, range)| {
+                    let context = &range.context;
+                    (context.start.cmp(&anchor, buffer_snapshot).is_le()
+                        && context.end.cmp(&anchor, buffer_snapshot).is_ge())
+                    .then_some(*excerpt_id)
+                });
+                if let Some(anchor) =
+                    excerpt_id.and_then(|excerpt_id| snapshot.anchor_in_excerpt(excerpt_id, anchor))
+                {
+                    bookmarks.push((anchor, label.map(|label| label.to_string().into())));
+                }
+            }
+        }
+        bookmarks.sort_by(|(a, _), (b, _)| a.cmp(b, &snapshot));
+        bookmarks
+    }
+
+    fn go_to_next_bookmark(
+        &mut self,
+        _: &GoToBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<Point>(cx).head();
+        let rows = self
+            .bookmarks(cx)
+            .into_iter()
+            .map(|(anchor, _)| anchor.to_point(&snapshot).row)
+            .collect::<Vec<_>>();
+        let row = rows
+            .iter()
+            .find(|row| **row > head.row)
+            .or_else(|| rows.first());
+        if let Some(row) = row {
+            self.go_to_bookmark_row(*row, window, cx);
+        }
+    }
+
+    fn go_to_prev_bookmark(
+        &mut self,
+        _: &GoToPrevBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<Point>(cx).head();
+        let rows = self
+            .bookmarks(cx)
+            .into_iter()
+            .map(|(anchor, _)| anchor.to_point(&snapshot).row)
+            .collect::<Vec<_>>();
+        let row = rows
+            .iter()
+            .rev()
+            .find(|row| **row < head.row)
+            .or_else(|| rows.last());
+        if let Some(row) = row {
+            self.go_to_bookmark_row(*row, window, cx);
+        }
+    }
+
+    fn go_to_bookmark_row(&mut self, row: u32, window: &mut Window, cx: &mut Context<Self>) {
+        let destination = Point::new(row, 0);
+        self.unfold_ranges(&[destination..destination], false, false, cx);
+        self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+            s.select_ranges(vec![destination..destination]);
+        });
+    }
+
+    fn render_bookmark_indicator(
+        &self,
+        row: DisplayRow,
+        position: Anchor,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> IconButton {
+        IconButton::new(
+            ("bookmark_indicator", row.0 as usize),
+            ui::IconName::Bookmark,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(Color::Accent)
+        .tooltip(move |window, cx| {
+            Tooltip::with_meta(
+                label.clone().unwrap_or_else(|| "Bookmark".into()),
+                None,
+                "Click to remove",
+                window,
+                cx,
+            )
+        })
+        .on_click(cx.listener(move |editor, _e, _, cx| {
+            editor.toggle_bookmarks_at([position], cx);
+        }))
+    }
+
     pub fn go_to_definition(
         &mut self,
         _: &GoToDefinition,
@@ -12687,6 +12960,11 @@ impl Editor {
         cx.notify();
     }
 
+    pub fn set_show_minimap(&mut self, show_minimap: bool, cx: &mut Context<Self>) {
+        self.show_minimap = show_minimap;
+        cx.notify();
+    }
+
     pub fn set_show_line_numbers(&mut self, show_line_numbers: bool, cx: &mut Context<Self>) {
         self.show_line_numbers = Some(show_line_numbers);
         cx.notify();
@@ -13482,31 +13760,33 @@ impl Editor {
         let mut results = Vec::new();
         for (color_fetcher, ranges) in self.background_highlights.values() {
             let color = color_fetcher(theme);
-            let start_ix = match ranges.binary_search_by(|probe| {
-                let cmp = probe
-                    .end
-                    .cmp(&search_range.start, &display_snapshot.buffer_snapshot);
-                if cmp.is_gt() {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                }
-            }) {
-                Ok(i) | Err(i) => i,
-            };
-            for range in &ranges[start_ix..] {
-                if range
-                    .start
-                    .cmp(&search_range.end, &display_snapshot.buffer_snapshot)
-                    .is_ge()
-                {
-                    break;
-                }
+            push_display_ranges_in_range(
+                ranges,
+                &search_range,
+                display_snapshot,
+                color,
+                &mut results,
+            );
+        }
+        results
+    }
 
-                let start = range.start.to_display_point(display_snapshot);
-                let end = range.end.to_display_point(display_snapshot);
-                results.push((start..end, color))
-            }
+    /// Like [`Editor::background_highlights_in_range`], but only returns the highlights of type `T`.
+    pub fn background_highlights_in_range_for<T: 'static>(
+        &self,
+        search_range: Range<Anchor>,
+        display_snapshot: &DisplaySnapshot,
+        theme: &ThemeColors,
+    ) -> Vec<(Range<DisplayPoint>, Hsla)> {
+        let mut results = Vec::new();
+        if let Some((color_fetcher, ranges)) = self.background_highlights.get(&TypeId::of::<T>()) {
+            push_display_ranges_in_range(
+                ranges,
+                &search_range,
+                display_snapshot,
+                color_fetcher(theme),
+                &mut results,
+            );
         }
         results
     }
@@ -13734,6 +14014,7 @@ impl Editor {
                 if self.has_active_inline_completion() {
                     self.update_visible_inline_completion(window, cx);
                 }
+                self.update_snippet_transformations(cx);
                 if let Some(buffer) = buffer_edited {
                     let buffer_id = buffer.read(cx).remote_id();
                     if !self.registered_buffers.contains_key(&buffer_id) {
@@ -14585,6 +14866,40 @@ fn get_uncommitted_diff_for_buffer(
     .detach();
 }
 
+fn push_display_ranges_in_range(
+    ranges: &[Range<Anchor>],
+    search_range: &Range<Anchor>,
+    display_snapshot: &DisplaySnapshot,
+    color: Hsla,
+    results: &mut Vec<(Range<DisplayPoint>, Hsla)>,
+) {
+    let start_ix = match ranges.binary_search_by(|probe| {
+        let cmp = probe
+            .end
+            .cmp(&search_range.start, &display_snapshot.buffer_snapshot);
+        if cmp.is_gt() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }) {
+        Ok(i) | Err(i) => i,
+    };
+    for range in &ranges[start_ix..] {
+        if range
+            .start
+            .cmp(&search_range.end, &display_snapshot.buffer_snapshot)
+            .is_ge()
+        {
+            break;
+        }
+
+        let start = range.start.to_display_point(display_snapshot);
+        let end = range.end.to_display_point(display_snapshot);
+        results.push((start..end, color))
+    }
+}
+
 fn char_len_with_expanded_tabs(offset: usize, text: &str, tab_size: NonZeroU32) -> usize {
     let tab_size = tab_size.get() as usize;
     let mut width = offset;
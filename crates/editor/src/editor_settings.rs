@@ -1,5 +1,6 @@
+use collections::HashMap;
 use gpui::App;
-use language::CursorShape;
+use language::{CursorShape, LanguageName};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -14,6 +15,8 @@ pub struct EditorSettings {
     pub hover_popover_delay: u64,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: MinimapSettings,
+    pub sticky_scroll: StickyScroll,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -110,6 +113,44 @@ pub struct Scrollbar {
     pub axes: ScrollbarAxes,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct MinimapSettings {
+    #[serde(flatten)]
+    defaults: Minimap,
+    #[serde(default)]
+    languages: HashMap<LanguageName, MinimapLanguageContent>,
+}
+
+impl MinimapSettings {
+    /// Returns the minimap settings for the given language, with its overrides applied.
+    pub fn for_language(&self, language: Option<&LanguageName>) -> Minimap {
+        let mut minimap = self.defaults;
+        if let Some(overrides) = language.and_then(|language| self.languages.get(language)) {
+            minimap.show = overrides.show.unwrap_or(minimap.show);
+            minimap.max_width_columns = overrides
+                .max_width_columns
+                .unwrap_or(minimap.max_width_columns);
+            minimap.search_results = overrides.search_results.unwrap_or(minimap.search_results);
+            minimap.diagnostics = overrides.diagnostics.unwrap_or(minimap.diagnostics);
+        }
+        minimap
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct Minimap {
+    pub show: bool,
+    pub max_width_columns: u32,
+    pub search_results: bool,
+    pub diagnostics: bool,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -290,6 +331,10 @@ pub struct EditorSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -441,6 +486,58 @@ pub struct ScrollbarAxesContent {
     vertical: Option<bool>,
 }
 
+/// Minimap related settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// Whether to show a minimap of the document next to the vertical scrollbar.
+    ///
+    /// Default: false
+    pub show: Option<bool>,
+    /// The maximum number of columns of each line to draw in the minimap.
+    ///
+    /// Default: 80
+    pub max_width_columns: Option<u32>,
+    /// Whether to highlight buffer search results in the minimap.
+    ///
+    /// Default: true
+    pub search_results: Option<bool>,
+    /// Whether to highlight diagnostics in the minimap.
+    ///
+    /// Default: true
+    pub diagnostics: Option<bool>,
+    /// Overrides of the minimap settings for specific languages.
+    ///
+    /// Default: {}
+    pub languages: Option<HashMap<LanguageName, MinimapLanguageContent>>,
+}
+
+/// Minimap settings that can be overridden for a specific language.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct MinimapLanguageContent {
+    /// Whether to show the minimap.
+    pub show: Option<bool>,
+    /// The maximum number of columns of each line to draw in the minimap.
+    pub max_width_columns: Option<u32>,
+    /// Whether to highlight buffer search results in the minimap.
+    pub search_results: Option<bool>,
+    /// Whether to highlight diagnostics in the minimap.
+    pub diagnostics: Option<bool>,
+}
+
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the first lines of the scopes enclosing the top of the
+    /// viewport, such as functions, classes and modules, to the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum number of nested scopes to pin.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
 /// Gutter related settings
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct GutterContent {
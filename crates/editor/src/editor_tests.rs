@@ -7071,6 +7071,87 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_transformations(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges(
+        indoc! {"
+            let ˇ;
+            let ˇ;
+        "},
+        false,
+    );
+
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|window, cx| build_editor(buffer, window, cx));
+
+    editor.update_in(cx, |editor, window, cx| {
+        let snippet = Snippet::parse("${1:name}: ${1/(.*)/${1:/pascalcase}/} = $0").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, window, cx)
+            .unwrap();
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let name: Name = ;
+                let name: Name = ;
+            "}
+        );
+
+        editor.finalize_last_transaction(cx);
+        editor.handle_input("foo_bar", window, cx);
+        // Keep the mirrors from being grouped with the edit of their tabstop by time alone.
+        editor.finalize_last_transaction(cx);
+    });
+    cx.run_until_parked();
+
+    editor.update_in(cx, |editor, window, cx| {
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let foo_bar: FooBar = ;
+                let foo_bar: FooBar = ;
+            "}
+        );
+
+        editor.finalize_last_transaction(cx);
+        assert!(editor.move_to_next_snippet_tabstop(window, cx));
+        editor.handle_input("1", window, cx);
+        editor.finalize_last_transaction(cx);
+    });
+    cx.run_until_parked();
+
+    editor.update_in(cx, |editor, window, cx| {
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let foo_bar: FooBar = 1;
+                let foo_bar: FooBar = 1;
+            "}
+        );
+
+        editor.undo(&Undo, window, cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let foo_bar: FooBar = ;
+                let foo_bar: FooBar = ;
+            "}
+        );
+
+        // the tabstop and its mirrors are undone in one step
+        editor.undo(&Undo, window, cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let name: Name = ;
+                let name: Name = ;
+            "}
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -11028,6 +11109,101 @@ async fn test_go_to_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppCon
     );
 }
 
+#[gpui::test]
+async fn test_bookmarks(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file(path!("/file.rs"), b"one\ntwo\nthree\nfour\nfive\n".to_vec())
+        .await;
+    let project = Project::test(fs, [path!("/file.rs").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/file.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let bookmark_store = project.read_with(cx, |project, _| project.bookmark_store().clone());
+    let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|window, cx| {
+        build_editor_with_project(project.clone(), buffer, window, cx)
+    });
+    let bookmark_rows = |editor: &Editor, cx: &App| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        editor
+            .bookmarks(cx)
+            .into_iter()
+            .map(|(anchor, label)| (anchor.to_point(&snapshot).row, label))
+            .collect::<Vec<_>>()
+    };
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([
+                Point::new(1, 1)..Point::new(1, 1),
+                Point::new(3, 0)..Point::new(3, 0),
+            ])
+        });
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+        assert_eq!(bookmark_rows(editor, cx), [(1, None), (3, None)]);
+
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([Point::new(0, 0)..Point::new(0, 0)])
+        });
+        editor.go_to_next_bookmark(&GoToBookmark, window, cx);
+        assert_eq!(
+            editor.selections.ranges::<Point>(cx),
+            [Point::new(1, 0)..Point::new(1, 0)]
+        );
+        editor.go_to_next_bookmark(&GoToBookmark, window, cx);
+        assert_eq!(
+            editor.selections.ranges::<Point>(cx),
+            [Point::new(3, 0)..Point::new(3, 0)]
+        );
+        // Navigation wraps around at either end.
+        editor.go_to_next_bookmark(&GoToBookmark, window, cx);
+        assert_eq!(
+            editor.selections.ranges::<Point>(cx),
+            [Point::new(1, 0)..Point::new(1, 0)]
+        );
+        editor.go_to_prev_bookmark(&GoToPrevBookmark, window, cx);
+        assert_eq!(
+            editor.selections.ranges::<Point>(cx),
+            [Point::new(3, 0)..Point::new(3, 0)]
+        );
+    });
+
+    // Bookmarks follow edits, and keep their labels.
+    bookmark_store.update(cx, |store, cx| {
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton().unwrap();
+        store.set_label(&buffer, 3, Some("four".into()), cx);
+    });
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([Point::new(0, 0)..Point::new(0, 0)])
+        });
+        editor.insert("zero\n", window, cx);
+    });
+    editor.update(cx, |editor, cx| {
+        assert_eq!(
+            bookmark_rows(editor, cx),
+            [(2, None), (4, Some("four".into()))]
+        );
+    });
+
+    // Toggling a bookmarked line removes its bookmark.
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([Point::new(2, 0)..Point::new(2, 0)])
+        });
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+        assert_eq!(bookmark_rows(editor, cx), [(4, Some("four".into()))]);
+
+        editor.clear_bookmarks(&ClearBookmarks, window, cx);
+        assert!(bookmark_rows(editor, cx).is_empty());
+    });
+}
+
 #[test]
 fn test_split_words() {
     fn split(text: &str) -> Vec<&str> {
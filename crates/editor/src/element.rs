@@ -5,8 +5,8 @@ use crate::{
         Block, BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ScrollbarDiagnostics, ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, Minimap, MultiCursorModifier,
+        ScrollBeyondLastLine, ScrollbarDiagnostics, ShowScrollbar,
     },
     git::blame::{CommitDetails, GitBlame},
     hover_popover::{
@@ -14,7 +14,7 @@ use crate::{
     },
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
-    scroll::{axis_pair, scroll_amount::ScrollAmount, AxisPair},
+    scroll::{axis_pair, scroll_amount::ScrollAmount, Autoscroll, AxisPair},
     BlockId, ChunkReplacement, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
     DocumentHighlightRead, DocumentHighlightWrite, EditDisplayMode, Editor, EditorMode,
     EditorSettings, EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GoToHunk,
@@ -335,6 +335,10 @@ impl EditorElement {
         register_action(editor, window, Editor::go_to_prev_diagnostic);
         register_action(editor, window, Editor::go_to_next_hunk);
         register_action(editor, window, Editor::go_to_prev_hunk);
+        register_action(editor, window, Editor::toggle_bookmark);
+        register_action(editor, window, Editor::clear_bookmarks);
+        register_action(editor, window, Editor::go_to_next_bookmark);
+        register_action(editor, window, Editor::go_to_prev_bookmark);
         register_action(editor, window, |editor, action, window, cx| {
             editor
                 .go_to_definition(action, window, cx)
@@ -1464,6 +1468,201 @@ impl EditorElement {
         axis_pair(horizontal_scrollbar, vertical_scrollbar)
     }
 
+    fn minimap_settings(&self, snapshot: &EditorSnapshot, cx: &App) -> Option<Minimap> {
+        if snapshot.mode != EditorMode::Full || !self.editor.read(cx).show_minimap {
+            return None;
+        }
+        let language = snapshot
+            .buffer_snapshot
+            .as_singleton()
+            .and_then(|(_, _, buffer)| buffer.language())
+            .map(|language| language.name());
+        let minimap = EditorSettings::get_global(cx)
+            .minimap
+            .for_language(language.as_ref());
+        (minimap.show && minimap.max_width_columns > 0).then_some(minimap)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        minimap: Minimap,
+        bounds: Bounds<Pixels>,
+        snapshot: &EditorSnapshot,
+        scroll_top: f32,
+        visible_rows: f32,
+        max_scroll_top: f32,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> MinimapLayout {
+        let line_height = MinimapLayout::LINE_HEIGHT;
+        let column_width = MinimapLayout::COLUMN_WIDTH;
+        let max_columns = minimap.max_width_columns;
+        let total_rows = snapshot.max_point().row().next_row().as_f32();
+        let minimap_rows = bounds.size.height / line_height;
+
+        // When the document is taller than the minimap, the minimap scrolls along with the
+        // editor so that its top and bottom line up with the top and bottom of the document.
+        let overflowing_rows = (total_rows - minimap_rows).max(0.);
+        let minimap_scroll_top = if max_scroll_top > 0. {
+            (scroll_top / max_scroll_top).clamp(0., 1.) * overflowing_rows
+        } else {
+            0.
+        };
+        let thumb_travel = (max_scroll_top - overflowing_rows).max(0.) * line_height;
+        let pixels_per_row = if max_scroll_top > 0. {
+            (thumb_travel / max_scroll_top).max(px(0.1))
+        } else {
+            line_height
+        };
+
+        let start_row = DisplayRow(minimap_scroll_top.floor() as u32);
+        let end_row = DisplayRow(
+            ((minimap_scroll_top + minimap_rows).ceil() as u32)
+                .min(snapshot.max_point().row().next_row().0),
+        );
+        let y_for_row =
+            |row: DisplayRow| bounds.top() + (row.as_f32() - minimap_scroll_top) * line_height;
+        let x_for_column =
+            |column: u32| bounds.left() + column.min(max_columns) as f32 * column_width;
+
+        let mut text_quads = Vec::new();
+        if start_row < end_row {
+            let default_color = self.style.text.color;
+            let mut row = start_row;
+            let mut column = 0;
+            let mut run: Option<(u32, u32, Hsla)> = None;
+            let mut flush_run = |run: &mut Option<(u32, u32, Hsla)>, row: DisplayRow| {
+                if let Some((start, end, color)) = run.take() {
+                    let top = y_for_row(row) + (line_height - MinimapLayout::TEXT_HEIGHT) / 2.;
+                    text_quads.push(fill(
+                        Bounds::from_corners(
+                            point(x_for_column(start), top),
+                            point(x_for_column(end), top + MinimapLayout::TEXT_HEIGHT),
+                        ),
+                        color,
+                    ));
+                }
+            };
+            for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+                let color = chunk
+                    .style
+                    .and_then(|style| style.color)
+                    .unwrap_or(default_color)
+                    .opacity(MinimapLayout::TEXT_OPACITY);
+                for character in chunk.text.chars() {
+                    if character == '\n' {
+                        flush_run(&mut run, row);
+                        row.0 += 1;
+                        column = 0;
+                        continue;
+                    }
+                    if column < max_columns && !character.is_whitespace() {
+                        match run.as_mut() {
+                            Some((_, end, run_color)) if *end == column && *run_color == color => {
+                                *end += 1;
+                            }
+                            _ => {
+                                flush_run(&mut run, row);
+                                run = Some((column, column + 1, color));
+                            }
+                        }
+                    }
+                    column += 1;
+                }
+            }
+            flush_run(&mut run, row);
+        }
+
+        let mut overlay_quads = Vec::new();
+        let mut push_overlay = |range: Range<DisplayPoint>, color: Hsla| {
+            let first_row = range.start.row().max(start_row);
+            let last_row = range.end.row().min(end_row.previous_row());
+            for row in first_row.0..=last_row.0 {
+                let row = DisplayRow(row);
+                let start_column = if row == range.start.row() {
+                    range.start.column()
+                } else {
+                    0
+                };
+                let end_column = if row == range.end.row() {
+                    range.end.column().max(start_column + 1)
+                } else {
+                    max_columns
+                };
+                let top = y_for_row(row);
+                overlay_quads.push(fill(
+                    Bounds::from_corners(
+                        point(x_for_column(start_column), top),
+                        point(x_for_column(end_column), top + line_height),
+                    ),
+                    color,
+                ));
+            }
+        };
+
+        if start_row < end_row {
+            let range_start = DisplayPoint::new(start_row, 0).to_point(snapshot);
+            let range_end = if end_row > snapshot.max_point().row() {
+                snapshot.buffer_snapshot.max_point()
+            } else {
+                DisplayPoint::new(end_row, 0).to_point(snapshot)
+            };
+            if minimap.search_results {
+                let start_anchor = snapshot.buffer_snapshot.anchor_before(range_start);
+                let end_anchor = snapshot.buffer_snapshot.anchor_after(range_end);
+                let search_highlights = self
+                    .editor
+                    .read(cx)
+                    .background_highlights_in_range_for::<BufferSearchHighlights>(
+                        start_anchor..end_anchor,
+                        &snapshot.display_snapshot,
+                        cx.theme().colors(),
+                    );
+                for (range, color) in search_highlights {
+                    push_overlay(range, color);
+                }
+            }
+            if minimap.diagnostics {
+                let diagnostics = snapshot
+                    .buffer_snapshot
+                    .diagnostics_in_range::<Point>(range_start..range_end)
+                    // Paint the most severe diagnostics last.
+                    .sorted_by_key(|diagnostic| std::cmp::Reverse(diagnostic.diagnostic.severity));
+                for diagnostic in diagnostics {
+                    let color =
+                        super::diagnostic_style(diagnostic.diagnostic.severity, &self.style.status);
+                    let start = diagnostic
+                        .range
+                        .start
+                        .to_display_point(&snapshot.display_snapshot);
+                    let end = diagnostic
+                        .range
+                        .end
+                        .to_display_point(&snapshot.display_snapshot);
+                    push_overlay(start..end, color.opacity(MinimapLayout::DIAGNOSTIC_OPACITY));
+                }
+            }
+        }
+
+        let thumb_top = bounds.top() + (scroll_top - minimap_scroll_top) * line_height;
+        let thumb_bounds = Bounds::from_corners(
+            point(bounds.left(), thumb_top),
+            point(bounds.right(), thumb_top + visible_rows * line_height),
+        );
+
+        MinimapLayout {
+            hitbox: window.insert_hitbox(bounds, false),
+            thumb_bounds,
+            scroll_top: minimap_scroll_top,
+            visible_rows,
+            max_scroll_top,
+            pixels_per_row,
+            text_quads,
+            overlay_quads,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_crease_toggles(
         &self,
@@ -1982,6 +2181,64 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_bookmark_indicators(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        code_actions_row: Option<DisplayRow>,
+        snapshot: &EditorSnapshot,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            let mut occupied_rows = HashSet::from_iter(code_actions_row);
+            editor
+                .bookmarks(cx)
+                .into_iter()
+                .filter_map(|(position, label)| {
+                    let multibuffer_point = position.to_point(&snapshot.buffer_snapshot);
+                    let display_row = multibuffer_point.to_display_point(snapshot).row();
+                    if !range.contains(&display_row) || !occupied_rows.insert(display_row) {
+                        return None;
+                    }
+
+                    // Run indicators take precedence over bookmarks, and folded buffers
+                    // don't show any of their lines.
+                    let (buffer_snapshot, line_range) = snapshot
+                        .buffer_snapshot
+                        .buffer_line_for_row(MultiBufferRow(multibuffer_point.row))?;
+                    let buffer_id = buffer_snapshot.remote_id();
+                    if editor
+                        .tasks
+                        .contains_key(&(buffer_id, line_range.start.row))
+                        || editor.is_buffer_folded(buffer_id, cx)
+                    {
+                        return None;
+                    }
+
+                    let button = editor.render_bookmark_indicator(display_row, position, label, cx);
+                    let button = prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        window,
+                        cx,
+                    );
+                    Some(button)
+                })
+                .collect_vec()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_code_actions_indicator(
         &self,
@@ -3086,6 +3343,87 @@ impl EditorElement {
         header
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_scopes(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_position: gpui::Point<f32>,
+        line_height: Pixels,
+        gutter_hitbox: &Hitbox,
+        text_hitbox: &Hitbox,
+        editor_width: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<StickyScopeLayout> {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled
+            || settings.max_depth == 0
+            || snapshot.mode != EditorMode::Full
+            || snapshot.buffer_snapshot.as_singleton().is_none()
+        {
+            return Vec::new();
+        }
+
+        let scroll_top = scroll_position.y;
+        let top_point = DisplayPoint::new(DisplayRow(scroll_top as u32), 0).to_point(snapshot);
+        let items = self.editor.update(cx, |editor, _| {
+            editor.scopes_containing_row(top_point.row, &snapshot.buffer_snapshot)
+        });
+
+        let mut scopes = Vec::new();
+        for item in items.iter() {
+            if scopes.len() == settings.max_depth {
+                break;
+            }
+            let slot = scopes.len() as f32;
+            let start_row = item.range.start.to_display_point(snapshot).row();
+            let end_row = item.range.end.to_display_point(snapshot).row();
+            // The first line of the scope is still visible below the pinned lines.
+            if start_row.as_f32() >= scroll_top + slot {
+                break;
+            }
+            // Push the pinned line up as the last line of its scope scrolls past it.
+            let top = (end_row.as_f32() - scroll_top).min(slot);
+            if top <= slot - 1. {
+                break;
+            }
+            scopes.push((start_row, top, item.range.start));
+            if top < slot {
+                break;
+            }
+        }
+
+        scopes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(depth, (row, top, anchor))| {
+                let line = Self::layout_lines(
+                    row..row.next_row(),
+                    snapshot,
+                    &self.style,
+                    editor_width,
+                    |_| false,
+                    window,
+                    cx,
+                )
+                .pop()?;
+                let bounds = Bounds::from_corners(
+                    point(gutter_hitbox.left(), text_hitbox.top() + top * line_height),
+                    point(
+                        text_hitbox.right(),
+                        text_hitbox.top() + (top + 1.) * line_height,
+                    ),
+                );
+                Some(StickyScopeLayout {
+                    line,
+                    hitbox: window.insert_hitbox(bounds, true),
+                    depth,
+                    anchor,
+                })
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_cursor_popovers(
         &self,
@@ -4614,6 +4952,10 @@ impl EditorElement {
                 }
             });
 
+            for bookmark_indicator in layout.bookmark_indicators.iter_mut() {
+                bookmark_indicator.paint(window, cx);
+            }
+
             for test_indicator in layout.test_indicators.iter_mut() {
                 test_indicator.paint(window, cx);
             }
@@ -5138,6 +5480,213 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_scopes(&mut self, layout: &EditorLayout, window: &mut Window, cx: &mut App) {
+        let Some(last_scope) = layout.sticky_scopes.last() else {
+            return;
+        };
+        let colors = cx.theme().colors();
+        let gutter_background = colors.editor_gutter_background;
+        let background = colors.editor_background;
+        let border = colors.border_variant;
+        let line_height = layout.position_map.line_height;
+        let text_bounds = layout.position_map.text_hitbox.bounds;
+        let bounds = Bounds::from_corners(
+            layout.hitbox.origin,
+            point(text_bounds.right(), last_scope.hitbox.bottom()),
+        );
+
+        window.paint_layer(bounds, |window| {
+            // Paint the innermost scopes first, so that lines that are pushed up slide
+            // underneath the lines of the scopes enclosing them.
+            for scope in layout.sticky_scopes.iter().rev() {
+                let gutter_bounds = Bounds::from_corners(
+                    scope.hitbox.origin,
+                    point(text_bounds.left(), scope.hitbox.bottom()),
+                );
+                let line_bounds = Bounds::from_corners(
+                    point(text_bounds.left(), scope.hitbox.top()),
+                    scope.hitbox.bottom_right(),
+                );
+                window.paint_quad(fill(gutter_bounds, gutter_background));
+                window.paint_quad(fill(line_bounds, background));
+
+                window.with_content_mask(
+                    Some(ContentMask {
+                        bounds: line_bounds,
+                    }),
+                    |window| {
+                        let mut fragment_origin = point(
+                            layout.content_origin.x - layout.position_map.scroll_pixel_position.x,
+                            scope.hitbox.top(),
+                        );
+                        for fragment in &scope.line.fragments {
+                            match fragment {
+                                LineFragment::Text(line) => {
+                                    line.paint(fragment_origin, line_height, window, cx)
+                                        .log_err();
+                                    fragment_origin.x += line.width;
+                                }
+                                LineFragment::Element { size, .. } => {
+                                    fragment_origin.x += size.width;
+                                }
+                            }
+                        }
+                    },
+                );
+            }
+
+            window.paint_quad(fill(
+                Bounds::from_corners(
+                    point(bounds.left(), bounds.bottom()),
+                    point(bounds.right(), bounds.bottom() + px(1.)),
+                ),
+                border,
+            ));
+        });
+
+        for scope in &layout.sticky_scopes {
+            let hitbox = scope.hitbox.clone();
+            let anchor = scope.anchor;
+            let depth = scope.depth;
+            window.set_cursor_style(CursorStyle::PointingHand, &hitbox);
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseDownEvent, phase, window, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(window)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        // Jump to the first line of the scope, keeping it where it was pinned.
+                        editor.change_selections(
+                            Some(Autoscroll::top_relative(depth)),
+                            window,
+                            cx,
+                            |selections| selections.select_anchor_ranges([anchor..anchor]),
+                        );
+                        window.focus(&editor.focus_handle(cx));
+                    });
+                    cx.stop_propagation();
+                }
+            });
+        }
+    }
+
+    fn paint_minimap(&mut self, layout: &EditorLayout, window: &mut Window, cx: &mut App) {
+        let Some(minimap) = layout.minimap.as_ref() else {
+            return;
+        };
+        let hitbox = minimap.hitbox.clone();
+        let thumb_bounds = minimap.thumb_bounds;
+        let minimap_scroll_top = minimap.scroll_top;
+        let visible_rows = minimap.visible_rows;
+        let max_scroll_top = minimap.max_scroll_top;
+        let pixels_per_row = minimap.pixels_per_row;
+        let is_dragging = self.editor.read(cx).scroll_manager.is_dragging_minimap();
+
+        window.paint_layer(hitbox.bounds, |window| {
+            let colors = cx.theme().colors();
+            window.paint_quad(quad(
+                hitbox.bounds,
+                Corners::default(),
+                colors.editor_background,
+                Edges {
+                    top: Pixels::ZERO,
+                    right: Pixels::ZERO,
+                    bottom: Pixels::ZERO,
+                    left: ScrollbarLayout::BORDER_WIDTH,
+                },
+                colors.scrollbar_track_border,
+            ));
+            for marker in minimap.overlay_quads.iter().chain(&minimap.text_quads) {
+                window.paint_quad(marker.clone());
+            }
+            let thumb_color = if is_dragging || hitbox.is_hovered(window) {
+                colors.scrollbar_thumb_hover_background
+            } else {
+                colors.scrollbar_thumb_background
+            };
+            window.paint_quad(fill(thumb_bounds, thumb_color));
+        });
+
+        window.set_cursor_style(CursorStyle::Arrow, &hitbox);
+
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            let mut mouse_position = window.mouse_position();
+            move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if !editor.scroll_manager.is_dragging_minimap() {
+                        return;
+                    }
+                    if event.pressed_button == Some(MouseButton::Left) {
+                        let mut position = editor.scroll_position(cx);
+                        position.y = (position.y
+                            + (event.position.y - mouse_position.y) / pixels_per_row)
+                            .clamp(0., max_scroll_top);
+                        editor.set_scroll_position(position, window, cx);
+                        cx.stop_propagation();
+                    } else {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                    }
+                });
+                mouse_position = event.position;
+            }
+        });
+
+        if is_dragging {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseDownEvent, phase, window, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(window)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        // Clicking outside of the thumb centers the clicked row in the editor,
+                        // while clicking on the thumb only starts dragging it.
+                        let y = event.position.y;
+                        if y < thumb_bounds.top() || thumb_bounds.bottom() < y {
+                            let mut position = editor.scroll_position(cx);
+                            let row = minimap_scroll_top
+                                + (y - hitbox.top()) / MinimapLayout::LINE_HEIGHT;
+                            position.y = (row - visible_rows / 2.).clamp(0., max_scroll_top);
+                            editor.set_scroll_position(position, window, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -6728,7 +7277,12 @@ impl Element for EditorElement {
                             cx,
                         )
                         .unwrap_or_default();
-                    let text_width = bounds.size.width - gutter_dimensions.width;
+                    let minimap = self.minimap_settings(&snapshot, cx);
+                    let minimap_width = minimap.map_or(Pixels::ZERO, |minimap| {
+                        (MinimapLayout::COLUMN_WIDTH * minimap.max_width_columns as f32)
+                            .min((bounds.size.width - gutter_dimensions.width) / 4.)
+                    });
+                    let text_width = bounds.size.width - gutter_dimensions.width - minimap_width;
 
                     let editor_width = text_width - gutter_dimensions.margin - em_width;
 
@@ -6782,7 +7336,7 @@ impl Element for EditorElement {
                         text_hitbox.origin + point(gutter_dimensions.margin, Pixels::ZERO);
 
                     let scrollbar_bounds =
-                        Bounds::from_corners(content_origin, bounds.bottom_right());
+                        Bounds::from_corners(content_origin, text_hitbox.bottom_right());
 
                     let height_in_lines = scrollbar_bounds.size.height / line_height;
 
@@ -7278,6 +7832,30 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let sticky_scopes = self.layout_sticky_scopes(
+                        &snapshot,
+                        scroll_position,
+                        line_height,
+                        &gutter_hitbox,
+                        &text_hitbox,
+                        editor_width,
+                        window,
+                        cx,
+                    );
+
+                    let minimap = minimap.map(|minimap| {
+                        self.layout_minimap(
+                            minimap,
+                            Bounds::from_corners(text_hitbox.top_right(), bounds.bottom_right()),
+                            &snapshot,
+                            scroll_position.y,
+                            height_in_lines,
+                            max_scroll_top,
+                            window,
+                            cx,
+                        )
+                    });
+
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
                     let rows_with_hunk_bounds = display_hunks
@@ -7387,6 +7965,21 @@ impl Element for EditorElement {
                         Vec::new()
                     };
 
+                    let bookmark_indicators = self.layout_bookmark_indicators(
+                        line_height,
+                        start_row..end_row,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        newest_selection_head
+                            .filter(|_| code_actions_indicator.is_some())
+                            .map(|head| head.row()),
+                        &snapshot,
+                        window,
+                        cx,
+                    );
+
                     self.layout_signature_help(
                         &hitbox,
                         content_origin,
@@ -7529,6 +8122,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbars_layout,
+                        minimap,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -7546,12 +8140,14 @@ impl Element for EditorElement {
                         diff_hunk_controls: hunk_controls,
                         mouse_context_menu,
                         test_indicators,
+                        bookmark_indicators,
                         code_actions_indicator,
                         crease_toggles,
                         crease_trailers,
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scopes,
                     }
                 })
             })
@@ -7618,6 +8214,9 @@ impl Element for EditorElement {
                         }
                     });
 
+                    self.paint_sticky_scopes(layout, window, cx);
+
+                    self.paint_minimap(layout, window, cx);
                     self.paint_scrollbars(layout, window, cx);
                     self.paint_inline_completion_popover(layout, window, cx);
                     self.paint_mouse_context_menu(layout, window, cx);
@@ -7699,6 +8298,7 @@ pub struct EditorLayout {
     gutter_hitbox: Hitbox,
     content_origin: gpui::Point<Pixels>,
     scrollbars_layout: AxisPair<Option<ScrollbarLayout>>,
+    minimap: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -7719,6 +8319,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    bookmark_indicators: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     diff_hunk_controls: Vec<AnyElement>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
@@ -7727,6 +8328,7 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scopes: Vec<StickyScopeLayout>,
 }
 
 impl EditorLayout {
@@ -7870,6 +8472,38 @@ impl ScrollbarLayout {
     }
 }
 
+struct MinimapLayout {
+    hitbox: Hitbox,
+    /// The part of the minimap showing the rows that are visible in the editor.
+    thumb_bounds: Bounds<Pixels>,
+    /// The display row at the top of the minimap, which scrolls along with the editor.
+    scroll_top: f32,
+    visible_rows: f32,
+    max_scroll_top: f32,
+    /// How far the thumb moves when the editor scrolls by a single row.
+    pixels_per_row: Pixels,
+    text_quads: Vec<PaintQuad>,
+    overlay_quads: Vec<PaintQuad>,
+}
+
+impl MinimapLayout {
+    const LINE_HEIGHT: Pixels = px(3.0);
+    const TEXT_HEIGHT: Pixels = px(2.0);
+    const COLUMN_WIDTH: Pixels = px(1.5);
+    const TEXT_OPACITY: f32 = 0.6;
+    const DIAGNOSTIC_OPACITY: f32 = 0.4;
+}
+
+/// The first line of a scope enclosing the top of the viewport, pinned to the top of the editor.
+struct StickyScopeLayout {
+    line: LineWithInvisibles,
+    hitbox: Hitbox,
+    /// How many pinned lines are above this one.
+    depth: usize,
+    /// The start of the scope, which clicking the pinned line jumps to.
+    anchor: Anchor,
+}
+
 struct CreaseTrailerLayout {
     element: AnyElement,
     bounds: Bounds<Pixels>,
@@ -8338,12 +8972,14 @@ mod tests {
     use super::*;
     use crate::{
         display_map::{BlockPlacement, BlockProperties},
+        editor_settings::{MinimapContent, StickyScrollContent},
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use language::{language_settings, Buffer, Language, LanguageConfig};
     use log::info;
+    use settings::SettingsStore;
     use similar::DiffableStr;
     use std::num::NonZeroU32;
     use util::test::sample_text;
@@ -8602,6 +9238,192 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.minimap = Some(MinimapContent {
+                        show: Some(true),
+                        max_width_columns: Some(40),
+                        ..Default::default()
+                    });
+                });
+            });
+        });
+
+        let window = cx.add_window(|window, cx| {
+            let buffer = MultiBuffer::build_simple(&"let x = 1;\n".repeat(1000), cx);
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style.clone()),
+        );
+        let minimap = state.minimap.as_ref().unwrap();
+        assert_eq!(minimap.hitbox.size.width, MinimapLayout::COLUMN_WIDTH * 40.);
+        assert_eq!(
+            minimap.hitbox.left(),
+            state.position_map.text_hitbox.right()
+        );
+        assert_eq!(minimap.scroll_top, 0.);
+        assert_eq!(minimap.thumb_bounds.top(), minimap.hitbox.top());
+        // One run of text per word on each row that fits in the minimap.
+        let minimap_rows = (px(500.) / MinimapLayout::LINE_HEIGHT).ceil() as usize;
+        assert_eq!(minimap.text_quads.len(), minimap_rows * 4);
+
+        // Scrolled to the bottom, the minimap shows the end of the document.
+        window
+            .update(cx, |editor, window, cx| {
+                editor.set_scroll_position(point(0., 1000.), window, cx);
+            })
+            .unwrap();
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style.clone()),
+        );
+        let minimap = state.minimap.as_ref().unwrap();
+        let total_rows = 1001.;
+        assert_eq!(
+            minimap.scroll_top,
+            total_rows - px(500.) / MinimapLayout::LINE_HEIGHT
+        );
+        assert!(minimap.thumb_bounds.top() > minimap.hitbox.top());
+
+        window
+            .update(cx, |editor, _, cx| editor.set_show_minimap(false, cx))
+            .unwrap();
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style.clone()),
+        );
+        assert!(state.minimap.is_none());
+        assert_eq!(state.position_map.text_hitbox.right(), px(1000.));
+    }
+
+    #[gpui::test]
+    async fn test_sticky_scopes(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"
+                (impl_item "impl" @context type: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = format!(
+            "impl Foo {{\n    const X: u32 = 1;\n\n    fn bar() {{\n{}    }}\n}}\n",
+            "        baz();\n".repeat(100)
+        );
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language, cx));
+        cx.condition(&buffer, |buffer, _| !buffer.is_parsing())
+            .await;
+
+        let window = cx.add_window(|window, cx| {
+            let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+        cx.update(|_, cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        ..Default::default()
+                    });
+                });
+            });
+        });
+
+        let pinned_lines = |scroll_top: f32, cx: &mut VisualTestContext| {
+            window
+                .update(cx, |editor, window, cx| {
+                    editor.set_scroll_position(point(0., scroll_top), window, cx);
+                })
+                .unwrap();
+            let (_, state) = cx.draw(
+                point(px(500.), px(500.)),
+                size(px(500.), px(500.)),
+                |_, _| EditorElement::new(&editor, style.clone()),
+            );
+            state
+                .sticky_scopes
+                .iter()
+                .map(|scope| {
+                    let text = scope
+                        .line
+                        .fragments
+                        .iter()
+                        .filter_map(|fragment| match fragment {
+                            LineFragment::Text(line) => Some(line.text.to_string()),
+                            LineFragment::Element { .. } => None,
+                        })
+                        .collect::<String>();
+                    (scope.depth, text)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pinned_lines(0., cx), Vec::new());
+        assert_eq!(pinned_lines(1., cx), vec![(0, "impl Foo {".to_string())]);
+        assert_eq!(
+            pinned_lines(50., cx),
+            vec![
+                (0, "impl Foo {".to_string()),
+                (1, "    fn bar() {".to_string())
+            ]
+        );
+
+        // Autoscrolling up to a line leaves room for the lines pinned above it.
+        window
+            .update(cx, |editor, window, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |selections| {
+                    selections.select_ranges([Point::new(51, 0)..Point::new(51, 0)])
+                });
+            })
+            .unwrap();
+        cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style.clone()),
+        );
+        let scroll_top =
+            cx.update(|_, cx| editor.update(cx, |editor, cx| editor.scroll_position(cx).y));
+        assert_eq!(scroll_top, 46.);
+
+        cx.update(|_, cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        max_depth: Some(1),
+                    });
+                });
+            });
+        });
+        assert_eq!(pinned_lines(50., cx), vec![(0, "impl Foo {".to_string())]);
+    }
+
     #[gpui::test]
     fn test_layout_with_placeholder_text_and_blocks(cx: &mut TestAppContext) {
         init_test(cx, |_| {});
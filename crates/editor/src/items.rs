@@ -1336,6 +1336,7 @@ impl SearchableItem for Editor {
                 replacement: false,
                 selection: false,
                 find_in_results: true,
+                syntax_scope: false,
             }
         } else {
             SearchOptions {
@@ -1345,6 +1346,7 @@ impl SearchableItem for Editor {
                 replacement: true,
                 selection: true,
                 find_in_results: false,
+                syntax_scope: true,
             }
         }
     }
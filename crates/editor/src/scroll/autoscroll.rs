@@ -171,10 +171,14 @@ impl Editor {
             }
         };
 
+        // Lines pinned by sticky scroll cover the top of the viewport.
+        let sticky_lines =
+            self.sticky_line_count(DisplayRow(target_top as u32), &display_map, cx) as f32;
+
         match strategy {
             AutoscrollStrategy::Fit | AutoscrollStrategy::Newest => {
                 let margin = margin.min(self.scroll_manager.vertical_scroll_margin);
-                let target_top = (target_top - margin).max(0.0);
+                let target_top = (target_top - margin - sticky_lines).max(0.0);
                 let target_bottom = target_bottom + margin;
                 let start_row = scroll_position.y;
                 let end_row = start_row + visible_lines;
@@ -197,7 +201,7 @@ impl Editor {
             }
             AutoscrollStrategy::Focused => {
                 let margin = margin.min(self.scroll_manager.vertical_scroll_margin);
-                scroll_position.y = (target_top - margin).max(0.0);
+                scroll_position.y = (target_top - margin - sticky_lines).max(0.0);
                 self.set_scroll_position_internal(scroll_position, local, true, window, cx);
             }
             AutoscrollStrategy::Top => {
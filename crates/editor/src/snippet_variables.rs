@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use gpui::App;
+use language::ToPoint as _;
+use multi_buffer::MultiBufferRow;
+use rand::Rng as _;
+
+use crate::Editor;
+
+/// The values of the variables snippets can refer to, like `$TM_FILENAME` or `$CURRENT_YEAR`,
+/// captured when a snippet is about to be inserted.
+///
+/// See https://code.visualstudio.com/docs/editor/userdefinedsnippets#_variables
+pub(crate) struct SnippetVariables {
+    selected_text: String,
+    current_line: String,
+    current_word: String,
+    line_index: u32,
+    abs_path: Option<PathBuf>,
+    relative_path: Option<PathBuf>,
+    worktree_abs_path: Option<PathBuf>,
+    clipboard: Option<String>,
+    line_comment: Option<String>,
+    block_comment: Option<(String, String)>,
+    now: DateTime<Local>,
+}
+
+impl SnippetVariables {
+    pub(crate) fn new(editor: &Editor, cx: &mut App) -> Self {
+        let selection = editor.selections.newest::<usize>(cx);
+        let multi_buffer = editor.buffer.read(cx);
+        let snapshot = multi_buffer.snapshot(cx);
+
+        let selected_text = snapshot
+            .text_for_range(selection.range())
+            .collect::<String>();
+        let head = snapshot.offset_to_point(selection.head());
+        let line = MultiBufferRow(head.row);
+        let current_line = snapshot
+            .text_for_range(
+                language::Point::new(head.row, 0)
+                    ..language::Point::new(head.row, snapshot.line_len(line)),
+            )
+            .collect::<String>();
+        let (word_range, _) = snapshot.surrounding_word(selection.head(), false);
+        let current_word = snapshot.text_for_range(word_range).collect::<String>();
+
+        let (line_comment, block_comment) = snapshot
+            .language_scope_at(selection.head())
+            .map(|scope| {
+                let line_comment = scope
+                    .line_comment_prefixes()
+                    .first()
+                    .map(|prefix| prefix.trim_end().to_string());
+                let block_comment = scope.block_comment_delimiters().map(|(start, end)| {
+                    (start.trim_end().to_string(), end.trim_start().to_string())
+                });
+                (line_comment, block_comment)
+            })
+            .unwrap_or_default();
+
+        let mut line_index = head.row;
+        let mut abs_path = None;
+        let mut relative_path = None;
+        let mut worktree_abs_path = None;
+        if let Some((buffer, offset)) = multi_buffer.point_to_buffer_offset(selection.head(), cx) {
+            let buffer = buffer.read(cx);
+            line_index = offset.to_point(buffer).row;
+            if let Some(file) = buffer.file() {
+                relative_path = Some(file.path().to_path_buf());
+                if let Some(local) = file.as_local() {
+                    let path = local.abs_path(cx);
+                    worktree_abs_path = path
+                        .ancestors()
+                        .nth(file.path().components().count())
+                        .map(|root| root.to_path_buf());
+                    abs_path = Some(path);
+                }
+            }
+        }
+
+        Self {
+            selected_text,
+            current_line,
+            current_word,
+            line_index,
+            abs_path,
+            relative_path,
+            worktree_abs_path,
+            clipboard: cx.read_from_clipboard().and_then(|item| item.text()),
+            line_comment,
+            block_comment,
+            now: Local::now(),
+        }
+    }
+
+    pub(crate) fn resolve(&self, name: &str) -> Option<String> {
+        let path_string = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+        };
+        let file_name = || {
+            self.relative_path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+        };
+        let date = |format: &str| Some(self.now.format(format).to_string());
+
+        match name {
+            "TM_SELECTED_TEXT" => Some(self.selected_text.clone()),
+            "TM_CURRENT_LINE" => Some(self.current_line.clone()),
+            "TM_CURRENT_WORD" => Some(self.current_word.clone()),
+            "TM_LINE_INDEX" => Some(self.line_index.to_string()),
+            "TM_LINE_NUMBER" => Some((self.line_index + 1).to_string()),
+            "TM_FILENAME" => file_name(),
+            "TM_FILENAME_BASE" => self
+                .relative_path
+                .as_ref()
+                .and_then(|path| path.file_stem())
+                .map(|stem| stem.to_string_lossy().into_owned()),
+            "TM_DIRECTORY" => self
+                .abs_path
+                .as_ref()
+                .and_then(|path| path.parent())
+                .map(|path| path.to_string_lossy().into_owned()),
+            "TM_FILEPATH" => path_string(&self.abs_path),
+            "RELATIVE_FILEPATH" => path_string(&self.relative_path),
+            "WORKSPACE_NAME" => self
+                .worktree_abs_path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned()),
+            "WORKSPACE_FOLDER" => path_string(&self.worktree_abs_path),
+            "CLIPBOARD" => self.clipboard.clone(),
+            // The same text is inserted at every cursor.
+            "CURSOR_INDEX" => Some("0".to_string()),
+            "CURSOR_NUMBER" => Some("1".to_string()),
+            "CURRENT_YEAR" => date("%Y"),
+            "CURRENT_YEAR_SHORT" => date("%y"),
+            "CURRENT_MONTH" => date("%m"),
+            "CURRENT_MONTH_NAME" => date("%B"),
+            "CURRENT_MONTH_NAME_SHORT" => date("%b"),
+            "CURRENT_DATE" => date("%d"),
+            "CURRENT_DAY_NAME" => date("%A"),
+            "CURRENT_DAY_NAME_SHORT" => date("%a"),
+            "CURRENT_HOUR" => date("%H"),
+            "CURRENT_MINUTE" => date("%M"),
+            "CURRENT_SECOND" => date("%S"),
+            "CURRENT_SECONDS_UNIX" => Some(self.now.timestamp().to_string()),
+            "CURRENT_TIMEZONE_OFFSET" => date("%:z"),
+            "RANDOM" => Some(format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))),
+            "RANDOM_HEX" => Some(format!(
+                "{:06x}",
+                rand::thread_rng().gen_range(0..0x1000000)
+            )),
+            "UUID" => Some(uuid::Uuid::new_v4().to_string()),
+            "LINE_COMMENT" => self.line_comment.clone(),
+            "BLOCK_COMMENT_START" => self.block_comment.as_ref().map(|(start, _)| start.clone()),
+            "BLOCK_COMMENT_END" => self.block_comment.as_ref().map(|(_, end)| end.clone()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use gpui::App;
+use language::OutlineItem;
+use multi_buffer::{Anchor, MultiBufferSnapshot};
+use settings::Settings;
+use text::Point;
+
+use crate::{
+    display_map::ToDisplayPoint, DisplayPoint, DisplayRow, DisplaySnapshot, Editor, EditorMode,
+    EditorSettings,
+};
+
+/// The scopes enclosing the row at the top of the viewport. They are kept between frames, so
+/// that they are only looked up again once the editor scrolls to another row or the buffer
+/// changes.
+#[derive(Default)]
+pub struct StickyScopesCache {
+    row: u32,
+    edit_count: usize,
+    non_text_state_update_count: usize,
+    scopes: Option<Arc<[OutlineItem<Anchor>]>>,
+}
+
+impl Editor {
+    /// Returns the scopes enclosing the start of the given buffer row, outermost first.
+    pub(crate) fn scopes_containing_row(
+        &mut self,
+        row: u32,
+        buffer: &MultiBufferSnapshot,
+    ) -> Arc<[OutlineItem<Anchor>]> {
+        let cache = &mut self.sticky_scopes_cache;
+        if cache.row == row
+            && cache.edit_count == buffer.edit_count()
+            && cache.non_text_state_update_count == buffer.non_text_state_update_count()
+        {
+            if let Some(scopes) = &cache.scopes {
+                return scopes.clone();
+            }
+        }
+
+        let scopes: Arc<[_]> = buffer
+            .symbols_containing(Point::new(row, 0), None)
+            .map(|(_, items)| items.into())
+            .unwrap_or_default();
+        *cache = StickyScopesCache {
+            row,
+            edit_count: buffer.edit_count(),
+            non_text_state_update_count: buffer.non_text_state_update_count(),
+            scopes: Some(scopes.clone()),
+        };
+        scopes
+    }
+
+    /// Returns how many lines sticky scroll pins over the viewport when the given row is
+    /// scrolled near its top, which autoscroll has to leave room for.
+    pub(crate) fn sticky_line_count(
+        &mut self,
+        row: DisplayRow,
+        display_map: &DisplaySnapshot,
+        cx: &App,
+    ) -> usize {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled
+            || self.mode != EditorMode::Full
+            || display_map.buffer_snapshot.as_singleton().is_none()
+        {
+            return 0;
+        }
+        let buffer_row = DisplayPoint::new(row, 0).to_point(display_map).row;
+        self.scopes_containing_row(buffer_row, &display_map.buffer_snapshot)
+            .iter()
+            .take(settings.max_depth)
+            .take_while(|scope| scope.range.start.to_display_point(display_map).row() < row)
+            .count()
+    }
+}
@@ -0,0 +1,326 @@
+mod active_buffer_encoding;
+
+pub use active_buffer_encoding::ActiveBufferEncoding;
+use anyhow::anyhow;
+use editor::Editor;
+use fs::Encoding;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    ParentElement, Render, Styled, Task, WeakEntity, Window,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::NotifyTaskExt, ModalView, Workspace};
+
+actions!(
+    encoding_selector,
+    [Toggle, ReopenWithEncoding, SaveWithEncoding]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(EncodingSelector::register).detach();
+}
+
+/// What to do with the encoding picked for the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EncodingAction {
+    /// Decode the file on disk again with the new encoding.
+    Reopen,
+    /// Write the buffer to disk in the new encoding.
+    Save,
+}
+
+impl EncodingAction {
+    const ALL: [Self; 2] = [Self::Reopen, Self::Save];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Reopen => "Reopen with Encoding",
+            Self::Save => "Save with Encoding",
+        }
+    }
+}
+
+pub struct EncodingSelector {
+    picker: Entity<Picker<EncodingSelectorDelegate>>,
+}
+
+impl EncodingSelector {
+    fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(move |workspace, _: &Toggle, window, cx| {
+            Self::toggle(workspace, None, window, cx);
+        });
+        workspace.register_action(move |workspace, _: &ReopenWithEncoding, window, cx| {
+            Self::toggle(workspace, Some(EncodingAction::Reopen), window, cx);
+        });
+        workspace.register_action(move |workspace, _: &SaveWithEncoding, window, cx| {
+            Self::toggle(workspace, Some(EncodingAction::Save), window, cx);
+        });
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        action: Option<EncodingAction>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Option<()> {
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+        buffer.read(cx).file()?;
+        let project = workspace.project().clone();
+
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            EncodingSelector::new(buffer, project, action, window, cx)
+        });
+        Some(())
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        action: Option<EncodingAction>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            EncodingSelectorDelegate::new(cx.entity().downgrade(), buffer, project, action);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for EncodingSelector {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for EncodingSelector {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for EncodingSelector {}
+impl ModalView for EncodingSelector {}
+
+pub struct EncodingSelectorDelegate {
+    encoding_selector: WeakEntity<EncodingSelector>,
+    buffer: Entity<Buffer>,
+    project: Entity<Project>,
+    /// The action to take once an encoding is picked, or `None` while the action is being picked.
+    action: Option<EncodingAction>,
+    encodings: Vec<Encoding>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl EncodingSelectorDelegate {
+    fn new(
+        encoding_selector: WeakEntity<EncodingSelector>,
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        action: Option<EncodingAction>,
+    ) -> Self {
+        let mut this = Self {
+            encoding_selector,
+            buffer,
+            project,
+            action: None,
+            encodings: Encoding::all().collect(),
+            candidates: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        this.set_action(action);
+        this
+    }
+
+    fn set_action(&mut self, action: Option<EncodingAction>) {
+        self.action = action;
+        self.candidates = if action.is_some() {
+            self.encodings
+                .iter()
+                .enumerate()
+                .map(|(id, encoding)| StringMatchCandidate::new(id, &encoding.to_string()))
+                .collect()
+        } else {
+            EncodingAction::ALL
+                .iter()
+                .enumerate()
+                .map(|(id, action)| StringMatchCandidate::new(id, action.label()))
+                .collect()
+        };
+        self.matches.clear();
+        self.selected_index = 0;
+    }
+
+    fn current_encoding_id(&self, cx: &App) -> Option<usize> {
+        self.action?;
+        let encoding = self.buffer.read(cx).encoding();
+        self.encodings.iter().position(|e| *e == encoding)
+    }
+
+    fn apply(&self, action: EncodingAction, encoding: Encoding, window: &mut Window, cx: &mut App) {
+        let buffer = self.buffer.clone();
+        match action {
+            EncodingAction::Reopen => {
+                let task = if buffer.read(cx).is_dirty() {
+                    Task::ready(Err(anyhow!(
+                        "Save or discard your changes before reopening the file with another encoding"
+                    )))
+                } else {
+                    self.project.update(cx, |project, cx| {
+                        project.reload_buffer_with_encoding(buffer, encoding, cx)
+                    })
+                };
+                task.detach_and_notify_err(window, cx);
+            }
+            EncodingAction::Save => {
+                self.project
+                    .update(cx, |project, cx| {
+                        project.save_buffer_with_encoding(buffer, encoding, cx)
+                    })
+                    .detach_and_notify_err(window, cx);
+            }
+        }
+    }
+}
+
+impl PickerDelegate for EncodingSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.action {
+            None => "Select an action…".into(),
+            Some(EncodingAction::Reopen) => "Reopen with encoding…".into(),
+            Some(EncodingAction::Save) => "Save with encoding…".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let candidate_id = mat.candidate_id;
+        match self.action {
+            None => {
+                self.set_action(Some(EncodingAction::ALL[candidate_id]));
+                cx.defer_in(window, |picker, window, cx| {
+                    picker.set_query("", window, cx);
+                    picker.refresh_placeholder(window, cx);
+                    picker.refresh(window, cx);
+                });
+            }
+            Some(action) => {
+                self.apply(action, self.encodings[candidate_id], window, cx);
+                self.dismissed(window, cx);
+            }
+        }
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.encoding_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = if query.is_empty() {
+                    delegate.current_encoding_id(cx).unwrap_or(0)
+                } else {
+                    delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1))
+                };
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let mut label = mat.string.clone();
+        if self.current_encoding_id(cx) == Some(mat.candidate_id) {
+            label.push_str(" (current)");
+        }
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(label, mat.positions.clone())),
+        )
+    }
+}
@@ -0,0 +1,357 @@
+use anyhow::{anyhow, Result};
+use std::{fmt, io};
+
+/// The encodings offered when reopening or saving a file with a specific encoding.
+const ENCODINGS: &[&encoding_rs::Encoding] = &[
+    encoding_rs::UTF_8,
+    encoding_rs::UTF_16LE,
+    encoding_rs::UTF_16BE,
+    encoding_rs::WINDOWS_1252,
+    encoding_rs::ISO_8859_2,
+    encoding_rs::ISO_8859_5,
+    encoding_rs::ISO_8859_7,
+    encoding_rs::ISO_8859_15,
+    encoding_rs::WINDOWS_1250,
+    encoding_rs::WINDOWS_1251,
+    encoding_rs::WINDOWS_1253,
+    encoding_rs::WINDOWS_1254,
+    encoding_rs::WINDOWS_1255,
+    encoding_rs::WINDOWS_1256,
+    encoding_rs::WINDOWS_1257,
+    encoding_rs::WINDOWS_1258,
+    encoding_rs::WINDOWS_874,
+    encoding_rs::KOI8_R,
+    encoding_rs::KOI8_U,
+    encoding_rs::IBM866,
+    encoding_rs::SHIFT_JIS,
+    encoding_rs::EUC_JP,
+    encoding_rs::ISO_2022_JP,
+    encoding_rs::EUC_KR,
+    encoding_rs::GBK,
+    encoding_rs::GB18030,
+    encoding_rs::BIG5,
+];
+
+const BOM_SUFFIX: &str = " with BOM";
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The character encoding of a file's contents on disk.
+///
+/// Buffers are always UTF-8 in memory; the encoding is detected when a file is
+/// loaded so that it can be written back the same way it was read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::UTF8
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.encoding.name())?;
+        if self.bom {
+            f.write_str(BOM_SUFFIX)?;
+        }
+        Ok(())
+    }
+}
+
+impl Encoding {
+    pub const UTF8: Self = Self {
+        encoding: encoding_rs::UTF_8,
+        bom: false,
+    };
+
+    /// Every encoding a file can be reopened or saved with.
+    pub fn all() -> impl Iterator<Item = Self> {
+        ENCODINGS.iter().flat_map(|&encoding| {
+            let with_bom = Self::is_unicode(encoding).then_some(Self {
+                encoding,
+                bom: true,
+            });
+            [Self {
+                encoding,
+                bom: false,
+            }]
+            .into_iter()
+            .chain(with_bom)
+        })
+    }
+
+    /// Looks up an encoding by the name it's displayed with, or by any of its WHATWG labels.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (name, bom) = match name.strip_suffix(BOM_SUFFIX) {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+        let encoding = encoding_rs::Encoding::for_label(name.trim().as_bytes())?;
+        if bom && !Self::is_unicode(encoding) {
+            return None;
+        }
+        Some(Self { encoding, bom })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8
+    }
+
+    fn is_unicode(encoding: &'static encoding_rs::Encoding) -> bool {
+        encoding == encoding_rs::UTF_8
+            || encoding == encoding_rs::UTF_16LE
+            || encoding == encoding_rs::UTF_16BE
+    }
+
+    /// Guesses the encoding of a file's contents, returning `None` if they look like binary data.
+    ///
+    /// A byte order mark always wins. Otherwise, the contents are checked for the null bytes
+    /// that UTF-16 encoded ASCII text is full of, and then for valid UTF-8. Contents that are
+    /// neither are binary if they contain null bytes, and are otherwise in whichever legacy
+    /// encoding `chardetng` finds most likely.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Some(Self {
+                encoding,
+                bom: true,
+            });
+        }
+        if let Some(encoding) = detect_utf16(bytes) {
+            return Some(Self {
+                encoding,
+                bom: false,
+            });
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Some(Self::UTF8);
+        }
+        if bytes.contains(&0) {
+            return None;
+        }
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(bytes, true);
+        Some(Self {
+            encoding: detector.guess(None, false),
+            bom: false,
+        })
+    }
+
+    /// Like [`Encoding::detect`], but for the start of a longer file, which may end in
+    /// the middle of a character.
+    pub fn detect_prefix(bytes: &[u8]) -> Option<Self> {
+        match std::str::from_utf8(bytes) {
+            Err(error) if error.error_len().is_none() => {
+                Self::detect(&bytes[..error.valid_up_to()])
+            }
+            _ => Self::detect(bytes),
+        }
+    }
+
+    /// Decodes the contents of a file, stripping this encoding's byte order mark if present.
+    /// Malformed sequences are replaced with U+FFFD.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let bytes = match encoding_rs::Encoding::for_bom(bytes) {
+            Some((encoding, bom_len)) if encoding == self.encoding => &bytes[bom_len..],
+            _ => bytes,
+        };
+        self.encoding
+            .decode_without_bom_handling(bytes)
+            .0
+            .into_owned()
+    }
+
+    /// Decodes the contents of `reader` a chunk at a time, passing the text to `f` as it
+    /// goes, so that large files never need to be held in memory as a single string.
+    pub fn decode_chunks(
+        &self,
+        mut reader: impl io::Read,
+        mut f: impl FnMut(&str),
+    ) -> io::Result<()> {
+        let mut decoder = self.encoding.new_decoder_with_bom_removal();
+        let mut input = vec![0; CHUNK_SIZE];
+        let mut output = String::with_capacity(
+            decoder
+                .max_utf8_buffer_length(CHUNK_SIZE)
+                .unwrap_or(CHUNK_SIZE * 3),
+        );
+        loop {
+            let len = reader.read(&mut input)?;
+            let last = len == 0;
+            let mut input = &input[..len];
+            loop {
+                let (result, read, _) = decoder.decode_to_string(input, &mut output, last);
+                input = &input[read..];
+                if !output.is_empty() {
+                    f(&output);
+                    output.clear();
+                }
+                if result == encoding_rs::CoderResult::InputEmpty {
+                    break;
+                }
+            }
+            if last {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Encodes text to be written to disk, failing if it contains characters that this
+    /// encoding can't represent.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if self.encoding == encoding_rs::UTF_16LE || self.encoding == encoding_rs::UTF_16BE {
+            // encoding_rs only decodes UTF-16, as the WHATWG spec has no UTF-16 encoders.
+            let big_endian = self.encoding == encoding_rs::UTF_16BE;
+            let units = self.bom.then_some(0xFEFF).into_iter();
+            for unit in units.chain(text.encode_utf16()) {
+                if big_endian {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            return Ok(bytes);
+        }
+
+        if self.bom {
+            bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        let (encoded, _, had_unmappable_characters) = self.encoding.encode(text);
+        if had_unmappable_characters {
+            let character = text
+                .chars()
+                .find(|character| {
+                    let mut buffer = [0; 4];
+                    let (_, _, unmappable) =
+                        self.encoding.encode(character.encode_utf8(&mut buffer));
+                    unmappable
+                })
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+            return Err(anyhow!(
+                "{character:?} can't be represented in {}",
+                self.encoding.name()
+            ));
+        }
+        bytes.extend_from_slice(&encoded);
+        Ok(bytes)
+    }
+}
+
+/// Recognizes UTF-16 without a byte order mark from the null bytes in the high
+/// (or low) half of most code units, which is typical of text that's mostly ASCII.
+fn detect_utf16(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+    let units = sample.len() / 2;
+    let (mut even_nulls, mut odd_nulls) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        even_nulls += (pair[0] == 0) as usize;
+        odd_nulls += (pair[1] == 0) as usize;
+    }
+    if odd_nulls * 10 >= units * 4 && even_nulls * 20 <= units {
+        Some(encoding_rs::UTF_16LE)
+    } else if even_nulls * 10 >= units * 4 && odd_nulls * 20 <= units {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(Encoding::detect(b"hello"), Some(Encoding::UTF8));
+        assert_eq!(Encoding::detect("héllo".as_bytes()), Some(Encoding::UTF8));
+        assert_eq!(Encoding::detect(b""), Some(Encoding::UTF8));
+        assert_eq!(
+            Encoding::detect(b"\xEF\xBB\xBFhello").map(|e| e.to_string()),
+            Some("UTF-8 with BOM".into())
+        );
+        assert_eq!(
+            Encoding::detect(b"\xFF\xFEh\0i\0").map(|e| e.to_string()),
+            Some("UTF-16LE with BOM".into())
+        );
+        assert_eq!(
+            Encoding::detect(b"\0h\0e\0l\0l\0o").map(|e| e.to_string()),
+            Some("UTF-16BE".into())
+        );
+        assert_eq!(
+            Encoding::detect(b"caf\xE9").map(|e| e.to_string()),
+            Some("windows-1252".into())
+        );
+        assert_eq!(
+            Encoding::detect(
+                encoding_rs::SHIFT_JIS
+                    .encode("これは日本語のテキストです。")
+                    .0
+                    .as_ref()
+            )
+            .map(|e| e.to_string()),
+            Some("Shift_JIS".into())
+        );
+        assert_eq!(
+            Encoding::detect(b"\x7FELF\x02\x01\x01\0\0\0\0\0\xC8\x8B"),
+            None
+        );
+        // Null bytes alone don't make valid UTF-8 binary.
+        assert_eq!(Encoding::detect(b"name\0value\0"), Some(Encoding::UTF8));
+        // The start of a file can end in the middle of a character.
+        assert_eq!(
+            Encoding::detect_prefix(&"日本".as_bytes()[..4]),
+            Some(Encoding::UTF8)
+        );
+    }
+
+    #[test]
+    fn test_decode_chunks() {
+        let text = "ab€d".repeat(CHUNK_SIZE);
+        for name in ["UTF-8 with BOM", "UTF-16BE", "windows-1252"] {
+            let encoding = Encoding::from_name(name).unwrap();
+            let bytes = encoding.encode(&text).unwrap();
+            let mut decoded = String::new();
+            encoding
+                .decode_chunks(bytes.as_slice(), |chunk| decoded.push_str(chunk))
+                .unwrap();
+            assert_eq!(decoded, text, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for name in [
+            "UTF-8 with BOM",
+            "UTF-16LE",
+            "UTF-16BE with BOM",
+            "Shift_JIS",
+        ] {
+            let encoding = Encoding::from_name(name).unwrap();
+            assert_eq!(encoding.to_string(), name);
+            let bytes = encoding.encode("こんにちは\n").unwrap();
+            assert_eq!(encoding.decode(&bytes), "こんにちは\n");
+        }
+
+        let latin1 = Encoding::from_name("latin1").unwrap();
+        assert_eq!(latin1.name(), "windows-1252");
+        assert_eq!(latin1.encode("café").unwrap(), b"caf\xE9");
+        assert_eq!(latin1.decode(b"caf\xE9"), "café");
+        assert!(latin1.encode("日本").is_err());
+        assert_eq!(Encoding::from_name("windows-1252 with BOM"), None);
+    }
+}
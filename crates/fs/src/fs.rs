@@ -4,6 +4,10 @@ mod mac_watcher;
 #[cfg(not(target_os = "macos"))]
 pub mod fs_watcher;
 
+mod encoding;
+
+pub use encoding::Encoding;
+
 use anyhow::{anyhow, Context as _, Result};
 use git::GitHostingProviderRegistry;
 #[cfg(any(test, feature = "test-support"))]
@@ -106,8 +110,68 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads a file as text, decoding it with the given encoding or else with the one
+    /// detected from its contents, which is returned alongside the text.
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        let bytes = self.load_bytes(path).await?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Encoding::detect(&bytes)
+                .ok_or_else(|| anyhow!("{path:?} appears to be a binary file"))?,
+        };
+        Ok((encoding.decode(&bytes), encoding))
+    }
+    /// Loads a file as a rope, reading and decoding it a chunk at a time rather than as one
+    /// string. Returns the file's line ending and detected encoding along with its text.
+    async fn load_rope(&self, path: &Path) -> Result<(Rope, LineEnding, Encoding)> {
+        let mut reader = self.open_sync(path).await?;
+        let mut prefix = Vec::new();
+        io::Read::read_to_end(&mut io::Read::take(&mut reader, 64 * 1024), &mut prefix)?;
+        let encoding = Encoding::detect_prefix(&prefix)
+            .ok_or_else(|| anyhow!("{path:?} appears to be a binary file"))?;
+
+        let mut rope = Rope::new();
+        let mut line_ending = None;
+        let mut text = String::new();
+        let mut pending_cr = false;
+        encoding.decode_chunks(io::Read::chain(io::Cursor::new(prefix), reader), |chunk| {
+            text.clear();
+            if pending_cr {
+                text.push('\r');
+            }
+            text.push_str(chunk);
+            // Hold back a trailing `\r` in case the next chunk starts with `\n`.
+            pending_cr = text.ends_with('\r');
+            if pending_cr {
+                text.pop();
+            }
+            if line_ending.is_none() && text.contains('\n') {
+                line_ending = Some(LineEnding::detect(&text));
+            }
+            LineEnding::normalize(&mut text);
+            rope.push(&text);
+        })?;
+        if pending_cr {
+            rope.push("\n");
+        }
+        Ok((rope, line_ending.unwrap_or_default(), encoding))
+    }
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.save_with_encoding(path, text, line_ending, Encoding::UTF8)
+            .await
+    }
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn is_dir(&self, path: &Path) -> bool;
@@ -547,15 +611,30 @@ impl Fs for RealFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
         let buffer_size = text.summary().len.min(10 * 1024);
+        let encoded = if encoding == Encoding::UTF8 {
+            None
+        } else {
+            Some(encoding.encode(&chunks(text, line_ending).collect::<String>())?)
+        };
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
         let file = smol::fs::File::create(path).await?;
         let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
-        for chunk in chunks(text, line_ending) {
-            writer.write_all(chunk.as_bytes()).await?;
+        if let Some(encoded) = encoded {
+            writer.write_all(&encoded).await?;
+        } else {
+            for chunk in chunks(text, line_ending) {
+                writer.write_all(chunk.as_bytes()).await?;
+            }
         }
         writer.flush().await?;
         Ok(())
@@ -1787,14 +1866,20 @@ impl Fs for FakeFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
-        let content = chunks(text, line_ending).collect::<String>();
+        let content = encoding.encode(&chunks(text, line_ending).collect::<String>())?;
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        self.write_file_internal(path, content.into_bytes())?;
+        self.write_file_internal(path, content)?;
         Ok(())
     }
 
@@ -1945,7 +2030,20 @@ impl Fs for FakeFs {
                     )))
                 })
                 .clone();
-            Some(git::repository::FakeGitRepository::open(state))
+            let this = self.this.clone();
+            let work_directory = abs_dot_git.parent()?.to_path_buf();
+            Some(git::repository::FakeGitRepository::open(
+                state,
+                Arc::new(move |repo_path: &RepoPath| {
+                    let path = work_directory.join(repo_path);
+                    let fs = this.upgrade()?;
+                    let state = fs.state.lock();
+                    let entry = state.read_path(&path).ok()?;
+                    let entry = entry.lock();
+                    let content = entry.file_content(&path).ok()?;
+                    String::from_utf8(content.clone()).ok()
+                }),
+            ))
         } else {
             None
         }
@@ -2146,4 +2244,23 @@ mod tests {
             "D",
         );
     }
+
+    #[gpui::test]
+    async fn test_load_rope(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor.clone());
+        // A `\r\n` straddles the boundary between the first two chunks that are read.
+        let line = format!("{}x\r\n", "é".repeat(32 * 1024 - 1));
+        let text = line.repeat(3);
+        fs.insert_file("/large.log", text.clone().into_bytes())
+            .await;
+
+        let (rope, line_ending, encoding) = fs.load_rope("/large.log".as_ref()).await.unwrap();
+        assert_eq!(rope.to_string(), text.replace("\r\n", "\n"));
+        assert_eq!(line_ending, LineEnding::Windows);
+        assert_eq!(encoding, Encoding::UTF8);
+
+        fs.insert_file("/binary", vec![0x7f, b'E', b'L', b'F', 0, 0, 0, 1])
+            .await;
+        assert!(fs.load_rope("/binary".as_ref()).await.is_err());
+    }
 }
@@ -8,6 +8,7 @@ use gpui::SharedString;
 use parking_lot::Mutex;
 use rope::Rope;
 use std::borrow::Borrow;
+use std::sync::atomic::{self, AtomicUsize};
 use std::sync::LazyLock;
 use std::{
     cmp::Ordering,
@@ -26,6 +27,14 @@ pub struct Branch {
     pub unix_timestamp: Option<i64>,
 }
 
+/// A commit, as listed in the history of a repository.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub sha: SharedString,
+    /// The first line of the commit message.
+    pub subject: SharedString,
+}
+
 pub trait GitRepository: Send + Sync {
     fn reload_index(&self);
 
@@ -80,6 +89,28 @@ pub trait GitRepository: Send + Sync {
     fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
 
     fn commit(&self, message: &str, name_and_email: Option<(&str, &str)>) -> Result<()>;
+
+    /// Returns the diff of the staged and unstaged changes against HEAD.
+    fn uncommitted_diff(&self) -> Result<String>;
+
+    /// Returns the message and the diff of the commit with the given SHA.
+    fn show_commit(&self, sha: &str) -> Result<String>;
+
+    /// Returns up to `limit` of the most recent commits reachable from HEAD, newest first.
+    fn recent_commits(&self, limit: usize) -> Result<Vec<CommitSummary>>;
+
+    /// Saves the working copy, including untracked files that aren't ignored, as a tree kept
+    /// under a hidden ref, leaving the index untouched. Returns the SHA of the tree.
+    fn create_checkpoint(&self) -> Result<String>;
+
+    /// Returns the files that changed in the working copy since the given checkpoint, along with
+    /// their contents in it, or `None` for the files created since. Files that aren't valid
+    /// UTF-8 are skipped.
+    fn changes_since_checkpoint(&self, checkpoint: &str)
+        -> Result<Vec<(RepoPath, Option<String>)>>;
+
+    /// Deletes the hidden ref of the given checkpoint.
+    fn delete_checkpoint(&self, checkpoint: &str) -> Result<()>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -111,6 +142,69 @@ impl RealGitRepository {
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
 const GIT_MODE_SYMLINK: u32 = 0o120000;
 
+impl RealGitRepository {
+    fn run_git_command(&self, args: &[&str]) -> Result<String> {
+        self.run_git_command_with_index(args, None)
+    }
+
+    /// Runs git with the given index file in place of the repository's index.
+    fn run_git_command_with_index(
+        &self,
+        args: &[&str],
+        index_path: Option<&Path>,
+    ) -> Result<String> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        let mut command = new_std_command(&self.git_binary_path);
+        command.current_dir(&working_directory).args(args);
+        if let Some(index_path) = index_path {
+            command.env("GIT_INDEX_FILE", index_path);
+        }
+        let output = command.output()?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to run git {}:\n{}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Writes the working copy, including untracked files that aren't ignored, as a tree. It's
+    /// staged in a copy of the index, so that unchanged files aren't hashed again.
+    fn write_working_copy_tree(&self) -> Result<String> {
+        static NEXT_INDEX_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let git_dir = self.repository.lock().path().to_path_buf();
+        let index_path = git_dir.join(format!(
+            "zed-checkpoint-index-{}-{}",
+            std::process::id(),
+            NEXT_INDEX_ID.fetch_add(1, atomic::Ordering::Relaxed)
+        ));
+        match std::fs::copy(git_dir.join("index"), &index_path) {
+            Ok(_) => {}
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+
+        let tree = self
+            .run_git_command_with_index(&["add", "--all"], Some(&index_path))
+            .and_then(|_| self.run_git_command_with_index(&["write-tree"], Some(&index_path)));
+        std::fs::remove_file(&index_path).log_err();
+        Ok(tree?.trim().to_string())
+    }
+}
+
+fn checkpoint_ref(checkpoint: &str) -> String {
+    format!("refs/zed/checkpoints/{checkpoint}")
+}
+
 impl GitRepository for RealGitRepository {
     fn reload_index(&self) {
         if let Ok(mut index) = self.repository.lock().index() {
@@ -359,11 +453,95 @@ impl GitRepository for RealGitRepository {
         }
         Ok(())
     }
+
+    fn uncommitted_diff(&self) -> Result<String> {
+        if self.repository.lock().head().is_ok() {
+            return self.run_git_command(&["diff", "--no-color", "HEAD"]);
+        }
+
+        // Before the first commit there's no HEAD, so everything is diffed against the empty tree.
+        let empty_tree = git2::Oid::hash_object(git2::ObjectType::Tree, &[])?.to_string();
+        self.run_git_command(&["diff", "--no-color", &empty_tree])
+    }
+
+    fn show_commit(&self, sha: &str) -> Result<String> {
+        if sha.is_empty() || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!("invalid commit SHA: {sha:?}"));
+        }
+        self.run_git_command(&["show", "--no-color", sha])
+    }
+
+    fn recent_commits(&self, limit: usize) -> Result<Vec<CommitSummary>> {
+        let limit = format!("--max-count={limit}");
+        let output = self.run_git_command(&["log", &limit, "--format=%H%x00%s"])?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let (sha, subject) = line.split_once('\0')?;
+                Some(CommitSummary {
+                    sha: sha.to_string().into(),
+                    subject: subject.to_string().into(),
+                })
+            })
+            .collect())
+    }
+
+    fn create_checkpoint(&self) -> Result<String> {
+        let tree = self.write_working_copy_tree()?;
+        self.run_git_command(&["update-ref", &checkpoint_ref(&tree), &tree])?;
+        Ok(tree)
+    }
+
+    fn changes_since_checkpoint(
+        &self,
+        checkpoint: &str,
+    ) -> Result<Vec<(RepoPath, Option<String>)>> {
+        let current_tree = self.write_working_copy_tree()?;
+        let repo = self.repository.lock();
+        let old_tree = repo.find_tree(git2::Oid::from_str(checkpoint)?)?;
+        let new_tree = repo.find_tree(git2::Oid::from_str(&current_tree)?)?;
+        let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+
+        let is_file = |mode: git2::FileMode| {
+            matches!(mode, git2::FileMode::Blob | git2::FileMode::BlobExecutable)
+        };
+        let mut changes = Vec::new();
+        for delta in diff.deltas() {
+            let (old_file, new_file) = (delta.old_file(), delta.new_file());
+            let Some(path) = old_file.path().or(new_file.path()) else {
+                continue;
+            };
+            let text = if delta.status() == git2::Delta::Added {
+                if !is_file(new_file.mode()) {
+                    continue;
+                }
+                None
+            } else {
+                if !is_file(old_file.mode()) {
+                    continue;
+                }
+                let blob = repo.find_blob(old_file.id())?;
+                let Ok(text) = std::str::from_utf8(blob.content()) else {
+                    continue;
+                };
+                Some(text.to_string())
+            };
+            changes.push((RepoPath::from(path), text));
+        }
+        Ok(changes)
+    }
+
+    fn delete_checkpoint(&self, checkpoint: &str) -> Result<()> {
+        self.run_git_command(&["update-ref", "-d", &checkpoint_ref(checkpoint)])?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct FakeGitRepository {
     state: Arc<Mutex<FakeGitRepositoryState>>,
+    /// Reads a file of the working copy, which lives in the fake file system.
+    load_working_copy_text: Arc<dyn Fn(&RepoPath) -> Option<String> + Send + Sync>,
 }
 
 #[derive(Debug, Clone)]
@@ -376,11 +554,19 @@ pub struct FakeGitRepositoryState {
     pub statuses: HashMap<RepoPath, FileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    /// The commits reachable from HEAD, oldest first, along with the output of showing them.
+    pub commits: Vec<(CommitSummary, String)>,
 }
 
 impl FakeGitRepository {
-    pub fn open(state: Arc<Mutex<FakeGitRepositoryState>>) -> Arc<dyn GitRepository> {
-        Arc::new(FakeGitRepository { state })
+    pub fn open(
+        state: Arc<Mutex<FakeGitRepositoryState>>,
+        load_working_copy_text: Arc<dyn Fn(&RepoPath) -> Option<String> + Send + Sync>,
+    ) -> Arc<dyn GitRepository> {
+        Arc::new(FakeGitRepository {
+            state,
+            load_working_copy_text,
+        })
     }
 }
 
@@ -395,6 +581,7 @@ impl FakeGitRepositoryState {
             statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            commits: Default::default(),
         }
     }
 }
@@ -521,6 +708,92 @@ impl GitRepository for FakeGitRepository {
     fn commit(&self, _message: &str, _name_and_email: Option<(&str, &str)>) -> Result<()> {
         unimplemented!()
     }
+
+    /// Like `git diff HEAD`, compares the working copy of the files in HEAD or the index with
+    /// HEAD, rendering each changed file as a single hunk.
+    fn uncommitted_diff(&self) -> Result<String> {
+        let (mut paths, head_contents) = {
+            let state = self.state.lock();
+            let paths = state
+                .head_contents
+                .keys()
+                .chain(state.index_contents.keys())
+                .cloned()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            (paths, state.head_contents.clone())
+        };
+        paths.sort();
+
+        let mut diff = String::new();
+        for path in paths {
+            let old_text = head_contents.get(&path).cloned();
+            let new_text = (self.load_working_copy_text)(&path);
+            if old_text == new_text {
+                continue;
+            }
+
+            let path = path.to_string_lossy();
+            diff.push_str(&format!("diff --git a/{path} b/{path}\n"));
+            match old_text {
+                Some(_) => diff.push_str(&format!("--- a/{path}\n")),
+                None => diff.push_str("--- /dev/null\n"),
+            }
+            match new_text {
+                Some(_) => diff.push_str(&format!("+++ b/{path}\n")),
+                None => diff.push_str("+++ /dev/null\n"),
+            }
+            for line in old_text.iter().flat_map(|text| text.lines()) {
+                diff.push_str(&format!("-{line}\n"));
+            }
+            for line in new_text.iter().flat_map(|text| text.lines()) {
+                diff.push_str(&format!("+{line}\n"));
+            }
+        }
+        Ok(diff)
+    }
+
+    fn show_commit(&self, sha: &str) -> Result<String> {
+        if sha.is_empty() {
+            return Err(anyhow!("invalid commit SHA: {sha:?}"));
+        }
+        let state = self.state.lock();
+        state
+            .commits
+            .iter()
+            .find(|(summary, _)| summary.sha.starts_with(sha))
+            .map(|(_, details)| details.clone())
+            .with_context(|| format!("unknown commit {sha:?}"))
+    }
+
+    fn recent_commits(&self, limit: usize) -> Result<Vec<CommitSummary>> {
+        let state = self.state.lock();
+        Ok(state
+            .commits
+            .iter()
+            .rev()
+            .take(limit)
+            .map(|(summary, _)| summary.clone())
+            .collect())
+    }
+
+    fn create_checkpoint(&self) -> Result<String> {
+        Err(anyhow!(
+            "checkpoints aren't supported by the fake git repository"
+        ))
+    }
+
+    fn changes_since_checkpoint(
+        &self,
+        _checkpoint: &str,
+    ) -> Result<Vec<(RepoPath, Option<String>)>> {
+        unimplemented!()
+    }
+
+    fn delete_checkpoint(&self, _checkpoint: &str) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
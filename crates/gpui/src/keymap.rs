@@ -54,6 +54,13 @@ impl Keymap {
         self.version.0 += 1;
     }
 
+    /// Remove the bindings for which the predicate returns true.
+    pub fn remove_bindings(&mut self, mut predicate: impl FnMut(&KeyBinding) -> bool) {
+        let bindings = std::mem::take(&mut self.bindings);
+        self.clear();
+        self.add_bindings(bindings.into_iter().filter(|binding| !predicate(binding)));
+    }
+
     /// Reset this keymap to its initial state.
     pub fn clear(&mut self) {
         self.bindings.clear();
@@ -274,6 +281,33 @@ mod tests {
             .is_empty());
     }
 
+    #[test]
+    fn test_remove_bindings() {
+        let bindings = [
+            KeyBinding::new("ctrl-a", ActionAlpha {}, Some("pane")),
+            KeyBinding::new("ctrl-b", ActionBeta {}, Some("pane")),
+            KeyBinding::new("ctrl-c", ActionAlpha {}, Some("pane")),
+            KeyBinding::new("ctrl-b", NoAction {}, Some("pane && active")),
+        ];
+
+        let mut keymap = Keymap::default();
+        keymap.add_bindings(bindings.clone());
+        let version = keymap.version();
+        keymap.remove_bindings(|binding| binding.action().partial_eq(&ActionAlpha {}));
+
+        assert!(keymap.version() != version);
+        assert_eq!(keymap.bindings().count(), 2);
+        assert_eq!(keymap.bindings_for_action(&ActionAlpha {}).count(), 0);
+        // the indices of the remaining bindings are kept up to date
+        assert!(keymap
+            .bindings_for_input(
+                &[Keystroke::parse("ctrl-b").unwrap()],
+                &[KeyContext::parse("pane active").unwrap()],
+            )
+            .0
+            .is_empty());
+    }
+
     #[test]
     fn test_bindings_for_action() {
         let bindings = [
@@ -22,8 +22,9 @@ use async_watch as watch;
 use clock::Lamport;
 pub use clock::ReplicaId;
 use collections::HashMap;
-use fs::MTime;
+use fs::{Encoding, MTime};
 use futures::channel::oneshot;
+use futures::future::Either;
 use gpui::{
     AnyElement, App, AppContext as _, Context, Entity, EventEmitter, HighlightStyle, Pixels,
     SharedString, StyledText, Task, TaskLabel, TextStyle, Window,
@@ -103,6 +104,12 @@ pub struct Buffer {
     /// The version vector when this buffer was last loaded from
     /// or saved to disk.
     saved_version: clock::Global,
+    /// The encoding of the file on disk, which the buffer is decoded
+    /// from when loaded and encoded into when saved.
+    encoding: Encoding,
+    /// Whether the buffer's file was opened in large-file mode, in which it is
+    /// never parsed.
+    large_file: bool,
     preview_version: clock::Global,
     transaction_depth: usize,
     was_dirty_before_starting_transaction: Option<bool>,
@@ -424,6 +431,13 @@ pub trait LocalFile: File {
     /// Loads the file contents from disk and returns them as a UTF-8 encoded string.
     fn load(&self, cx: &App) -> Task<Result<String>>;
 
+    /// Loads the file contents from disk, decoding them from the given encoding.
+    fn load_with_encoding(&self, encoding: Encoding, cx: &App) -> Task<Result<String>>;
+
+    /// Loads the file contents from disk a chunk at a time, as done for large files, with
+    /// normalized line endings. Returns the file's line ending along with its text.
+    fn load_rope(&self, cx: &App) -> Task<Result<(Rope, LineEnding)>>;
+
     /// Loads the file's contents from disk.
     fn load_bytes(&self, cx: &App) -> Task<Result<Vec<u8>>>;
 }
@@ -846,6 +860,11 @@ impl Buffer {
         let buffer_id = BufferId::new(message.id)
             .with_context(|| anyhow!("Could not deserialize buffer_id"))?;
         let buffer = TextBuffer::new(replica_id, buffer_id, message.base_text);
+        let capability = if message.large_file {
+            Capability::ReadOnly
+        } else {
+            capability
+        };
         let mut this = Self::build(buffer, file, capability);
         this.text.set_line_ending(proto::deserialize_line_ending(
             rpc::proto::LineEnding::from_i32(message.line_ending)
@@ -853,6 +872,10 @@ impl Buffer {
         ));
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
+        if let Some(encoding) = message.encoding.as_deref() {
+            this.encoding = proto::deserialize_encoding(encoding)?;
+        }
+        this.large_file = message.large_file;
         Ok(this)
     }
 
@@ -865,6 +888,8 @@ impl Buffer {
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
+            encoding: Some(proto::serialize_encoding(self.encoding)),
+            large_file: self.large_file,
         }
     }
 
@@ -924,6 +949,24 @@ impl Buffer {
         self
     }
 
+    /// Set the encoding of the buffer's file on disk, returning the buffer.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Mark the buffer as having been opened in large-file mode, returning the buffer.
+    pub fn with_large_file(mut self, large_file: bool) -> Self {
+        self.large_file = large_file;
+        self
+    }
+
+    /// Whether the buffer's file was too large to open normally. Large files are
+    /// never parsed, and aren't given to language servers or diffed against git.
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
     /// Returns the [`Capability`] of this buffer.
     pub fn capability(&self) -> Capability {
         self.capability
@@ -942,6 +985,8 @@ impl Buffer {
         Self {
             saved_mtime,
             saved_version: buffer.version(),
+            encoding: Encoding::default(),
+            large_file: false,
             preview_version: buffer.version(),
             reload_task: None,
             transaction_depth: 0,
@@ -1074,6 +1119,8 @@ impl Buffer {
                     merged_operations: Default::default(),
                 }),
                 language: self.language.clone(),
+                encoding: self.encoding,
+                large_file: self.large_file,
                 has_conflict: self.has_conflict,
                 has_unsaved_edits: Cell::new(self.has_unsaved_edits.get_mut().clone()),
                 _subscriptions: vec![cx.subscribe(&this, Self::on_base_buffer_event)],
@@ -1232,6 +1279,19 @@ impl Buffer {
         self.saved_mtime
     }
 
+    /// The encoding of the buffer's file on disk.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Changes the encoding the buffer will be saved with, and reloaded with.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut Context<Self>) {
+        if self.encoding != encoding {
+            self.encoding = encoding;
+            cx.notify();
+        }
+    }
+
     /// Assign a language to the buffer.
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut Context<Self>) {
         self.non_text_state_update_count += 1;
@@ -1286,18 +1346,33 @@ impl Buffer {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
-            let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
+            let Some((new_mtime, load)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.disk_state().mtime(), file.load(cx)))
+                // Large files are read a chunk at a time, as when they're opened.
+                let load = if this.large_file {
+                    Either::Right(file.load_rope(cx))
+                } else {
+                    Either::Left(file.load_with_encoding(this.encoding, cx))
+                };
+                Some((file.disk_state().mtime(), load))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
-            let diff = this
-                .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
-                .await;
+            let diff = match load {
+                Either::Left(load) => {
+                    let new_text = load.await?;
+                    this.update(&mut cx, |this, cx| this.diff(new_text, cx))?
+                }
+                Either::Right(load) => {
+                    let (new_text, line_ending) = load.await?;
+                    this.update(&mut cx, |this, cx| {
+                        this.diff_after_common_prefix(new_text, line_ending, cx)
+                    })?
+                }
+            }
+            .await;
             this.update(&mut cx, |this, cx| {
                 if this.version() == diff.base_version {
                     this.finalize_last_transaction();
@@ -1442,7 +1517,7 @@ impl Buffer {
     /// for the same buffer, we only initiate a new parse if we are not already
     /// parsing in the background.
     pub fn reparse(&mut self, cx: &mut Context<Self>) {
-        if self.parsing_in_background {
+        if self.parsing_in_background || self.large_file {
             return;
         }
         let language = if let Some(language) = self.language.clone() {
@@ -1789,6 +1864,44 @@ impl Buffer {
         }
     }
 
+    /// Computes a [`Diff`] that replaces everything after the text's common prefix with
+    /// the new text, which is how log files usually change. This is used for large files,
+    /// which are too big to diff character by character.
+    fn diff_after_common_prefix(
+        &self,
+        new_text: Rope,
+        line_ending: LineEnding,
+        cx: &App,
+    ) -> Task<Diff> {
+        let old_text = self.as_rope().clone();
+        let base_version = self.version();
+        cx.background_executor()
+            .spawn_labeled(*BUFFER_DIFF_TASK, async move {
+                let common_prefix_len = old_text
+                    .chars()
+                    .zip(new_text.chars())
+                    .take_while(|(old, new)| old == new)
+                    .map(|(old, _)| old.len_utf8())
+                    .sum::<usize>();
+
+                let old_len = old_text.len();
+                let edits = if common_prefix_len == old_len && common_prefix_len == new_text.len() {
+                    Vec::new()
+                } else {
+                    let new_suffix = new_text.chunks_in_range(common_prefix_len..new_text.len());
+                    vec![(
+                        common_prefix_len..old_len,
+                        Arc::from(new_suffix.collect::<String>()),
+                    )]
+                };
+                Diff {
+                    base_version,
+                    line_ending,
+                    edits,
+                }
+            })
+    }
+
     /// Spawns a background task that asynchronously computes a `Diff` between the buffer's text
     /// and the given new text.
     pub fn diff(&self, mut new_text: String, cx: &App) -> Task<Diff> {
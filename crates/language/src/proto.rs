@@ -27,6 +27,16 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Deserializes a [`fs::Encoding`] from the RPC representation.
+pub fn deserialize_encoding(message: &str) -> Result<fs::Encoding> {
+    fs::Encoding::from_name(message).ok_or_else(|| anyhow!("unknown encoding {message:?}"))
+}
+
+/// Serializes a [`fs::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: fs::Encoding) -> String {
+    encoding.to_string()
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
@@ -28,6 +28,9 @@ impl std::fmt::Debug for LanguageModelImage {
 /// Anthropic wants uploaded images to be smaller than this in both dimensions.
 const ANTHROPIC_SIZE_LIMT: f32 = 1568.;
 
+/// The smallest thinking budget Anthropic accepts.
+const MIN_ANTHROPIC_THINKING_BUDGET: u32 = 1024;
+
 impl LanguageModelImage {
     pub fn from_image(data: Image, cx: &mut App) -> Task<Option<Self>> {
         cx.background_executor().spawn(async move {
@@ -171,6 +174,12 @@ pub struct LanguageModelToolResult {
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum MessageContent {
     Text(String),
+    /// The reasoning the model did before answering, along with the provider's
+    /// signature for it, if any.
+    Thinking {
+        text: String,
+        signature: Option<String>,
+    },
     Image(LanguageModelImage),
     ToolUse(LanguageModelToolUse),
     ToolResult(LanguageModelToolResult),
@@ -201,7 +210,9 @@ impl LanguageModelRequestMessage {
         for string in self.content.iter().filter_map(|content| match content {
             MessageContent::Text(text) => Some(text),
             MessageContent::ToolResult(tool_result) => Some(&tool_result.content),
-            MessageContent::ToolUse(_) | MessageContent::Image(_) => None,
+            MessageContent::Thinking { .. }
+            | MessageContent::ToolUse(_)
+            | MessageContent::Image(_) => None,
         }) {
             string_buffer.push_str(string.as_str())
         }
@@ -218,7 +229,9 @@ impl LanguageModelRequestMessage {
                     MessageContent::ToolResult(tool_result) => {
                         tool_result.content.chars().all(|c| c.is_whitespace())
                     }
-                    MessageContent::ToolUse(_) | MessageContent::Image(_) => true,
+                    MessageContent::Thinking { .. }
+                    | MessageContent::ToolUse(_)
+                    | MessageContent::Image(_) => true,
                 })
                 .unwrap_or(false)
     }
@@ -237,11 +250,26 @@ pub struct LanguageModelRequest {
     pub tools: Vec<LanguageModelRequestTool>,
     pub stop: Vec<String>,
     pub temperature: Option<f32>,
+    /// How many output tokens the model may spend thinking before answering, for
+    /// models that support it.
+    pub thinking_budget: Option<u32>,
+    /// The most output tokens the response may use, when it must stay below the model's own
+    /// limit.
+    pub max_tokens: Option<u32>,
 }
 
 impl LanguageModelRequest {
+    /// Returns the most output tokens to request, given the model's own limit.
+    fn max_output_tokens(&self, model_max_output_tokens: Option<u32>) -> Option<u32> {
+        match (self.max_tokens, model_max_output_tokens) {
+            (Some(max_tokens), Some(model_max_tokens)) => Some(max_tokens.min(model_max_tokens)),
+            (max_tokens, model_max_tokens) => max_tokens.or(model_max_tokens),
+        }
+    }
+
     pub fn into_open_ai(self, model: String, max_output_tokens: Option<u32>) -> open_ai::Request {
         let stream = !model.starts_with("o1-");
+        let max_output_tokens = self.max_output_tokens(max_output_tokens);
         open_ai::Request {
             model,
             messages: self
@@ -266,10 +294,12 @@ impl LanguageModelRequest {
             max_tokens: max_output_tokens,
             tools: Vec::new(),
             tool_choice: None,
+            stream_options: None,
         }
     }
 
     pub fn into_google(self, model: String) -> google_ai::GenerateContentRequest {
+        let max_output_tokens = self.max_tokens.map(|max_tokens| max_tokens as usize);
         google_ai::GenerateContentRequest {
             model,
             contents: self
@@ -289,7 +319,7 @@ impl LanguageModelRequest {
             generation_config: Some(google_ai::GenerationConfig {
                 candidate_count: Some(1),
                 stop_sequences: Some(self.stop),
-                max_output_tokens: None,
+                max_output_tokens,
                 temperature: self.temperature.map(|t| t as f64).or(Some(1.0)),
                 top_p: None,
                 top_k: None,
@@ -303,9 +333,24 @@ impl LanguageModelRequest {
         model: String,
         default_temperature: f32,
         max_output_tokens: u32,
+        supports_thinking: bool,
     ) -> anthropic::Request {
+        let max_output_tokens = self
+            .max_output_tokens(Some(max_output_tokens))
+            .unwrap_or(max_output_tokens);
         let mut new_messages: Vec<anthropic::Message> = Vec::new();
         let mut system_message = String::new();
+        // Anthropic requires the thinking budget to be at least 1024 tokens and less than
+        // the maximum output tokens, so thinking is left off when neither can be satisfied.
+        let thinking_budget = self
+            .thinking_budget
+            .filter(|_| supports_thinking)
+            .map(|budget| {
+                budget
+                    .max(MIN_ANTHROPIC_THINKING_BUDGET)
+                    .min(max_output_tokens.saturating_sub(1))
+            })
+            .filter(|budget| *budget >= MIN_ANTHROPIC_THINKING_BUDGET);
 
         for message in self.messages {
             if message.contents_empty() {
@@ -335,6 +380,14 @@ impl LanguageModelRequest {
                                     None
                                 }
                             }
+                            // Thinking can only be sent back along with its signature, and
+                            // only while thinking is enabled.
+                            MessageContent::Thinking { text, signature } => signature
+                                .filter(|_| thinking_budget.is_some())
+                                .map(|signature| anthropic::RequestContent::Thinking {
+                                    thinking: text,
+                                    signature,
+                                }),
                             MessageContent::Image(image) => {
                                 Some(anthropic::RequestContent::Image {
                                     source: anthropic::ImageSource {
@@ -405,14 +458,22 @@ impl LanguageModelRequest {
             tool_choice: None,
             metadata: None,
             stop_sequences: Vec::new(),
-            temperature: self.temperature.or(Some(default_temperature)),
+            // Anthropic doesn't allow changing the temperature while thinking is enabled.
+            temperature: if thinking_budget.is_some() {
+                None
+            } else {
+                self.temperature.or(Some(default_temperature))
+            },
             top_k: None,
             top_p: None,
+            thinking: thinking_budget
+                .map(|budget_tokens| anthropic::Thinking::Enabled { budget_tokens }),
         }
     }
 
     pub fn into_deepseek(self, model: String, max_output_tokens: Option<u32>) -> deepseek::Request {
         let is_reasoner = model == "deepseek-reasoner";
+        let max_output_tokens = self.max_output_tokens(max_output_tokens);
 
         let len = self.messages.len();
         let merged_messages =
@@ -486,6 +547,7 @@ impl LanguageModelRequest {
                     },
                 })
                 .collect(),
+            stream_options: None,
         }
     }
 }
@@ -495,3 +557,150 @@ pub struct LanguageModelResponseMessage {
     pub role: Option<Role>,
     pub content: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request_with_thinking(thinking_budget: Option<u32>) -> LanguageModelRequest {
+        LanguageModelRequest {
+            messages: vec![
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec![MessageContent::Text("What is 2 + 2?".into())],
+                    cache: false,
+                },
+                LanguageModelRequestMessage {
+                    role: Role::Assistant,
+                    content: vec![
+                        MessageContent::Thinking {
+                            text: "Adding them up.".into(),
+                            signature: Some("signature".into()),
+                        },
+                        MessageContent::Text("4".into()),
+                    ],
+                    cache: false,
+                },
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec![MessageContent::Text("And 3 + 3?".into())],
+                    cache: false,
+                },
+            ],
+            tools: Vec::new(),
+            stop: Vec::new(),
+            temperature: Some(0.5),
+            thinking_budget,
+            max_tokens: None,
+        }
+    }
+
+    fn anthropic_request(
+        request: LanguageModelRequest,
+        max_output_tokens: u32,
+        supports_thinking: bool,
+    ) -> serde_json::Value {
+        let request = request.into_anthropic(
+            "claude-3-7-sonnet-latest".into(),
+            1.0,
+            max_output_tokens,
+            supports_thinking,
+        );
+        serde_json::to_value(&request).unwrap()
+    }
+
+    #[test]
+    fn test_anthropic_thinking_is_sent_back_with_its_signature() {
+        let request = anthropic_request(request_with_thinking(Some(2048)), 8192, true);
+        assert_eq!(
+            request["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 2048 })
+        );
+        assert_eq!(request["temperature"], serde_json::Value::Null);
+        assert_eq!(
+            request["messages"][1]["content"],
+            json!([
+                { "type": "thinking", "thinking": "Adding them up.", "signature": "signature" },
+                { "type": "text", "text": "4" },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_anthropic_thinking_requires_a_supporting_model() {
+        let request = anthropic_request(request_with_thinking(Some(2048)), 8192, false);
+        assert_eq!(request["thinking"], serde_json::Value::Null);
+        assert_eq!(request["temperature"], json!(0.5));
+        assert_eq!(
+            request["messages"][1]["content"],
+            json!([{ "type": "text", "text": "4" }])
+        );
+
+        let request = anthropic_request(request_with_thinking(None), 8192, true);
+        assert_eq!(request["thinking"], serde_json::Value::Null);
+        assert_eq!(
+            request["messages"][1]["content"],
+            json!([{ "type": "text", "text": "4" }])
+        );
+    }
+
+    #[test]
+    fn test_anthropic_thinking_budget_limits() {
+        let request = anthropic_request(request_with_thinking(Some(100)), 8192, true);
+        assert_eq!(request["thinking"]["budget_tokens"], json!(1024));
+
+        let request = anthropic_request(request_with_thinking(Some(100_000)), 8192, true);
+        assert_eq!(request["thinking"]["budget_tokens"], json!(8191));
+
+        let request = anthropic_request(request_with_thinking(Some(2048)), 1024, true);
+        assert_eq!(request["thinking"], serde_json::Value::Null);
+        assert_eq!(request["temperature"], json!(0.5));
+    }
+
+    #[test]
+    fn test_max_tokens() {
+        let request = LanguageModelRequest {
+            max_tokens: Some(100),
+            ..request_with_thinking(None)
+        };
+        assert_eq!(
+            anthropic_request(request.clone(), 8192, false)["max_tokens"],
+            json!(100)
+        );
+        assert_eq!(
+            request
+                .clone()
+                .into_open_ai("gpt-4o".into(), Some(50))
+                .max_tokens,
+            Some(50)
+        );
+        assert_eq!(
+            request
+                .clone()
+                .into_deepseek("deepseek-chat".into(), None)
+                .max_tokens,
+            Some(100)
+        );
+
+        let request = request_with_thinking(None);
+        assert_eq!(
+            anthropic_request(request.clone(), 8192, false)["max_tokens"],
+            json!(8192)
+        );
+        assert_eq!(request.into_open_ai("gpt-4o".into(), None).max_tokens, None);
+    }
+
+    #[test]
+    fn test_deepseek_drops_thinking() {
+        let request =
+            request_with_thinking(Some(2048)).into_deepseek("deepseek-reasoner".into(), None);
+        assert_eq!(
+            request.messages[1],
+            deepseek::RequestMessage::Assistant {
+                content: Some("4".into()),
+                tool_calls: Vec::new(),
+            }
+        );
+    }
+}
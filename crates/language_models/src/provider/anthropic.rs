@@ -1,5 +1,5 @@
 use crate::AllLanguageModelSettings;
-use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent};
+use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent, Usage};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{BTreeMap, HashMap};
 use editor::{Editor, EditorElement, EditorStyle};
@@ -14,7 +14,7 @@ use language_model::{
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
 };
-use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
+use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason, TokenUsage};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
@@ -265,7 +265,7 @@ pub fn count_anthropic_tokens(
 
                 for content in message.content {
                     match content {
-                        MessageContent::Text(text) => {
+                        MessageContent::Text(text) | MessageContent::Thinking { text, .. } => {
                             string_contents.push_str(&text);
                         }
                         MessageContent::Image(image) => {
@@ -378,6 +378,7 @@ impl LanguageModel for AnthropicModel {
             self.model.id().into(),
             self.model.default_temperature(),
             self.model.max_output_tokens(),
+            self.model.supports_thinking(),
         );
         let request = self.stream_completion(request, cx);
         let future = self.request_limiter.stream(async move {
@@ -409,6 +410,8 @@ impl LanguageModel for AnthropicModel {
             self.model.tool_model_id().into(),
             self.model.default_temperature(),
             self.model.max_output_tokens(),
+            // Thinking can't be used while a specific tool is forced.
+            false,
         );
         request.tool_choice = Some(anthropic::ToolChoice::Tool {
             name: tool_name.clone(),
@@ -446,12 +449,14 @@ pub fn map_to_language_model_completion_events(
     struct State {
         events: Pin<Box<dyn Send + Stream<Item = Result<Event, AnthropicError>>>>,
         tool_uses_by_index: HashMap<usize, RawToolUse>,
+        usage: TokenUsage,
     }
 
     futures::stream::unfold(
         State {
             events,
             tool_uses_by_index: HashMap::default(),
+            usage: TokenUsage::default(),
         },
         |mut state| async move {
             while let Some(event) = state.events.next().await {
@@ -463,10 +468,21 @@ pub fn map_to_language_model_completion_events(
                         } => match content_block {
                             ResponseContent::Text { text } => {
                                 return Some((
-                                    Some(Ok(LanguageModelCompletionEvent::Text(text))),
+                                    vec![Ok(LanguageModelCompletionEvent::Text(text))],
                                     state,
                                 ));
                             }
+                            ResponseContent::Thinking { thinking } => {
+                                return Some((
+                                    vec![Ok(LanguageModelCompletionEvent::Thinking {
+                                        text: thinking,
+                                        signature: None,
+                                    })],
+                                    state,
+                                ));
+                            }
+                            // Redacted thinking can't be shown, and isn't sent back.
+                            ResponseContent::RedactedThinking { .. } => {}
                             ResponseContent::ToolUse { id, name, .. } => {
                                 state.tool_uses_by_index.insert(
                                     index,
@@ -477,27 +493,45 @@ pub fn map_to_language_model_completion_events(
                                     },
                                 );
 
-                                return Some((None, state));
+                                return Some((Vec::new(), state));
                             }
                         },
                         Event::ContentBlockDelta { index, delta } => match delta {
                             ContentDelta::TextDelta { text } => {
                                 return Some((
-                                    Some(Ok(LanguageModelCompletionEvent::Text(text))),
+                                    vec![Ok(LanguageModelCompletionEvent::Text(text))],
+                                    state,
+                                ));
+                            }
+                            ContentDelta::ThinkingDelta { thinking } => {
+                                return Some((
+                                    vec![Ok(LanguageModelCompletionEvent::Thinking {
+                                        text: thinking,
+                                        signature: None,
+                                    })],
+                                    state,
+                                ));
+                            }
+                            ContentDelta::SignatureDelta { signature } => {
+                                return Some((
+                                    vec![Ok(LanguageModelCompletionEvent::Thinking {
+                                        text: String::new(),
+                                        signature: Some(signature),
+                                    })],
                                     state,
                                 ));
                             }
                             ContentDelta::InputJsonDelta { partial_json } => {
                                 if let Some(tool_use) = state.tool_uses_by_index.get_mut(&index) {
                                     tool_use.input_json.push_str(&partial_json);
-                                    return Some((None, state));
+                                    return Some((Vec::new(), state));
                                 }
                             }
                         },
                         Event::ContentBlockStop { index } => {
                             if let Some(tool_use) = state.tool_uses_by_index.remove(&index) {
                                 return Some((
-                                    Some(maybe!({
+                                    vec![maybe!({
                                         Ok(LanguageModelCompletionEvent::ToolUse(
                                             LanguageModelToolUse {
                                                 id: tool_use.id.into(),
@@ -512,20 +546,28 @@ pub fn map_to_language_model_completion_events(
                                                 },
                                             },
                                         ))
-                                    })),
+                                    })],
                                     state,
                                 ));
                             }
                         }
                         Event::MessageStart { message } => {
+                            update_usage(&mut state.usage, &message.usage);
                             return Some((
-                                Some(Ok(LanguageModelCompletionEvent::StartMessage {
-                                    message_id: message.id,
-                                })),
+                                vec![
+                                    Ok(LanguageModelCompletionEvent::StartMessage {
+                                        message_id: message.id,
+                                    }),
+                                    Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage)),
+                                ],
                                 state,
-                            ))
+                            ));
                         }
-                        Event::MessageDelta { delta, .. } => {
+                        Event::MessageDelta { delta, usage } => {
+                            update_usage(&mut state.usage, &usage);
+                            let mut events =
+                                vec![Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage))];
+
                             if let Some(stop_reason) = delta.stop_reason.as_deref() {
                                 let stop_reason = match stop_reason {
                                     "end_turn" => StopReason::EndTurn,
@@ -533,23 +575,21 @@ pub fn map_to_language_model_completion_events(
                                     "tool_use" => StopReason::ToolUse,
                                     _ => StopReason::EndTurn,
                                 };
-
-                                return Some((
-                                    Some(Ok(LanguageModelCompletionEvent::Stop(stop_reason))),
-                                    state,
-                                ));
+                                events.push(Ok(LanguageModelCompletionEvent::Stop(stop_reason)));
                             }
+
+                            return Some((events, state));
                         }
                         Event::Error { error } => {
                             return Some((
-                                Some(Err(anyhow!(AnthropicError::ApiError(error)))),
+                                vec![Err(anyhow!(AnthropicError::ApiError(error)))],
                                 state,
                             ));
                         }
                         _ => {}
                     },
                     Err(err) => {
-                        return Some((Some(Err(anyhow!(err))), state));
+                        return Some((vec![Err(anyhow!(err))], state));
                     }
                 }
             }
@@ -557,7 +597,24 @@ pub fn map_to_language_model_completion_events(
             None
         },
     )
-    .filter_map(|event| async move { event })
+    .flat_map(futures::stream::iter)
+}
+
+/// Updates the token usage with the counts in an event. Anthropic reports the input
+/// counts when the message starts and the running output count in message deltas.
+fn update_usage(usage: &mut TokenUsage, new: &Usage) {
+    if let Some(input_tokens) = new.input_tokens {
+        usage.input_tokens = input_tokens;
+    }
+    if let Some(output_tokens) = new.output_tokens {
+        usage.output_tokens = output_tokens;
+    }
+    if let Some(cache_creation_input_tokens) = new.cache_creation_input_tokens {
+        usage.cache_creation_input_tokens = cache_creation_input_tokens;
+    }
+    if let Some(cache_read_input_tokens) = new.cache_read_input_tokens {
+        usage.cache_read_input_tokens = cache_read_input_tokens;
+    }
 }
 
 struct ConfigurationView {
@@ -741,3 +798,168 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_update_usage() {
+        let mut usage = TokenUsage::default();
+        update_usage(
+            &mut usage,
+            &Usage {
+                input_tokens: Some(100),
+                output_tokens: Some(1),
+                cache_creation_input_tokens: Some(20),
+                cache_read_input_tokens: Some(30),
+            },
+        );
+        // Message deltas only report the output tokens, which replace the earlier count.
+        update_usage(
+            &mut usage,
+            &Usage {
+                input_tokens: None,
+                output_tokens: Some(15),
+                cache_creation_input_tokens: None,
+                cache_read_input_tokens: None,
+            },
+        );
+        assert_eq!(
+            usage,
+            TokenUsage {
+                input_tokens: 100,
+                output_tokens: 15,
+                cache_creation_input_tokens: 20,
+                cache_read_input_tokens: 30,
+            }
+        );
+    }
+
+    #[test]
+    fn test_usage_events() {
+        let events = [
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [],
+                    "model": "claude-3-5-sonnet-latest",
+                    "usage": {
+                        "input_tokens": 100,
+                        "output_tokens": 1,
+                        "cache_creation_input_tokens": 20,
+                        "cache_read_input_tokens": 30,
+                    },
+                },
+            }),
+            json!({
+                "type": "content_block_start",
+                "index": 0,
+                "content_block": { "type": "text", "text": "" },
+            }),
+            json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": { "type": "text_delta", "text": "Hello" },
+            }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({
+                "type": "message_delta",
+                "delta": { "stop_reason": "end_turn", "stop_sequence": null },
+                "usage": { "output_tokens": 15 },
+            }),
+            json!({ "type": "message_stop" }),
+        ];
+
+        let usage = |output_tokens| TokenUsage {
+            input_tokens: 100,
+            output_tokens,
+            cache_creation_input_tokens: 20,
+            cache_read_input_tokens: 30,
+        };
+        assert_eq!(
+            completion_events(&events),
+            [
+                LanguageModelCompletionEvent::StartMessage {
+                    message_id: "msg_1".into()
+                },
+                LanguageModelCompletionEvent::UsageUpdate(usage(1)),
+                LanguageModelCompletionEvent::Text(String::new()),
+                LanguageModelCompletionEvent::Text("Hello".into()),
+                LanguageModelCompletionEvent::UsageUpdate(usage(15)),
+                LanguageModelCompletionEvent::Stop(StopReason::EndTurn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thinking_events() {
+        let events = [
+            json!({
+                "type": "content_block_start",
+                "index": 0,
+                "content_block": { "type": "thinking", "thinking": "", "signature": "" },
+            }),
+            json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": { "type": "thinking_delta", "thinking": "Adding them up." },
+            }),
+            json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": { "type": "signature_delta", "signature": "signature" },
+            }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({
+                "type": "content_block_start",
+                "index": 1,
+                "content_block": { "type": "redacted_thinking", "data": "redacted" },
+            }),
+            json!({ "type": "content_block_stop", "index": 1 }),
+            json!({
+                "type": "content_block_start",
+                "index": 2,
+                "content_block": { "type": "text", "text": "4" },
+            }),
+            json!({ "type": "content_block_stop", "index": 2 }),
+        ];
+
+        assert_eq!(
+            completion_events(&events),
+            [
+                LanguageModelCompletionEvent::Thinking {
+                    text: String::new(),
+                    signature: None,
+                },
+                LanguageModelCompletionEvent::Thinking {
+                    text: "Adding them up.".into(),
+                    signature: None,
+                },
+                LanguageModelCompletionEvent::Thinking {
+                    text: String::new(),
+                    signature: Some("signature".into()),
+                },
+                LanguageModelCompletionEvent::Text("4".into()),
+            ]
+        );
+    }
+
+    fn completion_events(events: &[serde_json::Value]) -> Vec<LanguageModelCompletionEvent> {
+        let events = events
+            .iter()
+            .map(|event| Ok::<_, AnthropicError>(serde_json::from_value(event.clone()).unwrap()))
+            .collect::<Vec<_>>();
+        block_on(
+            map_to_language_model_completion_events(Box::pin(futures::stream::iter(events)))
+                .map(|event| event.unwrap())
+                .collect(),
+        )
+    }
+}
@@ -629,6 +629,7 @@ impl LanguageModel for CloudLanguageModel {
                     model.id().into(),
                     model.default_temperature(),
                     model.max_output_tokens(),
+                    model.supports_thinking(),
                 );
                 let client = self.client.clone();
                 let llm_api_token = self.llm_api_token.clone();
@@ -653,7 +654,15 @@ impl LanguageModel for CloudLanguageModel {
             }
             CloudModel::OpenAi(model) => {
                 let client = self.client.clone();
-                let request = request.into_open_ai(model.id().into(), model.max_output_tokens());
+                let mut request =
+                    request.into_open_ai(model.id().into(), model.max_output_tokens());
+                // The requests are forwarded to OpenAI's API, which reports the usage in a
+                // final chunk when asked.
+                if request.stream {
+                    request.stream_options = Some(open_ai::StreamOptions {
+                        include_usage: true,
+                    });
+                }
                 let llm_api_token = self.llm_api_token.clone();
                 let future = self.request_limiter.stream(async move {
                     let response = Self::perform_llm_completion(
@@ -668,15 +677,11 @@ impl LanguageModel for CloudLanguageModel {
                         },
                     )
                     .await?;
-                    Ok(open_ai::extract_text_from_events(response_lines(response)))
+                    Ok(super::open_ai::map_to_language_model_completion_events(
+                        response_lines(response),
+                    ))
                 });
-                async move {
-                    Ok(future
-                        .await?
-                        .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                        .boxed())
-                }
-                .boxed()
+                async move { Ok(future.await?.boxed()) }.boxed()
             }
             CloudModel::Google(model) => {
                 let client = self.client.clone();
@@ -695,17 +700,11 @@ impl LanguageModel for CloudLanguageModel {
                         },
                     )
                     .await?;
-                    Ok(google_ai::extract_text_from_events(response_lines(
-                        response,
-                    )))
+                    Ok(super::google::map_to_language_model_completion_events(
+                        response_lines(response),
+                    ))
                 });
-                async move {
-                    Ok(future
-                        .await?
-                        .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                        .boxed())
-                }
-                .boxed()
+                async move { Ok(future.await?.boxed()) }.boxed()
             }
         }
     }
@@ -727,6 +726,8 @@ impl LanguageModel for CloudLanguageModel {
                     model.tool_model_id().into(),
                     model.default_temperature(),
                     model.max_output_tokens(),
+                    // Thinking can't be used while a specific tool is forced.
+                    false,
                 );
                 request.tool_choice = Some(anthropic::ToolChoice::Tool {
                     name: tool_name.clone(),
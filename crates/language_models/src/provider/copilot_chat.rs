@@ -274,7 +274,8 @@ impl LanguageModel for CopilotChatLanguageModel {
 
 impl CopilotChatLanguageModel {
     pub fn to_copilot_chat_request(&self, request: LanguageModelRequest) -> CopilotChatRequest {
-        CopilotChatRequest::new(
+        let max_tokens = request.max_tokens;
+        let mut copilot_request = CopilotChatRequest::new(
             self.model.clone(),
             request
                 .messages
@@ -288,7 +289,9 @@ impl CopilotChatLanguageModel {
                     content: msg.string_contents(),
                 })
                 .collect(),
-        )
+        );
+        copilot_request.max_tokens = max_tokens;
+        copilot_request
     }
 }
 
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use collections::BTreeMap;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, Stream, StreamExt};
 use gpui::{
     AnyView, AppContext, AsyncApp, Entity, FontStyle, Subscription, Task, TextStyle, WhiteSpace,
 };
@@ -9,7 +9,7 @@ use http_client::HttpClient;
 use language_model::{
     LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role, TokenUsage,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -233,6 +233,16 @@ impl DeepSeekLanguageModel {
 
         async move { Ok(future.await?.boxed()) }.boxed()
     }
+
+    /// Whether requests go to DeepSeek's own API, rather than a compatible one configured
+    /// through `api_url`, which may reject options that DeepSeek's accepts.
+    fn uses_default_api_url(&self, cx: &AsyncApp) -> bool {
+        cx.read_entity(&self.state, |_, cx| {
+            let settings = &AllLanguageModelSettings::get_global(cx).deepseek;
+            settings.api_url.trim_end_matches('/') == deepseek::DEEPSEEK_API_URL
+        })
+        .unwrap_or(false)
+    }
 }
 
 impl LanguageModel for DeepSeekLanguageModel {
@@ -296,32 +306,18 @@ impl LanguageModel for DeepSeekLanguageModel {
         request: LanguageModelRequest,
         cx: &AsyncApp,
     ) -> BoxFuture<'static, Result<BoxStream<'static, Result<LanguageModelCompletionEvent>>>> {
-        let request = request.into_deepseek(self.model.id().to_string(), self.max_output_tokens());
+        let mut request =
+            request.into_deepseek(self.model.id().to_string(), self.max_output_tokens());
+        if request.stream && self.uses_default_api_url(cx) {
+            request.stream_options = Some(deepseek::StreamOptions {
+                include_usage: true,
+            });
+        }
         let stream = self.stream_completion(request, cx);
 
-        async move {
-            let stream = stream.await?;
-            Ok(stream
-                .map(|result| {
-                    result.and_then(|response| {
-                        response
-                            .choices
-                            .first()
-                            .ok_or_else(|| anyhow!("Empty response"))
-                            .map(|choice| {
-                                choice
-                                    .delta
-                                    .content
-                                    .clone()
-                                    .unwrap_or_default()
-                                    .map(LanguageModelCompletionEvent::Text)
-                            })
-                    })
-                })
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(map_to_language_model_completion_events(stream.await?).boxed()) }.boxed()
     }
+
     fn use_any_tool(
         &self,
         request: LanguageModelRequest,
@@ -375,6 +371,41 @@ impl LanguageModel for DeepSeekLanguageModel {
     }
 }
 
+pub fn map_to_language_model_completion_events(
+    events: impl Stream<Item = Result<deepseek::StreamResponse>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    events.flat_map(|result| {
+        let mut events = Vec::new();
+        match result {
+            Ok(response) => {
+                if let Some(choice) = response.choices.into_iter().next() {
+                    if let Some(text) = choice.delta.reasoning_content {
+                        events.push(Ok(LanguageModelCompletionEvent::Thinking {
+                            text,
+                            signature: None,
+                        }));
+                    }
+                    if let Some(text) = choice.delta.content {
+                        events.push(Ok(LanguageModelCompletionEvent::Text(text)));
+                    }
+                }
+                if let Some(usage) = response.usage {
+                    events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                        input_tokens: usage
+                            .prompt_tokens
+                            .saturating_sub(usage.prompt_cache_hit_tokens),
+                        output_tokens: usage.completion_tokens,
+                        cache_creation_input_tokens: 0,
+                        cache_read_input_tokens: usage.prompt_cache_hit_tokens,
+                    })));
+                }
+            }
+            Err(error) => events.push(Err(error)),
+        }
+        futures::stream::iter(events)
+    })
+}
+
 struct ConfigurationView {
     api_key_editor: Entity<Editor>,
     state: Entity<State>,
@@ -559,3 +590,86 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_map_to_language_model_completion_events() {
+        let events = [
+            json!({
+                "id": "1",
+                "object": "chat.completion.chunk",
+                "created": 1,
+                "model": "deepseek-reasoner",
+                "choices": [{
+                    "index": 0,
+                    "delta": { "role": "assistant", "content": null, "reasoning_content": "Adding" },
+                    "finish_reason": null,
+                }],
+            }),
+            json!({
+                "id": "1",
+                "object": "chat.completion.chunk",
+                "created": 1,
+                "model": "deepseek-reasoner",
+                "choices": [{
+                    "index": 0,
+                    "delta": { "content": null, "reasoning_content": " them up." },
+                    "finish_reason": null,
+                }],
+            }),
+            json!({
+                "id": "1",
+                "object": "chat.completion.chunk",
+                "created": 1,
+                "model": "deepseek-reasoner",
+                "choices": [{
+                    "index": 0,
+                    "delta": { "content": "4" },
+                    "finish_reason": "stop",
+                }],
+                "usage": {
+                    "prompt_tokens": 100,
+                    "completion_tokens": 10,
+                    "total_tokens": 110,
+                    "prompt_cache_hit_tokens": 60,
+                    "prompt_cache_miss_tokens": 40,
+                },
+            }),
+        ];
+        let events = events
+            .into_iter()
+            .map(|event| Ok(serde_json::from_value::<deepseek::StreamResponse>(event).unwrap()));
+
+        let events = block_on(
+            map_to_language_model_completion_events(futures::stream::iter(events))
+                .map(|event| event.unwrap())
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            events,
+            [
+                LanguageModelCompletionEvent::Thinking {
+                    text: "Adding".into(),
+                    signature: None,
+                },
+                LanguageModelCompletionEvent::Thinking {
+                    text: " them up.".into(),
+                    signature: None,
+                },
+                LanguageModelCompletionEvent::Text("4".into()),
+                LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                    input_tokens: 40,
+                    output_tokens: 10,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: 60,
+                }),
+            ]
+        );
+    }
+}
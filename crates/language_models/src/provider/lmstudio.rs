@@ -243,7 +243,11 @@ impl LmStudioLanguageModel {
                 })
                 .collect(),
             stream: true,
-            max_tokens: Some(-1),
+            max_tokens: Some(
+                request
+                    .max_tokens
+                    .map_or(-1, |max_tokens| max_tokens as i32),
+            ),
             stop: Some(request.stop),
             temperature: request.temperature.or(Some(0.0)),
             tools: vec![],
@@ -2,12 +2,12 @@ use anyhow::{anyhow, bail, Result};
 use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use gpui::{AnyView, App, AsyncApp, Context, Subscription, Task};
 use http_client::HttpClient;
-use language_model::LanguageModelCompletionEvent;
 use language_model::{
     LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
     LanguageModelRequest, RateLimiter, Role,
 };
+use language_model::{LanguageModelCompletionEvent, TokenUsage};
 use ollama::{
     get_models, preload_model, stream_chat_completion, ChatMessage, ChatOptions, ChatRequest,
     ChatResponseDelta, KeepAlive, OllamaToolCall,
@@ -253,6 +253,7 @@ impl OllamaLanguageModel {
             stream: true,
             options: Some(ChatOptions {
                 num_ctx: Some(self.model.max_tokens),
+                num_predict: request.max_tokens.map(|max_tokens| max_tokens as isize),
                 stop: Some(request.stop),
                 temperature: request.temperature.or(Some(1.0)),
                 ..Default::default()
@@ -338,7 +339,8 @@ impl LanguageModel for OllamaLanguageModel {
         let future = self.request_limiter.stream(async move {
             let response = stream_chat_completion(http_client.as_ref(), &api_url, request).await?;
             let stream = response
-                .filter_map(|response| async move {
+                .flat_map(|response| {
+                    let mut events = Vec::new();
                     match response {
                         Ok(delta) => {
                             let content = match delta.message {
@@ -346,22 +348,27 @@ impl LanguageModel for OllamaLanguageModel {
                                 ChatMessage::Assistant { content, .. } => content,
                                 ChatMessage::System { content } => content,
                             };
-                            Some(Ok(content))
+                            events.push(Ok(LanguageModelCompletionEvent::Text(content)));
+                            if delta.prompt_eval_count.is_some() || delta.eval_count.is_some() {
+                                events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(
+                                    TokenUsage {
+                                        input_tokens: delta.prompt_eval_count.unwrap_or(0),
+                                        output_tokens: delta.eval_count.unwrap_or(0),
+                                        cache_creation_input_tokens: 0,
+                                        cache_read_input_tokens: 0,
+                                    },
+                                )));
+                            }
                         }
-                        Err(error) => Some(Err(error)),
+                        Err(error) => events.push(Err(error)),
                     }
+                    futures::stream::iter(events)
                 })
                 .boxed();
             Ok(stream)
         });
 
-        async move {
-            Ok(future
-                .await?
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(future.await?.boxed()) }.boxed()
     }
 
     fn use_any_tool(
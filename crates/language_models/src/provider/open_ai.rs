@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use collections::BTreeMap;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, FutureExt, StreamExt};
+use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
 use gpui::{
     AnyView, App, AsyncApp, Context, Entity, FontStyle, Subscription, Task, TextStyle, WhiteSpace,
 };
@@ -9,7 +9,7 @@ use http_client::HttpClient;
 use language_model::{
     LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
-    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
+    LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role, TokenUsage,
 };
 use open_ai::{
     stream_completion, FunctionDefinition, ResponseStreamEvent, ToolChoice, ToolDefinition,
@@ -243,6 +243,16 @@ impl OpenAiLanguageModel {
 
         async move { Ok(future.await?.boxed()) }.boxed()
     }
+
+    /// Whether requests go to OpenAI's own API, rather than a compatible one configured
+    /// through `api_url`, which may reject options that OpenAI's accepts.
+    fn uses_default_api_url(&self, cx: &AsyncApp) -> bool {
+        cx.read_entity(&self.state, |_, cx| {
+            let settings = &AllLanguageModelSettings::get_global(cx).openai;
+            settings.api_url.trim_end_matches('/') == open_ai::OPEN_AI_API_URL
+        })
+        .unwrap_or(false)
+    }
 }
 
 impl LanguageModel for OpenAiLanguageModel {
@@ -290,14 +300,15 @@ impl LanguageModel for OpenAiLanguageModel {
         'static,
         Result<futures::stream::BoxStream<'static, Result<LanguageModelCompletionEvent>>>,
     > {
-        let request = request.into_open_ai(self.model.id().into(), self.max_output_tokens());
-        let completions = self.stream_completion(request, cx);
-        async move {
-            Ok(open_ai::extract_text_from_events(completions.await?)
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
+        let mut request = request.into_open_ai(self.model.id().into(), self.max_output_tokens());
+        if request.stream && self.uses_default_api_url(cx) {
+            request.stream_options = Some(open_ai::StreamOptions {
+                include_usage: true,
+            });
         }
-        .boxed()
+        let completions = self.stream_completion(request, cx);
+        async move { Ok(map_to_language_model_completion_events(completions.await?).boxed()) }
+            .boxed()
     }
 
     fn use_any_tool(
@@ -338,6 +349,34 @@ impl LanguageModel for OpenAiLanguageModel {
     }
 }
 
+pub fn map_to_language_model_completion_events(
+    events: impl Stream<Item = Result<ResponseStreamEvent>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    events.flat_map(|event| {
+        let mut events = Vec::new();
+        match event {
+            Ok(mut event) => {
+                if let Some(text) = event.choices.pop().and_then(|choice| choice.delta.content) {
+                    events.push(Ok(LanguageModelCompletionEvent::Text(text)));
+                }
+                if let Some(usage) = event.usage {
+                    let cached_tokens = usage
+                        .prompt_tokens_details
+                        .map_or(0, |details| details.cached_tokens);
+                    events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                        input_tokens: usage.prompt_tokens.saturating_sub(cached_tokens),
+                        output_tokens: usage.completion_tokens,
+                        cache_creation_input_tokens: 0,
+                        cache_read_input_tokens: cached_tokens,
+                    })));
+                }
+            }
+            Err(error) => events.push(Err(error)),
+        }
+        futures::stream::iter(events)
+    })
+}
+
 pub fn count_open_ai_tokens(
     request: LanguageModelRequest,
     model: open_ai::Model,
@@ -565,3 +604,71 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_map_to_language_model_completion_events() {
+        let events = [
+            json!({
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "delta": { "role": "assistant", "content": "Hello" },
+                    "finish_reason": null,
+                }],
+                "usage": null,
+            }),
+            json!({
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "delta": { "content": " there" },
+                    "finish_reason": "stop",
+                }],
+                "usage": null,
+            }),
+            // The usage comes in a final chunk without choices, when it's requested.
+            json!({
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [],
+                "usage": {
+                    "prompt_tokens": 100,
+                    "completion_tokens": 10,
+                    "total_tokens": 110,
+                    "prompt_tokens_details": { "cached_tokens": 60 },
+                },
+            }),
+        ];
+        let events = events
+            .into_iter()
+            .map(|event| Ok(serde_json::from_value::<ResponseStreamEvent>(event).unwrap()));
+
+        let events = block_on(
+            map_to_language_model_completion_events(futures::stream::iter(events))
+                .map(|event| event.unwrap())
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            events,
+            [
+                LanguageModelCompletionEvent::Text("Hello".into()),
+                LanguageModelCompletionEvent::Text(" there".into()),
+                LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                    input_tokens: 40,
+                    output_tokens: 10,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: 60,
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,460 @@
+use crate::{LocalHistory, RestoreSnapshot, Snapshot, Toggle};
+use diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task,
+    WeakEntity, Window,
+};
+use language::{Buffer, BufferEvent};
+use multi_buffer::MultiBuffer;
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::{any::TypeId, sync::Arc, time::Duration};
+use time::{OffsetDateTime, UtcOffset};
+use time_format::TimestampFormat;
+use ui::{prelude::*, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+use workspace::{searchable::SearchableItemHandle, Item, ModalView, Workspace};
+
+pub struct LocalHistoryPicker {
+    picker: Entity<Picker<LocalHistoryPickerDelegate>>,
+}
+
+impl LocalHistoryPicker {
+    pub(crate) fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(|workspace, _: &Toggle, window, cx| {
+            Self::toggle(workspace, window, cx);
+        });
+    }
+
+    /// Lists the snapshots of the file in the active editor.
+    fn toggle(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Option<()> {
+        let local_history = LocalHistory::global(cx)?;
+        let editor = workspace.active_item(cx)?.act_as::<Editor>(cx)?;
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        let abs_path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+        let snapshots = local_history.read(cx).snapshots(&abs_path, cx);
+        let weak_workspace = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate = LocalHistoryPickerDelegate {
+                local_history_picker: cx.entity().downgrade(),
+                workspace: weak_workspace,
+                local_history,
+                buffer,
+                snapshots: Vec::new(),
+                matches: Vec::new(),
+                selected_index: 0,
+                now: OffsetDateTime::now_utc(),
+                local_timezone: local_timezone(),
+            };
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            cx.spawn_in(window, {
+                let picker = picker.downgrade();
+                |_, mut cx| async move {
+                    let snapshots = snapshots.await?;
+                    picker.update_in(&mut cx, |picker, window, cx| {
+                        picker.delegate.snapshots = snapshots;
+                        picker.refresh(window, cx);
+                    })
+                }
+            })
+            .detach_and_log_err(cx);
+            Self { picker }
+        });
+        Some(())
+    }
+}
+
+impl Render for LocalHistoryPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for LocalHistoryPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for LocalHistoryPicker {}
+impl ModalView for LocalHistoryPicker {}
+
+pub struct LocalHistoryPickerDelegate {
+    local_history_picker: WeakEntity<LocalHistoryPicker>,
+    workspace: WeakEntity<Workspace>,
+    local_history: Entity<LocalHistory>,
+    buffer: Entity<Buffer>,
+    snapshots: Vec<Snapshot>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    now: OffsetDateTime,
+    local_timezone: UtcOffset,
+}
+
+impl LocalHistoryPickerDelegate {
+    fn format_timestamp(&self, snapshot: &Snapshot, format: TimestampFormat) -> String {
+        time_format::format_localized_timestamp(
+            snapshot.timestamp(),
+            self.now,
+            self.local_timezone,
+            format,
+        )
+    }
+}
+
+impl PickerDelegate for LocalHistoryPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Compare with snapshot…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        "No snapshots".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .snapshots
+            .iter()
+            .enumerate()
+            .map(|(id, snapshot)| {
+                StringMatchCandidate::new(
+                    id,
+                    &self.format_timestamp(snapshot, TimestampFormat::EnhancedAbsolute),
+                )
+            })
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(snapshot) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.snapshots.get(mat.candidate_id))
+            .cloned()
+        else {
+            return;
+        };
+        let load_snapshot = self.local_history.read(cx).load_snapshot(&snapshot, cx);
+        let buffer = self.buffer.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            let text = load_snapshot.await?;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let project = workspace.project().clone();
+                let view =
+                    cx.new(|cx| LocalHistoryView::new(buffer, snapshot, text, project, window, cx));
+                workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.local_history_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let snapshot = self.snapshots.get(mat.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            mat.string.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(self.format_timestamp(snapshot, TimestampFormat::Relative))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+/// Shows the changes made to a file since one of its snapshots, and allows restoring it.
+pub struct LocalHistoryView {
+    editor: Entity<Editor>,
+    buffer: Entity<Buffer>,
+    /// A buffer holding the snapshot's contents, which the file is diffed against.
+    snapshot_buffer: Entity<Buffer>,
+    diff: Entity<BufferDiff>,
+    title: SharedString,
+    timestamp: String,
+    recalculate_diff: Task<()>,
+    _buffer_subscription: Subscription,
+}
+
+impl LocalHistoryView {
+    pub fn new(
+        buffer: Entity<Buffer>,
+        snapshot: Snapshot,
+        text: String,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let language = buffer.read(cx).language().cloned();
+        let snapshot_buffer = cx.new(|cx| {
+            let mut snapshot_buffer = Buffer::local(text, cx);
+            snapshot_buffer.set_language(language, cx);
+            snapshot_buffer
+        });
+        let diff = cx.new(|cx| {
+            let mut diff = BufferDiff::new(&buffer, cx);
+            let _ =
+                diff.set_base_text(snapshot_buffer.clone(), buffer.read(cx).text_snapshot(), cx);
+            diff
+        });
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_read_only(true);
+            editor
+        });
+
+        let file_name = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let timestamp = time_format::format_localized_timestamp(
+            snapshot.timestamp(),
+            OffsetDateTime::now_utc(),
+            local_timezone(),
+            TimestampFormat::MediumAbsolute,
+        );
+        Self {
+            editor,
+            _buffer_subscription: cx.subscribe(&buffer, Self::on_buffer_event),
+            buffer,
+            snapshot_buffer,
+            diff,
+            title: format!("{file_name} ({timestamp})").into(),
+            timestamp,
+            recalculate_diff: Task::ready(()),
+        }
+    }
+
+    fn on_buffer_event(&mut self, _: Entity<Buffer>, event: &BufferEvent, cx: &mut Context<Self>) {
+        if let BufferEvent::Edited = event {
+            self.recalculate_diff = cx.spawn(|this, mut cx| async move {
+                cx.background_executor()
+                    .timer(Duration::from_millis(50))
+                    .await;
+                this.update(&mut cx, |this, cx| {
+                    let buffer = this.buffer.read(cx).text_snapshot();
+                    let snapshot_buffer = this.snapshot_buffer.clone();
+                    let _ = this.diff.update(cx, |diff, cx| {
+                        diff.set_base_text(snapshot_buffer, buffer, cx)
+                    });
+                })
+                .log_err();
+            });
+        }
+    }
+
+    /// Replaces the file's contents with the snapshot's, as an edit that can be undone.
+    fn restore(&mut self, _: &RestoreSnapshot, _: &mut Window, cx: &mut Context<Self>) {
+        // Keep the contents being replaced, in case they're needed again.
+        if let Some(local_history) = LocalHistory::global(cx) {
+            local_history.read(cx).snapshot_buffer(&self.buffer, cx);
+        }
+        let text = self.snapshot_buffer.read(cx).text();
+        let diff = self.buffer.read(cx).diff(text, cx);
+        let buffer = self.buffer.clone();
+        cx.spawn(|_, mut cx| async move {
+            let diff = diff.await;
+            buffer.update(&mut cx, |buffer, cx| buffer.apply_diff(diff, cx))
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl Render for LocalHistoryView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.editor.focus_handle(cx);
+        v_flex()
+            .size_full()
+            .key_context("LocalHistoryView")
+            .on_action(cx.listener(Self::restore))
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        Label::new(format!(
+                            "Changes since the snapshot from {}",
+                            self.timestamp
+                        ))
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("restore-snapshot", "Restore Snapshot")
+                            .icon(IconName::HistoryRerun)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .key_binding(KeyBinding::for_action_in(
+                                &RestoreSnapshot,
+                                &focus_handle,
+                                window,
+                            ))
+                            .on_click(move |_, window, cx| {
+                                focus_handle.dispatch_action(&RestoreSnapshot, window, cx)
+                            }),
+                    ),
+            )
+            .child(self.editor.clone())
+    }
+}
+
+impl Focusable for LocalHistoryView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for LocalHistoryView {}
+
+impl Item for LocalHistoryView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+}
+
+fn local_timezone() -> UtcOffset {
+    let local_offset = chrono::Local::now().offset().local_minus_utc();
+    UtcOffset::from_whole_seconds(local_offset).unwrap_or(UtcOffset::UTC)
+}
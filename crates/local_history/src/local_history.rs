@@ -0,0 +1,546 @@
+mod history_view;
+
+use anyhow::Result;
+use collections::{BTreeMap, HashMap};
+use fs::{Fs, RemoveOptions};
+use futures::{channel::mpsc, StreamExt as _};
+use gpui::{
+    actions, App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task,
+    WeakEntity, Window,
+};
+use language::{Buffer, BufferEvent};
+use project::buffer_store::BufferStoreEvent;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsStore};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use time::OffsetDateTime;
+use util::ResultExt as _;
+use workspace::Workspace;
+
+pub use history_view::{LocalHistoryPicker, LocalHistoryView};
+
+actions!(local_history, [Toggle, RestoreSnapshot]);
+
+/// The file in each history directory that records which file the snapshots are of.
+const PATH_FILE_NAME: &str = "path";
+
+pub fn init(cx: &mut App) {
+    LocalHistorySettings::register(cx);
+    let fs = <dyn Fs>::global(cx);
+    let local_history = cx.new(|cx| LocalHistory::new(fs, paths::local_history_dir().clone(), cx));
+    cx.set_global(GlobalLocalHistory(local_history));
+    cx.observe_new(track_project_buffers).detach();
+    cx.observe_new(LocalHistoryPicker::register).detach();
+}
+
+/// Settings for the snapshots that local history keeps of files.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct LocalHistorySettings {
+    pub enabled: bool,
+    pub snapshot_interval_seconds: u64,
+    pub max_snapshots_per_file: usize,
+    pub max_size_mb: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct LocalHistorySettingsContent {
+    /// Whether to record a snapshot of a file whenever it's saved.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// How often to also record snapshots of files with unsaved changes, in seconds.
+    /// When 0, snapshots are only recorded on save.
+    ///
+    /// Default: 0
+    pub snapshot_interval_seconds: Option<u64>,
+    /// The maximum number of snapshots to keep for each file.
+    ///
+    /// Default: 50
+    pub max_snapshots_per_file: Option<usize>,
+    /// The maximum total size of all snapshots, in megabytes. The oldest
+    /// snapshots are deleted first when it's exceeded.
+    ///
+    /// Default: 256
+    pub max_size_mb: Option<u64>,
+}
+
+impl Settings for LocalHistorySettings {
+    const KEY: Option<&'static str> = Some("local_history");
+
+    type FileContent = LocalHistorySettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+impl LocalHistorySettings {
+    fn snapshot_interval(&self) -> Option<Duration> {
+        (self.enabled && self.snapshot_interval_seconds > 0)
+            .then(|| Duration::from_secs(self.snapshot_interval_seconds))
+    }
+}
+
+/// A snapshot of a file's contents at a point in time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    /// When the snapshot was taken, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    path: PathBuf,
+}
+
+impl Snapshot {
+    pub fn timestamp(&self) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp_nanos(self.timestamp_ms as i128 * 1_000_000)
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+    }
+}
+
+struct PendingSnapshot {
+    abs_path: PathBuf,
+    text: String,
+    max_snapshots_per_file: usize,
+    max_size: u64,
+}
+
+struct GlobalLocalHistory(Entity<LocalHistory>);
+
+impl Global for GlobalLocalHistory {}
+
+/// Keeps snapshots of local files, independently of version control, so that earlier
+/// versions of a file can be compared against and restored.
+///
+/// Snapshots are stored in a directory per file, named after a hash of the file's path,
+/// and are recorded whenever a file is saved and, optionally, periodically while it has
+/// unsaved changes.
+pub struct LocalHistory {
+    fs: Arc<dyn Fs>,
+    dir: Arc<Path>,
+    buffers: HashMap<EntityId, WeakEntity<Buffer>>,
+    snapshot_tx: mpsc::UnboundedSender<PendingSnapshot>,
+    snapshot_interval: Option<Duration>,
+    _write_snapshots: Task<()>,
+    _snapshot_timer: Option<Task<()>>,
+    _settings_subscription: Subscription,
+}
+
+impl LocalHistory {
+    pub fn new(fs: Arc<dyn Fs>, dir: PathBuf, cx: &mut Context<Self>) -> Self {
+        let dir: Arc<Path> = dir.into();
+        let (snapshot_tx, mut snapshot_rx) = mpsc::unbounded::<PendingSnapshot>();
+        // Snapshots are written one at a time, so that pruning never races with a write.
+        let _write_snapshots = cx.background_executor().spawn({
+            let fs = fs.clone();
+            let dir = dir.clone();
+            async move {
+                let mut sizes = None;
+                while let Some(snapshot) = snapshot_rx.next().await {
+                    write_snapshot(fs.as_ref(), &dir, snapshot, &mut sizes)
+                        .await
+                        .log_err();
+                }
+            }
+        });
+        let mut this = Self {
+            fs,
+            dir,
+            buffers: HashMap::default(),
+            snapshot_tx,
+            snapshot_interval: None,
+            _write_snapshots,
+            _snapshot_timer: None,
+            _settings_subscription: cx
+                .observe_global::<SettingsStore>(|this, cx| this.schedule_snapshots(cx)),
+        };
+        this.schedule_snapshots(cx);
+        this
+    }
+
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalLocalHistory>()
+            .map(|local_history| local_history.0.clone())
+    }
+
+    /// Records a snapshot of a file's contents, unless they're the same as its latest snapshot.
+    pub fn record_snapshot(&self, abs_path: PathBuf, text: String, cx: &App) {
+        let settings = LocalHistorySettings::get_global(cx);
+        if !settings.enabled {
+            return;
+        }
+        self.snapshot_tx
+            .unbounded_send(PendingSnapshot {
+                abs_path,
+                text,
+                max_snapshots_per_file: settings.max_snapshots_per_file.max(1),
+                max_size: settings.max_size_mb * 1024 * 1024,
+            })
+            .ok();
+    }
+
+    /// Returns the snapshots of a file, newest first.
+    pub fn snapshots(&self, abs_path: &Path, cx: &App) -> Task<Result<Vec<Snapshot>>> {
+        let fs = self.fs.clone();
+        let history_dir = history_dir(&self.dir, abs_path);
+        cx.background_executor()
+            .spawn(async move { list_snapshots(fs.as_ref(), &history_dir).await })
+    }
+
+    pub fn load_snapshot(&self, snapshot: &Snapshot, cx: &App) -> Task<Result<String>> {
+        let fs = self.fs.clone();
+        let path = snapshot.path.clone();
+        cx.background_executor()
+            .spawn(async move { fs.load(&path).await })
+    }
+
+    /// Records a snapshot of the buffer's file whenever it's saved.
+    pub fn track_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.entity_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+        self.buffers.insert(buffer_id, buffer.downgrade());
+        cx.subscribe(buffer, |this, buffer, event, cx| {
+            if let BufferEvent::Saved = event {
+                this.snapshot_buffer(&buffer, cx);
+            }
+        })
+        .detach();
+        cx.observe_release(buffer, move |this, _, _| {
+            this.buffers.remove(&buffer_id);
+        })
+        .detach();
+    }
+
+    fn snapshot_buffer(&self, buffer: &Entity<Buffer>, cx: &App) {
+        let buffer = buffer.read(cx);
+        if buffer.is_large_file() {
+            return;
+        }
+        let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+            return;
+        };
+        self.record_snapshot(file.abs_path(cx), buffer.text(), cx);
+    }
+
+    fn schedule_snapshots(&mut self, cx: &mut Context<Self>) {
+        let snapshot_interval = LocalHistorySettings::get_global(cx).snapshot_interval();
+        if snapshot_interval == self.snapshot_interval {
+            return;
+        }
+        self.snapshot_interval = snapshot_interval;
+        self._snapshot_timer = snapshot_interval.map(|interval| {
+            cx.spawn(|this, mut cx| async move {
+                loop {
+                    cx.background_executor().timer(interval).await;
+                    let snapshotted = this.update(&mut cx, |this, cx| {
+                        for buffer in this.buffers.values().filter_map(|buffer| buffer.upgrade()) {
+                            if buffer.read(cx).is_dirty() {
+                                this.snapshot_buffer(&buffer, cx);
+                            }
+                        }
+                    });
+                    if snapshotted.is_err() {
+                        break;
+                    }
+                }
+            })
+        });
+    }
+}
+
+fn track_project_buffers(
+    workspace: &mut Workspace,
+    _window: Option<&mut Window>,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(local_history) = LocalHistory::global(cx) else {
+        return;
+    };
+    let project = workspace.project().read(cx);
+    if !project.is_local() {
+        return;
+    }
+    let buffer_store = project.buffer_store().clone();
+    let buffers = buffer_store.read(cx).buffers().collect::<Vec<_>>();
+    local_history.update(cx, |local_history, cx| {
+        for buffer in &buffers {
+            local_history.track_buffer(buffer, cx);
+        }
+    });
+    cx.subscribe(&buffer_store, move |_, _, event, cx| {
+        if let BufferStoreEvent::BufferAdded(buffer) = event {
+            local_history.update(cx, |local_history, cx| {
+                local_history.track_buffer(buffer, cx)
+            });
+        }
+    })
+    .detach();
+}
+
+fn history_dir(dir: &Path, abs_path: &Path) -> PathBuf {
+    let hash = Sha256::digest(abs_path.to_string_lossy().as_bytes());
+    dir.join(hex::encode(hash))
+}
+
+async fn list_snapshots(fs: &dyn Fs, history_dir: &Path) -> Result<Vec<Snapshot>> {
+    if !fs.is_dir(history_dir).await {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = Vec::new();
+    let mut entries = fs.read_dir(history_dir).await?;
+    while let Some(path) = entries.next().await {
+        let path = path?;
+        let timestamp_ms = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse().ok());
+        if let Some(timestamp_ms) = timestamp_ms {
+            snapshots.push(Snapshot { timestamp_ms, path });
+        }
+    }
+    snapshots.sort_by(|a, b| b.timestamp_ms.cmp(&a.timestamp_ms));
+    Ok(snapshots)
+}
+
+/// Writes a snapshot and prunes old snapshots. `sizes` is loaded by the first write, and is
+/// dropped to be loaded again if pruning fails, since it may no longer match the disk.
+async fn write_snapshot(
+    fs: &dyn Fs,
+    dir: &Path,
+    snapshot: PendingSnapshot,
+    sizes: &mut Option<SnapshotSizes>,
+) -> Result<()> {
+    let history_dir = history_dir(dir, &snapshot.abs_path);
+    let snapshots = list_snapshots(fs, &history_dir).await?;
+    let mut timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    if let Some(latest) = snapshots.first() {
+        if fs.load(&latest.path).await.ok().as_deref() == Some(snapshot.text.as_str()) {
+            return Ok(());
+        }
+        // Keep snapshots in order even if the clock goes backwards.
+        timestamp_ms = timestamp_ms.max(latest.timestamp_ms + 1);
+    } else {
+        fs.create_dir(&history_dir).await?;
+        fs.atomic_write(
+            history_dir.join(PATH_FILE_NAME),
+            snapshot.abs_path.to_string_lossy().into_owned(),
+        )
+        .await?;
+    }
+    let new_snapshot = Snapshot {
+        timestamp_ms,
+        path: history_dir.join(timestamp_ms.to_string()),
+    };
+    let len = snapshot.text.len() as u64;
+    fs.atomic_write(new_snapshot.path.clone(), snapshot.text)
+        .await?;
+
+    let stale_snapshots = snapshots
+        .iter()
+        .skip(snapshot.max_snapshots_per_file.saturating_sub(1));
+    for stale_snapshot in stale_snapshots.clone() {
+        fs.remove_file(&stale_snapshot.path, RemoveOptions::default())
+            .await
+            .log_err();
+    }
+
+    let mut snapshot_sizes = match sizes.take() {
+        Some(mut snapshot_sizes) => {
+            snapshot_sizes.insert(new_snapshot, len, history_dir);
+            for stale_snapshot in stale_snapshots {
+                snapshot_sizes.remove(stale_snapshot);
+            }
+            snapshot_sizes
+        }
+        None => SnapshotSizes::load(fs, dir).await?,
+    };
+    snapshot_sizes
+        .enforce_max_size(fs, snapshot.max_size)
+        .await?;
+    *sizes = Some(snapshot_sizes);
+    Ok(())
+}
+
+/// The sizes of the snapshots of all files. They're read from disk once, and then kept up to
+/// date as snapshots are written and deleted, so that staying within the size limit doesn't
+/// require listing every history directory on each write.
+#[derive(Default)]
+struct SnapshotSizes {
+    /// The size and history directory of each snapshot, oldest first.
+    snapshots: BTreeMap<(u64, PathBuf), (u64, PathBuf)>,
+    total_size: u64,
+}
+
+impl SnapshotSizes {
+    async fn load(fs: &dyn Fs, dir: &Path) -> Result<Self> {
+        let mut sizes = Self::default();
+        let mut history_dirs = fs.read_dir(dir).await?;
+        while let Some(history_dir) = history_dirs.next().await {
+            let history_dir = history_dir?;
+            for snapshot in list_snapshots(fs, &history_dir).await? {
+                let len = fs
+                    .metadata(&snapshot.path)
+                    .await?
+                    .map_or(0, |metadata| metadata.len);
+                sizes.insert(snapshot, len, history_dir.clone());
+            }
+        }
+        Ok(sizes)
+    }
+
+    fn insert(&mut self, snapshot: Snapshot, len: u64, history_dir: PathBuf) {
+        let key = (snapshot.timestamp_ms, snapshot.path);
+        if let Some((old_len, _)) = self.snapshots.insert(key, (len, history_dir)) {
+            self.total_size -= old_len;
+        }
+        self.total_size += len;
+    }
+
+    fn remove(&mut self, snapshot: &Snapshot) {
+        let key = (snapshot.timestamp_ms, snapshot.path.clone());
+        if let Some((len, _)) = self.snapshots.remove(&key) {
+            self.total_size -= len;
+        }
+    }
+
+    /// Deletes the oldest snapshots of all files until their total size is within `max_size`.
+    async fn enforce_max_size(&mut self, fs: &dyn Fs, max_size: u64) -> Result<()> {
+        let mut emptied_dirs = Vec::new();
+        // The newest snapshot is always kept, however large it is.
+        while self.total_size > max_size && self.snapshots.len() > 1 {
+            let Some(((_, path), (len, history_dir))) = self.snapshots.pop_first() else {
+                break;
+            };
+            self.total_size -= len;
+            fs.remove_file(&path, RemoveOptions::default()).await?;
+            if !emptied_dirs.contains(&history_dir) {
+                emptied_dirs.push(history_dir);
+            }
+        }
+        for history_dir in emptied_dirs {
+            if list_snapshots(fs, &history_dir).await?.is_empty() {
+                fs.remove_dir(
+                    &history_dir,
+                    RemoveOptions {
+                        recursive: true,
+                        ignore_if_not_exists: true,
+                    },
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_local_history(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            LocalHistorySettings::register(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/history", serde_json::json!({})).await;
+        let local_history = cx.new(|cx| LocalHistory::new(fs.clone(), "/history".into(), cx));
+
+        // Unchanged contents aren't recorded twice.
+        record(&local_history, "/project/a.txt", "one", cx);
+        record(&local_history, "/project/a.txt", "one", cx);
+        record(&local_history, "/project/a.txt", "two", cx);
+        assert_eq!(
+            snapshot_texts(&local_history, "/project/a.txt", cx).await,
+            ["two", "one"]
+        );
+        assert!(snapshot_texts(&local_history, "/project/b.txt", cx)
+            .await
+            .is_empty());
+
+        // Only the newest snapshots of each file are kept.
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<LocalHistorySettings>(cx, |settings| {
+                settings.max_snapshots_per_file = Some(2);
+            });
+        });
+        record(&local_history, "/project/a.txt", "three", cx);
+        assert_eq!(
+            snapshot_texts(&local_history, "/project/a.txt", cx).await,
+            ["three", "two"]
+        );
+
+        // The oldest snapshots of all files are deleted to stay within the size limit.
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<LocalHistorySettings>(cx, |settings| {
+                settings.max_size_mb = Some(1);
+            });
+        });
+        let large_text = "a".repeat(600 * 1024);
+        record(&local_history, "/project/b.txt", &large_text, cx);
+        assert_eq!(
+            snapshot_texts(&local_history, "/project/a.txt", cx).await,
+            ["three", "two"]
+        );
+        record(&local_history, "/project/c.txt", &large_text, cx);
+        assert!(snapshot_texts(&local_history, "/project/a.txt", cx)
+            .await
+            .is_empty());
+        assert!(snapshot_texts(&local_history, "/project/b.txt", cx)
+            .await
+            .is_empty());
+        assert_eq!(
+            snapshot_texts(&local_history, "/project/c.txt", cx).await,
+            [large_text]
+        );
+    }
+
+    fn record(
+        local_history: &Entity<LocalHistory>,
+        path: &str,
+        text: &str,
+        cx: &mut TestAppContext,
+    ) {
+        local_history.update(cx, |local_history, cx| {
+            local_history.record_snapshot(path.into(), text.into(), cx)
+        });
+        cx.run_until_parked();
+    }
+
+    async fn snapshot_texts(
+        local_history: &Entity<LocalHistory>,
+        path: &str,
+        cx: &mut TestAppContext,
+    ) -> Vec<String> {
+        let snapshots = local_history
+            .update(cx, |local_history, cx| {
+                local_history.snapshots(Path::new(path), cx)
+            })
+            .await
+            .unwrap();
+        let mut texts = Vec::new();
+        for snapshot in snapshots {
+            let text = local_history
+                .update(cx, |local_history, cx| {
+                    local_history.load_snapshot(&snapshot, cx)
+                })
+                .await
+                .unwrap();
+            texts.push(text);
+        }
+        texts
+    }
+}
@@ -1141,6 +1141,21 @@ impl MultiBuffer {
         }
     }
 
+    /// Returns the transaction that would be undone next.
+    pub fn last_transaction_id(&self, cx: &App) -> Option<TransactionId> {
+        if let Some(buffer) = self.as_singleton() {
+            buffer
+                .read(cx)
+                .peek_undo_stack()
+                .map(|entry| entry.transaction_id())
+        } else {
+            self.history
+                .undo_stack
+                .last()
+                .map(|transaction| transaction.id)
+        }
+    }
+
     pub fn finalize_last_transaction(&mut self, cx: &mut Context<Self>) {
         self.history.finalize_last_transaction();
         for BufferState { buffer, .. } in self.buffers.borrow().values() {
@@ -163,6 +163,12 @@ pub fn keymap_backup_file() -> &'static PathBuf {
     KEYMAP_FILE.get_or_init(|| config_dir().join("keymap_backup.json"))
 }
 
+/// Returns the path to the `vimrc` file, which is sourced when vim mode starts.
+pub fn vimrc_file() -> &'static PathBuf {
+    static VIMRC_FILE: OnceLock<PathBuf> = OnceLock::new();
+    VIMRC_FILE.get_or_init(|| config_dir().join("vimrc"))
+}
+
 /// Returns the path to the `tasks.json` file.
 pub fn tasks_file() -> &'static PathBuf {
     static TASKS_FILE: OnceLock<PathBuf> = OnceLock::new();
@@ -276,6 +282,14 @@ pub fn embeddings_dir() -> &'static PathBuf {
     })
 }
 
+/// Returns the path to the local history directory.
+///
+/// This is where the snapshots taken of files as they're saved are stored.
+pub fn local_history_dir() -> &'static PathBuf {
+    static LOCAL_HISTORY_DIR: OnceLock<PathBuf> = OnceLock::new();
+    LOCAL_HISTORY_DIR.get_or_init(|| support_dir().join("local_history"))
+}
+
 /// Returns the path to the languages directory.
 ///
 /// This is where language servers are downloaded to for languages built-in to Zed.
@@ -0,0 +1,359 @@
+//! Bookmarks are lines the user has marked to come back to later. They're kept per file by
+//! absolute path, so they outlive the buffers they were set in and can be listed across the
+//! whole project.
+
+use crate::{
+    buffer_store::{BufferStore, BufferStoreEvent},
+    worktree_store::WorktreeStore,
+    ProjectPath,
+};
+use collections::{BTreeMap, HashMap};
+use gpui::{App, Context, Entity, EventEmitter, Subscription};
+use language::{Buffer, BufferEvent, BufferSnapshot, Point};
+use std::{path::Path, sync::Arc};
+use text::{Anchor, BufferId, ToPoint as _};
+
+/// A bookmark as it's listed across all files and saved between sessions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SerializedBookmark {
+    pub path: Arc<Path>,
+    pub row: u32,
+    pub label: Option<String>,
+    /// The text of the bookmarked line, used to find it again when the file changes on disk.
+    pub line: String,
+}
+
+struct Bookmark {
+    /// Set while the file is open in a buffer, so that the bookmark follows edits.
+    anchor: Option<Anchor>,
+    row: u32,
+    label: Option<String>,
+    line: String,
+}
+
+pub enum BookmarkStoreEvent {
+    BookmarksChanged,
+}
+
+pub struct BookmarkStore {
+    worktree_store: Entity<WorktreeStore>,
+    buffer_store: Entity<BufferStore>,
+    files: BTreeMap<Arc<Path>, Vec<Bookmark>>,
+    buffer_paths: HashMap<BufferId, Arc<Path>>,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<BookmarkStoreEvent> for BookmarkStore {}
+
+impl BookmarkStore {
+    pub fn new(
+        worktree_store: Entity<WorktreeStore>,
+        buffer_store: Entity<BufferStore>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let _subscription = cx.subscribe(&buffer_store, Self::on_buffer_store_event);
+        Self {
+            worktree_store,
+            buffer_store,
+            files: BTreeMap::default(),
+            buffer_paths: HashMap::default(),
+            _subscription,
+        }
+    }
+
+    /// Adds a bookmark to the given row of the buffer, or removes the one that's already there.
+    pub fn toggle_bookmark(&mut self, buffer: &Entity<Buffer>, row: u32, cx: &mut Context<Self>) {
+        let Some(path) = self.register_buffer(buffer, cx) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let bookmarks = self.files.entry(path).or_default();
+        if let Some(ix) = bookmarks.iter().position(|bookmark| bookmark.row == row) {
+            bookmarks.remove(ix);
+        } else {
+            bookmarks.push(Bookmark::new(&snapshot, row, None));
+            bookmarks.sort_by_key(|bookmark| bookmark.row);
+        }
+        self.changed(cx);
+    }
+
+    /// Sets the label of the bookmark on the given row of the buffer, adding the bookmark if
+    /// there isn't one.
+    pub fn set_label(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        row: u32,
+        label: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(path) = self.register_buffer(buffer, cx) else {
+            return;
+        };
+        let label = label.filter(|label| !label.trim().is_empty());
+        let snapshot = buffer.read(cx).snapshot();
+        let bookmarks = self.files.entry(path).or_default();
+        if let Some(bookmark) = bookmarks.iter_mut().find(|bookmark| bookmark.row == row) {
+            bookmark.label = label;
+        } else {
+            bookmarks.push(Bookmark::new(&snapshot, row, label));
+            bookmarks.sort_by_key(|bookmark| bookmark.row);
+        }
+        self.changed(cx);
+    }
+
+    pub fn remove_bookmark(&mut self, path: &Path, row: u32, cx: &mut Context<Self>) {
+        let Some(bookmarks) = self.files.get_mut(path) else {
+            return;
+        };
+        bookmarks.retain(|bookmark| bookmark.row != row);
+        self.changed(cx);
+    }
+
+    pub fn clear_bookmarks(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(path) = self.buffer_paths.get(&buffer.read(cx).remote_id()) else {
+            return;
+        };
+        self.files.remove(path);
+        self.changed(cx);
+    }
+
+    /// The bookmarks in the given buffer, ordered by row.
+    pub fn bookmarks_for_buffer(
+        &self,
+        buffer: &Entity<Buffer>,
+        cx: &App,
+    ) -> impl Iterator<Item = (Anchor, Option<&str>)> + '_ {
+        self.buffer_paths
+            .get(&buffer.read(cx).remote_id())
+            .and_then(|path| self.files.get(path))
+            .into_iter()
+            .flatten()
+            .filter_map(|bookmark| Some((bookmark.anchor?, bookmark.label.as_deref())))
+    }
+
+    pub fn label_at_row(&self, buffer: &Entity<Buffer>, row: u32, cx: &App) -> Option<&str> {
+        let path = self.buffer_paths.get(&buffer.read(cx).remote_id())?;
+        self.files
+            .get(path)?
+            .iter()
+            .find(|bookmark| bookmark.row == row)?
+            .label
+            .as_deref()
+    }
+
+    /// Every bookmark in the project, ordered by path and row.
+    pub fn all_bookmarks(&self) -> Vec<SerializedBookmark> {
+        self.files
+            .iter()
+            .flat_map(|(path, bookmarks)| {
+                bookmarks.iter().map(|bookmark| SerializedBookmark {
+                    path: path.clone(),
+                    row: bookmark.row,
+                    label: bookmark.label.clone(),
+                    line: bookmark.line.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Adds bookmarks saved in a previous session. Bookmarks in files that are already open
+    /// are anchored right away, the others once their file is opened.
+    pub fn restore_bookmarks(
+        &mut self,
+        bookmarks: Vec<SerializedBookmark>,
+        cx: &mut Context<Self>,
+    ) {
+        for bookmark in bookmarks {
+            let bookmarks = self.files.entry(bookmark.path).or_default();
+            if bookmarks.iter().any(|b| b.row == bookmark.row) {
+                continue;
+            }
+            bookmarks.push(Bookmark {
+                anchor: None,
+                row: bookmark.row,
+                label: bookmark.label,
+                line: bookmark.line,
+            });
+            bookmarks.sort_by_key(|bookmark| bookmark.row);
+        }
+
+        let open_buffers = self
+            .files
+            .keys()
+            .filter_map(|path| {
+                let (worktree, relative_path) =
+                    self.worktree_store.read(cx).find_worktree(path, cx)?;
+                let project_path = ProjectPath {
+                    worktree_id: worktree.read(cx).id(),
+                    path: relative_path.into(),
+                };
+                self.buffer_store.read(cx).get_by_path(&project_path, cx)
+            })
+            .collect::<Vec<_>>();
+        for buffer in open_buffers {
+            self.register_buffer(&buffer, cx);
+        }
+        self.changed(cx);
+    }
+
+    fn on_buffer_store_event(
+        &mut self,
+        _: Entity<BufferStore>,
+        event: &BufferStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            BufferStoreEvent::BufferAdded(buffer) => {
+                cx.subscribe(buffer, Self::on_buffer_event).detach();
+                if self.register_buffer(buffer, cx).is_some() {
+                    cx.notify();
+                }
+            }
+            BufferStoreEvent::BufferChangedFilePath { buffer, .. } => {
+                let buffer_id = buffer.read(cx).remote_id();
+                let old_path = self.buffer_paths.remove(&buffer_id);
+                let new_path = self.buffer_abs_path(buffer, cx);
+                if let Some((old_path, new_path)) = old_path.zip(new_path) {
+                    if let Some(file) = self.files.remove(&old_path) {
+                        self.files.insert(new_path, file);
+                    }
+                }
+                self.register_buffer(buffer, cx);
+                self.changed(cx);
+            }
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                if let Some(path) = self.buffer_paths.remove(buffer_id) {
+                    for bookmark in self.files.get_mut(&path).into_iter().flatten() {
+                        bookmark.anchor = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn on_buffer_event(
+        &mut self,
+        buffer: Entity<Buffer>,
+        event: &BufferEvent,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(path) = self.buffer_paths.get(&buffer.read(cx).remote_id()) else {
+            return;
+        };
+        let Some(bookmarks) = self.files.get_mut(path) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        match event {
+            BufferEvent::Edited => {
+                if refresh_rows(bookmarks, &snapshot) {
+                    self.changed(cx);
+                }
+            }
+            BufferEvent::Reloaded => {
+                // Reloading applies a diff against the new contents, which keeps anchors on
+                // unchanged lines in place but can leave them on the wrong line when the
+                // bookmarked line moved. Look for the line's text near where it used to be.
+                for bookmark in bookmarks.iter_mut() {
+                    if let Some(anchor) = bookmark.anchor {
+                        bookmark.anchor_near(anchor.to_point(&snapshot).row, &snapshot);
+                    }
+                }
+                refresh_rows(bookmarks, &snapshot);
+                self.changed(cx);
+            }
+            BufferEvent::Saved => {
+                for bookmark in bookmarks.iter_mut() {
+                    bookmark.line = snapshot.trimmed_line_text(bookmark.row);
+                }
+                self.changed(cx);
+            }
+            _ => {}
+        }
+    }
+
+    /// Associates the buffer with the bookmarks of its file, anchoring any of them that were
+    /// set while the file was closed.
+    fn register_buffer(&mut self, buffer: &Entity<Buffer>, cx: &App) -> Option<Arc<Path>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let path = match self.buffer_paths.get(&buffer_id) {
+            Some(path) => path.clone(),
+            None => {
+                let path = self.buffer_abs_path(buffer, cx)?;
+                self.buffer_paths.insert(buffer_id, path.clone());
+                path
+            }
+        };
+        if let Some(bookmarks) = self.files.get_mut(&path) {
+            let snapshot = buffer.read(cx).snapshot();
+            for bookmark in bookmarks.iter_mut() {
+                if bookmark.anchor.is_none() {
+                    bookmark.anchor_near(bookmark.row, &snapshot);
+                }
+            }
+            refresh_rows(bookmarks, &snapshot);
+        }
+        Some(path)
+    }
+
+    fn buffer_abs_path(&self, buffer: &Entity<Buffer>, cx: &App) -> Option<Arc<Path>> {
+        let file = buffer.read(cx).file()?;
+        let worktree = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(file.worktree_id(cx), cx)?;
+        let abs_path = worktree.read(cx).absolutize(file.path()).ok()?;
+        Some(abs_path.into())
+    }
+
+    fn changed(&mut self, cx: &mut Context<Self>) {
+        self.files.retain(|_, bookmarks| !bookmarks.is_empty());
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        cx.notify();
+    }
+}
+
+impl Bookmark {
+    fn new(snapshot: &BufferSnapshot, row: u32, label: Option<String>) -> Self {
+        let row = row.min(snapshot.max_point().row);
+        Self {
+            anchor: Some(snapshot.anchor_before(Point::new(row, 0))),
+            row,
+            label,
+            line: snapshot.trimmed_line_text(row),
+        }
+    }
+
+    /// Anchors the bookmark to the line nearest to `row` that has its text, as the file was
+    /// changed while the bookmark wasn't anchored to it. Stays on `row` if the line is gone.
+    fn anchor_near(&mut self, row: u32, snapshot: &BufferSnapshot) {
+        self.row = snapshot
+            .find_line_near(row, &self.line)
+            .unwrap_or(row.min(snapshot.max_point().row));
+        self.anchor = Some(snapshot.anchor_before(Point::new(self.row, 0)));
+        self.line = snapshot.trimmed_line_text(self.row);
+    }
+}
+
+/// Updates the rows of bookmarks from their anchors, merging bookmarks whose lines were joined.
+/// Returns whether any row changed.
+fn refresh_rows(bookmarks: &mut Vec<Bookmark>, snapshot: &BufferSnapshot) -> bool {
+    let mut changed = false;
+    for bookmark in bookmarks.iter_mut() {
+        if let Some(anchor) = bookmark.anchor {
+            let row = anchor.to_point(snapshot).row;
+            changed |= row != bookmark.row;
+            bookmark.row = row;
+        }
+    }
+    bookmarks.sort_by_key(|bookmark| bookmark.row);
+    bookmarks.dedup_by(|removed, kept| {
+        if removed.row == kept.row {
+            kept.label = kept.label.take().or(removed.label.take());
+            true
+        } else {
+            false
+        }
+    });
+    changed
+}
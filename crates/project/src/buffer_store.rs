@@ -9,7 +9,7 @@ use anyhow::{anyhow, bail, Context as _, Result};
 use client::Client;
 use collections::{hash_map, HashMap, HashSet};
 use diff::{BufferDiff, BufferDiffEvent, BufferDiffSnapshot};
-use fs::Fs;
+use fs::{Encoding, Fs};
 use futures::{channel::oneshot, future::Shared, Future, FutureExt as _, StreamExt};
 use git::{blame::Blame, repository::RepoPath};
 use gpui::{
@@ -18,8 +18,8 @@ use gpui::{
 use http_client::Url;
 use language::{
     proto::{
-        deserialize_line_ending, deserialize_version, serialize_line_ending, serialize_version,
-        split_operations,
+        deserialize_encoding, deserialize_line_ending, deserialize_version, serialize_encoding,
+        serialize_line_ending, serialize_version, split_operations,
     },
     Buffer, BufferEvent, Capability, DiskState, File as _, Language, LanguageRegistry, Operation,
 };
@@ -404,6 +404,7 @@ impl RemoteBufferStore {
         &self,
         buffer_handle: Entity<Buffer>,
         new_path: Option<proto::ProjectPath>,
+        encoding: Option<Encoding>,
         cx: &Context<BufferStore>,
     ) -> Task<Result<()>> {
         let buffer = buffer_handle.read(cx);
@@ -418,12 +419,21 @@ impl RemoteBufferStore {
                     buffer_id,
                     new_path,
                     version: serialize_version(&version),
+                    encoding: encoding.map(serialize_encoding),
                 })
                 .await?;
             let version = deserialize_version(&response.version);
             let mtime = response.mtime.map(|mtime| mtime.into());
+            let encoding = response
+                .encoding
+                .as_deref()
+                .map(deserialize_encoding)
+                .transpose()?;
 
             buffer_handle.update(&mut cx, |buffer, cx| {
+                if let Some(encoding) = encoding {
+                    buffer.set_encoding(encoding, cx);
+                }
                 buffer.did_save(version.clone(), mtime, cx);
             })?;
 
@@ -620,6 +630,7 @@ impl RemoteBufferStore {
         &self,
         buffers: HashSet<Entity<Buffer>>,
         push_to_history: bool,
+        encoding: Option<Encoding>,
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<ProjectTransaction>> {
         let request = self.upstream_client.request(proto::ReloadBuffers {
@@ -628,6 +639,7 @@ impl RemoteBufferStore {
                 .iter()
                 .map(|buffer| buffer.read(cx).remote_id().to_proto())
                 .collect(),
+            encoding: encoding.map(serialize_encoding),
         });
 
         cx.spawn(|this, mut cx| async move {
@@ -691,6 +703,7 @@ impl LocalBufferStore {
 
         let text = buffer.as_rope().clone();
         let line_ending = buffer.line_ending();
+        let encoding = buffer.encoding();
         let version = buffer.version();
         let buffer_id = buffer.remote_id();
         if buffer
@@ -701,7 +714,7 @@ impl LocalBufferStore {
         }
 
         let save = worktree.update(cx, |worktree, cx| {
-            worktree.write_file(path.as_ref(), text, line_ending, cx)
+            worktree.write_file(path.as_ref(), text, line_ending, encoding, cx)
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -724,6 +737,7 @@ impl LocalBufferStore {
                             buffer_id: buffer_id.to_proto(),
                             version: serialize_version(&version),
                             mtime: mtime.map(|time| time.into()),
+                            encoding: Some(serialize_encoding(encoding)),
                         })
                         .log_err();
                 }
@@ -1116,9 +1130,32 @@ impl LocalBufferStore {
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<Entity<Buffer>>> {
         let load_buffer = worktree.update(cx, |worktree, cx| {
-            let load_file = worktree.load_file(path.as_ref(), cx);
             let reservation = cx.reserve_entity();
             let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
+            if worktree.is_large_file(path.as_ref()) {
+                let load_file = worktree.load_large_file(path.as_ref(), cx);
+                return cx.spawn(move |_, mut cx| async move {
+                    let loaded = load_file.await?;
+                    let text_buffer = cx
+                        .background_executor()
+                        .spawn(async move {
+                            text::Buffer::new_normalized(
+                                0,
+                                buffer_id,
+                                loaded.line_ending,
+                                loaded.text,
+                            )
+                        })
+                        .await;
+                    cx.insert_entity(reservation, |_| {
+                        Buffer::build(text_buffer, Some(loaded.file), Capability::ReadOnly)
+                            .with_encoding(loaded.encoding)
+                            .with_large_file(true)
+                    })
+                });
+            }
+
+            let load_file = worktree.load_file(path.as_ref(), cx);
             cx.spawn(move |_, mut cx| async move {
                 let loaded = load_file.await?;
                 let text_buffer = cx
@@ -1127,6 +1164,7 @@ impl LocalBufferStore {
                     .await;
                 cx.insert_entity(reservation, |_| {
                     Buffer::build(text_buffer, Some(loaded.file), Capability::ReadWrite)
+                        .with_encoding(loaded.encoding)
                 })
             })
         });
@@ -1349,6 +1387,10 @@ impl BufferStore {
         buffer: Entity<Buffer>,
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<BufferDiff>>> {
+        // Git diffs are disabled for large files, which are too big to diff.
+        if buffer.read(cx).is_large_file() {
+            return Task::ready(Ok(cx.new(|cx| BufferDiff::new(&buffer, cx))));
+        }
         let buffer_id = buffer.read(cx).remote_id();
         if let Some(diff) = self.get_unstaged_diff(buffer_id, cx) {
             return Task::ready(Ok(diff));
@@ -1390,6 +1432,10 @@ impl BufferStore {
         buffer: Entity<Buffer>,
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<BufferDiff>>> {
+        // Git diffs are disabled for large files, which are too big to diff.
+        if buffer.read(cx).is_large_file() {
+            return Task::ready(Ok(cx.new(|cx| BufferDiff::new(&buffer, cx))));
+        }
         let buffer_id = buffer.read(cx).remote_id();
         if let Some(diff) = self.get_uncommitted_diff(buffer_id, cx) {
             return Task::ready(Ok(diff));
@@ -1531,7 +1577,25 @@ impl BufferStore {
     ) -> Task<Result<()>> {
         match &mut self.state {
             BufferStoreState::Local(this) => this.save_buffer(buffer, cx),
-            BufferStoreState::Remote(this) => this.save_remote_buffer(buffer.clone(), None, cx),
+            BufferStoreState::Remote(this) => {
+                this.save_remote_buffer(buffer.clone(), None, None, cx)
+            }
+        }
+    }
+
+    /// Saves the buffer after changing the encoding its file is written in.
+    pub fn save_buffer_with_encoding(
+        &mut self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        buffer.update(cx, |buffer, cx| buffer.set_encoding(encoding, cx));
+        match &mut self.state {
+            BufferStoreState::Local(this) => this.save_buffer(buffer, cx),
+            BufferStoreState::Remote(this) => {
+                this.save_remote_buffer(buffer, None, Some(encoding), cx)
+            }
         }
     }
 
@@ -1545,7 +1609,7 @@ impl BufferStore {
         let task = match &self.state {
             BufferStoreState::Local(this) => this.save_buffer_as(buffer.clone(), path, cx),
             BufferStoreState::Remote(this) => {
-                this.save_remote_buffer(buffer.clone(), Some(path.to_proto()), cx)
+                this.save_remote_buffer(buffer.clone(), Some(path.to_proto()), None, cx)
             }
         };
         cx.spawn(|this, mut cx| async move {
@@ -1980,6 +2044,7 @@ impl BufferStore {
                         version: serialize_version(&buffer.version()),
                         mtime: buffer.saved_mtime().map(|t| t.into()),
                         line_ending: serialize_line_ending(buffer.line_ending()) as i32,
+                        encoding: Some(serialize_encoding(buffer.encoding())),
                     })
                     .log_err();
             }
@@ -2106,6 +2171,7 @@ impl BufferStore {
                         mtime: buffer.saved_mtime().map(|time| time.into()),
                         line_ending: language::proto::serialize_line_ending(buffer.line_ending())
                             as i32,
+                        encoding: Some(serialize_encoding(buffer.encoding())),
                     })
                     .log_err();
 
@@ -2221,6 +2287,10 @@ impl BufferStore {
             })?
             .await?;
         let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id())?;
+        if let Some(encoding) = envelope.payload.encoding.as_deref() {
+            let encoding = deserialize_encoding(encoding)?;
+            buffer.update(&mut cx, |buffer, cx| buffer.set_encoding(encoding, cx))?;
+        }
 
         if let Some(new_path) = envelope.payload.new_path {
             let new_path = ProjectPath::from_proto(new_path);
@@ -2238,6 +2308,7 @@ impl BufferStore {
             buffer_id: buffer_id.into(),
             version: serialize_version(buffer.saved_version()),
             mtime: buffer.saved_mtime().map(|time| time.into()),
+            encoding: Some(serialize_encoding(buffer.encoding())),
         })
     }
 
@@ -2273,9 +2344,18 @@ impl BufferStore {
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let version = deserialize_version(&envelope.payload.version);
         let mtime = envelope.payload.mtime.clone().map(|time| time.into());
+        let encoding = envelope
+            .payload
+            .encoding
+            .as_deref()
+            .map(deserialize_encoding)
+            .transpose()?;
         this.update(&mut cx, move |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_save(version, mtime, cx);
                 });
             }
@@ -2287,6 +2367,7 @@ impl BufferStore {
                         buffer_id: buffer_id.into(),
                         mtime: envelope.payload.mtime,
                         version: envelope.payload.version,
+                        encoding: envelope.payload.encoding,
                     })
                     .log_err();
             }
@@ -2305,9 +2386,18 @@ impl BufferStore {
             proto::LineEnding::from_i32(envelope.payload.line_ending)
                 .ok_or_else(|| anyhow!("missing line ending"))?,
         );
+        let encoding = envelope
+            .payload
+            .encoding
+            .as_deref()
+            .map(deserialize_encoding)
+            .transpose()?;
         this.update(&mut cx, |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_reload(version, line_ending, mtime, cx);
                 });
             }
@@ -2320,6 +2410,7 @@ impl BufferStore {
                         mtime: envelope.payload.mtime,
                         version: envelope.payload.version,
                         line_ending: envelope.payload.line_ending,
+                        encoding: envelope.payload.encoding,
                     })
                     .log_err();
             }
@@ -2494,7 +2585,26 @@ impl BufferStore {
         }
         match &self.state {
             BufferStoreState::Local(this) => this.reload_buffers(buffers, push_to_history, cx),
-            BufferStoreState::Remote(this) => this.reload_buffers(buffers, push_to_history, cx),
+            BufferStoreState::Remote(this) => {
+                this.reload_buffers(buffers, push_to_history, None, cx)
+            }
+        }
+    }
+
+    /// Reloads the buffer from disk, decoding its file with the given encoding.
+    pub fn reload_buffer_with_encoding(
+        &mut self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        buffer.update(cx, |buffer, cx| buffer.set_encoding(encoding, cx));
+        let buffers = HashSet::from_iter([buffer]);
+        match &self.state {
+            BufferStoreState::Local(this) => this.reload_buffers(buffers, true, cx),
+            BufferStoreState::Remote(this) => {
+                this.reload_buffers(buffers, true, Some(encoding), cx)
+            }
         }
     }
 
@@ -2504,11 +2614,21 @@ impl BufferStore {
         mut cx: AsyncApp,
     ) -> Result<proto::ReloadBuffersResponse> {
         let sender_id = envelope.original_sender_id().unwrap_or_default();
+        let encoding = envelope
+            .payload
+            .encoding
+            .as_deref()
+            .map(deserialize_encoding)
+            .transpose()?;
         let reload = this.update(&mut cx, |this, cx| {
             let mut buffers = HashSet::default();
             for buffer_id in &envelope.payload.buffer_ids {
                 let buffer_id = BufferId::new(*buffer_id)?;
-                buffers.insert(this.get_existing(buffer_id)?);
+                let buffer = this.get_existing(buffer_id)?;
+                if let Some(encoding) = encoding {
+                    buffer.update(cx, |buffer, cx| buffer.set_encoding(encoding, cx));
+                }
+                buffers.insert(buffer);
             }
             Ok::<_, anyhow::Error>(this.reload_buffers(buffers, false, cx))
         })??;
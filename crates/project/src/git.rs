@@ -6,7 +6,7 @@ use client::ProjectId;
 use futures::channel::{mpsc, oneshot};
 use futures::StreamExt as _;
 use git::{
-    repository::{GitRepository, RepoPath},
+    repository::{CommitSummary, GitRepository, RepoPath},
     status::{GitSummary, TrackedSummary},
 };
 use gpui::{
@@ -503,6 +503,59 @@ impl Repository {
         return self.have_changes() && (commit_all || self.have_staged_changes());
     }
 
+    /// Returns the diff of the staged and unstaged changes against HEAD.
+    pub fn uncommitted_diff(&self, cx: &App) -> Task<anyhow::Result<String>> {
+        self.spawn_local_job(cx, |git_repo| git_repo.uncommitted_diff())
+    }
+
+    /// Returns the message and the diff of the commit with the given SHA.
+    pub fn show_commit(&self, sha: SharedString, cx: &App) -> Task<anyhow::Result<String>> {
+        self.spawn_local_job(cx, move |git_repo| git_repo.show_commit(&sha))
+    }
+
+    /// Returns up to `limit` of the most recent commits reachable from HEAD, newest first.
+    pub fn recent_commits(
+        &self,
+        limit: usize,
+        cx: &App,
+    ) -> Task<anyhow::Result<Vec<CommitSummary>>> {
+        self.spawn_local_job(cx, move |git_repo| git_repo.recent_commits(limit))
+    }
+
+    /// Saves the working copy under a hidden ref, returning the checkpoint to compare with later.
+    pub fn create_checkpoint(&self, cx: &App) -> Task<anyhow::Result<String>> {
+        self.spawn_local_job(cx, |git_repo| git_repo.create_checkpoint())
+    }
+
+    /// Returns the files that changed since the given checkpoint, along with their contents in
+    /// it, or `None` for the files created since, and deletes the checkpoint.
+    pub fn take_changes_since_checkpoint(
+        &self,
+        checkpoint: String,
+        cx: &App,
+    ) -> Task<anyhow::Result<Vec<(RepoPath, Option<String>)>>> {
+        self.spawn_local_job(cx, move |git_repo| {
+            let changes = git_repo.changes_since_checkpoint(&checkpoint);
+            git_repo.delete_checkpoint(&checkpoint).log_err();
+            changes
+        })
+    }
+
+    fn spawn_local_job<R: Send + 'static>(
+        &self,
+        cx: &App,
+        job: impl FnOnce(Arc<dyn GitRepository>) -> anyhow::Result<R> + Send + 'static,
+    ) -> Task<anyhow::Result<R>> {
+        match self.git_repo.clone() {
+            GitRepo::Local(git_repo) => {
+                cx.background_executor().spawn(async move { job(git_repo) })
+            }
+            GitRepo::Remote { .. } => Task::ready(Err(anyhow::anyhow!(
+                "not supported for remote repositories"
+            ))),
+        }
+    }
+
     pub fn commit(
         &self,
         message: SharedString,
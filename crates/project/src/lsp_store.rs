@@ -3257,6 +3257,9 @@ impl LspStore {
         let buffer_id = buffer.read(cx).remote_id();
 
         let handle = cx.new(|_| buffer.clone());
+        if buffer.read(cx).is_large_file() {
+            return handle;
+        }
 
         if let Some(local) = self.as_local_mut() {
             let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
@@ -3387,6 +3390,12 @@ impl LspStore {
         // If the buffer has a language, set it and start the language server if we haven't already.
         let buffer = buffer_handle.read(cx);
         let file = buffer.file()?;
+        if buffer.is_large_file() {
+            buffer_handle.update(cx, |buffer, cx| {
+                buffer.set_language(Some(language::PLAIN_TEXT.clone()), cx)
+            });
+            return None;
+        }
 
         let content = buffer.as_rope();
         let available_language = self.languages.language_for_file(file, Some(content), cx);
@@ -1,3 +1,4 @@
+pub mod bookmark_store;
 pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
@@ -14,6 +15,7 @@ mod task_inventory;
 pub mod task_store;
 pub mod terminals;
 pub mod toolchain_store;
+pub mod trigram_index;
 pub mod worktree_store;
 
 #[cfg(test)]
@@ -29,6 +31,7 @@ mod yarn;
 
 use crate::git::GitState;
 use anyhow::{anyhow, Context as _, Result};
+use bookmark_store::BookmarkStore;
 use buffer_store::{BufferStore, BufferStoreEvent};
 use client::{
     proto, Client, Collaborator, PendingEntitySubscription, ProjectId, TypedEnvelope, UserStore,
@@ -58,8 +61,8 @@ use itertools::Itertools;
 use language::{
     language_settings::InlayHintKind, proto::split_operations, Buffer, BufferEvent,
     CachedLspAdapter, Capability, CodeLabel, CompletionDocumentation, File as _, Language,
-    LanguageName, LanguageRegistry, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList,
-    Transaction, Unclipped,
+    LanguageName, LanguageRegistry, ParseStatus, PointUtf16, ToOffset, ToPointUtf16, Toolchain,
+    ToolchainList, Transaction, Unclipped,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, LanguageServer,
@@ -76,7 +79,7 @@ use rpc::{
     proto::{LanguageServerPromptResponse, SSH_PROJECT_ID},
     AnyProtoClient, ErrorCode,
 };
-use search::{SearchInputKind, SearchQuery, SearchResult};
+use search::{SearchInputKind, SearchQuery, SearchResult, SyntaxScope};
 use search_history::SearchHistory;
 use settings::{InvalidSettingsError, Settings, SettingsLocation, SettingsStore};
 use smol::channel::Receiver;
@@ -163,6 +166,7 @@ pub struct Project {
     client_subscriptions: Vec<client::Subscription>,
     worktree_store: Entity<WorktreeStore>,
     buffer_store: Entity<BufferStore>,
+    bookmark_store: Entity<BookmarkStore>,
     image_store: Entity<ImageStore>,
     lsp_store: Entity<LspStore>,
     _subscriptions: Vec<gpui::Subscription>,
@@ -641,6 +645,8 @@ impl Project {
             let buffer_store = cx.new(|cx| BufferStore::local(worktree_store.clone(), cx));
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
+            let bookmark_store =
+                cx.new(|cx| BookmarkStore::new(worktree_store.clone(), buffer_store.clone(), cx));
 
             let image_store = cx.new(|cx| ImageStore::local(worktree_store.clone(), cx));
             cx.subscribe(&image_store, Self::on_image_store_event)
@@ -710,6 +716,7 @@ impl Project {
                 collaborators: Default::default(),
                 worktree_store,
                 buffer_store,
+                bookmark_store,
                 image_store,
                 lsp_store,
                 join_project_response_message_id: 0,
@@ -785,6 +792,8 @@ impl Project {
             });
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
+            let bookmark_store =
+                cx.new(|cx| BookmarkStore::new(worktree_store.clone(), buffer_store.clone(), cx));
             let toolchain_store = cx
                 .new(|cx| ToolchainStore::remote(SSH_PROJECT_ID, ssh.read(cx).proto_client(), cx));
             let task_store = cx.new(|cx| {
@@ -838,6 +847,7 @@ impl Project {
                 collaborators: Default::default(),
                 worktree_store,
                 buffer_store,
+                bookmark_store,
                 image_store,
                 lsp_store,
                 join_project_response_message_id: 0,
@@ -990,6 +1000,8 @@ impl Project {
         let image_store = cx.new(|cx| {
             ImageStore::remote(worktree_store.clone(), client.clone().into(), remote_id, cx)
         })?;
+        let bookmark_store =
+            cx.new(|cx| BookmarkStore::new(worktree_store.clone(), buffer_store.clone(), cx))?;
 
         let lsp_store = cx.new(|cx| {
             let mut lsp_store = LspStore::new_remote(
@@ -1063,6 +1075,7 @@ impl Project {
             let mut this = Self {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
+                bookmark_store,
                 image_store,
                 worktree_store: worktree_store.clone(),
                 lsp_store: lsp_store.clone(),
@@ -1792,7 +1805,12 @@ impl Project {
 
             *capability = new_capability;
             for buffer in self.opened_buffers(cx) {
-                buffer.update(cx, |buffer, cx| buffer.set_capability(new_capability, cx));
+                buffer.update(cx, |buffer, cx| {
+                    // Large files stay read-only.
+                    if !buffer.is_large_file() {
+                        buffer.set_capability(new_capability, cx)
+                    }
+                });
             }
         }
     }
@@ -2043,6 +2061,17 @@ impl Project {
             .update(cx, |buffer_store, cx| buffer_store.save_buffer(buffer, cx))
     }
 
+    pub fn save_buffer_with_encoding(
+        &self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.save_buffer_with_encoding(buffer, encoding, cx)
+        })
+    }
+
     pub fn save_buffer_as(
         &mut self,
         buffer: Entity<Buffer>,
@@ -2705,6 +2734,17 @@ impl Project {
         })
     }
 
+    pub fn reload_buffer_with_encoding(
+        &self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.reload_buffer_with_encoding(buffer, encoding, cx)
+        })
+    }
+
     pub fn reload_images(
         &self,
         images: HashSet<Entity<ImageItem>>,
@@ -3092,6 +3132,25 @@ impl Project {
             // ranges in the buffer matched by the query.
             let mut chunks = pin!(chunks);
             'outer: while let Some(matching_buffer_chunk) = chunks.next().await {
+                // Syntax-scoped queries classify matches using the syntax tree, so
+                // wait for freshly opened buffers to finish parsing.
+                if query.syntax_scope() != SyntaxScope::All {
+                    let parse_statuses = matching_buffer_chunk
+                        .iter()
+                        .map(|buffer| buffer.read_with(&cx, |buffer, _| buffer.parse_status()))
+                        .collect::<Result<Vec<_>>>()?;
+                    futures::future::join_all(parse_statuses.into_iter().map(
+                        |mut parse_status| async move {
+                            while *parse_status.borrow() != ParseStatus::Idle {
+                                if parse_status.changed().await.is_err() {
+                                    break;
+                                }
+                            }
+                        },
+                    ))
+                    .await;
+                }
+
                 let mut chunk_results = Vec::new();
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
@@ -4323,6 +4382,10 @@ impl Project {
         &self.buffer_store
     }
 
+    pub fn bookmark_store(&self) -> &Entity<BookmarkStore> {
+        &self.bookmark_store
+    }
+
     pub fn git_state(&self) -> &Entity<GitState> {
         &self.git_state
     }
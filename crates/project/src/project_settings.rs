@@ -56,6 +56,10 @@ pub struct ProjectSettings {
     /// Configuration for session-related features
     #[serde(default)]
     pub session: SessionSettings,
+
+    /// Configuration for the index used to speed up project search
+    #[serde(default)]
+    pub search_index: SearchIndexSettings,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -203,6 +207,18 @@ impl Default for SessionSettings {
     }
 }
 
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SearchIndexSettings {
+    /// Whether to maintain an on-disk trigram index of the files in local worktrees,
+    /// which lets project search skip files that can't contain a match.
+    ///
+    /// Changing this setting only affects worktrees opened afterwards.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
+}
+
 impl Settings for ProjectSettings {
     const KEY: Option<&'static str> = None;
 
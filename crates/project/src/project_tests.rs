@@ -3003,6 +3003,172 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_file_encodings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({})).await;
+    fs.insert_file(path!("/dir/latin1.txt"), b"caf\xE9\n".to_vec())
+        .await;
+    fs.insert_file(path!("/dir/utf16.txt"), b"\xFF\xFEh\0i\0".to_vec())
+        .await;
+
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let latin1 = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer(path!("/dir/latin1.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let utf16 = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/utf16.txt"), cx))
+        .await
+        .unwrap();
+    latin1.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "café\n");
+        assert_eq!(buffer.encoding().name(), "windows-1252");
+        buffer.edit([(0..0, "à la ")], None, cx);
+    });
+    utf16.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "hi");
+        assert_eq!(buffer.encoding().to_string(), "UTF-16LE with BOM");
+        buffer.edit([(2..2, "!")], None, cx);
+    });
+
+    // Saving writes the file back in the encoding it was read with.
+    project
+        .update(cx, |project, cx| project.save_buffer(latin1.clone(), cx))
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| project.save_buffer(utf16.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes(Path::new(path!("/dir/latin1.txt")))
+            .await
+            .unwrap(),
+        b"\xE0 la caf\xE9\n"
+    );
+    assert_eq!(
+        fs.load_bytes(Path::new(path!("/dir/utf16.txt")))
+            .await
+            .unwrap(),
+        b"\xFF\xFEh\0i\0!\0"
+    );
+
+    // Characters the encoding can't represent fail the save instead of being lost.
+    latin1.update(cx, |buffer, cx| buffer.edit([(0..0, "日本 ")], None, cx));
+    assert!(project
+        .update(cx, |project, cx| project.save_buffer(latin1.clone(), cx))
+        .await
+        .is_err());
+    project
+        .update(cx, |project, cx| {
+            project.save_buffer_with_encoding(latin1.clone(), Encoding::UTF8, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load(Path::new(path!("/dir/latin1.txt"))).await.unwrap(),
+        "日本 à la café\n"
+    );
+
+    // Reopening with another encoding decodes the file's bytes again.
+    project
+        .update(cx, |project, cx| {
+            let encoding = Encoding::from_name("windows-1252").unwrap();
+            project.reload_buffer_with_encoding(latin1.clone(), encoding, cx)
+        })
+        .await
+        .unwrap();
+    latin1.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "æ—¥æœ¬ Ã\u{a0} la cafÃ©\n");
+        assert_eq!(buffer.encoding().name(), "windows-1252");
+        assert!(!buffer.is_dirty());
+    });
+}
+
+#[gpui::test]
+async fn test_large_file_mode(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |settings| {
+                settings.large_file_threshold_mb = Some(1);
+            });
+        });
+    });
+
+    let large_text = "{\"line\": 1}\r\n".repeat(100_000);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "large.json": large_text,
+            "small.json": "{}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(json_lang());
+
+    let small = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer(path!("/dir/small.json"), cx)
+        })
+        .await
+        .unwrap();
+    let large = project
+        .update(cx, |p, cx| {
+            p.open_local_buffer(path!("/dir/large.json"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    small.update(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert!(!buffer.read_only());
+        assert_eq!(buffer.language().unwrap().name(), "JSON".into());
+    });
+    large.update(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert!(buffer.read_only());
+        assert_eq!(buffer.text(), large_text.replace("\r\n", "\n"));
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        assert_eq!(buffer.language().unwrap().name(), "Plain Text".into());
+    });
+    // Git diffs are empty for large files.
+    let uncommitted_diff = project
+        .update(cx, |project, cx| {
+            project.open_uncommitted_diff(large.clone(), cx)
+        })
+        .await
+        .unwrap();
+    uncommitted_diff.read_with(cx, |diff, _| {
+        assert_eq!(diff.base_text_string(), None);
+    });
+
+    // Reloading picks up lines appended to the file.
+    let appended_text = format!("{large_text}{{\"line\": 2}}\r\n");
+    fs.insert_file(path!("/dir/large.json"), appended_text.clone().into_bytes())
+        .await;
+    project
+        .update(cx, |project, cx| {
+            project.reload_buffers(collections::HashSet::from_iter([large.clone()]), false, cx)
+        })
+        .await
+        .unwrap();
+    large.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), appended_text.replace("\r\n", "\n"));
+        assert!(!buffer.is_dirty());
+    });
+}
+
 #[gpui::test(iterations = 30)]
 async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3705,6 +3871,99 @@ async fn test_buffer_file_changes_on_disk(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_bookmarks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn a() {}\nfn b() {}\nfn c() {}\n",
+            "b.rs": "one\ntwo\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let bookmark_store = project.read_with(cx, |project, _| project.bookmark_store().clone());
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/a.rs"), cx))
+        .await
+        .unwrap();
+    let rows = |cx: &mut gpui::TestAppContext| {
+        bookmark_store.read_with(cx, |store, _| {
+            store
+                .all_bookmarks()
+                .into_iter()
+                .map(|bookmark| (bookmark.row, bookmark.label))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    bookmark_store.update(cx, |store, cx| {
+        store.toggle_bookmark(&buffer, 1, cx);
+        store.set_label(&buffer, 2, Some("c".into()), cx);
+    });
+    assert_eq!(rows(cx), [(1, None), (2, Some("c".into()))]);
+
+    // Bookmarks follow edits to their buffer.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "// a\n")], None, cx));
+    assert_eq!(rows(cx), [(2, None), (3, Some("c".into()))]);
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+
+    // When the file changes on disk, bookmarks move to the lines they were set on, even if
+    // the diff applied on reload leaves their anchors elsewhere.
+    fs.save(
+        path!("/dir/a.rs").as_ref(),
+        &"fn c() {}\n// a\nfn a() {}\nfn b() {}\n".into(),
+        LineEnding::Unix,
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(rows(cx), [(0, Some("c".into())), (3, None)]);
+
+    // Toggling a bookmark again removes it.
+    bookmark_store.update(cx, |store, cx| store.toggle_bookmark(&buffer, 3, cx));
+    assert_eq!(rows(cx), [(0, Some("c".into()))]);
+
+    // Restored bookmarks are anchored when their file is opened, using the text of the line
+    // to find them if the file changed in the meantime.
+    bookmark_store.update(cx, |store, cx| {
+        store.restore_bookmarks(
+            vec![bookmark_store::SerializedBookmark {
+                path: Path::new(path!("/dir/b.rs")).into(),
+                row: 0,
+                label: None,
+                line: "two".into(),
+            }],
+            cx,
+        )
+    });
+    let other_buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/b.rs"), cx))
+        .await
+        .unwrap();
+    let anchors = bookmark_store.read_with(cx, |store, cx| {
+        store
+            .bookmarks_for_buffer(&other_buffer, cx)
+            .map(|(anchor, _)| anchor)
+            .collect::<Vec<_>>()
+    });
+    other_buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            anchors
+                .iter()
+                .map(|anchor| anchor.to_point(buffer))
+                .collect::<Vec<_>>(),
+            [Point::new(1, 0)]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_buffer_line_endings(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4354,6 +4613,151 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_with_trigram_index(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    let files = json!({
+        "one.rs": "const ONE: usize = 1;",
+        "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+        "three.rs": "// Ünicode ABC\nconst THREE: usize = one::ONE + two::TWO;",
+        "four.rs": "fn main() {\n    println!(\"déjà vu\");\n}",
+    });
+    fs.insert_tree(path!("/unindexed"), files.clone()).await;
+    fs.insert_tree(path!("/indexed"), files).await;
+    let unindexed_project = Project::test(fs.clone(), [path!("/unindexed").as_ref()], cx).await;
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.search_index.enabled = true;
+            });
+        });
+    });
+    let indexed_project = Project::test(fs.clone(), [path!("/indexed").as_ref()], cx).await;
+    cx.run_until_parked();
+
+    let queries = [
+        SearchQuery::text(
+            "ONE",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::text(
+            "one::one",
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::text(
+            "Déjà",
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::regex(
+            r"const \w+: usize",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::regex(
+            r"\x41BC",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::regex(
+            r"\u0041BC",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::regex(
+            r"\pLnicode",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::regex(
+            r"(?x) print ln!",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::regex(
+            r"(?i)const two",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::regex(
+            r"d.j. vu",
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+        SearchQuery::text(
+            "missing",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        ),
+    ];
+    for query in queries {
+        let query = query.unwrap();
+        let expected = search(&unindexed_project, query.clone(), cx)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(path, ranges)| (path.replacen("unindexed", "indexed", 1), ranges))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            search(&indexed_project, query.clone(), cx).await.unwrap(),
+            expected,
+            "unexpected results for {:?}",
+            query.as_str()
+        );
+        if query.as_str() != "missing" {
+            assert!(!expected.is_empty(), "no results for {:?}", query.as_str());
+        }
+    }
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4786,6 +5190,66 @@ async fn test_search_multiple_worktrees_with_inclusions(cx: &mut gpui::TestAppCo
     );
 }
 
+#[gpui::test]
+async fn test_search_with_syntax_scope(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "main.rs": "// TODO: rename foo\nfn foo() -> &'static str {\n    \"foo\"\n}\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_override_query(
+            "(string_literal) @string [(line_comment) (block_comment)] @comment.inclusive",
+        )
+        .unwrap(),
+    ));
+
+    let query = |syntax_scope| {
+        SearchQuery::text(
+            "foo",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+        .with_syntax_scope(syntax_scope)
+    };
+
+    for (syntax_scope, expected_ranges) in [
+        (SyntaxScope::All, vec![16..19, 23..26, 52..55]),
+        (SyntaxScope::Code, vec![23..26]),
+        (SyntaxScope::Comments, vec![16..19]),
+        (SyntaxScope::Strings, vec![52..55]),
+    ] {
+        assert_eq!(
+            search(&project, query(syntax_scope), cx).await.unwrap(),
+            HashMap::from_iter([(separator!("dir/main.rs").to_string(), expected_ranges)]),
+            "unexpected matches for {syntax_scope:?}"
+        );
+    }
+}
+
 #[gpui::test]
 async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1,6 +1,8 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
+use collections::HashSet;
+use convert_case::{Case, Casing};
 use fancy_regex::{Captures, Regex, RegexBuilder};
 use gpui::Entity;
 use language::{Buffer, BufferSnapshot, CharKind};
@@ -15,6 +17,8 @@ use std::{
 use text::Anchor;
 use util::paths::PathMatcher;
 
+use crate::trigram_index::{regex_literals, trigrams, TrigramQuery};
+
 pub enum SearchResult {
     Buffer {
         buffer: Entity<Buffer>,
@@ -30,12 +34,62 @@ pub enum SearchInputKind {
     Exclude,
 }
 
+/// Restricts search matches to a kind of syntax region, as reported by the
+/// `comment` and `string` overrides of the language at the match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SyntaxScope {
+    #[default]
+    All,
+    Code,
+    Comments,
+    Strings,
+}
+
+impl SyntaxScope {
+    /// Returns whether a match starting at `offset` falls into this scope.
+    pub fn contains(&self, buffer: &BufferSnapshot, offset: usize) -> bool {
+        if *self == SyntaxScope::All {
+            return true;
+        }
+
+        let scope = buffer.language_scope_at(offset);
+        let override_name = scope.as_ref().and_then(|scope| scope.override_name());
+        let is_comment = override_name.map_or(false, |name| name.starts_with("comment"));
+        let is_string = override_name.map_or(false, |name| name.starts_with("string"));
+        match self {
+            SyntaxScope::All => true,
+            SyntaxScope::Code => !is_comment && !is_string,
+            SyntaxScope::Comments => is_comment,
+            SyntaxScope::Strings => is_string,
+        }
+    }
+
+    pub fn from_proto(scope: proto::search_query::SyntaxScope) -> Self {
+        match scope {
+            proto::search_query::SyntaxScope::All => SyntaxScope::All,
+            proto::search_query::SyntaxScope::Code => SyntaxScope::Code,
+            proto::search_query::SyntaxScope::Comments => SyntaxScope::Comments,
+            proto::search_query::SyntaxScope::Strings => SyntaxScope::Strings,
+        }
+    }
+
+    pub fn to_proto(self) -> proto::search_query::SyntaxScope {
+        match self {
+            SyntaxScope::All => proto::search_query::SyntaxScope::All,
+            SyntaxScope::Code => proto::search_query::SyntaxScope::Code,
+            SyntaxScope::Comments => proto::search_query::SyntaxScope::Comments,
+            SyntaxScope::Strings => proto::search_query::SyntaxScope::Strings,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SearchInputs {
     query: Arc<str>,
     files_to_include: PathMatcher,
     files_to_exclude: PathMatcher,
     buffers: Option<Vec<Entity<Buffer>>>,
+    syntax_scope: SyntaxScope,
 }
 
 impl SearchInputs {
@@ -51,12 +105,16 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Entity<Buffer>>> {
         &self.buffers
     }
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        self.syntax_scope
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
     Text {
         search: Arc<AhoCorasick>,
         replacement: Option<String>,
+        preserve_case: bool,
         whole_word: bool,
         case_sensitive: bool,
         include_ignored: bool,
@@ -66,6 +124,7 @@ pub enum SearchQuery {
     Regex {
         regex: Regex,
         replacement: Option<String>,
+        preserve_case: bool,
         multiline: bool,
         whole_word: bool,
         case_sensitive: bool,
@@ -99,10 +158,12 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            syntax_scope: SyntaxScope::All,
         };
         Ok(Self::Text {
             search: Arc::new(search),
             replacement: None,
+            preserve_case: false,
             whole_word,
             case_sensitive,
             include_ignored,
@@ -146,10 +207,12 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            syntax_scope: SyntaxScope::All,
         };
         Ok(Self::Regex {
             regex,
             replacement: None,
+            preserve_case: false,
             multiline,
             whole_word,
             case_sensitive,
@@ -159,7 +222,10 @@ impl SearchQuery {
     }
 
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        let syntax_scope = proto::search_query::SyntaxScope::from_i32(message.syntax_scope)
+            .map(SyntaxScope::from_proto)
+            .unwrap_or_default();
+        let query = if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -179,6 +245,16 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_exclude)?,
                 None, // search opened only don't need search remote
             )
+        }?;
+        let query = query.with_syntax_scope(syntax_scope);
+        if message.preserve_case {
+            // The replacement isn't sent to the host, which only needs to find the case variants
+            // that are going to be replaced.
+            Ok(query
+                .with_preserve_case(true)
+                .with_replacement(String::new()))
+        } else {
+            Ok(query)
         }
     }
 
@@ -193,6 +269,93 @@ impl SearchQuery {
                 ..
             } => {
                 *replacement = Some(new_replacement);
+            }
+        }
+        if self.preserve_case() {
+            self.rebuild_text_search()
+        } else {
+            self
+        }
+    }
+
+    /// Makes replacements follow the casing of the text they replace. While a replacement is
+    /// set, text queries additionally match the lower, UPPER, Title, camel, Pascal, snake and
+    /// kebab case variants of the query, so that every variant gets replaced. Without one, the
+    /// matches are the same as when case isn't preserved.
+    pub fn with_preserve_case(mut self, new_preserve_case: bool) -> Self {
+        if self.preserve_case() == new_preserve_case {
+            return self;
+        }
+        match self {
+            Self::Text {
+                ref mut preserve_case,
+                ..
+            }
+            | Self::Regex {
+                ref mut preserve_case,
+                ..
+            } => {
+                *preserve_case = new_preserve_case;
+            }
+        }
+        self.rebuild_text_search()
+    }
+
+    /// Returns whether text queries match the case variants of the query.
+    fn matches_case_variants(&self) -> bool {
+        match self {
+            Self::Text {
+                preserve_case,
+                replacement,
+                ..
+            } => *preserve_case && replacement.is_some(),
+            Self::Regex { .. } => false,
+        }
+    }
+
+    fn text_patterns(&self) -> Vec<String> {
+        if self.matches_case_variants() {
+            case_variants(self.as_str())
+        } else {
+            vec![self.as_str().to_string()]
+        }
+    }
+
+    fn rebuild_text_search(self) -> Self {
+        let patterns = self.text_patterns();
+        match self {
+            Self::Text {
+                search,
+                replacement,
+                preserve_case,
+                whole_word,
+                case_sensitive,
+                include_ignored,
+                inner,
+            } => {
+                let search = AhoCorasickBuilder::new()
+                    .ascii_case_insensitive(!case_sensitive)
+                    .build(patterns)
+                    .map(Arc::new)
+                    .unwrap_or(search);
+                Self::Text {
+                    search,
+                    replacement,
+                    preserve_case,
+                    whole_word,
+                    case_sensitive,
+                    include_ignored,
+                    inner,
+                }
+            }
+            query @ Self::Regex { .. } => query,
+        }
+    }
+
+    pub fn with_syntax_scope(mut self, syntax_scope: SyntaxScope) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. } | Self::Regex { ref mut inner, .. } => {
+                inner.syntax_scope = syntax_scope;
                 self
             }
         }
@@ -207,6 +370,41 @@ impl SearchQuery {
             include_ignored: self.include_ignored(),
             files_to_include: self.files_to_include().sources().join(","),
             files_to_exclude: self.files_to_exclude().sources().join(","),
+            syntax_scope: self.syntax_scope().to_proto() as i32,
+            preserve_case: self.matches_case_variants(),
+        }
+    }
+
+    /// Returns the trigrams a file has to contain to possibly match this query, or `None`
+    /// when they can't be determined and every file has to be scanned.
+    pub fn trigram_query(&self) -> Option<TrigramQuery> {
+        match self {
+            Self::Text { .. } => TrigramQuery::any_of(
+                self.text_patterns()
+                    .iter()
+                    .map(|pattern| trigrams(pattern, false)),
+            ),
+            Self::Regex {
+                inner,
+                case_sensitive,
+                ..
+            } => {
+                let literals = regex_literals(inner.as_str());
+                // Unicode case folding matches `k` and `s` with non-ASCII characters, like the
+                // Kelvin sign, whose trigrams are indexed apart from theirs.
+                if !case_sensitive
+                    && literals
+                        .iter()
+                        .any(|literal| literal.contains(&['k', 'K', 's', 'S'][..]))
+                {
+                    return None;
+                }
+                let required = literals
+                    .iter()
+                    .flat_map(|literal| trigrams(literal, true))
+                    .collect();
+                TrigramQuery::any_of([required])
+            }
         }
     }
 
@@ -258,29 +456,45 @@ impl SearchQuery {
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    ///
+    /// Regex replacements support `\u` and `\l` to change the case of the next character,
+    /// and `\U` and `\L` to change the case of everything up to the next `\E`.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
-            SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
-            SearchQuery::Regex {
-                regex, replacement, ..
+            SearchQuery::Text {
+                replacement,
+                preserve_case,
+                ..
             } => {
-                if let Some(replacement) = replacement {
-                    static TEXT_REPLACEMENT_SPECIAL_CHARACTERS_REGEX: LazyLock<Regex> =
-                        LazyLock::new(|| Regex::new(r"\\\\|\\n|\\t").unwrap());
-                    let replacement = TEXT_REPLACEMENT_SPECIAL_CHARACTERS_REGEX.replace_all(
-                        replacement,
-                        |c: &Captures| match c.get(0).unwrap().as_str() {
-                            r"\\" => "\\",
-                            r"\n" => "\n",
-                            r"\t" => "\t",
-                            x => unreachable!("Unexpected escape sequence: {}", x),
-                        },
-                    );
-                    Some(regex.replace(text, replacement))
+                let replacement = replacement.as_ref()?;
+                if *preserve_case {
+                    Some(Cow::Owned(match_case(text, replacement)))
                 } else {
-                    None
+                    Some(Cow::Owned(replacement.clone()))
                 }
             }
+            SearchQuery::Regex {
+                regex,
+                replacement,
+                preserve_case,
+                ..
+            } => {
+                let replacement = replacement.as_ref()?;
+                let Some(captures) = regex.captures(text).ok().flatten() else {
+                    return Some(Cow::Borrowed(text));
+                };
+                let mat = captures.get(0)?;
+                let mut expanded = expand_replacement(&captures, &parse_replacement(replacement));
+                if *preserve_case {
+                    expanded = match_case(mat.as_str(), &expanded);
+                }
+                Some(Cow::Owned(format!(
+                    "{}{}{}",
+                    &text[..mat.start()],
+                    expanded,
+                    &text[mat.end()..]
+                )))
+            }
         }
     }
 
@@ -378,6 +592,11 @@ impl SearchQuery {
             }
         }
 
+        let syntax_scope = self.syntax_scope();
+        if syntax_scope != SyntaxScope::All {
+            matches.retain(|range| syntax_scope.contains(buffer, range_offset + range.start));
+        }
+
         matches
     }
 
@@ -414,6 +633,16 @@ impl SearchQuery {
         }
     }
 
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        self.as_inner().syntax_scope()
+    }
+
+    pub fn preserve_case(&self) -> bool {
+        match self {
+            Self::Text { preserve_case, .. } | Self::Regex { preserve_case, .. } => *preserve_case,
+        }
+    }
+
     pub fn is_regex(&self) -> bool {
         matches!(self, Self::Regex { .. })
     }
@@ -460,6 +689,177 @@ impl SearchQuery {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CaseModifier {
+    UpperNext,
+    LowerNext,
+    Upper,
+    Lower,
+    End,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ReplacementPart {
+    Template(String),
+    Case(CaseModifier),
+}
+
+/// Splits a regex replacement into templates to be expanded with the match's captures
+/// and the case modifiers between them, resolving `\\`, `\n` and `\t` escapes.
+fn parse_replacement(replacement: &str) -> Vec<ReplacementPart> {
+    let mut parts = Vec::new();
+    let mut template = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            template.push(c);
+            continue;
+        }
+
+        let modifier = match chars.next() {
+            Some('\\') => {
+                template.push('\\');
+                continue;
+            }
+            Some('n') => {
+                template.push('\n');
+                continue;
+            }
+            Some('t') => {
+                template.push('\t');
+                continue;
+            }
+            Some('u') => CaseModifier::UpperNext,
+            Some('l') => CaseModifier::LowerNext,
+            Some('U') => CaseModifier::Upper,
+            Some('L') => CaseModifier::Lower,
+            Some('E') => CaseModifier::End,
+            Some(c) => {
+                template.push('\\');
+                template.push(c);
+                continue;
+            }
+            None => {
+                template.push('\\');
+                break;
+            }
+        };
+        if !template.is_empty() {
+            parts.push(ReplacementPart::Template(std::mem::take(&mut template)));
+        }
+        parts.push(ReplacementPart::Case(modifier));
+    }
+    if !template.is_empty() {
+        parts.push(ReplacementPart::Template(template));
+    }
+    parts
+}
+
+fn expand_replacement(captures: &Captures, parts: &[ReplacementPart]) -> String {
+    let mut result = String::new();
+    let mut next_char_case = None;
+    let mut span_case = None;
+    for part in parts {
+        match part {
+            ReplacementPart::Case(
+                modifier @ (CaseModifier::UpperNext | CaseModifier::LowerNext),
+            ) => {
+                next_char_case = Some(*modifier);
+            }
+            ReplacementPart::Case(modifier @ (CaseModifier::Upper | CaseModifier::Lower)) => {
+                span_case = Some(*modifier);
+            }
+            ReplacementPart::Case(CaseModifier::End) => span_case = None,
+            ReplacementPart::Template(template) => {
+                let mut expanded = String::new();
+                captures.expand(template, &mut expanded);
+                for c in expanded.chars() {
+                    match next_char_case.take().or(span_case) {
+                        Some(CaseModifier::UpperNext | CaseModifier::Upper) => {
+                            result.extend(c.to_uppercase())
+                        }
+                        Some(CaseModifier::LowerNext | CaseModifier::Lower) => {
+                            result.extend(c.to_lowercase())
+                        }
+                        _ => result.push(c),
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Returns the spellings of `query` in the casings that [`match_case`] can reproduce.
+fn case_variants(query: &str) -> Vec<String> {
+    let mut variants = vec![query.to_string()];
+    for case in [
+        Case::Flat,
+        Case::UpperFlat,
+        Case::Camel,
+        Case::Pascal,
+        Case::Snake,
+        Case::UpperSnake,
+        Case::Kebab,
+    ] {
+        variants.push(query.to_case(case));
+    }
+    variants.push(capitalize(&query.to_case(Case::Flat)));
+    variants.retain(|variant| !variant.is_empty());
+    let mut seen = HashSet::default();
+    variants.retain(|variant| seen.insert(variant.clone()));
+    variants
+}
+
+/// Converts `replacement` to the casing of `text`, the way a user would type it
+/// in place of `text`.
+fn match_case(text: &str, replacement: &str) -> String {
+    let has_lowercase = text.chars().any(char::is_lowercase);
+    let has_uppercase = text.chars().any(char::is_uppercase);
+    if !has_lowercase && !has_uppercase {
+        return replacement.to_string();
+    }
+
+    let words_case = if text.contains('_') {
+        Some(if has_lowercase {
+            Case::Snake
+        } else {
+            Case::UpperSnake
+        })
+    } else if text.contains('-') && !has_uppercase {
+        Some(Case::Kebab)
+    } else {
+        None
+    };
+    if let Some(case) = words_case {
+        return replacement.to_case(case);
+    }
+
+    if !has_lowercase {
+        return replacement.to_uppercase();
+    }
+    if !has_uppercase {
+        return replacement.to_lowercase();
+    }
+
+    let starts_uppercase = text.chars().next().map_or(false, char::is_uppercase);
+    let has_inner_uppercase = text.chars().skip(1).any(char::is_uppercase);
+    match (starts_uppercase, has_inner_uppercase) {
+        (true, true) => replacement.to_case(Case::Pascal),
+        (true, false) => capitalize(replacement),
+        (false, true) => replacement.to_case(Case::Camel),
+        (false, false) => replacement.to_string(),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 pub fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<PathMatcher> {
     let globs = glob_set
         .split(',')
@@ -474,6 +874,132 @@ pub fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<PathMatcher> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_regex_replacement_case_modifiers() {
+        let query = SearchQuery::regex(
+            r"(\w+)_(\w+)",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap();
+        for (replacement, expected) in [
+            (r"${1}_$2", "foo_bar"),
+            (r"\u$1\u$2", "FooBar"),
+            (r"\U$1\E_$2", "FOO_bar"),
+            (r"\L\u$2\E$1", "Barfoo"),
+            (r"\U$1\l$2", "FOObAR"),
+            (r"$1\\u$2", r"foo\ubar"),
+            (r"$1\n\t$2", "foo\n\tbar"),
+        ] {
+            let query = query.clone().with_replacement(replacement.to_string());
+            assert_eq!(
+                query.replacement_for("foo_bar").as_deref(),
+                Some(expected),
+                "unexpected replacement for {replacement:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_case_insensitive_regex_trigrams() {
+        let regex = |query: &str, case_sensitive: bool| {
+            SearchQuery::regex(
+                query,
+                false,
+                case_sensitive,
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap()
+        };
+
+        // The Kelvin sign matches `k` case-insensitively, but has none of its trigrams.
+        let query = regex("kelvin", false);
+        let text: Box<dyn Read + Send + Sync> = Box::new("\u{212A}elvin".as_bytes());
+        assert!(query.detect(BufReader::new(text)).unwrap());
+        assert!(query.trigram_query().is_none());
+
+        assert!(regex("kelvin", true).trigram_query().is_some());
+        assert!(regex("foo_bar", false).trigram_query().is_some());
+    }
+
+    #[test]
+    fn test_preserve_case_replacement() {
+        let query = SearchQuery::text(
+            "fooBar",
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+        .with_preserve_case(true)
+        .with_replacement("bazQux".to_string());
+        for (text, expected) in [
+            ("fooBar", "bazQux"),
+            ("FooBar", "BazQux"),
+            ("foobar", "bazqux"),
+            ("FOOBAR", "BAZQUX"),
+            ("Foobar", "BazQux"),
+            ("foo_bar", "baz_qux"),
+            ("FOO_BAR", "BAZ_QUX"),
+            ("foo-bar", "baz-qux"),
+        ] {
+            assert_eq!(
+                query.replacement_for(text).as_deref(),
+                Some(expected),
+                "unexpected replacement for {text:?}"
+            );
+        }
+    }
+
+    #[gpui::test]
+    async fn test_preserve_case_matches_case_variants(cx: &mut gpui::TestAppContext) {
+        let text = "fooBar FooBar foo_bar FOO_BAR foo-bar foobar";
+        let buffer = cx.new(|cx| Buffer::local(text, cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let query = SearchQuery::text(
+            "fooBar",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(query.search(&snapshot, None).await, vec![0..6]);
+
+        // Preserving case doesn't change what is found until there's something to replace.
+        let query = query.with_preserve_case(true);
+        assert_eq!(query.search(&snapshot, None).await, vec![0..6]);
+        assert_eq!(
+            query
+                .clone()
+                .with_replacement("bazQux".to_string())
+                .search(&snapshot, None)
+                .await,
+            vec![0..6, 7..13, 14..21, 22..29, 30..37, 38..44]
+        );
+        assert_eq!(
+            query
+                .with_replacement("bazQux".to_string())
+                .with_preserve_case(false)
+                .search(&snapshot, None)
+                .await,
+            vec![0..6]
+        );
+    }
+
     #[test]
     fn path_matcher_creation_for_valid_paths() {
         for valid_path in [
@@ -0,0 +1,579 @@
+//! A trigram index of the file contents of local worktrees, persisted in the database.
+//!
+//! Project search uses the index to skip files that can't contain a match without reading
+//! them from disk. Files the index doesn't know about, or whose index entry is older than
+//! the file on disk, are always scanned.
+
+use std::{
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::Chars,
+    sync::Arc,
+};
+
+use collections::{HashMap, HashSet};
+use fs::{Fs, MTime};
+use gpui::{App, Task};
+use parking_lot::RwLock;
+use smol::{channel::Sender, stream::StreamExt};
+use util::ResultExt;
+use worktree::{PathChange, Snapshot, UpdatedEntriesSet};
+
+use persistence::TRIGRAM_INDEX_DB;
+
+pub type Trigram = u32;
+
+/// Files larger than this are not indexed, and are always scanned.
+const MAX_INDEXED_FILE_SIZE: usize = 4 * 1024 * 1024;
+const FILTER_BITS_PER_TRIGRAM: usize = 8;
+const MIN_FILTER_BITS: usize = 64;
+const MAX_FILTER_BITS: usize = 1 << 18;
+
+/// The trigrams a file has to contain in order to possibly match a search query.
+///
+/// A file may match if it contains every trigram of at least one of the alternatives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrigramQuery {
+    alternatives: Vec<Vec<Trigram>>,
+}
+
+impl TrigramQuery {
+    /// Returns `None` if any of the alternatives has no trigrams, as every file may match it.
+    pub fn any_of(alternatives: impl IntoIterator<Item = Vec<Trigram>>) -> Option<Self> {
+        let alternatives = alternatives.into_iter().collect::<Vec<_>>();
+        if alternatives.is_empty() || alternatives.iter().any(Vec::is_empty) {
+            return None;
+        }
+        Some(Self { alternatives })
+    }
+
+    fn may_match(&self, filter: &TrigramFilter) -> bool {
+        self.alternatives
+            .iter()
+            .any(|trigrams| trigrams.iter().all(|trigram| filter.may_contain(*trigram)))
+    }
+}
+
+/// Returns the distinct trigrams of `text`, ignoring ASCII case.
+///
+/// When `ascii_only` is set, trigrams containing non-ASCII bytes are skipped, for queries
+/// that match those bytes case-insensitively.
+pub fn trigrams(text: &str, ascii_only: bool) -> Vec<Trigram> {
+    let mut trigrams = text
+        .as_bytes()
+        .windows(3)
+        .filter(|window| !ascii_only || window.is_ascii())
+        .map(trigram)
+        .collect::<Vec<_>>();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+fn trigram(bytes: &[u8]) -> Trigram {
+    bytes.iter().fold(0, |trigram, byte| {
+        trigram << 8 | byte.to_ascii_lowercase() as u32
+    })
+}
+
+/// Returns literal strings that every match of the regular expression `pattern` contains.
+///
+/// The extraction is conservative: groups, character classes and escape sequences other
+/// than escaped punctuation are skipped, and no literals are returned for patterns with a
+/// top-level alternation or with inline flags, which may change how the pattern is parsed.
+pub fn regex_literals(pattern: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    let mut current = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '|' => return Vec::new(),
+            '\\' => match chars.next() {
+                Some(escaped) if escaped.is_ascii_punctuation() => current.push(escaped),
+                Some(escaped) => {
+                    literals.extend(take_literal(&mut current));
+                    skip_escape(&mut chars, escaped);
+                }
+                None => literals.extend(take_literal(&mut current)),
+            },
+            '(' if chars.peek() == Some(&'?') && is_flag_group(chars.clone()) => {
+                return Vec::new();
+            }
+            '(' | '[' => {
+                literals.extend(take_literal(&mut current));
+                let close = if c == '(' { ')' } else { ']' };
+                if !skip_group(&mut chars, close) {
+                    return Vec::new();
+                }
+            }
+            '?' | '*' => {
+                current.pop();
+                literals.extend(take_literal(&mut current));
+            }
+            '{' => {
+                let mut repetition = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    repetition.push(c);
+                }
+                let min = repetition.split(',').next().unwrap_or_default();
+                if min.trim().parse::<usize>().map_or(true, |min| min == 0) {
+                    current.pop();
+                }
+                literals.extend(take_literal(&mut current));
+            }
+            '.' | '^' | '$' | '+' | ')' | ']' | '}' => {
+                literals.extend(take_literal(&mut current));
+            }
+            c => current.push(c),
+        }
+    }
+    literals.extend(take_literal(&mut current));
+    literals
+}
+
+fn take_literal(current: &mut String) -> Option<String> {
+    (!current.is_empty()).then(|| std::mem::take(current))
+}
+
+/// Skips over the rest of an escape sequence, whose backslash and first character have been
+/// consumed, such as the code point of `\x41` or `\u{41}` and the class name of `\pL`.
+fn skip_escape(chars: &mut Peekable<Chars>, escaped: char) {
+    let char_count = match escaped {
+        'x' => 2,
+        'u' => 4,
+        'U' => 8,
+        'p' | 'P' => 1,
+        'k' => {
+            if chars.next_if_eq(&'<').is_some() {
+                chars.by_ref().find(|c| *c == '>');
+            }
+            return;
+        }
+        '0'..='9' => {
+            while chars.next_if(char::is_ascii_digit).is_some() {}
+            return;
+        }
+        _ => return,
+    };
+    if chars.next_if_eq(&'{').is_some() {
+        chars.by_ref().find(|c| *c == '}');
+    } else {
+        for _ in 0..char_count {
+            chars.next();
+        }
+    }
+}
+
+/// Returns whether a group, whose opening parenthesis has been consumed, only sets flags
+/// such as `(?x)` or `(?i:...)`, rather than being a non-capturing, named or lookaround group.
+fn is_flag_group(mut chars: Peekable<Chars>) -> bool {
+    chars.next();
+    chars
+        .next()
+        .is_some_and(|c| c == '-' || (c.is_ascii_alphabetic() && c != 'P'))
+}
+
+/// Skips over a group or a character class, whose opening bracket has been consumed.
+/// Returns false if the closing bracket is missing.
+fn skip_group(chars: &mut Peekable<Chars>, close: char) -> bool {
+    if close == ']' {
+        chars.next_if_eq(&'^');
+        chars.next_if_eq(&']');
+    }
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => {
+                if !skip_group(chars, ']') {
+                    return false;
+                }
+            }
+            '(' if close == ')' => {
+                if !skip_group(chars, ')') {
+                    return false;
+                }
+            }
+            c if c == close => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// A bloom filter over the trigrams of a file.
+#[derive(Clone, Debug)]
+struct TrigramFilter {
+    words: Box<[u64]>,
+}
+
+impl TrigramFilter {
+    fn new(trigrams: &HashSet<Trigram>) -> Self {
+        let bit_count = (trigrams.len() * FILTER_BITS_PER_TRIGRAM)
+            .next_power_of_two()
+            .clamp(MIN_FILTER_BITS, MAX_FILTER_BITS);
+        let mut words = vec![0; bit_count / 64].into_boxed_slice();
+        for trigram in trigrams {
+            for bit in Self::bits(*trigram, bit_count) {
+                words[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        Self { words }
+    }
+
+    fn may_contain(&self, trigram: Trigram) -> bool {
+        Self::bits(trigram, self.words.len() * 64)
+            .into_iter()
+            .all(|bit| self.words[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    fn bits(trigram: Trigram, bit_count: usize) -> [usize; 2] {
+        let hash = (trigram as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let mask = bit_count as u64 - 1;
+        [(hash & mask) as usize, ((hash >> 32) & mask) as usize]
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let word_count = bytes.len() / 8;
+        if bytes.len() % 8 != 0 || !word_count.is_power_of_two() {
+            return None;
+        }
+        let words = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        Some(Self { words })
+    }
+}
+
+struct IndexedFile {
+    mtime: MTime,
+    filter: TrigramFilter,
+}
+
+/// A read handle to the trigram index of a worktree.
+#[derive(Clone, Default)]
+pub struct TrigramIndex {
+    files: Arc<RwLock<HashMap<Arc<Path>, IndexedFile>>>,
+}
+
+impl TrigramIndex {
+    /// Whether the file at `path` may contain a match for `query`. Files that aren't indexed
+    /// yet, or that changed since they were indexed, always may.
+    pub fn may_match(&self, path: &Path, mtime: Option<MTime>, query: &TrigramQuery) -> bool {
+        match self.files.read().get(path) {
+            Some(file) if Some(file.mtime) == mtime => query.may_match(&file.filter),
+            _ => true,
+        }
+    }
+}
+
+enum FileChange {
+    Updated { path: Arc<Path>, mtime: MTime },
+    Removed { path: Arc<Path> },
+}
+
+/// A change to the persisted index. The changes of a batch are written in one transaction.
+enum IndexWrite {
+    Save {
+        path: PathBuf,
+        mtime_seconds: u64,
+        mtime_nanos: u32,
+        filter: Vec<u8>,
+    },
+    Delete {
+        path: PathBuf,
+    },
+}
+
+/// The trigram index of a local worktree, along with the task keeping it in sync with the
+/// worktree's entries.
+pub(crate) struct WorktreeTrigramIndex {
+    index: TrigramIndex,
+    changes_tx: Sender<Vec<FileChange>>,
+    _maintain_index: Task<()>,
+}
+
+impl WorktreeTrigramIndex {
+    pub fn new(worktree_abs_path: Arc<Path>, fs: Arc<dyn Fs>, cx: &App) -> Self {
+        let index = TrigramIndex::default();
+        let (changes_tx, mut changes_rx) = smol::channel::unbounded::<Vec<FileChange>>();
+        let files = index.files.clone();
+        let _maintain_index = cx.background_executor().spawn(async move {
+            let worktree_path = worktree_abs_path.to_path_buf();
+            if let Some(persisted) = TRIGRAM_INDEX_DB
+                .indexed_files(worktree_path.clone())
+                .log_err()
+            {
+                let mut files = files.write();
+                for (path, mtime_seconds, mtime_nanos, filter) in persisted {
+                    if let Some(filter) = TrigramFilter::from_bytes(&filter) {
+                        let mtime = MTime::from_seconds_and_nanos(mtime_seconds, mtime_nanos);
+                        files.insert(path.into(), IndexedFile { mtime, filter });
+                    }
+                }
+            }
+
+            while let Some(changes) = changes_rx.next().await {
+                let mut writes = Vec::new();
+
+                // Removals are applied in a single pass, as removing a large directory reports
+                // each of its entries.
+                let removed_paths = changes
+                    .iter()
+                    .filter_map(|change| match change {
+                        FileChange::Removed { path } => Some(path.clone()),
+                        FileChange::Updated { .. } => None,
+                    })
+                    .collect::<HashSet<_>>();
+                if !removed_paths.is_empty() {
+                    files.write().retain(|file_path, _| {
+                        let is_removed = file_path
+                            .ancestors()
+                            .any(|ancestor| removed_paths.contains(ancestor));
+                        if is_removed {
+                            writes.push(IndexWrite::Delete {
+                                path: file_path.to_path_buf(),
+                            });
+                        }
+                        !is_removed
+                    });
+                }
+
+                for change in changes {
+                    match change {
+                        FileChange::Updated { path, mtime } => {
+                            let is_up_to_date = files
+                                .read()
+                                .get(&path)
+                                .is_some_and(|file| file.mtime == mtime);
+                            if is_up_to_date {
+                                continue;
+                            }
+                            let Some((mtime_seconds, mtime_nanos)) =
+                                mtime.to_seconds_and_nanos_for_persistence()
+                            else {
+                                continue;
+                            };
+                            let filter =
+                                index_file(fs.as_ref(), &worktree_abs_path.join(&path)).await;
+                            match filter {
+                                Some(filter) => {
+                                    writes.push(IndexWrite::Save {
+                                        path: path.to_path_buf(),
+                                        mtime_seconds,
+                                        mtime_nanos,
+                                        filter: filter.to_bytes(),
+                                    });
+                                    files.write().insert(path, IndexedFile { mtime, filter });
+                                }
+                                None => {
+                                    let was_indexed = files.write().remove(&path).is_some();
+                                    if was_indexed {
+                                        writes.push(IndexWrite::Delete {
+                                            path: path.to_path_buf(),
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                        FileChange::Removed { .. } => {}
+                    }
+                }
+                if !writes.is_empty() {
+                    TRIGRAM_INDEX_DB
+                        .write_changes(worktree_path.clone(), writes)
+                        .await
+                        .log_err();
+                }
+            }
+        });
+
+        Self {
+            index,
+            changes_tx,
+            _maintain_index,
+        }
+    }
+
+    pub fn index(&self) -> TrigramIndex {
+        self.index.clone()
+    }
+
+    /// Reindexes the files affected by a batch of changes reported by the worktree scanner.
+    pub fn update(&self, snapshot: &Snapshot, changes: &UpdatedEntriesSet) {
+        let changes = changes
+            .iter()
+            .filter_map(|(path, _, change)| {
+                if *change == PathChange::Removed {
+                    return Some(FileChange::Removed { path: path.clone() });
+                }
+                let entry = snapshot.entry_for_path(path)?;
+                if !entry.is_file() || entry.is_fifo {
+                    return None;
+                }
+                Some(FileChange::Updated {
+                    path: path.clone(),
+                    mtime: entry.mtime?,
+                })
+            })
+            .collect::<Vec<_>>();
+        if !changes.is_empty() {
+            self.changes_tx.try_send(changes).ok();
+        }
+    }
+}
+
+/// Computes the trigram filter of a file, unless the file is too large or isn't valid UTF-8.
+async fn index_file(fs: &dyn Fs, abs_path: &Path) -> Option<TrigramFilter> {
+    let metadata = fs.metadata(abs_path).await.ok()??;
+    if metadata.len as usize > MAX_INDEXED_FILE_SIZE {
+        return None;
+    }
+    let content = fs.load_bytes(abs_path).await.ok()?;
+    std::str::from_utf8(&content).ok()?;
+    let trigrams = content.windows(3).map(trigram).collect::<HashSet<_>>();
+    Some(TrigramFilter::new(&trigrams))
+}
+
+mod persistence {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use db::{define_connection, query, sqlez_macros::sql};
+
+    use super::IndexWrite;
+
+    define_connection!(
+        pub static ref TRIGRAM_INDEX_DB: TrigramIndexDb<()> =
+            &[sql!(
+                CREATE TABLE trigram_index_files (
+                    worktree_path BLOB NOT NULL,
+                    path BLOB NOT NULL,
+                    mtime_seconds INTEGER NOT NULL,
+                    mtime_nanos INTEGER NOT NULL,
+                    filter BLOB NOT NULL,
+                    PRIMARY KEY(worktree_path, path)
+                ) STRICT;
+            )];
+    );
+
+    impl TrigramIndexDb {
+        query! {
+            pub fn indexed_files(worktree_path: PathBuf) -> Result<Vec<(PathBuf, u64, u32, Vec<u8>)>> {
+                SELECT path, mtime_seconds, mtime_nanos, filter
+                FROM trigram_index_files
+                WHERE worktree_path = ?
+            }
+        }
+
+        /// Saves and deletes the index entries of a batch of changed files in one transaction.
+        pub async fn write_changes(
+            &self,
+            worktree_path: PathBuf,
+            writes: Vec<IndexWrite>,
+        ) -> Result<()> {
+            self.write(move |conn| {
+                conn.with_savepoint("write_trigram_index_changes", || {
+                    for write in writes {
+                        match write {
+                            IndexWrite::Save {
+                                path,
+                                mtime_seconds,
+                                mtime_nanos,
+                                filter,
+                            } => {
+                                conn.exec_bound(sql!(
+                                    INSERT OR REPLACE INTO trigram_index_files
+                                        (worktree_path, path, mtime_seconds, mtime_nanos, filter)
+                                    VALUES
+                                        (?, ?, ?, ?, ?)
+                                ))?((
+                                    worktree_path.clone(),
+                                    path,
+                                    mtime_seconds,
+                                    mtime_nanos,
+                                    filter,
+                                ))?;
+                            }
+                            IndexWrite::Delete { path } => {
+                                conn.exec_bound(sql!(
+                                    DELETE FROM trigram_index_files
+                                    WHERE worktree_path = ? AND path = ?
+                                ))?((worktree_path.clone(), path))?;
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_literals() {
+        assert_eq!(regex_literals("foo_bar"), vec!["foo_bar"]);
+        assert_eq!(regex_literals(r"fn\s+main\(\)"), vec!["fn", "main()"]);
+        assert_eq!(regex_literals("colou?r"), vec!["colo", "r"]);
+        assert_eq!(regex_literals("ab{0,2}cd{2}"), vec!["a", "cd"]);
+        assert_eq!(regex_literals(r"impl(<.*>)? Foo"), vec!["impl", " Foo"]);
+        assert_eq!(regex_literals("[]a-z]+_suffix"), vec!["_suffix"]);
+        assert_eq!(regex_literals("foo|bar"), Vec::<String>::new());
+        assert_eq!(regex_literals(r"\w+\d*"), Vec::<String>::new());
+        assert_eq!(regex_literals(r"a\x41bc\x{42}de"), vec!["a", "bc", "de"]);
+        assert_eq!(
+            regex_literals(r"\u0041bcd\U0001F600efg"),
+            vec!["bcd", "efg"]
+        );
+        assert_eq!(regex_literals(r"\pLfoo\p{Greek}bar"), vec!["foo", "bar"]);
+        assert_eq!(
+            regex_literals(r"(\w+) \1abc\k<x>def"),
+            vec![" ", "abc", "def"]
+        );
+        assert_eq!(regex_literals(r"(?:foo)bar(?<name>baz)"), vec!["bar"]);
+        assert_eq!(regex_literals("(?x) foo bar"), Vec::<String>::new());
+        assert_eq!(regex_literals("foo(?i:bar)"), Vec::<String>::new());
+        assert_eq!(regex_literals("foo(?-i)bar"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_trigram_filter() {
+        let content = "fn main() {\n    println!(\"Hello, World\");\n}\n";
+        let file_trigrams = content.as_bytes().windows(3).map(trigram).collect();
+        let filter =
+            TrigramFilter::from_bytes(&TrigramFilter::new(&file_trigrams).to_bytes()).unwrap();
+
+        let query = TrigramQuery::any_of([trigrams("hello, world", false)]).unwrap();
+        assert!(query.may_match(&filter));
+        let query = TrigramQuery::any_of([trigrams("println", false)]).unwrap();
+        assert!(query.may_match(&filter));
+        let query =
+            TrigramQuery::any_of([trigrams("goodbye", false), trigrams("main", false)]).unwrap();
+        assert!(query.may_match(&filter));
+        let query = TrigramQuery::any_of([trigrams("goodbye", false)]).unwrap();
+        assert!(!query.may_match(&filter));
+
+        assert_eq!(TrigramQuery::any_of([trigrams("fn", false)]), None);
+        assert_eq!(
+            TrigramQuery::any_of([trigrams("main", false), trigrams("fn", false)]),
+            None
+        );
+    }
+}
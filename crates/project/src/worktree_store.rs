@@ -18,6 +18,7 @@ use rpc::{
     proto::{self, SSH_PROJECT_ID},
     AnyProtoClient, ErrorExt, TypedEnvelope,
 };
+use settings::Settings as _;
 use smol::{
     channel::{Receiver, Sender},
     stream::StreamExt,
@@ -26,7 +27,12 @@ use text::ReplicaId;
 use util::{paths::SanitizedPath, ResultExt};
 use worktree::{Entry, ProjectEntryId, UpdatedEntriesSet, Worktree, WorktreeId, WorktreeSettings};
 
-use crate::{search::SearchQuery, ProjectPath};
+use crate::{
+    project_settings::ProjectSettings,
+    search::SearchQuery,
+    trigram_index::{TrigramIndex, WorktreeTrigramIndex},
+    ProjectPath,
+};
 
 struct MatchingEntry {
     worktree_path: Arc<Path>,
@@ -53,6 +59,7 @@ pub struct WorktreeStore {
     #[allow(clippy::type_complexity)]
     loading_worktrees:
         HashMap<SanitizedPath, Shared<Task<Result<Entity<Worktree>, Arc<anyhow::Error>>>>>,
+    trigram_indexes: HashMap<WorktreeId, WorktreeTrigramIndex>,
     state: WorktreeStoreState,
 }
 
@@ -89,6 +96,7 @@ impl WorktreeStore {
             worktrees: Vec::new(),
             worktrees_reordered: false,
             retain_worktrees,
+            trigram_indexes: Default::default(),
             state: WorktreeStoreState::Local { fs },
         }
     }
@@ -105,6 +113,7 @@ impl WorktreeStore {
             worktrees: Vec::new(),
             worktrees_reordered: false,
             retain_worktrees,
+            trigram_indexes: Default::default(),
             state: WorktreeStoreState::Remote {
                 upstream_client,
                 upstream_project_id,
@@ -360,14 +369,25 @@ impl WorktreeStore {
             self.worktrees.insert(i, handle);
         }
 
+        if let WorktreeStoreState::Local { fs } = &self.state {
+            if worktree.read(cx).is_local() && ProjectSettings::get_global(cx).search_index.enabled
+            {
+                let index = WorktreeTrigramIndex::new(worktree.read(cx).abs_path(), fs.clone(), cx);
+                self.trigram_indexes.insert(worktree_id, index);
+            }
+        }
+
         cx.emit(WorktreeStoreEvent::WorktreeAdded(worktree.clone()));
         self.send_project_updates(cx);
 
         let handle_id = worktree.entity_id();
-        cx.subscribe(worktree, |_, worktree, event, cx| {
+        cx.subscribe(worktree, |this, worktree, event, cx| {
             let worktree_id = worktree.update(cx, |worktree, _| worktree.id());
             match event {
                 worktree::Event::UpdatedEntries(changes) => {
+                    if let Some(index) = this.trigram_indexes.get(&worktree_id) {
+                        index.update(&worktree.read(cx).snapshot(), changes);
+                    }
                     cx.emit(WorktreeStoreEvent::WorktreeUpdatedEntries(
                         worktree.read(cx).id(),
                         changes.clone(),
@@ -385,6 +405,7 @@ impl WorktreeStore {
         })
         .detach();
         cx.observe_release(worktree, move |this, worktree, cx| {
+            this.trigram_indexes.remove(&worktree.id());
             cx.emit(WorktreeStoreEvent::WorktreeReleased(
                 handle_id,
                 worktree.id(),
@@ -402,6 +423,7 @@ impl WorktreeStore {
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
                 if worktree.read(cx).id() == id_to_remove {
+                    self.trigram_indexes.remove(&id_to_remove);
                     cx.emit(WorktreeStoreEvent::WorktreeRemoved(
                         worktree.entity_id(),
                         id_to_remove,
@@ -656,7 +678,11 @@ impl WorktreeStore {
             .visible_worktrees(cx)
             .filter_map(|tree| {
                 let tree = tree.read(cx);
-                Some((tree.snapshot(), tree.as_local()?.settings()))
+                let trigram_index = self
+                    .trigram_indexes
+                    .get(&tree.id())
+                    .map(|index| index.index());
+                Some((tree.snapshot(), tree.as_local()?.settings(), trigram_index))
             })
             .collect::<Vec<_>>();
 
@@ -814,14 +840,15 @@ impl WorktreeStore {
 
     async fn find_candidate_paths(
         fs: Arc<dyn Fs>,
-        snapshots: Vec<(worktree::Snapshot, WorktreeSettings)>,
+        snapshots: Vec<(worktree::Snapshot, WorktreeSettings, Option<TrigramIndex>)>,
         open_entries: HashSet<ProjectEntryId>,
         query: SearchQuery,
         filter_tx: Sender<MatchingEntry>,
         output_tx: Sender<oneshot::Receiver<ProjectPath>>,
     ) -> Result<()> {
         let include_root = snapshots.len() > 1;
-        for (snapshot, settings) in snapshots {
+        let trigram_query = query.trigram_query();
+        for (snapshot, settings, trigram_index) in snapshots {
             for entry in snapshot.entries(query.include_ignored(), 0) {
                 if entry.is_dir() && entry.is_ignored {
                     if !settings.is_path_excluded(&entry.path) {
@@ -856,9 +883,19 @@ impl WorktreeStore {
                     }
                 }
 
+                let is_open = open_entries.contains(&entry.id);
+                if let Some((trigram_index, trigram_query)) =
+                    trigram_index.as_ref().zip(trigram_query.as_ref())
+                {
+                    if !is_open && !trigram_index.may_match(&entry.path, entry.mtime, trigram_query)
+                    {
+                        continue;
+                    }
+                }
+
                 let (mut tx, rx) = oneshot::channel();
 
-                if open_entries.contains(&entry.id) {
+                if is_open {
                     tx.send(ProjectPath {
                         worktree_id: snapshot.id(),
                         path: entry.path.clone(),
@@ -907,6 +907,8 @@ impl PromptLibrary {
                                     tools: Vec::new(),
                                     stop: Vec::new(),
                                     temperature: None,
+                                    thinking_budget: None,
+                                    max_tokens: None,
                                 },
                                 cx,
                             )
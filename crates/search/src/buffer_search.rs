@@ -3,7 +3,8 @@ mod registrar;
 use crate::{
     search_bar::render_nav_button, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll,
     ReplaceNext, SearchOptions, SelectAllMatches, SelectNextMatch, SelectPrevMatch,
-    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleCodeOnly, ToggleCommentsOnly, TogglePreserveCase, ToggleRegex,
+    ToggleReplace, ToggleSelection, ToggleStringsOnly, ToggleWholeWord,
 };
 use any_vec::AnyVec;
 use collections::HashMap;
@@ -274,7 +275,40 @@ impl Render for BufferSearchBar {
                                             this.toggle_regex(&ToggleRegex, window, cx)
                                         }),
                                     )
-                                })),
+                                }))
+                                .when(supported_options.syntax_scope, |this| {
+                                    this.child(self.render_search_option_button(
+                                        SearchOptions::CODE_ONLY,
+                                        focus_handle.clone(),
+                                        cx.listener(|this, _, window, cx| {
+                                            this.toggle_code_only(&ToggleCodeOnly, window, cx)
+                                        }),
+                                    ))
+                                    .child(self.render_search_option_button(
+                                        SearchOptions::COMMENTS_ONLY,
+                                        focus_handle.clone(),
+                                        cx.listener(|this, _, window, cx| {
+                                            this.toggle_comments_only(
+                                                &ToggleCommentsOnly,
+                                                window,
+                                                cx,
+                                            )
+                                        }),
+                                    ))
+                                    .child(
+                                        self.render_search_option_button(
+                                            SearchOptions::STRINGS_ONLY,
+                                            focus_handle.clone(),
+                                            cx.listener(|this, _, window, cx| {
+                                                this.toggle_strings_only(
+                                                    &ToggleStringsOnly,
+                                                    window,
+                                                    cx,
+                                                )
+                                            }),
+                                        ),
+                                    )
+                                }),
                         )
                     }),
             )
@@ -419,6 +453,13 @@ impl Render for BufferSearchBar {
                     h_flex()
                         .min_w_64()
                         .gap_1()
+                        .child(self.render_search_option_button(
+                            SearchOptions::PRESERVE_CASE,
+                            focus_handle.clone(),
+                            cx.listener(|this, _, window, cx| {
+                                this.toggle_preserve_case(&TogglePreserveCase, window, cx)
+                            }),
+                        ))
                         .child(
                             IconButton::new("search-replace-next", ui::IconName::ReplaceNext)
                                 .shape(IconButtonShape::Square)
@@ -589,6 +630,25 @@ impl BufferSearchBar {
                 this.toggle_regex(action, window, cx);
             }
         }));
+        registrar.register_handler(ForDeployed(|this, action: &ToggleCodeOnly, window, cx| {
+            if this.supported_options(cx).syntax_scope {
+                this.toggle_code_only(action, window, cx);
+            }
+        }));
+        registrar.register_handler(ForDeployed(
+            |this, action: &ToggleCommentsOnly, window, cx| {
+                if this.supported_options(cx).syntax_scope {
+                    this.toggle_comments_only(action, window, cx);
+                }
+            },
+        ));
+        registrar.register_handler(ForDeployed(
+            |this, action: &ToggleStringsOnly, window, cx| {
+                if this.supported_options(cx).syntax_scope {
+                    this.toggle_strings_only(action, window, cx);
+                }
+            },
+        ));
         registrar.register_handler(ForDeployed(|this, action: &ToggleSelection, window, cx| {
             if this.supported_options(cx).selection {
                 this.toggle_selection(action, window, cx);
@@ -603,6 +663,15 @@ impl BufferSearchBar {
                 cx.propagate();
             }
         }));
+        registrar.register_handler(ForDeployed(
+            |this, action: &TogglePreserveCase, window, cx| {
+                if this.supported_options(cx).replacement {
+                    this.toggle_preserve_case(action, window, cx);
+                } else {
+                    cx.propagate();
+                }
+            },
+        ));
         registrar.register_handler(WithResults(|this, action: &SelectNextMatch, window, cx| {
             if this.supported_options(cx).find_in_results {
                 cx.propagate();
@@ -829,6 +898,18 @@ impl BufferSearchBar {
         self.query_editor.read(cx).text(cx)
     }
 
+    /// Fills an empty search history with the given queries, oldest first,
+    /// e.g. with queries saved in a previous session.
+    pub fn seed_search_history(&mut self, queries: impl IntoIterator<Item = String>) {
+        if !self.search_history.is_empty() {
+            return;
+        }
+        let mut cursor = SearchHistoryCursor::default();
+        for query in queries {
+            self.search_history.add(&mut cursor, query);
+        }
+    }
+
     pub fn replacement(&self, cx: &mut App) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
@@ -907,7 +988,7 @@ impl BufferSearchBar {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.search_options.toggle(search_option);
+        self.search_options.toggle_option(search_option);
         self.default_options = self.search_options;
         drop(self.update_matches(false, window, cx));
         cx.notify();
@@ -1118,6 +1199,42 @@ impl BufferSearchBar {
         self.toggle_search_option(SearchOptions::REGEX, window, cx)
     }
 
+    fn toggle_preserve_case(
+        &mut self,
+        _: &TogglePreserveCase,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_search_option(SearchOptions::PRESERVE_CASE, window, cx)
+    }
+
+    fn toggle_code_only(
+        &mut self,
+        _: &ToggleCodeOnly,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_search_option(SearchOptions::CODE_ONLY, window, cx)
+    }
+
+    fn toggle_comments_only(
+        &mut self,
+        _: &ToggleCommentsOnly,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_search_option(SearchOptions::COMMENTS_ONLY, window, cx)
+    }
+
+    fn toggle_strings_only(
+        &mut self,
+        _: &ToggleStringsOnly,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_search_option(SearchOptions::STRINGS_ONLY, window, cx)
+    }
+
     fn clear_active_searchable_item_matches(&mut self, window: &mut Window, cx: &mut App) {
         if let Some(active_searchable_item) = self.active_searchable_item.as_ref() {
             self.active_match_index = None;
@@ -1181,7 +1298,12 @@ impl BufferSearchBar {
                             Default::default(),
                             None,
                         ) {
-                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Ok(query) => self
+                                .with_active_replacement(query, cx)
+                                .with_preserve_case(
+                                    self.search_options.contains(SearchOptions::PRESERVE_CASE),
+                                )
+                                .with_syntax_scope(self.search_options.syntax_scope()),
                             Err(_) => {
                                 self.query_contains_error = true;
                                 self.clear_active_searchable_item_matches(window, cx);
@@ -1199,7 +1321,12 @@ impl BufferSearchBar {
                             Default::default(),
                             None,
                         ) {
-                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Ok(query) => self
+                                .with_active_replacement(query, cx)
+                                .with_preserve_case(
+                                    self.search_options.contains(SearchOptions::PRESERVE_CASE),
+                                )
+                                .with_syntax_scope(self.search_options.syntax_scope()),
                             Err(_) => {
                                 self.query_contains_error = true;
                                 self.clear_active_searchable_item_matches(window, cx);
@@ -1354,10 +1481,23 @@ impl BufferSearchBar {
                 self.query_editor.focus_handle(cx)
             };
             self.focus(&handle, window, cx);
+            if self.search_options.contains(SearchOptions::PRESERVE_CASE) {
+                drop(self.update_matches(false, window, cx));
+            }
             cx.notify();
         }
     }
 
+    /// Preserving case only widens the matches to the case variants of the query while
+    /// replacing, so the replacement is only part of the query when replace is enabled.
+    fn with_active_replacement(&self, query: SearchQuery, cx: &mut App) -> SearchQuery {
+        if self.replace_enabled {
+            query.with_replacement(self.replacement(cx))
+        } else {
+            query
+        }
+    }
+
     fn replace_next(&mut self, _: &ReplaceNext, window: &mut Window, cx: &mut Context<Self>) {
         let mut should_propagate = true;
         if !self.dismissed && self.active_search.is_some() {
@@ -1,7 +1,10 @@
 use crate::{
-    buffer_search::Deploy, BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery,
-    ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
-    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    buffer_search::Deploy,
+    saved_searches::{SavedSearch, SavedSearchPicker, SearchSite, SAVED_SEARCHES_DB},
+    BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext,
+    SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleCodeOnly,
+    ToggleCommentsOnly, ToggleIncludeIgnored, TogglePreserveCase, ToggleRegex, ToggleReplace,
+    ToggleStringsOnly, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
@@ -15,7 +18,7 @@ use gpui::{
     ParentElement, Point, Render, SharedString, Styled, Subscription, Task, TextStyle,
     UpdateGlobal, WeakEntity, Window,
 };
-use language::Buffer;
+use language::{Buffer, ToPoint as _};
 use menu::Confirm;
 use project::{
     search::{SearchInputKind, SearchQuery},
@@ -25,9 +28,10 @@ use project::{
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
+    collections::BTreeSet,
     mem,
     ops::{Not, Range},
-    path::Path,
+    path::{Path, PathBuf},
     pin::pin,
 };
 use theme::ThemeSettings;
@@ -45,7 +49,16 @@ use workspace::{
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        SaveSearch,
+        OpenSavedSearch,
+        TogglePinResults,
+        ToggleSiteDone
+    ]
 );
 
 #[derive(Default)]
@@ -80,12 +93,36 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, _, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleCodeOnly, _, cx| {
+            search_bar.toggle_search_option(SearchOptions::CODE_ONLY, cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleCommentsOnly, _, cx| {
+                search_bar.toggle_search_option(SearchOptions::COMMENTS_ONLY, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleStringsOnly, _, cx| {
+                search_bar.toggle_search_option(SearchOptions::STRINGS_ONLY, cx);
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
                 search_bar.toggle_replace(action, window, cx)
             },
         );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &TogglePinResults, window, cx| {
+                search_bar.toggle_pin_results(window, cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleSiteDone, _, cx| {
+            search_bar.toggle_site_done(cx);
+        });
         register_workspace_action(
             workspace,
             move |search_bar, action: &SelectPrevMatch, window, cx| {
@@ -103,6 +140,15 @@ pub fn init(cx: &mut App) {
         register_workspace_action_for_present_search(workspace, |workspace, action, window, cx| {
             ProjectSearchView::search_in_new(workspace, action, window, cx)
         });
+        register_workspace_action_for_present_search(
+            workspace,
+            |workspace, _: &SaveSearch, window, cx| {
+                SavedSearchPicker::toggle_save(workspace, window, cx)
+            },
+        );
+        workspace.register_action(|workspace, _: &OpenSavedSearch, window, cx| {
+            SavedSearchPicker::toggle_open(workspace, window, cx);
+        });
 
         // Both on present and dismissed search, we need to unconditionally handle those actions to focus from the editor.
         workspace.register_action(move |workspace, action: &DeploySearch, window, cx| {
@@ -167,9 +213,19 @@ pub struct ProjectSearchView {
     filters_enabled: bool,
     replace_enabled: bool,
     included_opened_only: bool,
+    saved_search_name: Option<String>,
+    pinned_results: Option<PinnedResults>,
+    pending_done_sites: Option<Vec<SearchSite>>,
     _subscriptions: Vec<Subscription>,
 }
 
+/// Results that are frozen and no longer rerun, so that their matches can be checked off
+/// one by one. Matches are identified by their index in the search results.
+#[derive(Default)]
+struct PinnedResults {
+    done: BTreeSet<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ProjectSearchSettings {
     search_options: SearchOptions,
@@ -445,11 +501,16 @@ impl Item for ProjectSearchView {
                 let query_text = util::truncate_and_trailoff(&query, MAX_TAB_TITLE_LEN);
                 query_text.into()
             });
-        Some(
-            last_query
-                .filter(|query| !query.is_empty())
-                .unwrap_or_else(|| "Project Search".into()),
-        )
+        let title = last_query
+            .filter(|query| !query.is_empty())
+            .unwrap_or_else(|| "Project Search".into());
+        match &self.pinned_results {
+            Some(pinned_results) => {
+                let total = self.entity.read(cx).match_ranges.len();
+                Some(format!("{title} ({}/{total})", pinned_results.done.len()).into())
+            }
+            None => Some(title),
+        }
     }
 
     fn telemetry_event_text(&self) -> Option<&'static str> {
@@ -606,7 +667,7 @@ impl ProjectSearchView {
     }
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut Context<Self>) {
-        self.search_options.toggle(option);
+        self.search_options.toggle_option(option);
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.entity.read(cx).project.downgrade(),
@@ -808,6 +869,9 @@ impl ProjectSearchView {
             filters_enabled,
             replace_enabled: false,
             included_opened_only: false,
+            saved_search_name: None,
+            pinned_results: None,
+            pending_done_sites: None,
             _subscriptions: subscriptions,
         };
         this.entity_changed(window, cx);
@@ -966,6 +1030,9 @@ impl ProjectSearchView {
     }
 
     fn search(&mut self, cx: &mut Context<Self>) {
+        if self.pinned_results.is_some() {
+            return;
+        }
         if let Some(query) = self.build_search_query(cx) {
             self.entity.update(cx, |model, cx| model.search(query, cx));
         }
@@ -975,6 +1042,260 @@ impl ProjectSearchView {
         self.query_editor.read(cx).text(cx)
     }
 
+    /// Captures the query, options and filters of this search under the given name.
+    pub fn saved_search(&self, name: String, cx: &App) -> SavedSearch {
+        SavedSearch {
+            name,
+            query: self.search_query_text(cx),
+            options: self.search_options,
+            files_to_include: self.included_files_editor.read(cx).text(cx),
+            files_to_exclude: self.excluded_files_editor.read(cx).text(cx),
+            pinned: self.pinned_results.is_some(),
+        }
+    }
+
+    /// Opens a new search tab for the saved search and runs it. If its results were pinned,
+    /// they are pinned again with the matches that were checked off marked as done.
+    pub(crate) fn open_saved_search(
+        workspace: &mut Workspace,
+        saved_search: SavedSearch,
+        done_sites: Vec<SearchSite>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let weak_workspace = cx.entity().downgrade();
+        let entity = cx.new(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let search_view =
+            cx.new(|cx| ProjectSearchView::new(weak_workspace, entity, window, cx, None));
+        workspace.add_item_to_active_pane(Box::new(search_view.clone()), None, true, window, cx);
+        search_view.update(cx, |search_view, cx| {
+            search_view.set_search_editor(SearchInputKind::Query, &saved_search.query, window, cx);
+            search_view.set_search_editor(
+                SearchInputKind::Include,
+                &saved_search.files_to_include,
+                window,
+                cx,
+            );
+            search_view.set_search_editor(
+                SearchInputKind::Exclude,
+                &saved_search.files_to_exclude,
+                window,
+                cx,
+            );
+            search_view.filters_enabled = !saved_search.files_to_include.is_empty()
+                || !saved_search.files_to_exclude.is_empty();
+            search_view.search_options = saved_search.options;
+            search_view.saved_search_name = Some(saved_search.name);
+            search_view.search(cx);
+            if saved_search.pinned {
+                search_view.pending_done_sites = Some(done_sites);
+            }
+        });
+    }
+
+    pub(crate) fn set_saved_search_name(&mut self, name: String, cx: &mut Context<Self>) {
+        self.saved_search_name = Some(name);
+        let done = self
+            .pinned_results
+            .iter()
+            .flat_map(|pinned_results| pinned_results.done.iter().copied())
+            .collect::<Vec<_>>();
+        for ix in done {
+            if let Some(site) = self.match_site(ix, cx) {
+                self.persist_site_done(site, true, cx);
+            }
+        }
+        cx.notify();
+    }
+
+    fn toggle_pin_results(&mut self, cx: &mut Context<Self>) {
+        let pinned = self.pinned_results.take().is_none();
+        if pinned {
+            self.pinned_results = Some(PinnedResults::default());
+        }
+        self.pending_done_sites = None;
+        if let Some((workspace_id, name)) = self.saved_search_key(cx) {
+            cx.background_executor()
+                .spawn(async move {
+                    if !pinned {
+                        SAVED_SEARCHES_DB
+                            .clear_done_sites(workspace_id, name.clone())
+                            .await?;
+                    }
+                    SAVED_SEARCHES_DB
+                        .set_pinned(workspace_id, name, pinned)
+                        .await
+                })
+                .detach_and_log_err(cx);
+        }
+        self.update_done_highlights(cx);
+        cx.emit(ViewEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn toggle_site_done(&mut self, cx: &mut Context<Self>) {
+        let Some(ix) = self.active_match_index else {
+            return;
+        };
+        let Some(pinned_results) = self.pinned_results.as_mut() else {
+            return;
+        };
+        let done = pinned_results.done.insert(ix);
+        if !done {
+            pinned_results.done.remove(&ix);
+        }
+        if let Some(site) = self.match_site(ix, cx) {
+            self.persist_site_done(site, done, cx);
+        }
+        self.update_done_highlights(cx);
+        cx.emit(ViewEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn match_buffer(&self, ix: usize, cx: &App) -> Option<(Entity<Buffer>, language::Point)> {
+        let entity = self.entity.read(cx);
+        let anchor = entity.match_ranges.get(ix)?.start;
+        let buffer = entity.excerpts.read(cx).buffer(anchor.buffer_id?)?;
+        let position = anchor.text_anchor.to_point(buffer.read(cx));
+        Some((buffer, position))
+    }
+
+    fn match_site(&self, ix: usize, cx: &App) -> Option<SearchSite> {
+        let (buffer, position) = self.match_buffer(ix, cx)?;
+        let buffer = buffer.read(cx);
+        let indent = buffer.line_indent_for_row(position.row);
+        Some(SearchSite {
+            path: buffer.file()?.full_path(cx),
+            row: position.row,
+            column: position.column.saturating_sub(indent.tabs + indent.spaces),
+            line: buffer.trimmed_line_text(position.row),
+        })
+    }
+
+    /// Finds the matches that were checked off in a previous session. A file may have been
+    /// edited since, so each site is looked for on the nearest line with the same text, and
+    /// sites that moved are saved at their new position.
+    fn restore_done_sites(
+        &self,
+        done_sites: Vec<SearchSite>,
+        cx: &mut Context<Self>,
+    ) -> BTreeSet<usize> {
+        let match_count = self.entity.read(cx).match_ranges.len();
+        let mut matches_by_path = HashMap::<PathBuf, (Entity<Buffer>, Vec<_>)>::default();
+        for ix in 0..match_count {
+            let (Some((buffer, _)), Some(site)) =
+                (self.match_buffer(ix, cx), self.match_site(ix, cx))
+            else {
+                continue;
+            };
+            matches_by_path
+                .entry(site.path.clone())
+                .or_insert_with(|| (buffer, Vec::new()))
+                .1
+                .push((ix, site));
+        }
+
+        let mut done = BTreeSet::new();
+        let mut moved_sites = Vec::new();
+        for site in done_sites {
+            let Some((buffer, matches)) = matches_by_path.get(&site.path) else {
+                continue;
+            };
+            let buffer = buffer.read(cx);
+            let found = buffer.find_line_near(site.row, &site.line).and_then(|row| {
+                matches
+                    .iter()
+                    .find(|(_, candidate)| candidate.row == row && candidate.column == site.column)
+            });
+            if let Some((ix, candidate)) = found {
+                done.insert(*ix);
+                if *candidate != site {
+                    moved_sites.push((site, candidate.clone()));
+                }
+            }
+        }
+
+        if let Some((workspace_id, name)) = self.saved_search_key(cx) {
+            if !moved_sites.is_empty() {
+                cx.background_executor()
+                    .spawn(async move {
+                        for (old_site, new_site) in moved_sites {
+                            SAVED_SEARCHES_DB
+                                .set_site_not_done(
+                                    workspace_id,
+                                    name.clone(),
+                                    old_site.path,
+                                    old_site.row,
+                                    old_site.column,
+                                )
+                                .await?;
+                            SAVED_SEARCHES_DB
+                                .set_site_done(
+                                    workspace_id,
+                                    name.clone(),
+                                    new_site.path,
+                                    new_site.row,
+                                    new_site.column,
+                                    new_site.line,
+                                )
+                                .await?;
+                        }
+                        anyhow::Ok(())
+                    })
+                    .detach_and_log_err(cx);
+            }
+        }
+        done
+    }
+
+    fn saved_search_key(&self, cx: &App) -> Option<(WorkspaceId, String)> {
+        let name = self.saved_search_name.clone()?;
+        let workspace_id = self.workspace.upgrade()?.read(cx).database_id()?;
+        Some((workspace_id, name))
+    }
+
+    fn persist_site_done(&self, site: SearchSite, done: bool, cx: &mut Context<Self>) {
+        let Some((workspace_id, name)) = self.saved_search_key(cx) else {
+            return;
+        };
+        let SearchSite {
+            path,
+            row,
+            column,
+            line,
+        } = site;
+        cx.background_executor()
+            .spawn(async move {
+                if done {
+                    SAVED_SEARCHES_DB
+                        .set_site_done(workspace_id, name, path, row, column, line)
+                        .await
+                } else {
+                    SAVED_SEARCHES_DB
+                        .set_site_not_done(workspace_id, name, path, row, column)
+                        .await
+                }
+            })
+            .detach_and_log_err(cx);
+    }
+
+    fn update_done_highlights(&mut self, cx: &mut Context<Self>) {
+        let match_ranges = &self.entity.read(cx).match_ranges;
+        let done_ranges = self
+            .pinned_results
+            .iter()
+            .flat_map(|pinned_results| pinned_results.done.iter())
+            .filter_map(|ix| match_ranges.get(*ix).cloned())
+            .collect::<Vec<_>>();
+        self.results_editor.update(cx, |editor, cx| {
+            editor.highlight_background::<PinnedResults>(
+                &done_ranges,
+                |theme| theme.version_control_added_background,
+                cx,
+            );
+        });
+    }
+
     fn build_search_query(&mut self, cx: &mut Context<Self>) -> Option<SearchQuery> {
         // Do not bail early in this function, as we want to fill out `self.panels_with_errors`.
         let text = self.query_editor.read(cx).text(cx);
@@ -1077,6 +1398,18 @@ impl ProjectSearchView {
         if !self.panels_with_errors.is_empty() {
             return None;
         }
+        let query = query.map(|query| {
+            // Preserving case only widens the matches to the case variants of the query while
+            // replacing, so the replacement is only part of the query when replace is enabled.
+            let query = if self.replace_enabled {
+                query.with_replacement(self.replacement(cx))
+            } else {
+                query
+            };
+            query
+                .with_preserve_case(self.search_options.contains(SearchOptions::PRESERVE_CASE))
+                .with_syntax_scope(self.search_options.syntax_scope())
+        });
         if query.as_ref().is_some_and(|query| query.is_empty()) {
             return None;
         }
@@ -1210,6 +1543,14 @@ impl ProjectSearchView {
             }
         }
 
+        if self.entity.read(cx).pending_search.is_none() {
+            if let Some(done_sites) = self.pending_done_sites.take() {
+                let done = self.restore_done_sites(done_sites, cx);
+                self.pinned_results = Some(PinnedResults { done });
+                self.update_done_highlights(cx);
+            }
+        }
+
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
     }
@@ -1474,6 +1815,11 @@ impl ProjectSearchBar {
                     this.query_editor.focus_handle(cx)
                 };
                 window.focus(&editor_to_focus);
+                if this.search_options.contains(SearchOptions::PRESERVE_CASE)
+                    && this.entity.read(cx).active_query.is_some()
+                {
+                    this.search(cx);
+                }
                 cx.notify();
             });
         }
@@ -1499,6 +1845,19 @@ impl ProjectSearchBar {
         }
     }
 
+    fn toggle_pin_results(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_pin_results(cx));
+            cx.notify();
+        }
+    }
+
+    fn toggle_site_done(&mut self, cx: &mut Context<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| search_view.toggle_site_done(cx));
+        }
+    }
+
     fn toggle_opened_only(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1745,6 +2104,27 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
+                    ))
+                    .child(SearchOptions::CODE_ONLY.as_button(
+                        self.is_option_enabled(SearchOptions::CODE_ONLY, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, _, cx| {
+                            this.toggle_search_option(SearchOptions::CODE_ONLY, cx);
+                        }),
+                    ))
+                    .child(SearchOptions::COMMENTS_ONLY.as_button(
+                        self.is_option_enabled(SearchOptions::COMMENTS_ONLY, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, _, cx| {
+                            this.toggle_search_option(SearchOptions::COMMENTS_ONLY, cx);
+                        }),
+                    ))
+                    .child(SearchOptions::STRINGS_ONLY.as_button(
+                        self.is_option_enabled(SearchOptions::STRINGS_ONLY, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRINGS_ONLY, cx);
+                        }),
                     )),
             );
 
@@ -1802,7 +2182,93 @@ impl Render for ProjectSearchBar {
                             )
                         }
                     }),
-            );
+            )
+            .child(
+                IconButton::new("project-search-save", IconName::Save)
+                    .shape(IconButtonShape::Square)
+                    .on_click(|_, window, cx| window.dispatch_action(SaveSearch.boxed_clone(), cx))
+                    .tooltip({
+                        let focus_handle = focus_handle.clone();
+                        move |window, cx| {
+                            Tooltip::for_action_in(
+                                "Save Search",
+                                &SaveSearch,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                        }
+                    }),
+            )
+            .child(
+                IconButton::new("project-search-open-saved", IconName::HistoryRerun)
+                    .shape(IconButtonShape::Square)
+                    .on_click(|_, window, cx| {
+                        window.dispatch_action(OpenSavedSearch.boxed_clone(), cx)
+                    })
+                    .tooltip({
+                        let focus_handle = focus_handle.clone();
+                        move |window, cx| {
+                            Tooltip::for_action_in(
+                                "Open Saved Search",
+                                &OpenSavedSearch,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                        }
+                    }),
+            )
+            .child(
+                IconButton::new(
+                    "project-search-pin-results",
+                    if search.pinned_results.is_some() {
+                        IconName::Unpin
+                    } else {
+                        IconName::Pin
+                    },
+                )
+                .shape(IconButtonShape::Square)
+                .disabled(!search.has_matches())
+                .on_click(cx.listener(|this, _, window, cx| {
+                    this.toggle_pin_results(window, cx);
+                }))
+                .toggle_state(search.pinned_results.is_some())
+                .tooltip({
+                    let focus_handle = focus_handle.clone();
+                    move |window, cx| {
+                        Tooltip::for_action_in(
+                            "Toggle Pinned Results",
+                            &TogglePinResults,
+                            &focus_handle,
+                            window,
+                            cx,
+                        )
+                    }
+                }),
+            )
+            .when(search.pinned_results.is_some(), |this| {
+                this.child(
+                    IconButton::new("project-search-toggle-done", IconName::Check)
+                        .shape(IconButtonShape::Square)
+                        .disabled(search.active_match_index.is_none())
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.toggle_site_done(cx);
+                        }))
+                        .tooltip({
+                            let focus_handle = focus_handle.clone();
+                            move |window, cx| {
+                                Tooltip::for_action_in(
+                                    "Toggle Match Done",
+                                    &ToggleSiteDone,
+                                    &focus_handle,
+                                    window,
+                                    cx,
+                                )
+                            }
+                        }),
+                )
+            });
 
         let limit_reached = search.entity.read(cx).limit_reached;
 
@@ -1902,11 +2368,18 @@ impl Render for ProjectSearchBar {
             .child(h_flex().min_w_64().child(mode_column).child(matches_column));
 
         let replace_line = search.replace_enabled.then(|| {
-            let replace_column =
-                input_base_styles().child(self.render_text_input(&search.replacement_editor, cx));
-
             let focus_handle = search.replacement_editor.read(cx).focus_handle(cx);
 
+            let replace_column = input_base_styles()
+                .child(self.render_text_input(&search.replacement_editor, cx))
+                .child(SearchOptions::PRESERVE_CASE.as_button(
+                    self.is_option_enabled(SearchOptions::PRESERVE_CASE, cx),
+                    focus_handle.clone(),
+                    cx.listener(|this, _, _, cx| {
+                        this.toggle_search_option(SearchOptions::PRESERVE_CASE, cx);
+                    }),
+                ));
+
             let replace_actions =
                 h_flex()
                     .min_w_64()
@@ -2057,6 +2530,24 @@ impl Render for ProjectSearchBar {
             .on_action(cx.listener(|this, _: &ToggleCaseSensitive, _, cx| {
                 this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
             }))
+            .on_action(cx.listener(|this, _: &TogglePinResults, window, cx| {
+                this.toggle_pin_results(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleSiteDone, _, cx| {
+                this.toggle_site_done(cx);
+            }))
+            .on_action(cx.listener(|this, _: &TogglePreserveCase, _, cx| {
+                this.toggle_search_option(SearchOptions::PRESERVE_CASE, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCodeOnly, _, cx| {
+                this.toggle_search_option(SearchOptions::CODE_ONLY, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCommentsOnly, _, cx| {
+                this.toggle_search_option(SearchOptions::COMMENTS_ONLY, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleStringsOnly, _, cx| {
+                this.toggle_search_option(SearchOptions::STRINGS_ONLY, cx);
+            }))
             .on_action(cx.listener(|this, action, window, cx| {
                 if let Some(search) = this.active_project_search.as_ref() {
                     search.update(cx, |this, cx| {
@@ -3844,6 +4335,76 @@ pub mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_pinned_search_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, window, cx| {
+                assert_eq!(search_view.active_match_index, Some(0));
+                search_view.toggle_pin_results(cx);
+                search_view.toggle_site_done(cx);
+                assert_eq!(
+                    search_view.match_site(0, cx),
+                    Some(SearchSite {
+                        path: Path::new("dir").join("three.rs"),
+                        row: 0,
+                        column: 32,
+                        line: "const THREE: usize = one::ONE + two::TWO;".to_string(),
+                    })
+                );
+                assert_eq!(
+                    search_view.tab_content_text(window, cx),
+                    Some("TWO (1/3)".into())
+                );
+            })
+            .unwrap();
+
+        perform_search(search_view, "THREE", cx);
+        search_view
+            .update(cx, |search_view, window, cx| {
+                assert_eq!(
+                    search_view.entity.read(cx).match_ranges.len(),
+                    3,
+                    "Pinned results should not be replaced by a new search"
+                );
+                search_view.toggle_site_done(cx);
+                assert_eq!(
+                    search_view.tab_content_text(window, cx),
+                    Some("TWO (0/3)".into())
+                );
+                search_view.toggle_pin_results(cx);
+                assert!(search_view.pinned_results.is_none());
+            })
+            .unwrap();
+
+        perform_search(search_view, "THREE", cx);
+        search_view
+            .update(cx, |search_view, _, cx| {
+                assert_eq!(search_view.entity.read(cx).match_ranges.len(), 1);
+            })
+            .unwrap();
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
@@ -3860,6 +4421,54 @@ pub mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_restoring_done_sites_after_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "one.rs": "const ONE: usize = 1;\nconst TWO: usize = ONE + ONE;\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, _, cx| {
+                assert_eq!(search_view.entity.read(cx).match_ranges.len(), 3);
+                let sites = (0..3)
+                    .map(|ix| search_view.match_site(ix, cx).unwrap())
+                    .collect::<Vec<_>>();
+
+                // Insert a line above the matches and indent the line of the last two.
+                let (buffer, _) = search_view.match_buffer(0, cx).unwrap();
+                buffer.update(cx, |buffer, cx| {
+                    buffer.edit([(0..0, "// header\n"), (22..22, "    ")], None, cx);
+                });
+                assert_eq!(
+                    search_view.restore_done_sites(sites[1..].to_vec(), cx),
+                    BTreeSet::from_iter([1, 2])
+                );
+
+                // Sites whose line is gone aren't restored.
+                buffer.update(cx, |buffer, cx| buffer.edit([(10..32, "")], None, cx));
+                assert_eq!(
+                    search_view.restore_done_sites(sites[..1].to_vec(), cx),
+                    BTreeSet::new()
+                );
+            })
+            .unwrap();
+    }
+
     fn perform_search(
         search_view: WindowHandle<ProjectSearchView>,
         text: impl Into<Arc<str>>,
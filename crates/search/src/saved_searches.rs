@@ -0,0 +1,549 @@
+use crate::{project_search::ProjectSearchView, SearchOptions};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity,
+    Window,
+};
+use picker::{Picker, PickerDelegate};
+use std::{path::PathBuf, sync::Arc};
+use ui::{prelude::*, HighlightedLabel, IconButton, IconButtonShape, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace, WorkspaceId};
+
+pub(crate) use persistence::SAVED_SEARCHES_DB;
+
+/// A project search stored under a name in the workspace database, so it can be rerun later.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub options: SearchOptions,
+    pub files_to_include: String,
+    pub files_to_exclude: String,
+    /// Whether the results are pinned, so that matches can be checked off.
+    pub pinned: bool,
+}
+
+/// The location of a search match that was marked as done in pinned search results. The text
+/// of its line is remembered along with its position, so that the match can be found again
+/// after its file was edited.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SearchSite {
+    pub path: PathBuf,
+    pub row: u32,
+    /// The column of the match, relative to the indentation of its line.
+    pub column: u32,
+    /// The text of the line, without leading and trailing whitespace.
+    pub line: String,
+}
+
+enum Mode {
+    Open,
+    Save {
+        search: SavedSearch,
+        search_view: WeakEntity<ProjectSearchView>,
+    },
+}
+
+pub struct SavedSearchPicker {
+    picker: Entity<Picker<SavedSearchPickerDelegate>>,
+}
+
+impl SavedSearchPicker {
+    /// Shows the saved searches of the workspace and reruns the selected one.
+    pub fn toggle_open(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        Self::toggle(workspace, Mode::Open, window, cx);
+    }
+
+    /// Saves the active project search under a new or an existing name.
+    pub fn toggle_save(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(search_view) = workspace.active_item_as::<ProjectSearchView>(cx) else {
+            return;
+        };
+        let search = search_view.read(cx).saved_search(String::new(), cx);
+        if search.query.is_empty() {
+            return;
+        }
+        let mode = Mode::Save {
+            search,
+            search_view: search_view.downgrade(),
+        };
+        Self::toggle(workspace, mode, window, cx);
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        mode: Mode,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        let saved_searches = SAVED_SEARCHES_DB
+            .saved_searches(workspace_id)
+            .log_err()
+            .unwrap_or_default();
+        let weak_workspace = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate = SavedSearchPickerDelegate {
+                saved_search_picker: cx.entity().downgrade(),
+                workspace: weak_workspace,
+                workspace_id,
+                mode,
+                saved_searches,
+                matches: Vec::new(),
+                selected_index: 0,
+                query: String::new(),
+            };
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl Render for SavedSearchPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for SavedSearchPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearchPicker {}
+impl ModalView for SavedSearchPicker {}
+
+pub struct SavedSearchPickerDelegate {
+    saved_search_picker: WeakEntity<SavedSearchPicker>,
+    workspace: WeakEntity<Workspace>,
+    workspace_id: WorkspaceId,
+    mode: Mode,
+    saved_searches: Vec<SavedSearch>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+}
+
+impl SavedSearchPickerDelegate {
+    /// The name under which the search is saved when the first entry is confirmed, if the
+    /// query doesn't name an existing saved search.
+    fn new_name(&self) -> Option<&str> {
+        let name = self.query.trim();
+        let is_new = matches!(self.mode, Mode::Save { .. })
+            && !name.is_empty()
+            && !self.saved_searches.iter().any(|search| search.name == name);
+        is_new.then_some(name)
+    }
+
+    fn saved_search_at(&self, ix: usize) -> Option<&SavedSearch> {
+        let ix = if self.new_name().is_some() {
+            ix.checked_sub(1)?
+        } else {
+            ix
+        };
+        let mat = self.matches.get(ix)?;
+        self.saved_searches.get(mat.candidate_id)
+    }
+
+    fn delete_saved_search(
+        &mut self,
+        ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(name) = self.saved_search_at(ix).map(|search| search.name.clone()) else {
+            return;
+        };
+        self.saved_searches.retain(|search| search.name != name);
+        let workspace_id = self.workspace_id;
+        cx.background_executor()
+            .spawn(async move {
+                SAVED_SEARCHES_DB
+                    .delete_saved_search(workspace_id, name)
+                    .await
+            })
+            .detach_and_log_err(cx);
+        cx.spawn_in(window, |picker, mut cx| async move {
+            picker.update_in(&mut cx, |picker, window, cx| picker.refresh(window, cx))
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl PickerDelegate for SavedSearchPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.mode {
+            Mode::Open => "Run a saved search…".into(),
+            Mode::Save { .. } => "Save search as…".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        match self.mode {
+            Mode::Open => "No saved searches".into(),
+            Mode::Save { .. } => "Type a name for the search".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + self.new_name().map_or(0, |_| 1)
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .saved_searches
+            .iter()
+            .enumerate()
+            .map(|(id, search)| StringMatchCandidate::new(id, &search.name))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.query = query;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let workspace_id = self.workspace_id;
+        match &self.mode {
+            Mode::Open => {
+                let Some(search) = self.saved_search_at(self.selected_index).cloned() else {
+                    return;
+                };
+                let done_sites = SAVED_SEARCHES_DB
+                    .done_sites(workspace_id, search.name.clone())
+                    .log_err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(path, row, column, line)| SearchSite {
+                        path,
+                        row,
+                        column,
+                        line,
+                    })
+                    .collect();
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        ProjectSearchView::open_saved_search(
+                            workspace, search, done_sites, window, cx,
+                        );
+                    })
+                    .log_err();
+            }
+            Mode::Save {
+                search,
+                search_view,
+            } => {
+                let name = match self.new_name() {
+                    Some(name) if self.selected_index == 0 => name.to_string(),
+                    _ => match self.saved_search_at(self.selected_index) {
+                        Some(existing) => existing.name.clone(),
+                        None => return,
+                    },
+                };
+                let search = SavedSearch {
+                    name: name.clone(),
+                    ..search.clone()
+                };
+                let search_view = search_view.clone();
+                cx.spawn(|_, mut cx| async move {
+                    SAVED_SEARCHES_DB.save_search(workspace_id, search).await?;
+                    search_view.update(&mut cx, |search_view, cx| {
+                        search_view.set_saved_search_name(name, cx);
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.saved_search_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        if let Some(name) = self.new_name().filter(|_| ix == 0) {
+            return Some(
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(selected)
+                    .start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                    .child(Label::new(format!("Save as “{name}”"))),
+            );
+        }
+
+        let search = self.saved_search_at(ix)?;
+        let mat = self.matches.get(ix - self.new_name().map_or(0, |_| 1))?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::MagnifyingGlass).color(Color::Muted))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            search.name.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(search.query.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                )
+                .end_slot(
+                    IconButton::new(("delete-saved-search", ix), IconName::Trash)
+                        .shape(IconButtonShape::Square)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("Delete Saved Search"))
+                        .on_click(cx.listener(move |picker, _, window, cx| {
+                            cx.stop_propagation();
+                            picker.delegate.delete_saved_search(ix, window, cx);
+                        })),
+                ),
+        )
+    }
+}
+
+mod persistence {
+    use anyhow::Result;
+    use db::sqlez::bindable::{Bind, Column, StaticColumnCount};
+    use db::sqlez::statement::Statement;
+    use db::{define_connection, query, sqlez_macros::sql};
+    use std::path::PathBuf;
+    use workspace::{WorkspaceDb, WorkspaceId};
+
+    use super::SavedSearch;
+    use crate::SearchOptions;
+
+    impl StaticColumnCount for SavedSearch {
+        fn column_count() -> usize {
+            6
+        }
+    }
+
+    impl Bind for SavedSearch {
+        fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+            let start_index = statement.bind(&self.name, start_index)?;
+            let start_index = statement.bind(&self.query, start_index)?;
+            let start_index = statement.bind(&(self.options.bits() as u32), start_index)?;
+            let start_index = statement.bind(&self.files_to_include, start_index)?;
+            let start_index = statement.bind(&self.files_to_exclude, start_index)?;
+            statement.bind(&self.pinned, start_index)
+        }
+    }
+
+    impl Column for SavedSearch {
+        fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+            let (name, start_index): (String, i32) = Column::column(statement, start_index)?;
+            let (query, start_index): (String, i32) = Column::column(statement, start_index)?;
+            let (options, start_index): (u32, i32) = Column::column(statement, start_index)?;
+            let (files_to_include, start_index): (String, i32) =
+                Column::column(statement, start_index)?;
+            let (files_to_exclude, start_index): (String, i32) =
+                Column::column(statement, start_index)?;
+            let (pinned, start_index): (bool, i32) = Column::column(statement, start_index)?;
+            Ok((
+                SavedSearch {
+                    name,
+                    query,
+                    options: SearchOptions::from_bits_truncate(options as u8),
+                    files_to_include,
+                    files_to_exclude,
+                    pinned,
+                },
+                start_index,
+            ))
+        }
+    }
+
+    define_connection! {
+        pub static ref SAVED_SEARCHES_DB: SavedSearchesDb<WorkspaceDb> =
+            &[sql!(
+                CREATE TABLE saved_searches (
+                    workspace_id INTEGER NOT NULL,
+                    name TEXT NOT NULL,
+                    query TEXT NOT NULL,
+                    options INTEGER NOT NULL,
+                    files_to_include TEXT NOT NULL,
+                    files_to_exclude TEXT NOT NULL,
+                    pinned INTEGER NOT NULL,
+                    PRIMARY KEY(workspace_id, name),
+                    FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                ) STRICT;
+
+                CREATE TABLE saved_search_done_sites (
+                    workspace_id INTEGER NOT NULL,
+                    name TEXT NOT NULL,
+                    path BLOB NOT NULL,
+                    start_row INTEGER NOT NULL,
+                    start_column INTEGER NOT NULL,
+                    line TEXT NOT NULL,
+                    PRIMARY KEY(workspace_id, name, path, start_row, start_column),
+                    FOREIGN KEY(workspace_id, name) REFERENCES saved_searches(workspace_id, name)
+                    ON DELETE CASCADE
+                ) STRICT;
+            )];
+    }
+
+    impl SavedSearchesDb {
+        query! {
+            pub fn saved_searches(workspace_id: WorkspaceId) -> Result<Vec<SavedSearch>> {
+                SELECT name, query, options, files_to_include, files_to_exclude, pinned
+                FROM saved_searches
+                WHERE workspace_id = ?
+                ORDER BY name
+            }
+        }
+
+        query! {
+            pub async fn save_search(workspace_id: WorkspaceId, search: SavedSearch) -> Result<()> {
+                INSERT INTO saved_searches
+                    (workspace_id, name, query, options, files_to_include, files_to_exclude, pinned)
+                VALUES
+                    (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT DO UPDATE SET
+                    query = ?3,
+                    options = ?4,
+                    files_to_include = ?5,
+                    files_to_exclude = ?6,
+                    pinned = ?7
+            }
+        }
+
+        query! {
+            pub async fn set_pinned(workspace_id: WorkspaceId, name: String, pinned: bool) -> Result<()> {
+                UPDATE saved_searches
+                SET pinned = ?3
+                WHERE workspace_id = ?1 AND name = ?2
+            }
+        }
+
+        query! {
+            pub async fn clear_done_sites(workspace_id: WorkspaceId, name: String) -> Result<()> {
+                DELETE FROM saved_search_done_sites
+                WHERE workspace_id = ? AND name = ?
+            }
+        }
+
+        query! {
+            pub async fn delete_saved_search(workspace_id: WorkspaceId, name: String) -> Result<()> {
+                DELETE FROM saved_searches
+                WHERE workspace_id = ? AND name = ?
+            }
+        }
+
+        query! {
+            pub fn done_sites(workspace_id: WorkspaceId, name: String) -> Result<Vec<(PathBuf, u32, u32, String)>> {
+                SELECT path, start_row, start_column, line
+                FROM saved_search_done_sites
+                WHERE workspace_id = ? AND name = ?
+            }
+        }
+
+        query! {
+            pub async fn set_site_done(
+                workspace_id: WorkspaceId,
+                name: String,
+                path: PathBuf,
+                start_row: u32,
+                start_column: u32,
+                line: String
+            ) -> Result<()> {
+                INSERT OR REPLACE INTO saved_search_done_sites
+                    (workspace_id, name, path, start_row, start_column, line)
+                VALUES
+                    (?, ?, ?, ?, ?, ?)
+            }
+        }
+
+        query! {
+            pub async fn set_site_not_done(
+                workspace_id: WorkspaceId,
+                name: String,
+                path: PathBuf,
+                start_row: u32,
+                start_column: u32
+            ) -> Result<()> {
+                DELETE FROM saved_search_done_sites
+                WHERE workspace_id = ? AND name = ? AND path = ? AND start_row = ? AND start_column = ?
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
 use editor::SearchSettings;
 use gpui::{actions, Action, App, FocusHandle, IntoElement};
-use project::search::SearchQuery;
+use project::search::{SearchQuery, SyntaxScope};
 pub use project_search::ProjectSearchView;
 use ui::{prelude::*, Tooltip};
 use ui::{ButtonStyle, IconButton, IconButtonShape};
@@ -11,6 +11,7 @@ use workspace::{Toast, Workspace};
 
 pub mod buffer_search;
 pub mod project_search;
+pub mod saved_searches;
 pub(crate) mod search_bar;
 
 pub fn init(cx: &mut App) {
@@ -27,6 +28,10 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleCodeOnly,
+        ToggleCommentsOnly,
+        ToggleStringsOnly,
+        TogglePreserveCase,
         ToggleReplace,
         ToggleSelection,
         SelectNextMatch,
@@ -47,6 +52,12 @@ bitflags! {
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        const CODE_ONLY = 0b1_0000;
+        const COMMENTS_ONLY = 0b10_0000;
+        const STRINGS_ONLY = 0b100_0000;
+        const PRESERVE_CASE = 0b1000_0000;
+        const SYNTAX_SCOPES =
+            Self::CODE_ONLY.bits() | Self::COMMENTS_ONLY.bits() | Self::STRINGS_ONLY.bits();
     }
 }
 
@@ -57,6 +68,10 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "Match Case Sensitively",
             SearchOptions::INCLUDE_IGNORED => "Also search files ignored by configuration",
             SearchOptions::REGEX => "Use Regular Expressions",
+            SearchOptions::CODE_ONLY => "Only Match in Code",
+            SearchOptions::COMMENTS_ONLY => "Only Match in Comments",
+            SearchOptions::STRINGS_ONLY => "Only Match in Strings",
+            SearchOptions::PRESERVE_CASE => "Preserve Case When Replacing",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -67,6 +82,10 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::Sliders,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::CODE_ONLY => ui::IconName::Code,
+            SearchOptions::COMMENTS_ONLY => ui::IconName::MessageBubbles,
+            SearchOptions::STRINGS_ONLY => ui::IconName::Quote,
+            SearchOptions::PRESERVE_CASE => ui::IconName::Font,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -77,6 +96,10 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::CODE_ONLY => Box::new(ToggleCodeOnly),
+            SearchOptions::COMMENTS_ONLY => Box::new(ToggleCommentsOnly),
+            SearchOptions::STRINGS_ONLY => Box::new(ToggleStringsOnly),
+            SearchOptions::PRESERVE_CASE => Box::new(TogglePreserveCase),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -91,9 +114,38 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::PRESERVE_CASE, query.preserve_case());
+        options.insert(match query.syntax_scope() {
+            SyntaxScope::All => SearchOptions::NONE,
+            SyntaxScope::Code => SearchOptions::CODE_ONLY,
+            SyntaxScope::Comments => SearchOptions::COMMENTS_ONLY,
+            SyntaxScope::Strings => SearchOptions::STRINGS_ONLY,
+        });
         options
     }
 
+    /// Returns the syntax scope selected by these options.
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        if self.contains(SearchOptions::CODE_ONLY) {
+            SyntaxScope::Code
+        } else if self.contains(SearchOptions::COMMENTS_ONLY) {
+            SyntaxScope::Comments
+        } else if self.contains(SearchOptions::STRINGS_ONLY) {
+            SyntaxScope::Strings
+        } else {
+            SyntaxScope::All
+        }
+    }
+
+    /// Toggles the given option. Syntax scopes are mutually exclusive, so enabling
+    /// one of them disables the others.
+    pub fn toggle_option(&mut self, option: SearchOptions) {
+        if SearchOptions::SYNTAX_SCOPES.intersects(option) && !self.contains(option) {
+            self.remove(SearchOptions::SYNTAX_SCOPES);
+        }
+        self.toggle(option);
+    }
+
     pub fn from_settings(settings: &SearchSettings) -> SearchOptions {
         let mut options = SearchOptions::NONE;
         options.set(SearchOptions::WHOLE_WORD, settings.whole_word);
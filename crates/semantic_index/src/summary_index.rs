@@ -561,6 +561,8 @@ impl SummaryIndex {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            thinking_budget: None,
+            max_tokens: None,
         };
 
         let code_len = code.len();
@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Context as _, Result};
+use regex::{Captures, Regex, RegexBuilder};
 use smallvec::SmallVec;
-use std::{collections::BTreeMap, ops::Range};
+use std::{collections::BTreeMap, mem, ops::Range};
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Snippet {
@@ -12,14 +13,77 @@ pub struct Snippet {
 pub struct TabStop {
     pub ranges: SmallVec<[Range<isize>; 2]>,
     pub choices: Option<Vec<String>>,
+    /// Ranges that mirror the text of this tabstop through a transformation, as in
+    /// `${1/(.*)/${1:/upcase}/}`. They need to be updated whenever the tabstop is edited.
+    pub transformations: Vec<(Range<isize>, Transform)>,
+}
+
+/// A regex transformation, applied to a variable or to the text of a tabstop.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, CaseChange),
+    Conditional {
+        group: usize,
+        if_text: String,
+        else_text: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CaseChange {
+    Upcase,
+    Downcase,
+    Capitalize,
+    CamelCase,
+    PascalCase,
 }
 
 impl Snippet {
+    /// Parses a snippet in which every variable is unknown. Like in VS Code, unknown
+    /// variables are turned into placeholders holding their name.
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
+        Self::parse_with_variables(source, |_| None)
+    }
+
+    /// Parses a snippet, resolving variables like `$TM_FILENAME` with `variables`.
+    pub fn parse_with_variables(
+        source: &str,
+        variables: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut parser = SnippetParser::new(&variables);
+        parser.text.reserve(source.len());
+        parser
+            .parse_snippet(source, false)
             .context("failed to parse snippet")?;
+        let SnippetParser {
+            text,
+            mut tabstops,
+            unknown_variables,
+            ..
+        } = parser;
+
+        // Like in VS Code, unknown variables become placeholders after the numbered ones.
+        let first_index = tabstops.keys().last().map_or(1, |index| index + 1);
+        for (index, range) in (first_index..).zip(unknown_variables) {
+            tabstops.insert(
+                index,
+                TabStop {
+                    ranges: [range].into_iter().collect(),
+                    ..Default::default()
+                },
+            );
+        }
+        // Tabstops that only appear in transformations can't be edited.
+        tabstops.retain(|_, tabstop| !tabstop.ranges.is_empty());
 
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
@@ -30,7 +94,7 @@ impl Snippet {
         } else {
             let end_tabstop = TabStop {
                 ranges: [len..len].into_iter().collect(),
-                choices: None,
+                ..Default::default()
             };
 
             if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
@@ -42,95 +106,273 @@ impl Snippet {
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    loop {
-        match source.chars().next() {
-            None => return Ok(""),
-            Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
-            }
-            Some('\\') => {
-                // As specified in the LSP spec (`Grammar` section),
-                // backslashes can escape some characters:
-                // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
-                source = &source[1..];
-                if let Some(c) = source.chars().next() {
-                    if c == '$' || c == '\\' || c == '}' {
-                        text.push(c);
-                        // All escapable characters are 1 byte long:
-                        source = &source[1..];
+impl Transform {
+    pub fn apply(&self, text: &str) -> String {
+        let replacer = |captures: &Captures| self.format(captures);
+        if self.global {
+            self.regex.replace_all(text, replacer).into_owned()
+        } else {
+            self.regex.replace(text, replacer).into_owned()
+        }
+    }
+
+    fn format(&self, captures: &Captures) -> String {
+        let group = |index: usize| captures.get(index).map_or("", |group| group.as_str());
+        let mut result = String::new();
+        for item in &self.format {
+            match item {
+                FormatItem::Text(text) => result.push_str(text),
+                FormatItem::Group(index) => result.push_str(group(*index)),
+                FormatItem::Case(index, case) => result.push_str(&case.apply(group(*index))),
+                FormatItem::Conditional {
+                    group: index,
+                    if_text,
+                    else_text,
+                } => {
+                    if group(*index).is_empty() {
+                        result.push_str(else_text)
                     } else {
-                        text.push('\\');
+                        result.push_str(if_text)
                     }
-                } else {
-                    text.push('\\');
                 }
             }
-            Some('}') => {
-                if nested {
-                    return Ok(source);
-                } else {
-                    text.push('}');
+        }
+        result
+    }
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.format == other.format
+            && self.global == other.global
+    }
+}
+
+impl CaseChange {
+    fn apply(self, text: &str) -> String {
+        match self {
+            CaseChange::Upcase => text.to_uppercase(),
+            CaseChange::Downcase => text.to_lowercase(),
+            CaseChange::Capitalize => capitalize(text),
+            CaseChange::PascalCase => words(text).map(capitalize).collect(),
+            CaseChange::CamelCase => words(text)
+                .enumerate()
+                .map(|(ix, word)| {
+                    if ix == 0 {
+                        let mut chars = word.chars();
+                        chars
+                            .next()
+                            .map(|first| first.to_lowercase().chain(chars).collect())
+                            .unwrap_or_default()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+struct SnippetParser<'a> {
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    variables: &'a dyn Fn(&str) -> Option<String>,
+    unknown_variables: Vec<Range<isize>>,
+}
+
+impl<'a> SnippetParser<'a> {
+    fn new(variables: &'a dyn Fn(&str) -> Option<String>) -> Self {
+        Self {
+            text: String::new(),
+            tabstops: BTreeMap::new(),
+            variables,
+            unknown_variables: Vec::new(),
+        }
+    }
+
+    fn parse_snippet<'b>(&mut self, mut source: &'b str, nested: bool) -> Result<&'b str> {
+        loop {
+            match source.chars().next() {
+                None => return Ok(""),
+                Some('$') => {
+                    source = self.parse_dollar(&source[1..])?;
+                }
+                Some('\\') => {
+                    // As specified in the LSP spec (`Grammar` section),
+                    // backslashes can escape some characters:
+                    // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
                     source = &source[1..];
+                    if let Some(c) = source.chars().next() {
+                        if c == '$' || c == '\\' || c == '}' {
+                            self.text.push(c);
+                            // All escapable characters are 1 byte long:
+                            source = &source[1..];
+                        } else {
+                            self.text.push('\\');
+                        }
+                    } else {
+                        self.text.push('\\');
+                    }
+                }
+                Some('}') => {
+                    if nested {
+                        return Ok(source);
+                    } else {
+                        self.text.push('}');
+                        source = &source[1..];
+                    }
+                }
+                Some(_) => {
+                    let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
+                    let (chunk, rest) = source.split_at(chunk_end);
+                    self.text.push_str(chunk);
+                    source = rest;
                 }
             }
-            Some(_) => {
-                let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
-                let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
-                source = rest;
+        }
+    }
+
+    fn parse_dollar<'b>(&mut self, source: &'b str) -> Result<&'b str> {
+        let (braced, rest) = match source.strip_prefix('{') {
+            Some(rest) => (true, rest),
+            None => (false, source),
+        };
+        match rest.chars().next() {
+            Some(c) if c.is_ascii_digit() => self.parse_tabstop(source),
+            Some(c) if c == '_' || c.is_ascii_alphabetic() => self.parse_variable(source),
+            _ if braced => Err(anyhow!("expected a tabstop or a variable")),
+            // A dollar sign that doesn't start a tabstop or a variable is just text.
+            _ => {
+                self.text.push('$');
+                Ok(source)
             }
         }
     }
-}
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
-    let tabstop_index;
-    let mut choices = None;
-
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
-        tabstop_index = index;
-        source = rest;
-
-        if source.starts_with("|") {
-            (source, choices) = parse_choices(&source[1..], text)?;
+    fn parse_tabstop<'b>(&mut self, mut source: &'b str) -> Result<&'b str> {
+        let tabstop_start = self.text.len();
+        let tabstop_index;
+        let mut choices = None;
+        let mut transform = None;
+
+        if source.starts_with('{') {
+            let (index, rest) = parse_int(&source[1..])?;
+            tabstop_index = index;
+            source = rest;
+
+            if source.starts_with("|") {
+                (source, choices) = parse_choices(&source[1..], &mut self.text)?;
+            }
+
+            if source.starts_with(':') {
+                source = self.parse_snippet(&source[1..], true)?;
+            } else if source.starts_with('/') {
+                let (parsed, rest) = parse_transform(&source[1..])?;
+                // The mirrored text is brought up to date once the snippet is inserted, as
+                // the tabstop's placeholder may only appear later in the snippet.
+                let initial_text = self
+                    .tabstops
+                    .get(&tabstop_index)
+                    .and_then(|tabstop| {
+                        let range = tabstop.ranges.first()?;
+                        self.text.get(range.start as usize..range.end as usize)
+                    })
+                    .unwrap_or_default();
+                let transformed = parsed.apply(initial_text);
+                self.text.push_str(&transformed);
+                transform = Some(parsed);
+                source = rest;
+            }
+
+            if source.starts_with('}') {
+                source = &source[1..];
+            } else {
+                return Err(anyhow!("expected a closing brace"));
+            }
+        } else {
+            let (index, rest) = parse_int(source)?;
+            tabstop_index = index;
+            source = rest;
         }
 
-        if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+        let range = tabstop_start as isize..self.text.len() as isize;
+        let tabstop = self.tabstops.entry(tabstop_index).or_default();
+        if tabstop.choices.is_none() {
+            tabstop.choices = choices;
+        }
+        if let Some(transform) = transform {
+            tabstop.transformations.push((range, transform));
+        } else {
+            tabstop.ranges.push(range);
+        }
+        Ok(source)
+    }
+
+    fn parse_variable<'b>(&mut self, source: &'b str) -> Result<&'b str> {
+        let (braced, source) = match source.strip_prefix('{') {
+            Some(rest) => (true, rest),
+            None => (false, source),
+        };
+        let name_len = source
+            .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+            .unwrap_or(source.len());
+        let (name, mut source) = source.split_at(name_len);
+        let value = (self.variables)(name);
+
+        if braced {
+            if let Some(rest) = source.strip_prefix(':') {
+                // When a variable is empty or unknown, its default is inserted instead.
+                match value.filter(|value| !value.is_empty()) {
+                    Some(value) => {
+                        let mut skipped = SnippetParser::new(self.variables);
+                        source = skipped.parse_snippet(rest, true)?;
+                        self.text.push_str(&value);
+                    }
+                    None => source = self.parse_snippet(rest, true)?,
+                }
+            } else if let Some(rest) = source.strip_prefix('/') {
+                let (transform, rest) = parse_transform(rest)?;
+                self.text
+                    .push_str(&transform.apply(value.as_deref().unwrap_or_default()));
+                source = rest;
+            } else {
+                self.push_variable(name, value);
+            }
+
+            if source.starts_with('}') {
+                source = &source[1..];
+            } else {
+                return Err(anyhow!("expected a closing brace"));
+            }
+        } else {
+            self.push_variable(name, value);
         }
+        Ok(source)
+    }
 
-        if source.starts_with('}') {
-            source = &source[1..];
+    fn push_variable(&mut self, name: &str, value: Option<String>) {
+        if let Some(value) = value {
+            self.text.push_str(&value);
         } else {
-            return Err(anyhow!("expected a closing brace"));
+            let start = self.text.len() as isize;
+            self.text.push_str(name);
+            self.unknown_variables.push(start..self.text.len() as isize);
         }
-    } else {
-        let (index, rest) = parse_int(source)?;
-        tabstop_index = index;
-        source = rest;
-    }
-
-    tabstops
-        .entry(tabstop_index)
-        .or_insert_with(|| TabStop {
-            ranges: Default::default(),
-            choices,
-        })
-        .ranges
-        .push(tabstop_start as isize..text.len() as isize);
-    Ok(source)
+    }
 }
 
 fn parse_int(source: &str) -> Result<(usize, &str)> {
@@ -199,6 +441,163 @@ fn parse_choices<'a>(
     }
 }
 
+/// Parses the `regex/format/options` part of a transformation, up to the closing brace.
+fn parse_transform(source: &str) -> Result<(Transform, &str)> {
+    let mut pattern = String::new();
+    let mut chars = source.char_indices();
+    let format_start = loop {
+        match chars.next() {
+            None => return Err(anyhow!("expected a '/' after the transformation's regex")),
+            Some((ix, '/')) => break ix + 1,
+            Some((_, '\\')) => match chars.next() {
+                Some((_, '/')) => pattern.push('/'),
+                Some((_, c)) => {
+                    pattern.push('\\');
+                    pattern.push(c);
+                }
+                None => pattern.push('\\'),
+            },
+            Some((_, c)) => pattern.push(c),
+        }
+    };
+
+    let (format, source) = parse_format(&source[format_start..])?;
+    let options_len = source.find('}').unwrap_or(source.len());
+    let (options, source) = source.split_at(options_len);
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(options.contains('i'))
+        .multi_line(options.contains('m'))
+        .dot_matches_new_line(options.contains('s'))
+        .build()
+        .with_context(|| format!("invalid regex in transformation: {pattern}"))?;
+    let transform = Transform {
+        regex,
+        format,
+        global: options.contains('g'),
+    };
+    Ok((transform, source))
+}
+
+/// Parses the format string of a transformation, up to and including its closing `/`.
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected a '/' after the transformation's format")),
+            Some('/') => {
+                source = &source[1..];
+                break;
+            }
+            Some('\\') => {
+                source = &source[1..];
+                match source.chars().next() {
+                    Some(c @ ('$' | '\\' | '/')) => {
+                        text.push(c);
+                        source = &source[1..];
+                    }
+                    _ => text.push('\\'),
+                }
+            }
+            Some('$') => {
+                let after_dollar = &source[1..];
+                let (item, rest) = if let Some(rest) = after_dollar.strip_prefix('{') {
+                    let (group, rest) = parse_int(rest)?;
+                    parse_format_group(group, rest)?
+                } else if after_dollar.starts_with(|c: char| c.is_ascii_digit()) {
+                    let (group, rest) = parse_int(after_dollar)?;
+                    (FormatItem::Group(group), rest)
+                } else {
+                    text.push('$');
+                    source = after_dollar;
+                    continue;
+                };
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(mem::take(&mut text)));
+                }
+                items.push(item);
+                source = rest;
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+    if !text.is_empty() {
+        items.push(FormatItem::Text(text));
+    }
+    Ok((items, source))
+}
+
+/// Parses the rest of a `${group...}` format item, after the group number.
+fn parse_format_group(group: usize, source: &str) -> Result<(FormatItem, &str)> {
+    if let Some(rest) = source.strip_prefix('}') {
+        return Ok((FormatItem::Group(group), rest));
+    }
+    let source = source
+        .strip_prefix(':')
+        .ok_or_else(|| anyhow!("expected a ':' or a closing brace"))?;
+
+    if let Some(rest) = source.strip_prefix('/') {
+        let name_len = rest.find('}').unwrap_or(rest.len());
+        let case = match &rest[..name_len] {
+            "upcase" => CaseChange::Upcase,
+            "downcase" => CaseChange::Downcase,
+            "capitalize" => CaseChange::Capitalize,
+            "camelcase" => CaseChange::CamelCase,
+            "pascalcase" => CaseChange::PascalCase,
+            name => return Err(anyhow!("unknown case transformation: {name}")),
+        };
+        let rest = rest[name_len..]
+            .strip_prefix('}')
+            .ok_or_else(|| anyhow!("expected a closing brace"))?;
+        return Ok((FormatItem::Case(group, case), rest));
+    }
+
+    let (if_text, else_text, rest) = if let Some(rest) = source.strip_prefix('+') {
+        let (if_text, rest) = parse_format_text(rest, &['}'])?;
+        (if_text, String::new(), rest)
+    } else if let Some(rest) = source.strip_prefix('?') {
+        let (if_text, rest) = parse_format_text(rest, &[':'])?;
+        let (else_text, rest) = parse_format_text(&rest[1..], &['}'])?;
+        (if_text, else_text, rest)
+    } else {
+        let rest = source.strip_prefix('-').unwrap_or(source);
+        let (else_text, rest) = parse_format_text(rest, &['}'])?;
+        (String::new(), else_text, rest)
+    };
+    let item = FormatItem::Conditional {
+        group,
+        if_text,
+        else_text,
+    };
+    Ok((item, &rest[1..]))
+}
+
+/// Parses the text of a conditional format item, up to (but not including) one of the
+/// `terminators`.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected one of {terminators:?}")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    text.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,6 +726,92 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+
+        let snippet = Snippet::parse_with_variables("// $TM_FILENAME: $1", variables).unwrap();
+        assert_eq!(snippet.text, "// main.rs: ");
+        assert_eq!(tabstops(&snippet), &[vec![12..12]]);
+
+        // Defaults are used for empty and unknown variables.
+        let snippet = Snippet::parse_with_variables(
+            "${TM_FILENAME:none} ${TM_SELECTED_TEXT:${1:empty}} ${UNKNOWN:unknown}",
+            variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "main.rs empty unknown");
+        assert_eq!(tabstops(&snippet), &[vec![8..13], vec![21..21]]);
+
+        // Unknown variables without a default become placeholders.
+        let snippet = Snippet::parse_with_variables("$1 $UNKNOWN ${OTHER}$0", variables).unwrap();
+        assert_eq!(snippet.text, " UNKNOWN OTHER");
+        assert_eq!(
+            tabstops(&snippet),
+            &[vec![0..0], vec![1..8], vec![9..14], vec![14..14]]
+        );
+
+        let snippet =
+            Snippet::parse_with_variables("${TM_FILENAME/(.*)\\.rs$/${1:/upcase}/}", variables)
+                .unwrap();
+        assert_eq!(snippet.text, "MAIN");
+
+        // A dollar sign that isn't followed by a tabstop or a variable is kept.
+        let snippet = Snippet::parse("cost: $ 5").unwrap();
+        assert_eq!(snippet.text, "cost: $ 5");
+    }
+
+    #[test]
+    fn test_snippet_with_transformations() {
+        let snippet = Snippet::parse("${1:name} ${1/(.*)/${1:/upcase}/}").unwrap();
+        assert_eq!(snippet.text, "name NAME");
+        assert_eq!(tabstops(&snippet), &[vec![0..4], vec![9..9]]);
+        let (range, transform) = &snippet.tabstops[0].transformations[0];
+        assert_eq!(range, &(5..9));
+        assert_eq!(transform.apply("other"), "OTHER");
+
+        // A transformation can come before the placeholder it mirrors.
+        let snippet = Snippet::parse("${1/(.*)/$1!/} ${1:x}").unwrap();
+        assert_eq!(snippet.text, "! x");
+        assert_eq!(snippet.tabstops[0].transformations[0].0, 0..1);
+        assert_eq!(snippet.tabstops[0].transformations[0].1.apply("x"), "x!");
+    }
+
+    #[test]
+    fn test_transform_formats() {
+        fn transform(source: &str, text: &str) -> String {
+            let snippet = Snippet::parse(&format!("$1${{1/{source}}}")).unwrap();
+            snippet.tabstops[0].transformations[0].1.apply(text)
+        }
+
+        assert_eq!(transform("(.*)/${1:/downcase}/", "FOO"), "foo");
+        assert_eq!(transform("(.*)/${1:/capitalize}/", "foo bar"), "Foo bar");
+        assert_eq!(
+            transform("(.*)/${1:/pascalcase}/", "foo_bar-baz"),
+            "FooBarBaz"
+        );
+        assert_eq!(
+            transform("(.*)/${1:/camelcase}/", "Foo bar baz"),
+            "fooBarBaz"
+        );
+        assert_eq!(transform("^(a)?.*/${1:+yes}/", "abc"), "yes");
+        assert_eq!(transform("^(a)?.*/${1:?yes:no}/", "bcd"), "no");
+        assert_eq!(transform("^(a)?.*/${1:-none}/", "bcd"), "none");
+        assert_eq!(transform("^(a)?.*/${1:none}/", "abc"), "");
+        assert_eq!(transform("o/0/", "foo"), "f0o");
+        assert_eq!(transform("o/0/g", "foo"), "f00");
+        assert_eq!(transform("O/0/gi", "foo"), "f00");
+        assert_eq!(transform("(\\w+) (\\w+)/$2 \\/ ${1}/", "a b"), "b / a");
+
+        assert!(Snippet::parse("$1${1/(.*)/${1:/unknown}/}").is_err());
+        assert!(Snippet::parse("$1${1/(/$1/}").is_err());
+        assert!(Snippet::parse("$1${1/(.*)/$1}").is_err());
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
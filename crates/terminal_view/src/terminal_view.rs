@@ -1493,6 +1493,7 @@ impl SearchableItem for TerminalView {
             replacement: false,
             selection: false,
             find_in_results: false,
+            syntax_scope: false,
         }
     }
 
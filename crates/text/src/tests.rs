@@ -205,6 +205,23 @@ fn test_line_len() {
     assert_eq!(buffer.line_len(5), 0);
 }
 
+#[test]
+fn test_find_line_near() {
+    let buffer = Buffer::new(
+        0,
+        BufferId::new(1).unwrap(),
+        "fn one() {}\n  fn two() {}\n\nfn one() {}\n".into(),
+    );
+
+    assert_eq!(buffer.trimmed_line_text(1), "fn two() {}");
+    assert_eq!(buffer.find_line_near(0, "fn one() {}"), Some(0));
+    assert_eq!(buffer.find_line_near(0, "fn two() {}"), Some(1));
+    assert_eq!(buffer.find_line_near(2, "fn one() {}"), Some(3));
+    assert_eq!(buffer.find_line_near(10, "fn one() {}"), Some(3));
+    assert_eq!(buffer.find_line_near(0, "fn three() {}"), None);
+    assert_eq!(buffer.find_line_near(2, ""), None);
+}
+
 #[test]
 fn test_common_prefix_at_position() {
     let text = "a = str; b = δα";
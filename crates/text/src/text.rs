@@ -2024,6 +2024,44 @@ impl BufferSnapshot {
         (row_end_offset - row_start_offset) as u32
     }
 
+    /// Returns the text of the given row without its leading and trailing whitespace.
+    pub fn trimmed_line_text(&self, row: u32) -> String {
+        let range = Point::new(row, 0)..Point::new(row, self.line_len(row));
+        self.text_for_range(range)
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    /// Finds the row nearest to `row` whose trimmed text is `line`, which is used to find a
+    /// location remembered by its row again after the file was edited. Rows up to
+    /// `LINE_SEARCH_RADIUS` away are considered. Returns `None` for empty lines, since they
+    /// don't identify a location.
+    pub fn find_line_near(&self, row: u32, line: &str) -> Option<u32> {
+        const LINE_SEARCH_RADIUS: u32 = 500;
+
+        if line.is_empty() {
+            return None;
+        }
+        let max_row = self.max_point().row;
+        let matches = |row: u32| row <= max_row && self.trimmed_line_text(row) == line;
+        if matches(row) {
+            return Some(row);
+        }
+        for distance in 1..=LINE_SEARCH_RADIUS {
+            if row >= distance && matches(row - distance) {
+                return Some(row - distance);
+            }
+            if matches(row + distance) {
+                return Some(row + distance);
+            }
+            if row.saturating_sub(distance) == 0 && row + distance > max_row {
+                break;
+            }
+        }
+        None
+    }
+
     pub fn line_indents_in_row_range(
         &self,
         row_range: Range<u32>,
@@ -5,11 +5,14 @@ use editor::{
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
     display_map::ToDisplayPoint,
     scroll::Autoscroll,
-    Bias, Editor, ToPoint,
+    Bias, Editor, NavigationData, ToPoint,
+};
+use gpui::{
+    actions, impl_internal_actions, Action, App, AppContext as _, Context, Global, Keystroke,
+    Modifiers, Window,
 };
-use gpui::{actions, impl_internal_actions, Action, App, Context, Global, Window};
 use language::Point;
-use multi_buffer::MultiBufferRow;
+use multi_buffer::{MultiBuffer, MultiBufferRow, MultiBufferSnapshot};
 use regex::Regex;
 use schemars::JsonSchema;
 use search::{BufferSearchBar, SearchOptions};
@@ -18,6 +21,7 @@ use std::{
     io::Write,
     iter::Peekable,
     ops::{Deref, Range},
+    path::PathBuf,
     process::Stdio,
     str::Chars,
     sync::OnceLock,
@@ -30,13 +34,15 @@ use workspace::{notifications::NotifyResultExt, SaveIntent};
 use zed_actions::RevealTarget;
 
 use crate::{
+    mapping::{bind_mappings, mapping_listing, replay_keys, MapMode, Mapping, TypeaheadKey},
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
         search::{FindCommand, ReplaceCommand, Replacement},
         JoinLines,
     },
     object::Object,
-    state::Mode,
+    persistence::editor_path,
+    state::{Mode, VimGlobals},
     visual::VisualDeleteLine,
     Vim,
 };
@@ -67,7 +73,19 @@ pub struct WithCount {
 #[derive(Debug)]
 struct WrappedAction(Box<dyn Action>);
 
-actions!(vim, [VisualCommand, CountCommand, ShellCommand]);
+actions!(
+    vim,
+    [
+        VisualCommand,
+        CountCommand,
+        ShellCommand,
+        ShowRegisters,
+        ShowMarks,
+        ShowJumps,
+        ShowChanges,
+        ShowHistory
+    ]
+);
 impl_internal_actions!(
     vim,
     [
@@ -76,7 +94,12 @@ impl_internal_actions!(
         WithRange,
         WithCount,
         OnMatchingLines,
-        ShellExec
+        ShellExec,
+        NormalCommand,
+        MapCommand,
+        SetCommand,
+        LetCommand,
+        SourceCommand
     ]
 );
 
@@ -241,6 +264,53 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
 
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &NormalCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &MapCommand, window, cx| {
+        let result = action.run(cx);
+        if matches!(result, Ok(None)) {
+            bind_mappings(cx);
+        }
+        vim.show_command_result("Mappings", result, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &SetCommand, window, cx| {
+        let result = action.run(cx);
+        vim.apply_options(window, cx);
+        vim.show_command_result("Options", result, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &LetCommand, window, cx| {
+        let result = action.run(cx).map(|_| None);
+        vim.show_command_result("", result, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &SourceCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, _: &ShowRegisters, window, cx| {
+        vim.show_registers(window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, _: &ShowMarks, window, cx| {
+        vim.show_marks(window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, _: &ShowJumps, window, cx| {
+        vim.show_jumps(window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, _: &ShowChanges, window, cx| {
+        vim.show_changes(window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, _: &ShowHistory, window, cx| {
+        vim.show_history(window, cx)
     })
 }
 
@@ -760,6 +830,12 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::new(("e", "dit"), editor::actions::ReloadFile)
             .bang(editor::actions::ReloadFile),
         VimCommand::new(("cpp", "link"), editor::actions::CopyPermalinkToLine).range(act_on_range),
+        VimCommand::new(("reg", "isters"), ShowRegisters),
+        VimCommand::new(("di", "splay"), ShowRegisters),
+        VimCommand::new(("marks", ""), ShowMarks),
+        VimCommand::new(("ju", "mps"), ShowJumps),
+        VimCommand::new(("changes", ""), ShowChanges),
+        VimCommand::new(("his", "tory"), ShowHistory),
     ]
 }
 
@@ -834,6 +910,14 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Option<CommandIntercept
             }
             .boxed_clone(),
         )
+    } else if let Some(command) = MapCommand::parse(query) {
+        Some(command.boxed_clone())
+    } else if let Some(command) = SetCommand::parse(query) {
+        Some(command.boxed_clone())
+    } else if let Some(command) = LetCommand::parse(query) {
+        Some(command.boxed_clone())
+    } else if let Some(command) = SourceCommand::parse(query) {
+        Some(command.boxed_clone())
     } else if query.starts_with('s') {
         let mut substitute = "substitute".chars().peekable();
         let mut query = query.chars().peekable();
@@ -878,6 +962,8 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Option<CommandIntercept
         } else {
             None
         }
+    } else if query.starts_with("norm") {
+        NormalCommand::parse(query, range.clone())
     } else if query.contains('!') {
         ShellExec::parse(query, range.clone())
     } else {
@@ -1455,20 +1541,840 @@ impl ShellExec {
     }
 }
 
+/// `:normal {keys}` replays `keys` as normal mode keystrokes, once per line in the range
+/// with the cursor at the start of that line. Mappings apply to the keys unless `:normal!`
+/// is used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NormalCommand {
+    range: Option<CommandRange>,
+    keys: String,
+    remap: bool,
+}
+
+impl NormalCommand {
+    // vim stops replaying runaway :normal commands much later than this, but we dispatch
+    // each keystroke individually so keep the total bounded.
+    const MAX_KEYSTROKES: usize = 10_000;
+
+    pub fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let rest = query.strip_prefix("norm")?;
+        let rest = rest
+            .strip_prefix("al")
+            .or_else(|| rest.strip_prefix('a'))
+            .unwrap_or(rest);
+        let (rest, remap) = match rest.strip_prefix('!') {
+            Some(rest) => (rest, false),
+            None => (rest, true),
+        };
+        let keys = rest.strip_prefix(' ')?.trim_start();
+        if keys.is_empty() {
+            return None;
+        }
+
+        Some(
+            NormalCommand {
+                range,
+                keys: keys.to_string(),
+                remap,
+            }
+            .boxed_clone(),
+        )
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let range = self.range.clone().unwrap_or(CommandRange {
+            start: Position::CurrentLine { offset: 0 },
+            end: None,
+        });
+        let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+            let range = range.buffer_range(vim, editor, window, cx)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            anyhow::Ok(
+                (range.start.0..=range.end.0)
+                    .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        let line_starts = match result {
+            None => return,
+            Some(e @ Err(_)) => {
+                let Some(workspace) = vim.workspace(window) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    e.notify_err(workspace, cx);
+                });
+                return;
+            }
+            Some(Ok(result)) => result,
+        };
+        let Some(editor) = vim.editor() else {
+            return;
+        };
+
+        let keystrokes = parse_normal_keys(&self.keys);
+        if keystrokes.len() * line_starts.len() > Self::MAX_KEYSTROKES {
+            let Some(workspace) = vim.workspace(window) else {
+                return;
+            };
+            let result: Result<()> = Err(anyhow!(
+                "Too many keystrokes: :normal is limited to {} in total",
+                Self::MAX_KEYSTROKES
+            ));
+            workspace.update(cx, |workspace, cx| {
+                result.notify_err(workspace, cx);
+            });
+            return;
+        }
+
+        vim.switch_mode(Mode::Normal, false, window, cx);
+        editor.update(cx, |editor, cx| {
+            editor.start_transaction_at(Instant::now(), window, cx);
+        });
+
+        // Keystrokes have to be dispatched outside of this update so that each one sees the
+        // mode (and key context) left behind by the previous one.
+        let remap = self.remap;
+        cx.spawn_in(window, |vim, mut cx| async move {
+            // The transaction is ended however replaying stops, including when the window
+            // is closed midway.
+            let result = async {
+                let escape = Keystroke::parse("escape")?;
+                for line_start in line_starts {
+                    cx.update(|window, cx| {
+                        editor.update(cx, |editor, cx| {
+                            editor.change_selections(None, window, cx, |s| {
+                                s.select_anchor_ranges([line_start..line_start])
+                            });
+                        });
+                        // Like vim, an incomplete command is aborted once the keys run out.
+                        let mut keys = TypeaheadKey::new(keystrokes.clone(), remap);
+                        keys.extend(TypeaheadKey::new(vec![escape.clone()], false));
+                        replay_keys(&vim, keys, window, cx)
+                    })??;
+                }
+                anyhow::Ok(())
+            }
+            .await;
+            editor.update(&mut cx, |editor, cx| {
+                editor.end_transaction_at(Instant::now(), cx);
+            })?;
+            result
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MapKind {
+    Map { recursive: bool },
+    Unmap,
+    Clear,
+}
+
+/// The `:map` family: `(name, shortest abbreviation, mode, kind)`. A mode of `None` means
+/// normal, visual and operator pending mode, or insert mode when used with a `!`.
+const MAP_COMMANDS: &[(&str, usize, Option<MapMode>, MapKind)] = &[
+    ("map", 3, None, MapKind::Map { recursive: true }),
+    (
+        "nmap",
+        2,
+        Some(MapMode::Normal),
+        MapKind::Map { recursive: true },
+    ),
+    (
+        "vmap",
+        2,
+        Some(MapMode::Visual),
+        MapKind::Map { recursive: true },
+    ),
+    (
+        "xmap",
+        2,
+        Some(MapMode::Visual),
+        MapKind::Map { recursive: true },
+    ),
+    (
+        "omap",
+        2,
+        Some(MapMode::OperatorPending),
+        MapKind::Map { recursive: true },
+    ),
+    (
+        "imap",
+        2,
+        Some(MapMode::Insert),
+        MapKind::Map { recursive: true },
+    ),
+    ("noremap", 2, None, MapKind::Map { recursive: false }),
+    (
+        "nnoremap",
+        2,
+        Some(MapMode::Normal),
+        MapKind::Map { recursive: false },
+    ),
+    (
+        "vnoremap",
+        2,
+        Some(MapMode::Visual),
+        MapKind::Map { recursive: false },
+    ),
+    (
+        "xnoremap",
+        2,
+        Some(MapMode::Visual),
+        MapKind::Map { recursive: false },
+    ),
+    (
+        "onoremap",
+        3,
+        Some(MapMode::OperatorPending),
+        MapKind::Map { recursive: false },
+    ),
+    (
+        "inoremap",
+        3,
+        Some(MapMode::Insert),
+        MapKind::Map { recursive: false },
+    ),
+    ("unmap", 3, None, MapKind::Unmap),
+    ("nunmap", 3, Some(MapMode::Normal), MapKind::Unmap),
+    ("vunmap", 2, Some(MapMode::Visual), MapKind::Unmap),
+    ("xunmap", 2, Some(MapMode::Visual), MapKind::Unmap),
+    ("ounmap", 2, Some(MapMode::OperatorPending), MapKind::Unmap),
+    ("iunmap", 2, Some(MapMode::Insert), MapKind::Unmap),
+    ("mapclear", 4, None, MapKind::Clear),
+    ("nmapclear", 5, Some(MapMode::Normal), MapKind::Clear),
+    ("vmapclear", 5, Some(MapMode::Visual), MapKind::Clear),
+    ("xmapclear", 5, Some(MapMode::Visual), MapKind::Clear),
+    (
+        "omapclear",
+        5,
+        Some(MapMode::OperatorPending),
+        MapKind::Clear,
+    ),
+    ("imapclear", 5, Some(MapMode::Insert), MapKind::Clear),
+];
+
+/// Arguments that may precede the left hand side of a mapping, and whether we support them.
+/// The supported ones don't change anything in Zed.
+const MAP_ARGUMENTS: &[(&str, bool)] = &[
+    ("<silent>", true),
+    ("<nowait>", true),
+    ("<unique>", true),
+    ("<special>", true),
+    ("<script>", true),
+    ("<buffer>", false),
+    ("<expr>", false),
+];
+
+/// `:map {lhs} {rhs}` and its variants define, list and remove key mappings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapCommand {
+    modes: Vec<MapMode>,
+    kind: MapKind,
+    args: String,
+}
+
+impl MapCommand {
+    pub fn parse(query: &str) -> Option<Self> {
+        let name_len = query
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(query.len());
+        let (name, rest) = query.split_at(name_len);
+        let (rest, bang) = match rest.strip_prefix('!') {
+            Some(rest) => (rest, true),
+            None => (rest, false),
+        };
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let (_, _, mode, kind) = MAP_COMMANDS
+            .iter()
+            .find(|(command, min_len, _, _)| name.len() >= *min_len && command.starts_with(name))?;
+        let modes = match (mode, bang) {
+            (Some(mode), false) => vec![*mode],
+            (None, false) => MapMode::NORMAL_VISUAL_OPERATOR.to_vec(),
+            (None, true) => vec![MapMode::Insert],
+            (Some(_), true) => return None,
+        };
+        Some(MapCommand {
+            modes,
+            kind: *kind,
+            args: rest.trim().to_string(),
+        })
+    }
+
+    /// Updates the mappings, returning a listing when mappings were only queried. The key
+    /// bindings for the mappings are left to the caller to update with [`bind_mappings`], so
+    /// that a whole file of mappings is only bound once.
+    pub fn run(&self, cx: &mut App) -> Result<Option<String>> {
+        let args = skip_map_arguments(&self.args)?;
+        let (lhs, rhs) = match args.split_once(char::is_whitespace) {
+            Some((lhs, rhs)) => (lhs, rhs.trim_start()),
+            None => (args, ""),
+        };
+        let lhs = expand_leaders(lhs, cx);
+        let rhs = expand_leaders(rhs, cx);
+        let lhs_keystrokes = parse_normal_keys(&lhs);
+        let globals = Vim::globals(cx);
+
+        match self.kind {
+            MapKind::Clear => {
+                if !args.is_empty() {
+                    return Err(anyhow!("E474: Invalid argument"));
+                }
+                globals
+                    .mappings
+                    .retain(|mapping| !self.modes.contains(&mapping.mode));
+            }
+            MapKind::Unmap => {
+                if lhs_keystrokes.is_empty() {
+                    return Err(anyhow!("E474: Invalid argument"));
+                }
+                let count = globals.mappings.len();
+                globals.mappings.retain(|mapping| {
+                    !self.modes.contains(&mapping.mode)
+                        || mapping.lhs_keystrokes() != lhs_keystrokes
+                });
+                if globals.mappings.len() == count {
+                    return Err(anyhow!("E31: No such mapping"));
+                }
+            }
+            MapKind::Map { .. } if rhs.is_empty() => {
+                let listing = mapping_listing(globals.mappings.iter().filter(|mapping| {
+                    self.modes.contains(&mapping.mode)
+                        && mapping.lhs_keystrokes().starts_with(&lhs_keystrokes)
+                }));
+                return Ok(Some(listing));
+            }
+            MapKind::Map { recursive } => {
+                globals.mappings.retain(|mapping| {
+                    !self.modes.contains(&mapping.mode)
+                        || mapping.lhs_keystrokes() != lhs_keystrokes
+                });
+                for mode in &self.modes {
+                    globals.mappings.push(Mapping {
+                        mode: *mode,
+                        lhs: lhs.clone(),
+                        rhs: rhs.clone(),
+                        recursive,
+                    });
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn skip_map_arguments(mut args: &str) -> Result<&str> {
+    'outer: loop {
+        for (argument, supported) in MAP_ARGUMENTS {
+            let Some(prefix) = args.get(..argument.len()) else {
+                continue;
+            };
+            if prefix.eq_ignore_ascii_case(argument) {
+                if !supported {
+                    return Err(anyhow!("{argument} mappings are not supported"));
+                }
+                args = args[argument.len()..].trim_start();
+                continue 'outer;
+            }
+        }
+        return Ok(args);
+    }
+}
+
+/// Replaces `<Leader>` and `<LocalLeader>` with the current values of `mapleader` and
+/// `maplocalleader`, which default to a backslash.
+fn expand_leaders(keys: &str, cx: &mut App) -> String {
+    static LEADER: OnceLock<Regex> = OnceLock::new();
+    let leader_regex = LEADER.get_or_init(|| Regex::new(r"(?i)<(local)?leader>").unwrap());
+    let globals = Vim::globals(cx);
+    let leader_keys = |leader: Option<&String>| match leader.map(|leader| leader.as_str()) {
+        None | Some("") => "\\".to_string(),
+        Some(" ") => "<Space>".to_string(),
+        Some(leader) => leader.to_string(),
+    };
+    let leader = leader_keys(globals.mapleader.as_ref());
+    let local_leader = leader_keys(globals.maplocalleader.as_ref());
+    leader_regex
+        .replace_all(keys, |captures: &regex::Captures| {
+            if captures.get(1).is_some() {
+                local_leader.clone()
+            } else {
+                leader.clone()
+            }
+        })
+        .into_owned()
+}
+
+/// `:set {option}` changes the options in [`crate::vimrc::VimOptions`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetCommand {
+    args: String,
+}
+
+impl SetCommand {
+    pub fn parse(query: &str) -> Option<Self> {
+        let rest = query
+            .strip_prefix("set")
+            .or_else(|| query.strip_prefix("se"))?;
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        Some(SetCommand {
+            args: rest.trim().to_string(),
+        })
+    }
+
+    /// Updates the options, returning a listing when options were only queried.
+    pub fn run(&self, cx: &mut App) -> Result<Option<String>> {
+        Vim::globals(cx).options.set(&self.args)
+    }
+}
+
+/// `:let mapleader = "x"`. Only the leader variables are supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LetCommand {
+    name: String,
+    value: String,
+}
+
+impl LetCommand {
+    pub fn parse(query: &str) -> Option<Self> {
+        let rest = query.strip_prefix("let")?;
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let (name, value) = rest.split_once('=')?;
+        let name = name.trim();
+        Some(LetCommand {
+            name: name.strip_prefix("g:").unwrap_or(name).to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+
+    pub fn run(&self, cx: &mut App) -> Result<()> {
+        let value = parse_string_literal(&self.value)
+            .ok_or_else(|| anyhow!("E15: Invalid expression: {}", self.value))?;
+        let globals = Vim::globals(cx);
+        match self.name.as_str() {
+            "mapleader" => globals.mapleader = Some(value),
+            "maplocalleader" => globals.maplocalleader = Some(value),
+            name => return Err(anyhow!("Setting {name} is not supported")),
+        }
+        Ok(())
+    }
+}
+
+/// Parses a vim string literal. Double quoted strings understand backslash escapes,
+/// including `\<Space>`, single quoted strings only `''`.
+fn parse_string_literal(literal: &str) -> Option<String> {
+    if let Some(inner) = literal
+        .strip_prefix('\'')
+        .and_then(|literal| literal.strip_suffix('\''))
+    {
+        return Some(inner.replace("''", "'"));
+    }
+    let inner = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut rest = inner;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('<') {
+            let end = after.find('>')?;
+            let key = parse_special_key(&after[..end])?;
+            value.push_str(match key.key.as_str() {
+                "space" => " ",
+                "tab" => "\t",
+                "enter" => "\r",
+                "escape" => "\x1b",
+                key => key,
+            });
+            rest = &after[end + 1..];
+            continue;
+        }
+        let escaped = rest.chars().next()?;
+        rest = &rest[escaped.len_utf8()..];
+        value.push(match escaped {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'e' => '\x1b',
+            c => c,
+        });
+    }
+    Some(value)
+}
+
+/// `:source [file]` runs the commands in a vimrc file, by default the user's vimrc.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceCommand {
+    path: Option<PathBuf>,
+}
+
+impl SourceCommand {
+    pub fn parse(query: &str) -> Option<Self> {
+        let name_len = query
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(query.len());
+        let (name, rest) = query.split_at(name_len);
+        if name.len() < 2 || !"source".starts_with(name) {
+            return None;
+        }
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let path = rest.trim();
+        let path = if path.is_empty() {
+            None
+        } else if let Some(path) = path.strip_prefix("~/") {
+            Some(util::paths::home_dir().join(path))
+        } else {
+            Some(PathBuf::from(path))
+        };
+        Some(SourceCommand { path })
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let path = self
+            .path
+            .clone()
+            .unwrap_or_else(|| paths::vimrc_file().clone());
+        vim.source_file(path, false, window, cx);
+    }
+}
+
+/// Converts the argument of `:normal` into keystrokes. Each character is typed as-is,
+/// and the `<Esc>`, `<CR>`, `<Tab>`, `<BS>`, `<Space>` and `<C-x>` notations are understood
+/// so that mappings copied from a vimrc keep working.
+pub(crate) fn parse_normal_keys(keys: &str) -> Vec<Keystroke> {
+    let mut keystrokes = Vec::new();
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                if let Some(keystroke) = parse_special_key(&rest[1..end]) {
+                    keystrokes.push(keystroke);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+        keystrokes.push(keystroke_for_char(c));
+        rest = &rest[c.len_utf8()..];
+    }
+    keystrokes
+}
+
+fn parse_special_key(name: &str) -> Option<Keystroke> {
+    let key = match name.to_lowercase().as_str() {
+        "esc" => "escape".to_string(),
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "tab" => "tab".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "space" => "space".to_string(),
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        "up" | "down" | "left" | "right" | "home" | "end" => name.to_lowercase(),
+        lower => {
+            let key = lower.strip_prefix("c-")?;
+            if key.chars().count() != 1 {
+                return None;
+            }
+            format!("ctrl-{key}")
+        }
+    };
+    Keystroke::parse(&key).ok()
+}
+
+fn keystroke_for_char(c: char) -> Keystroke {
+    match c {
+        ' ' => Keystroke {
+            modifiers: Modifiers::none(),
+            key: "space".into(),
+            key_char: None,
+        },
+        c if c.is_uppercase() => Keystroke {
+            modifiers: Modifiers::shift(),
+            key: c.to_lowercase().to_string(),
+            key_char: None,
+        },
+        c => Keystroke {
+            modifiers: Modifiers::none(),
+            key: c.to_string(),
+            key_char: None,
+        },
+    }
+}
+
+impl Vim {
+    fn show_registers(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mut registers = Vim::globals(cx)
+            .registers
+            .iter()
+            .map(|(name, register)| (*name, register.clone()))
+            .collect::<Vec<_>>();
+        if let Some(item) = cx.read_from_clipboard() {
+            registers.push(('+', item.into()));
+        }
+        registers.retain(|(_, register)| !register.text.is_empty());
+        registers.sort_by_key(|(name, _)| {
+            let group = match name {
+                '"' => 0,
+                '0'..='9' => 1,
+                'a'..='z' => 2,
+                '-' => 3,
+                _ => 4,
+            };
+            (group, *name)
+        });
+
+        let mut listing = "Type Name Content\n".to_string();
+        for (name, register) in registers {
+            let linewise = register
+                .clipboard_selections
+                .as_ref()
+                .is_some_and(|selections| {
+                    !selections.is_empty()
+                        && selections.iter().all(|selection| selection.is_entire_line)
+                });
+            listing.push_str(&format!(
+                "  {}  \"{}   {}\n",
+                if linewise { 'l' } else { 'c' },
+                name,
+                escape_listing_text(&register.text)
+            ));
+        }
+        self.open_listing("Registers", listing, window, cx);
+    }
+
+    fn show_marks(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(listing) = self.update_editor(window, cx, |vim, editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let path = editor_path(editor, cx);
+            let mut marks = vim
+                .marks
+                .iter()
+                .filter_map(|(name, anchors)| {
+                    let point = anchors.first()?.to_point(&snapshot);
+                    Some((name.clone(), point, line_text(&snapshot, point.row)))
+                })
+                .collect::<Vec<_>>();
+            if let Some(anchor) = vim.change_list.last().and_then(|anchors| anchors.first()) {
+                let point = anchor.to_point(&snapshot);
+                marks.push((".".to_string(), point, line_text(&snapshot, point.row)));
+            }
+            // Uppercase marks in other files are listed with the file instead of the text.
+            for (name, mark) in &cx.global::<VimGlobals>().global_marks {
+                if path.as_ref() != Some(&mark.path) {
+                    let point = Point::new(mark.row, mark.column);
+                    marks.push((
+                        name.clone(),
+                        point,
+                        mark.path.to_string_lossy().into_owned(),
+                    ));
+                }
+            }
+            marks.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let mut listing = "mark line  col file/text\n".to_string();
+            for (name, point, text) in marks {
+                listing.push_str(&format!(
+                    " {:<3}{:>6}{:>5} {}\n",
+                    name,
+                    point.row + 1,
+                    point.column,
+                    text
+                ));
+            }
+            listing
+        }) else {
+            return;
+        };
+        self.open_listing("Marks", listing, window, cx);
+    }
+
+    fn show_changes(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(listing) = self.update_editor(window, cx, |vim, editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let current = vim.change_list_position.unwrap_or(vim.change_list.len());
+
+            let mut listing = "change line  col text\n".to_string();
+            for (ix, anchors) in vim.change_list.iter().enumerate() {
+                let Some(anchor) = anchors.first() else {
+                    continue;
+                };
+                let point = anchor.to_point(&snapshot);
+                listing.push_str(&format!(
+                    "{}{:>5}{:>6}{:>5} {}\n",
+                    if ix == current { '>' } else { ' ' },
+                    current.abs_diff(ix),
+                    point.row + 1,
+                    point.column,
+                    line_text(&snapshot, point.row)
+                ));
+            }
+            if current == vim.change_list.len() {
+                listing.push_str(">\n");
+            }
+            listing
+        }) else {
+            return;
+        };
+        self.open_listing("Changes", listing, window, cx);
+    }
+
+    fn show_jumps(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let mut jumps = Vec::new();
+        pane.read(cx)
+            .nav_history()
+            .for_each_entry(cx, |entry, (project_path, _)| {
+                let position = entry
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.downcast_ref::<NavigationData>())
+                    .map(|data| data.cursor_position());
+                jumps.push((entry.timestamp, project_path.path, position));
+            });
+        jumps.sort_by_key(|(timestamp, _, _)| *timestamp);
+
+        let mut listing = " jump line  col file\n".to_string();
+        let count = jumps.len();
+        for (ix, (_, path, position)) in jumps.into_iter().enumerate() {
+            let position = position.unwrap_or_default();
+            listing.push_str(&format!(
+                "{:>5}{:>6}{:>5} {}\n",
+                count - ix,
+                position.row + 1,
+                position.column,
+                path.display()
+            ));
+        }
+        listing.push_str(">\n");
+        self.open_listing("Jumps", listing, window, cx);
+    }
+
+    fn show_history(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let globals = Vim::globals(cx);
+        let mut listing = String::new();
+        for (kind, history) in [
+            ("cmd", &globals.command_history),
+            ("search", &globals.search_history),
+        ] {
+            listing.push_str(&format!("      #  {kind} history\n"));
+            for (ix, entry) in history.iter().enumerate() {
+                listing.push_str(&format!("{:>7}  {}\n", ix + 1, entry));
+            }
+        }
+        self.open_listing("History", listing, window, cx);
+    }
+
+    /// Opens the listing a command produced, or shows the error it failed with.
+    fn show_command_result(
+        &self,
+        title: &str,
+        result: Result<Option<String>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match result {
+            Ok(Some(listing)) => self.open_listing(title, listing, window, cx),
+            Ok(None) => {}
+            Err(error) => {
+                let Some(workspace) = self.workspace(window) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    Err::<(), _>(error).notify_err(workspace, cx);
+                });
+            }
+        }
+    }
+
+    fn open_listing(
+        &self,
+        title: &str,
+        listing: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace(window) else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            let project = workspace.project().clone();
+            let buffer = project.update(cx, |project, cx| {
+                project.create_local_buffer(&listing, None, cx)
+            });
+            let buffer =
+                cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title.to_string()));
+            let editor = cx.new(|cx| {
+                let mut editor = Editor::for_multibuffer(buffer, Some(project), true, window, cx);
+                editor.set_read_only(true);
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+        });
+    }
+}
+
+pub(crate) fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    let line_len = snapshot.line_len(MultiBufferRow(row));
+    let text = snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, line_len))
+        .collect::<String>();
+    text.trim().to_string()
+}
+
+// Like vim, show control characters in register contents using caret notation.
+fn escape_listing_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("^J"),
+            '\t' => escaped.push_str("^I"),
+            '\r' => escaped.push_str("^M"),
+            c if c.is_control() && (c as u32) < 32 => {
+                escaped.push('^');
+                escaped.push((b'@' + c as u8) as char);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 #[cfg(test)]
 mod test {
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
 
     use crate::{
+        mapping::MapMode,
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
+        Vim,
     };
     use editor::Editor;
-    use gpui::{Context, TestAppContext};
+    use gpui::{Context, Keystroke, TestAppContext};
     use indoc::indoc;
     use util::path;
     use workspace::Workspace;
 
+    use super::{parse_string_literal, MapCommand, MapKind, SourceCommand};
+
     #[gpui::test]
     async fn test_command_basics(cx: &mut TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -1780,4 +2686,254 @@ mod test {
             a
             ˇa"});
     }
+
+    #[gpui::test]
+    async fn test_normal_command(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": % n o r m space A ; enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one;
+            two;
+            threeˇ;"},
+            Mode::Normal,
+        );
+
+        // the whole command is undone at once
+        cx.simulate_keystrokes("u");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ˇone
+            two
+            three"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": 2 , 3 n o r m a l space d w i < l t > < e s c > enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one
+            <
+            ˇ<"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_show_registers(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone two
+            three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("\" a y w y y");
+        cx.simulate_keystrokes(": r e g enter");
+        cx.run_until_parked();
+
+        let listing = cx.workspace(|workspace, _, cx| {
+            workspace
+                .active_item_as::<Editor>(cx)
+                .unwrap()
+                .read(cx)
+                .text(cx)
+        });
+        // the system clipboard may also be listed, as "+
+        assert!(listing.starts_with(concat!(
+            "Type Name Content\n",
+            "  l  \"\"   one two^J\n",
+            "  l  \"0   one two^J\n",
+            "  c  \"a   one \n",
+        )));
+    }
+
+    #[gpui::test]
+    async fn test_map_commands(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three
+            four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": n m a p space , space d d enter");
+        cx.simulate_keystrokes(",");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ˇtwo
+            three
+            four"},
+            Mode::Normal,
+        );
+
+        // recursive mappings expand mapped keys in their right hand side
+        cx.simulate_keystrokes(": n n o r e m a p space x space d d enter");
+        cx.simulate_keystrokes(": n m a p space , space x enter");
+        cx.simulate_keystrokes(",");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ˇthree
+            four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": n n o r e m a p space , space x enter");
+        cx.simulate_keystrokes(",");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ˇhree
+            four"},
+            Mode::Normal,
+        );
+        // remapping replaces the key binding of the earlier mapping
+        assert_eq!(mapping_bindings(&mut cx, ","), 1);
+
+        cx.simulate_keystrokes(": u n m a p space x enter");
+        cx.simulate_keystrokes(": u n m a p space , enter");
+        cx.simulate_keystrokes(", x");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ˇree
+            four"},
+            Mode::Normal,
+        );
+        assert_eq!(mapping_bindings(&mut cx, ","), 0);
+
+        fn mapping_bindings(cx: &mut VimTestContext, keys: &str) -> usize {
+            cx.update(|_, cx| {
+                cx.all_bindings_for_input(&[Keystroke::parse(keys).unwrap()])
+                    .iter()
+                    .filter(|binding| binding.action().name() == "vim::ApplyMapping")
+                    .count()
+            })
+        }
+    }
+
+    #[gpui::test]
+    async fn test_map_modes(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes(": i m a p space j k space < e s c > enter");
+        cx.simulate_keystrokes(": o m a p space w space e enter");
+        cx.simulate_keystrokes("i x j k");
+        cx.run_until_parked();
+        cx.assert_state("ˇxone two", Mode::Normal);
+
+        cx.simulate_keystrokes("c w");
+        cx.run_until_parked();
+        cx.assert_state("ˇ two", Mode::Insert);
+    }
+
+    #[gpui::test]
+    async fn test_normal_command_remaps(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": n n o r e m a p space x space d d enter");
+        cx.simulate_keystrokes(": n o r m a l ! space x enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ˇne
+            two"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": n o r m a l space x enter");
+        cx.run_until_parked();
+        cx.assert_state("ˇtwo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_source_vimrc(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(
+                path!("/root/vimrc"),
+                indoc! {r#"
+                    " leader mappings
+                    let mapleader = "\<Space>"
+                    nnoremap <leader>d dd
+                    :set so=3 nowrap
+                "#}
+                .as_bytes()
+                .to_vec(),
+            )
+            .await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two"},
+            Mode::Normal,
+        );
+        cx.dispatch_action(SourceCommand {
+            path: Some(PathBuf::from(path!("/root/vimrc"))),
+        });
+        cx.run_until_parked();
+        cx.simulate_keystrokes("space d");
+        cx.run_until_parked();
+        cx.assert_state("ˇtwo", Mode::Normal);
+
+        let options = cx.update(|_, cx| Vim::globals(cx).options);
+        assert_eq!(options.scroll_off, Some(3));
+        assert_eq!(options.wrap, Some(false));
+    }
+
+    #[test]
+    fn test_parse_map_commands() {
+        let command = MapCommand::parse("nno <silent> j gj").unwrap();
+        assert_eq!(command.modes, vec![MapMode::Normal]);
+        assert_eq!(command.kind, MapKind::Map { recursive: false });
+        assert_eq!(command.args, "<silent> j gj");
+
+        let command = MapCommand::parse("map! jk <Esc>").unwrap();
+        assert_eq!(command.modes, vec![MapMode::Insert]);
+        assert_eq!(command.kind, MapKind::Map { recursive: true });
+
+        let command = MapCommand::parse("unm Q").unwrap();
+        assert_eq!(command.modes, MapMode::NORMAL_VISUAL_OPERATOR.to_vec());
+        assert_eq!(command.kind, MapKind::Unmap);
+
+        assert_eq!(MapCommand::parse("nmapc").unwrap().kind, MapKind::Clear);
+        assert!(MapCommand::parse("ma x y").is_none());
+        assert!(MapCommand::parse("vsplit").is_none());
+        assert!(MapCommand::parse("nmap! x y").is_none());
+    }
+
+    #[test]
+    fn test_parse_string_literal() {
+        assert_eq!(parse_string_literal(r#"",""#).as_deref(), Some(","));
+        assert_eq!(parse_string_literal(r#""\<Space>""#).as_deref(), Some(" "));
+        assert_eq!(parse_string_literal("' '").as_deref(), Some(" "));
+        assert_eq!(parse_string_literal("'it''s'").as_deref(), Some("it's"));
+        assert_eq!(parse_string_literal(","), None);
+    }
 }
@@ -1,15 +1,92 @@
-use editor::{movement, scroll::Autoscroll, DisplayPoint, Editor};
-use gpui::{actions, Action};
+use std::{mem, ops::Range, sync::Arc};
+
+use editor::{
+    display_map::{DisplayRow, Inlay, ToDisplayPoint},
+    movement,
+    scroll::Autoscroll,
+    Anchor, Bias, DisplayPoint, Editor, InlayId, ToOffset,
+};
+use gpui::{actions, impl_actions, Action};
 use gpui::{Context, Window};
-use language::{CharClassifier, CharKind};
+use language::{CharClassifier, CharKind, Point, SelectionGoal};
+use multi_buffer::MultiBufferSnapshot;
+use regex::Regex;
+use schemars::JsonSchema;
+use search::{buffer_search, BufferSearchBar, SearchOptions};
+use serde::Deserialize;
+
+use crate::{
+    motion::Motion,
+    object::Object,
+    state::{Mode, Operator, SearchState, VimGlobals},
+    surrounds::SurroundsType,
+    Vim,
+};
+
+/// Rotates which selection is the primary (newest) one.
+#[derive(Clone, Deserialize, JsonSchema, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct HelixRotateSelections {
+    #[serde(default)]
+    backward: bool,
+}
+
+actions!(
+    vim,
+    [
+        HelixNormalAfter,
+        HelixDelete,
+        HelixSelectLine,
+        HelixExtendToLineBounds,
+        HelixCollapseSelection,
+        HelixKeepPrimarySelection,
+        HelixSelectRegex,
+        HelixSplitSelection,
+        HelixJumpToWord,
+    ]
+);
+impl_actions!(vim, [HelixRotateSelections]);
 
-use crate::{motion::Motion, state::Mode, Vim};
+const JUMP_LABEL_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
 
-actions!(vim, [HelixNormalAfter, HelixDelete]);
+/// Which part of the selections is kept once the regex typed after `s` or `S`
+/// is submitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegexSelection {
+    /// `s`: select every match within the selections.
+    Matches,
+    /// `S`: split the selections on every match.
+    Splits,
+}
+
+/// A two character label shown in front of a word by `gw`.
+pub(crate) struct JumpLabel {
+    label: [char; 2],
+    range: Range<Anchor>,
+    inlay_id: InlayId,
+}
 
 pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     Vim::action(editor, cx, Vim::helix_normal_after);
     Vim::action(editor, cx, Vim::helix_delete);
+    Vim::action(editor, cx, |vim, _: &HelixSelectLine, window, cx| {
+        vim.helix_select_lines(true, window, cx)
+    });
+    Vim::action(
+        editor,
+        cx,
+        |vim, _: &HelixExtendToLineBounds, window, cx| vim.helix_select_lines(false, window, cx),
+    );
+    Vim::action(editor, cx, Vim::helix_collapse_selection);
+    Vim::action(editor, cx, Vim::helix_keep_primary_selection);
+    Vim::action(editor, cx, Vim::helix_rotate_selections);
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, window, cx| {
+        vim.helix_select_regex(RegexSelection::Matches, window, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelection, window, cx| {
+        vim.helix_select_regex(RegexSelection::Splits, window, cx)
+    });
+    Vim::action(editor, cx, Vim::helix_jump_to_word);
 }
 
 impl Vim {
@@ -258,6 +335,455 @@ impl Vim {
             editor.insert("", window, cx);
         });
     }
+
+    /// Selects the lines touched by each selection. When `extend` is set, selections
+    /// that already cover whole lines grow by another `count` lines instead.
+    fn helix_select_lines(&mut self, extend: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let count = Vim::take_count(cx).unwrap_or(1) as u32;
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let max_point = snapshot.max_point();
+            let line_end = |row: u32| {
+                if row < max_point.row {
+                    Point::new(row + 1, 0)
+                } else {
+                    max_point
+                }
+            };
+
+            let mut selections = editor.selections.all::<Point>(cx);
+            for selection in &mut selections {
+                let mut last_row =
+                    if selection.end.column == 0 && selection.end.row > selection.start.row {
+                        selection.end.row - 1
+                    } else {
+                        selection.end.row
+                    };
+                if extend {
+                    let covers_lines =
+                        selection.start.column == 0 && selection.end == line_end(last_row);
+                    last_row += if covers_lines { count } else { count - 1 };
+                }
+                selection.start = Point::new(selection.start.row, 0);
+                selection.end = line_end(last_row.min(max_point.row));
+                selection.reversed = false;
+                selection.goal = SelectionGoal::None;
+            }
+
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.select(selections)
+            });
+        });
+    }
+
+    fn helix_collapse_selection(
+        &mut self,
+        _: &HelixCollapseSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = if selection.is_empty() || selection.reversed {
+                        selection.head()
+                    } else {
+                        movement::left(map, selection.head())
+                    };
+                    selection.collapse_to(cursor, selection.goal)
+                });
+            });
+        });
+    }
+
+    fn helix_keep_primary_selection(
+        &mut self,
+        _: &HelixKeepPrimarySelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let newest = editor.selections.newest::<usize>(cx);
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.select(vec![newest])
+            });
+        });
+    }
+
+    fn helix_rotate_selections(
+        &mut self,
+        action: &HelixRotateSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let selections = editor.selections.all::<usize>(cx);
+            let newest_id = editor.selections.newest::<usize>(cx).id;
+            let Some(ix) = selections.iter().position(|s| s.id == newest_id) else {
+                return;
+            };
+            let len = selections.len();
+            let primary_ix = if action.backward {
+                (ix + len - 1) % len
+            } else {
+                (ix + 1) % len
+            };
+
+            // The last selection given to `select_ranges` becomes the newest one.
+            let mut ranges = Vec::with_capacity(len);
+            for (ix, selection) in selections.iter().enumerate() {
+                if ix != primary_ix {
+                    ranges.push(selection.tail()..selection.head());
+                }
+            }
+            let primary = &selections[primary_ix];
+            ranges.push(primary.tail()..primary.head());
+
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.select_ranges(ranges)
+            });
+        });
+    }
+
+    pub(crate) fn helix_object(
+        &mut self,
+        object: Object,
+        around: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    // Objects are found around the character under the block cursor,
+                    // which is the one before the head of a forward selection.
+                    let mut cursor = selection.clone();
+                    if !selection.is_empty() && !selection.reversed && object != Object::Tag {
+                        cursor.set_head(movement::left(map, cursor.head()), cursor.goal);
+                    }
+                    if let Some(range) = object.range(map, cursor, around) {
+                        if !range.is_empty() {
+                            selection.start = range.start;
+                            selection.end = range.end;
+                            selection.reversed = false;
+                        }
+                    }
+                });
+            });
+        });
+    }
+
+    pub(crate) fn helix_add_surrounds(
+        &mut self,
+        text: Arc<str>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(None, window, cx, |s| {
+                s.move_with(|map, selection| {
+                    if selection.is_empty() && !selection.reversed {
+                        selection.end = movement::right(map, selection.end);
+                    }
+                });
+            });
+        });
+        self.add_surrounds(text, SurroundsType::Selection, window, cx);
+    }
+
+    fn helix_select_regex(
+        &mut self,
+        kind: RegexSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let prior_selections = self.editor_selections(window, cx);
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    if !search_bar.show(window, cx) {
+                        return;
+                    }
+                    search_bar.select_query(window, cx);
+                    cx.focus_self(window);
+                    search_bar.set_replacement(None, cx);
+                    search_bar.set_search_options(SearchOptions::REGEX, cx);
+
+                    self.search = SearchState {
+                        prior_selections,
+                        prior_mode: self.mode,
+                        helix_select: Some(kind),
+                        ..Default::default()
+                    };
+                });
+            }
+        });
+    }
+
+    pub(crate) fn helix_select_regex_submit(
+        &mut self,
+        kind: RegexSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let query = pane.update(cx, |pane, cx| {
+            let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+            search_bar.update(cx, |search_bar, cx| {
+                let query = search_bar.query(cx);
+                search_bar.dismiss(&buffer_search::Dismiss, window, cx);
+                Some(query)
+            })
+        });
+        let prior_selections = mem::take(&mut self.search.prior_selections);
+        let Some(query) = query else {
+            return;
+        };
+        let regex = Regex::new(&query).ok();
+        VimGlobals::push_search_history(&query, cx);
+        Vim::globals(cx).registers.insert('/', query.into());
+
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let prior_selections = prior_selections
+                .into_iter()
+                .filter(|range| range.start.is_valid(&snapshot) && range.end.is_valid(&snapshot))
+                .collect::<Vec<_>>();
+
+            let mut ranges = Vec::new();
+            if let Some(regex) = regex {
+                for selection in &prior_selections {
+                    let mut range =
+                        selection.start.to_offset(&snapshot)..selection.end.to_offset(&snapshot);
+                    if range.start > range.end {
+                        range = range.end..range.start;
+                    }
+                    if range.is_empty() {
+                        range.end += snapshot
+                            .chars_at(range.end)
+                            .next()
+                            .map_or(0, char::len_utf8);
+                    }
+                    ranges.extend(regex_selection_ranges(&snapshot, range, &regex, kind));
+                }
+            }
+
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                if ranges.is_empty() {
+                    s.select_anchor_ranges(prior_selections)
+                } else {
+                    s.select_ranges(ranges)
+                }
+            });
+        });
+    }
+
+    /// Labels the words visible in the editor so that typing a label jumps to its word.
+    fn helix_jump_to_word(
+        &mut self,
+        _: &HelixJumpToWord,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.clear_jump_labels(window, cx);
+        let labels = self
+            .update_editor(window, cx, |_, editor, window, cx| {
+                let map = editor.snapshot(window, cx);
+                let text_layout_details = editor.text_layout_details(window);
+                let first_row = text_layout_details
+                    .scroll_anchor
+                    .anchor
+                    .to_display_point(&map)
+                    .row();
+                let last_row = text_layout_details.visible_rows.map_or(
+                    map.max_point().row(),
+                    |visible_rows| {
+                        DisplayRow(first_row.0 + visible_rows.ceil() as u32)
+                            .min(map.max_point().row())
+                    },
+                );
+                let start = DisplayPoint::new(first_row, 0).to_offset(&map, Bias::Left);
+                let end = DisplayPoint::new(last_row, map.line_len(last_row))
+                    .to_offset(&map, Bias::Right);
+                let cursor = editor.selections.newest::<usize>(cx).head();
+
+                let mut words = word_ranges(&map.buffer_snapshot, start..end);
+                words.sort_by_key(|word| {
+                    if word.start >= cursor {
+                        word.start - cursor
+                    } else {
+                        cursor.saturating_sub(word.end)
+                    }
+                });
+
+                let mut labels = Vec::new();
+                let mut inlays = Vec::new();
+                for (ix, word) in words
+                    .into_iter()
+                    .enumerate()
+                    .take(JUMP_LABEL_ALPHABET.len() * JUMP_LABEL_ALPHABET.len())
+                {
+                    let label = [
+                        JUMP_LABEL_ALPHABET[ix / JUMP_LABEL_ALPHABET.len()] as char,
+                        JUMP_LABEL_ALPHABET[ix % JUMP_LABEL_ALPHABET.len()] as char,
+                    ];
+                    let id = editor.next_inlay_id();
+                    let position = map.buffer_snapshot.anchor_before(word.start);
+                    inlays.push(Inlay::inline_completion(
+                        id,
+                        position,
+                        label.iter().collect::<String>(),
+                    ));
+                    labels.push(JumpLabel {
+                        label,
+                        range: position..map.buffer_snapshot.anchor_after(word.end),
+                        inlay_id: InlayId::InlineCompletion(id),
+                    });
+                }
+                editor.splice_inlays(&[], inlays, cx);
+                labels
+            })
+            .unwrap_or_default();
+
+        if labels.is_empty() {
+            return;
+        }
+        self.jump_labels = labels;
+        self.push_operator(Operator::JumpLabel { first_char: None }, window, cx);
+    }
+
+    pub(crate) fn helix_jump_label_input(
+        &mut self,
+        text: Arc<str>,
+        first_char: Option<char>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ch) = text.chars().next() else {
+            return;
+        };
+        let Some(first_char) = first_char else {
+            // Hide the labels that can no longer match.
+            let (matching, hidden): (Vec<_>, Vec<_>) = mem::take(&mut self.jump_labels)
+                .into_iter()
+                .partition(|label| label.label[0] == ch);
+            let hidden = hidden
+                .into_iter()
+                .map(|label| label.inlay_id)
+                .collect::<Vec<_>>();
+            self.update_editor(window, cx, |_, editor, _, cx| {
+                editor.splice_inlays(&hidden, Vec::new(), cx)
+            });
+            self.jump_labels = matching;
+            if self.jump_labels.is_empty() {
+                self.clear_operator(window, cx);
+            } else {
+                self.pop_operator(window, cx);
+                self.push_operator(
+                    Operator::JumpLabel {
+                        first_char: Some(ch),
+                    },
+                    window,
+                    cx,
+                );
+            }
+            return;
+        };
+
+        let target = self
+            .jump_labels
+            .iter()
+            .find(|label| label.label == [first_char, ch])
+            .map(|label| label.range.clone());
+        self.clear_operator(window, cx);
+        if let Some(target) = target {
+            self.update_editor(window, cx, |_, editor, window, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                    s.select_anchor_ranges([target])
+                });
+            });
+        }
+    }
+
+    pub(crate) fn clear_jump_labels(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.jump_labels.is_empty() {
+            return;
+        }
+        let inlay_ids = self
+            .jump_labels
+            .drain(..)
+            .map(|label| label.inlay_id)
+            .collect::<Vec<_>>();
+        self.update_editor(window, cx, |_, editor, _, cx| {
+            editor.splice_inlays(&inlay_ids, Vec::new(), cx)
+        });
+    }
+}
+
+/// The ranges `s` (matches) or `S` (the text between matches) select within `range`.
+fn regex_selection_ranges(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<usize>,
+    regex: &Regex,
+    kind: RegexSelection,
+) -> Vec<Range<usize>> {
+    let text = snapshot.text_for_range(range.clone()).collect::<String>();
+    let mut ranges = Vec::new();
+    match kind {
+        RegexSelection::Matches => {
+            for found in regex.find_iter(&text) {
+                if !found.is_empty() {
+                    ranges.push(range.start + found.start()..range.start + found.end());
+                }
+            }
+        }
+        RegexSelection::Splits => {
+            let mut piece_start = range.start;
+            for found in regex.find_iter(&text) {
+                if found.is_empty() {
+                    continue;
+                }
+                if range.start + found.start() > piece_start {
+                    ranges.push(piece_start..range.start + found.start());
+                }
+                piece_start = range.start + found.end();
+            }
+            if range.end > piece_start {
+                ranges.push(piece_start..range.end);
+            }
+        }
+    }
+    ranges
+}
+
+/// The words that start within `range`, in buffer order.
+fn word_ranges(snapshot: &MultiBufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+    let classifier = snapshot.char_classifier_at(range.start);
+    let mut words = Vec::new();
+    let mut word_start = None;
+    let mut offset = range.start;
+    for ch in snapshot.chars_at(range.start) {
+        let is_word = classifier.kind(ch) == CharKind::Word;
+        match word_start {
+            Some(start) if !is_word => {
+                words.push(start..offset);
+                word_start = None;
+            }
+            None if is_word && offset < range.end => word_start = Some(offset),
+            None if offset >= range.end => break,
+            _ => {}
+        }
+        offset += ch.len_utf8();
+    }
+    if let Some(start) = word_start {
+        words.push(start..offset);
+    }
+    words
 }
 
 #[cfg(test)]
@@ -380,4 +906,253 @@ mod test {
             Mode::HelixNormal,
         );
     }
+
+    #[gpui::test]
+    async fn test_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            ˇ»fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        // a selection of whole lines grows by a line
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            the lazy dog.ˇ»"},
+            Mode::HelixNormal,
+        );
+
+        // with a count
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("2 x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_extend_to_line_bounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The qu«ick brown
+            fox ˇ»jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("shift-x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        // unlike `x`, whole lines are not extended
+        cx.simulate_keystrokes("shift-x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_collapse_and_keep_primary_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The qu«ick ˇ»brown", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The quickˇ brown", Mode::HelixNormal);
+
+        cx.set_state("«Theˇ» «quickˇ» «brownˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes(",");
+        cx.assert_state("The quick «brownˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_copy_selection_on_next_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The qu«icˇ»k brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("shift-c");
+        cx.assert_state(
+            indoc! {"
+            The qu«icˇ»k brown
+            fox ju«mpˇ»s over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rotate_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("«Theˇ» «quickˇ» «brownˇ»", Mode::HelixNormal);
+        let primary = |cx: &mut VimTestContext| {
+            cx.update_editor(|editor, _, cx| editor.selections.newest::<usize>(cx).range())
+        };
+        assert_eq!(primary(&mut cx), 10..15);
+
+        cx.simulate_keystrokes(")");
+        assert_eq!(primary(&mut cx), 0..3);
+        cx.simulate_keystrokes(")");
+        assert_eq!(primary(&mut cx), 4..9);
+        cx.simulate_keystrokes("(");
+        assert_eq!(primary(&mut cx), 0..3);
+        cx.simulate_keystrokes("(");
+        assert_eq!(primary(&mut cx), 10..15);
+
+        cx.assert_state("«Theˇ» «quickˇ» «brownˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            «one two one
+            ˇ»three one"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("s o n e");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            «oneˇ» two «oneˇ»
+            three one"},
+            Mode::HelixNormal,
+        );
+
+        // a regex without matches keeps the selections
+        cx.simulate_keystrokes("s x y z");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            «oneˇ» two «oneˇ»
+            three one"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_split_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("«one, two,  threeˇ» four", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("shift-s , space +");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state("«oneˇ», «twoˇ»,  «threeˇ» four", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_match_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇ(hello world)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m m");
+        cx.assert_state("(hello worldˇ)", Mode::HelixNormal);
+
+        cx.set_state("(hello woˇrld)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i w");
+        cx.assert_state("(hello «worldˇ»)", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m i (");
+        cx.assert_state("(«hello worldˇ»)", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("«(hello world)ˇ»", Mode::HelixNormal);
+
+        // surrounding keeps the delimiters selected
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s ]");
+        cx.assert_state("one «[two]ˇ» three", Mode::HelixNormal);
+
+        // an empty selection surrounds the character under the cursor
+        cx.set_state("one ˇx three", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s '");
+        cx.assert_state("one «'x'ˇ» three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_jump_to_word(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("g w");
+        cx.update_editor(|editor, _, cx| {
+            assert_eq!(editor.display_text(cx), "aaone abtwo acthree");
+        });
+
+        // the first character hides the labels that no longer match
+        cx.simulate_keystrokes("a");
+        cx.update_editor(|editor, _, cx| {
+            assert_eq!(editor.display_text(cx), "aaone abtwo acthree");
+        });
+
+        cx.simulate_keystrokes("b");
+        cx.assert_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.update_editor(|editor, _, cx| {
+            assert_eq!(editor.display_text(cx), "one two three");
+        });
+
+        // escape removes the labels
+        cx.simulate_keystrokes("g w escape");
+        cx.update_editor(|editor, _, cx| {
+            assert_eq!(editor.display_text(cx), "one two three");
+        });
+        cx.assert_state("one «twoˇ» three", Mode::HelixNormal);
+    }
 }
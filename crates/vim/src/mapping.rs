@@ -0,0 +1,309 @@
+use std::{collections::VecDeque, rc::Rc};
+
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use gpui::{
+    impl_internal_actions, App, AppContext as _, Context, KeyBinding, KeyBindingContextPredicate,
+    Keystroke, WeakEntity, Window,
+};
+use util::ResultExt;
+
+use crate::{
+    command::parse_normal_keys,
+    state::{Mode, VimGlobals},
+    Vim,
+};
+
+/// Vim gives up on a mapping that keeps expanding into itself after this many expansions
+/// (its `maxmapdepth` option).
+const MAX_MAPPING_DEPTH: usize = 1000;
+
+/// The modes a mapping can be defined for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapMode {
+    Normal,
+    Visual,
+    OperatorPending,
+    Insert,
+}
+
+impl MapMode {
+    pub(crate) const NORMAL_VISUAL_OPERATOR: &'static [MapMode] =
+        &[MapMode::Normal, MapMode::Visual, MapMode::OperatorPending];
+
+    fn vim_mode(self) -> &'static str {
+        match self {
+            MapMode::Normal => "normal",
+            MapMode::Visual => "visual",
+            MapMode::OperatorPending => "operator",
+            MapMode::Insert => "insert",
+        }
+    }
+
+    /// The character vim uses for this mode when listing mappings.
+    fn indicator(self) -> char {
+        match self {
+            MapMode::Normal => 'n',
+            MapMode::Visual => 'v',
+            MapMode::OperatorPending => 'o',
+            MapMode::Insert => 'i',
+        }
+    }
+}
+
+/// A key mapping created with `:map` or one of its variants.
+///
+/// `<leader>` and `<localleader>` are expanded when the mapping is defined, like in vim.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mapping {
+    pub mode: MapMode,
+    pub lhs: String,
+    pub rhs: String,
+    pub recursive: bool,
+}
+
+impl Mapping {
+    pub(crate) fn lhs_keystrokes(&self) -> Vec<Keystroke> {
+        parse_normal_keys(&self.lhs)
+    }
+}
+
+/// A key waiting to be dispatched, and whether mappings may still be applied to it.
+#[derive(Clone, Debug)]
+pub(crate) struct TypeaheadKey {
+    keystroke: Keystroke,
+    remap: bool,
+}
+
+impl TypeaheadKey {
+    pub(crate) fn new(keys: Vec<Keystroke>, remap: bool) -> Vec<Self> {
+        keys.into_iter()
+            .map(|keystroke| TypeaheadKey { keystroke, remap })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ApplyMapping {
+    lhs: String,
+    rhs: String,
+    recursive: bool,
+}
+
+impl_internal_actions!(vim, [ApplyMapping]);
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &ApplyMapping, window, cx| {
+        let lhs = parse_normal_keys(&action.lhs);
+        let rhs = parse_normal_keys(&action.rhs);
+        vim.feed_keys(expand_mapping(&lhs, rhs, action.recursive), window, cx)
+    });
+}
+
+/// Installs a key binding for every mapping, replacing the bindings installed for the
+/// mappings before they changed.
+///
+/// This has to be called again whenever the keymap is reloaded, as that clears all bindings.
+pub fn bind_mappings(cx: &mut App) {
+    if !cx.has_global::<VimGlobals>() || !Vim::enabled(cx) {
+        return;
+    }
+    cx.remove_key_bindings(|binding| binding.action().as_any().is::<ApplyMapping>());
+    let bindings = Vim::globals(cx)
+        .mappings
+        .iter()
+        .filter_map(|mapping| {
+            let keystrokes = mapping.lhs_keystrokes();
+            if keystrokes.is_empty() {
+                return None;
+            }
+            let keystrokes = keystrokes
+                .iter()
+                .map(|keystroke| keystroke.unparse())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let context = format!(
+                "Editor && vim_mode == {} && vim_mappings == active",
+                mapping.mode.vim_mode()
+            );
+            let predicate = KeyBindingContextPredicate::parse(&context).log_err()?;
+            KeyBinding::load(
+                &keystrokes,
+                Box::new(ApplyMapping {
+                    lhs: mapping.lhs.clone(),
+                    rhs: mapping.rhs.clone(),
+                    recursive: mapping.recursive,
+                }),
+                Some(Rc::new(predicate)),
+                None,
+            )
+            .log_err()
+        })
+        .collect::<Vec<_>>();
+    cx.bind_keys(bindings);
+}
+
+/// The value of the `vim_mappings` key context entry. Mapping bindings only match it while
+/// no keys are being replayed, as those are matched against mappings by [`replay_keys`].
+pub(crate) fn mappings_key_context(cx: &App) -> String {
+    if cx.global::<VimGlobals>().replaying_keys > 0 {
+        "none".to_string()
+    } else {
+        "active".to_string()
+    }
+}
+
+/// Turns the right hand side of a mapping into typeahead. Like vim, when the right hand side
+/// of a recursive mapping starts with its left hand side, that first key is not remapped.
+pub(crate) fn expand_mapping(
+    lhs: &[Keystroke],
+    rhs: Vec<Keystroke>,
+    recursive: bool,
+) -> Vec<TypeaheadKey> {
+    let starts_with_lhs = rhs.starts_with(lhs);
+    let mut keys = TypeaheadKey::new(rhs, recursive);
+    if starts_with_lhs {
+        if let Some(first) = keys.first_mut() {
+            first.remap = false;
+        }
+    }
+    keys
+}
+
+impl Vim {
+    /// Feeds `keys` through the keymap as if they were typed, applying mappings to them.
+    pub(crate) fn feed_keys(
+        &mut self,
+        keys: Vec<TypeaheadKey>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let vim = cx.entity().downgrade();
+        // Keystrokes have to be dispatched outside of this update so that each one sees the
+        // mode (and key context) left behind by the previous one.
+        cx.spawn_in(window, |_, mut cx| async move {
+            cx.update(|window, cx| replay_keys(&vim, keys, window, cx))?
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn map_mode(&self) -> Option<MapMode> {
+        if let Some(operator) = self.active_operator() {
+            // Keys that complete a pending command (like the character after `f`) are
+            // never remapped.
+            return (!operator.is_waiting(self.mode)).then_some(MapMode::OperatorPending);
+        }
+        Some(match self.mode {
+            Mode::Normal | Mode::HelixNormal => MapMode::Normal,
+            Mode::Visual | Mode::VisualLine | Mode::VisualBlock => MapMode::Visual,
+            Mode::Insert | Mode::Replace => MapMode::Insert,
+        })
+    }
+}
+
+/// Dispatches typeahead one key at a time. Before each key is dispatched, the mappings for
+/// the mode vim is in at that point are checked against the remaining typeahead, so that
+/// mappings expand through the vim state machine the way they do in vim.
+pub(crate) fn replay_keys(
+    vim: &WeakEntity<Vim>,
+    keys: Vec<TypeaheadKey>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Result<()> {
+    Vim::globals(cx).replaying_keys += 1;
+    refresh_key_context(vim, cx);
+    window.draw(cx);
+
+    let result = dispatch_typeahead(vim, keys.into(), window, cx);
+
+    Vim::globals(cx).replaying_keys -= 1;
+    refresh_key_context(vim, cx);
+    window.draw(cx);
+    result
+}
+
+fn dispatch_typeahead(
+    vim: &WeakEntity<Vim>,
+    mut typeahead: VecDeque<TypeaheadKey>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Result<()> {
+    let mut depth = 0;
+    while let Some(key) = typeahead.front() {
+        if key.remap {
+            if let Some(mapping) = matching_mapping(vim, &typeahead, cx) {
+                depth += 1;
+                if depth > MAX_MAPPING_DEPTH {
+                    return Err(anyhow!("E223: Recursive mapping"));
+                }
+                let lhs = mapping.lhs_keystrokes();
+                typeahead.drain(..lhs.len());
+                let rhs = parse_normal_keys(&mapping.rhs);
+                for key in expand_mapping(&lhs, rhs, mapping.recursive)
+                    .into_iter()
+                    .rev()
+                {
+                    typeahead.push_front(key);
+                }
+                continue;
+            }
+        }
+
+        let Some(key) = typeahead.pop_front() else {
+            break;
+        };
+        window.dispatch_keystroke(key.keystroke, cx);
+        window.draw(cx);
+    }
+    Ok(())
+}
+
+/// The longest mapping for the current mode whose left hand side the typeahead starts with.
+fn matching_mapping(
+    vim: &WeakEntity<Vim>,
+    typeahead: &VecDeque<TypeaheadKey>,
+    cx: &mut App,
+) -> Option<Mapping> {
+    let mode = vim.upgrade()?.read(cx).map_mode()?;
+    Vim::globals(cx)
+        .mappings
+        .iter()
+        .filter(|mapping| mapping.mode == mode)
+        .filter_map(|mapping| {
+            let lhs = mapping.lhs_keystrokes();
+            let matches = !lhs.is_empty()
+                && lhs.len() <= typeahead.len()
+                && typeahead
+                    .iter()
+                    .zip(&lhs)
+                    .all(|(key, keystroke)| key.remap && &key.keystroke == keystroke);
+            matches.then(|| (lhs.len(), mapping))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, mapping)| mapping.clone())
+}
+
+fn refresh_key_context(vim: &WeakEntity<Vim>, cx: &mut App) {
+    let Some(editor) = vim.upgrade().and_then(|vim| vim.read(cx).editor()) else {
+        return;
+    };
+    editor.update(cx, |_, cx| cx.notify());
+}
+
+/// Formats mappings the way vim's `:map` lists them.
+pub(crate) fn mapping_listing<'a>(mappings: impl IntoIterator<Item = &'a Mapping>) -> String {
+    let mut listing = String::new();
+    for mapping in mappings {
+        listing.push_str(&format!(
+            "{}  {:<12} {} {}\n",
+            mapping.mode.indicator(),
+            mapping.lhs,
+            if mapping.recursive { ' ' } else { '*' },
+            mapping.rhs
+        ));
+    }
+    if listing.is_empty() {
+        listing.push_str("No mapping found\n");
+    }
+    listing
+}
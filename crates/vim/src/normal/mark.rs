@@ -4,13 +4,14 @@ use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor,
 };
 use gpui::{Context, Window};
 use language::SelectionGoal;
 
 use crate::{
     motion::{self, Motion},
+    persistence::{is_global_mark, is_persisted_mark},
     state::Mode,
     Vim,
 };
@@ -34,6 +35,12 @@ impl Vim {
             return;
         };
         self.marks.insert(text.to_string(), anchors);
+        if is_global_mark(&text) {
+            self.set_global_mark(&text, window, cx);
+        }
+        if is_persisted_mark(&text) {
+            self.persist_marks(window, cx);
+        }
         self.clear_operator(window, cx);
     }
 
@@ -85,6 +92,13 @@ impl Vim {
     ) {
         self.pop_operator(window, cx);
 
+        if is_global_mark(&text) && !self.is_global_mark_in_editor(&text, window, cx) {
+            if self.active_operator().is_none() {
+                self.jump_to_global_mark(&text, line, window, cx);
+            }
+            return;
+        }
+
         let anchors = match &*text {
             "{" | "}" => self.update_editor(window, cx, |_, editor, _, cx| {
                 let (map, selections) = editor.selections.all_display(cx);
@@ -142,6 +156,47 @@ impl Vim {
             });
         }
     }
+
+    /// Opens the file of an uppercase mark that was set in another file, and moves to it.
+    fn jump_to_global_mark(
+        &mut self,
+        name: &str,
+        line: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(mark) = Vim::globals(cx).global_marks.get(name).cloned() else {
+            return;
+        };
+        let Some(workspace) = self.workspace(window) else {
+            return;
+        };
+        let open = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(mark.path.clone(), true, window, cx)
+        });
+        cx.spawn_in(window, |_, mut cx| async move {
+            let item = open.await?;
+            let Some(editor) = item.downcast::<Editor>() else {
+                return anyhow::Ok(());
+            };
+            editor.update_in(&mut cx, |editor, window, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let mut point = mark.point(&snapshot);
+                if line {
+                    let map = editor.snapshot(window, cx);
+                    let display_point = point.to_display_point(&map.display_snapshot);
+                    point =
+                        motion::first_non_whitespace(&map.display_snapshot, false, display_point)
+                            .to_point(&map.display_snapshot);
+                }
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
 }
 
 pub fn jump_motion(
@@ -11,7 +11,7 @@ use workspace::{notifications::NotifyResultExt, searchable::Direction};
 use crate::{
     command::CommandRange,
     motion::Motion,
-    state::{Mode, SearchState},
+    state::{Mode, SearchState, VimGlobals},
     Vim,
 };
 
@@ -134,12 +134,14 @@ impl Vim {
         };
         let count = Vim::take_count(cx).unwrap_or(1);
         let prior_selections = self.editor_selections(window, cx);
+        let search_history = Vim::globals(cx).search_history.clone();
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
                 search_bar.update(cx, |search_bar, cx| {
                     if !search_bar.show(window, cx) {
                         return;
                     }
+                    search_bar.seed_search_history(search_history);
                     let query = search_bar.query(cx);
 
                     search_bar.select_query(window, cx);
@@ -164,6 +166,7 @@ impl Vim {
                         prior_selections,
                         prior_operator: self.operator_stack.last().cloned(),
                         prior_mode,
+                        helix_select: None,
                     }
                 });
             }
@@ -177,6 +180,10 @@ impl Vim {
     }
 
     pub fn search_submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(kind) = self.search.helix_select.take() {
+            self.helix_select_regex_submit(kind, window, cx);
+            return;
+        }
         self.store_visual_marks(window, cx);
         let Some(pane) = self.pane(window, cx) else {
             return;
@@ -201,8 +208,9 @@ impl Vim {
                 let prior_mode = self.search.prior_mode;
                 let prior_operator = self.search.prior_operator.take();
 
-                let query = search_bar.query(cx).into();
-                Vim::globals(cx).registers.insert('/', query);
+                let query = search_bar.query(cx);
+                VimGlobals::push_search_history(&query, cx);
+                Vim::globals(cx).registers.insert('/', query.into());
                 Some((prior_selections, prior_mode, prior_operator))
             })
         });
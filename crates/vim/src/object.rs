@@ -178,7 +178,15 @@ impl Vim {
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
                 self.visual_object(object, window, cx)
             }
-            Mode::Insert | Mode::Replace | Mode::HelixNormal => {
+            Mode::HelixNormal => {
+                if let Some(Operator::Object { around }) = self.active_operator() {
+                    self.pop_operator(window, cx);
+                    self.helix_object(object, around, window, cx)
+                } else {
+                    self.normal_object(object, window, cx)
+                }
+            }
+            Mode::Insert | Mode::Replace => {
                 // Shouldn't execute a text object in insert mode. Ignoring
             }
         }
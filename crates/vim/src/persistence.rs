@@ -0,0 +1,775 @@
+//! Saves registers, recorded macros, marks and command/search history between sessions,
+//! similar to vim's viminfo (or neovim's shada) file.
+
+use std::{ops::Range, path::PathBuf};
+
+use anyhow::Result;
+use collections::HashMap;
+use db::{define_connection, query, sqlez_macros::sql};
+use editor::{Bias, ClipboardSelection, Editor, ToPoint};
+use gpui::{App, Context, Window};
+use language::Point;
+use multi_buffer::MultiBufferSnapshot;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use util::ResultExt;
+
+use crate::{
+    command::line_text,
+    state::{Register, ReplayableAction, VimGlobals},
+    Vim, VimSettings,
+};
+
+const COMMAND_HISTORY: &str = "command";
+const SEARCH_HISTORY: &str = "search";
+
+/// Which parts of vim's state are saved between sessions.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PersistSettings {
+    /// Whether to save the contents of registers and recorded macros.
+    pub registers: bool,
+    /// Whether to save the marks set in each file, and the uppercase marks that can be
+    /// jumped to from any file.
+    pub marks: bool,
+    /// Whether to save the history of ex commands and searches.
+    pub history: bool,
+    /// The maximum number of ex commands and searches to keep in history.
+    pub max_history_entries: usize,
+}
+
+impl Default for PersistSettings {
+    fn default() -> Self {
+        Self {
+            registers: true,
+            marks: true,
+            history: true,
+            max_history_entries: 100,
+        }
+    }
+}
+
+/// Recorded macros are saved as action names, so only recordings made up of actions
+/// without parameters (and text insertions) can be persisted.
+#[derive(Serialize, Deserialize)]
+enum PersistedAction {
+    Action(String),
+    Insertion {
+        text: String,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+/// A mark saved as a position plus the text of its line, so that it can be found again
+/// when lines were added or removed above it while the file was closed.
+struct PersistedMark {
+    name: String,
+    ix: u32,
+    row: u32,
+    column: u32,
+    line: String,
+}
+
+/// An uppercase mark. Unlike the marks of a file it can be jumped to from any file, so it is
+/// kept with the path of its file rather than as an anchor in the file's editor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GlobalMark {
+    pub path: PathBuf,
+    pub row: u32,
+    pub column: u32,
+    pub line: String,
+}
+
+impl GlobalMark {
+    /// Returns where the mark is in the given snapshot of its file, following its line if the
+    /// file was changed since the mark was set.
+    pub(crate) fn point(&self, snapshot: &MultiBufferSnapshot) -> Point {
+        mark_point(snapshot, self.row, self.column, &self.line)
+    }
+}
+
+#[derive(Default)]
+struct PersistedGlobals {
+    registers: Vec<(String, String, Option<String>)>,
+    recordings: Vec<(String, String)>,
+    command_history: Vec<String>,
+    search_history: Vec<String>,
+    global_marks: Vec<(String, PathBuf, u32, u32, String)>,
+}
+
+impl VimGlobals {
+    pub(crate) fn push_command_history(command: &str, cx: &mut App) {
+        let command = command.trim().trim_start_matches(':');
+        if !command.is_empty() {
+            let max_entries = VimSettings::get_global(cx).persist.max_history_entries;
+            push_history(&mut Vim::globals(cx).command_history, command, max_entries);
+        }
+    }
+
+    /// Returns the ex commands in history that start with the given prefix, oldest first, so
+    /// that they can be recalled in the command palette.
+    pub(crate) fn matching_command_history(prefix: &str, cx: &App) -> Vec<String> {
+        let prefix = prefix.trim_start().trim_start_matches(':');
+        cx.global::<VimGlobals>()
+            .command_history
+            .iter()
+            .filter(|command| command.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn push_search_history(query: &str, cx: &mut App) {
+        if !query.is_empty() {
+            let max_entries = VimSettings::get_global(cx).persist.max_history_entries;
+            push_history(&mut Vim::globals(cx).search_history, query, max_entries);
+        }
+    }
+
+    /// Loads the state saved by a previous session, without overwriting anything that has
+    /// already been set in this one.
+    pub(crate) fn load_persisted_state(cx: &mut App) {
+        if Vim::globals(cx).loaded_persisted_state {
+            return;
+        }
+        Vim::globals(cx).loaded_persisted_state = true;
+
+        let settings = VimSettings::get_global(cx).persist;
+        let load = cx.background_executor().spawn(async move {
+            let mut state = PersistedGlobals::default();
+            if settings.registers {
+                state.registers = VIM_STATE_DB.registers()?;
+                state.recordings = VIM_STATE_DB.recordings()?;
+            }
+            if settings.history {
+                state.command_history = VIM_STATE_DB.history(COMMAND_HISTORY.to_string())?;
+                state.search_history = VIM_STATE_DB.history(SEARCH_HISTORY.to_string())?;
+            }
+            if settings.marks {
+                state.global_marks = VIM_STATE_DB.global_marks()?;
+            }
+            anyhow::Ok(state)
+        });
+
+        cx.spawn(|mut cx| async move {
+            let state = load.await?;
+            cx.update(|cx| {
+                let mut recordings = Vec::new();
+                for (name, actions) in state.recordings {
+                    let Some(name) = name.chars().next() else {
+                        continue;
+                    };
+                    let Some(actions) =
+                        serde_json::from_str::<Vec<PersistedAction>>(&actions).log_err()
+                    else {
+                        continue;
+                    };
+                    let actions = actions
+                        .into_iter()
+                        .map(|action| match action {
+                            PersistedAction::Action(name) => {
+                                cx.build_action(&name, None).map(ReplayableAction::Action)
+                            }
+                            PersistedAction::Insertion {
+                                text,
+                                utf16_range_to_replace,
+                            } => Ok(ReplayableAction::Insertion {
+                                text: text.into(),
+                                utf16_range_to_replace,
+                            }),
+                        })
+                        .collect::<Result<Vec<_>>>();
+                    if let Some(actions) = actions.log_err() {
+                        recordings.push((name, actions));
+                    }
+                }
+
+                let max_entries = VimSettings::get_global(cx).persist.max_history_entries;
+                let globals = Vim::globals(cx);
+                for (name, text, clipboard_selections) in state.registers {
+                    let Some(name) = name.chars().next() else {
+                        continue;
+                    };
+                    globals.registers.entry(name).or_insert_with(|| Register {
+                        text: text.into(),
+                        clipboard_selections: clipboard_selections.and_then(|selections| {
+                            serde_json::from_str::<Vec<ClipboardSelection>>(&selections).ok()
+                        }),
+                    });
+                }
+                for (name, actions) in recordings {
+                    globals.recordings.entry(name).or_insert(actions);
+                }
+                for (name, path, row, column, line) in state.global_marks {
+                    globals.global_marks.entry(name).or_insert(GlobalMark {
+                        path,
+                        row,
+                        column,
+                        line,
+                    });
+                }
+                for (history, mut persisted) in [
+                    (&mut globals.command_history, state.command_history),
+                    (&mut globals.search_history, state.search_history),
+                ] {
+                    persisted.drain(..persisted.len().saturating_sub(max_entries));
+                    for entry in history.drain(..) {
+                        push_history(&mut persisted, &entry, max_entries);
+                    }
+                    *history = persisted;
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Saves registers, recordings and history, replacing what was saved before.
+    pub(crate) fn save_persisted_state(cx: &mut App) -> impl std::future::Future<Output = ()> {
+        let settings = VimSettings::get_global(cx).persist;
+        let mut state = PersistedGlobals::default();
+        // If vim mode was never enabled in this session there is nothing new to save,
+        // and saving would forget the state of previous sessions.
+        let should_save = Vim::enabled(cx) && Vim::globals(cx).loaded_persisted_state;
+
+        if should_save {
+            if settings.registers {
+                let globals = Vim::globals(cx);
+                state.registers = globals
+                    .registers
+                    .iter()
+                    .filter(|(name, _)| is_persisted_register(**name))
+                    .map(|(name, register)| {
+                        (
+                            name.to_string(),
+                            register.text.to_string(),
+                            register
+                                .clipboard_selections
+                                .as_ref()
+                                .and_then(|selections| serde_json::to_string(selections).ok()),
+                        )
+                    })
+                    .collect();
+
+                let recordings = globals
+                    .recordings
+                    .iter()
+                    .map(|(name, actions)| (*name, actions.clone()))
+                    .collect::<Vec<_>>();
+                for (name, actions) in recordings {
+                    let Some(actions) = persisted_actions(&actions, cx) else {
+                        log::info!(
+                            "not saving the recording in register {name}, \
+                            since it contains actions with parameters"
+                        );
+                        continue;
+                    };
+                    if let Some(actions) = serde_json::to_string(&actions).log_err() {
+                        state.recordings.push((name.to_string(), actions));
+                    }
+                }
+            }
+            if settings.history {
+                let globals = Vim::globals(cx);
+                state.command_history = globals.command_history.clone();
+                state.search_history = globals.search_history.clone();
+            }
+            if settings.marks {
+                state.global_marks = Vim::globals(cx)
+                    .global_marks
+                    .iter()
+                    .map(|(name, mark)| {
+                        (
+                            name.clone(),
+                            mark.path.clone(),
+                            mark.row,
+                            mark.column,
+                            mark.line.clone(),
+                        )
+                    })
+                    .collect();
+            }
+        }
+
+        async move {
+            if should_save {
+                VIM_STATE_DB.save_globals(state).await.log_err();
+            }
+        }
+    }
+}
+
+impl Vim {
+    /// Saves the marks of the current file so that they can be restored the next time it
+    /// is opened, and moves the uppercase marks that are in this file along with their lines.
+    pub(crate) fn persist_marks(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.update_global_marks(window, cx);
+        if !VimSettings::get_global(cx).persist.marks {
+            return;
+        }
+        let Some((path, marks)) = self
+            .update_editor(window, cx, |vim, editor, _, cx| {
+                let path = editor_path(editor, cx)?;
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let marks = vim
+                    .marks
+                    .iter()
+                    .filter(|(name, _)| is_persisted_mark(name) && !is_global_mark(name))
+                    .flat_map(|(name, anchors)| {
+                        anchors.iter().enumerate().map(|(ix, anchor)| {
+                            let point = anchor.to_point(&snapshot);
+                            PersistedMark {
+                                name: name.clone(),
+                                ix: ix as u32,
+                                row: point.row,
+                                column: point.column,
+                                line: line_text(&snapshot, point.row),
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                Some((path, marks))
+            })
+            .flatten()
+        else {
+            return;
+        };
+
+        cx.background_executor()
+            .spawn(async move { VIM_STATE_DB.save_marks(path, marks).await.log_err() })
+            .detach();
+    }
+
+    /// Sets an uppercase mark at the newest selection of the current file.
+    pub(crate) fn set_global_mark(
+        &mut self,
+        name: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(mark) = self
+            .update_editor(window, cx, |_, editor, _, cx| {
+                let path = editor_path(editor, cx)?;
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let point = editor.selections.newest_anchor().head().to_point(&snapshot);
+                Some(GlobalMark {
+                    path,
+                    row: point.row,
+                    column: point.column,
+                    line: line_text(&snapshot, point.row),
+                })
+            })
+            .flatten()
+        else {
+            return;
+        };
+        Vim::globals(cx).global_marks.insert(name.to_string(), mark);
+    }
+
+    /// Returns whether the uppercase mark is in the current file, in which case its anchor in
+    /// this editor's marks is up to date.
+    pub(crate) fn is_global_mark_in_editor(
+        &mut self,
+        name: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(mark_path) = Vim::globals(cx)
+            .global_marks
+            .get(name)
+            .map(|mark| mark.path.clone())
+        else {
+            return true;
+        };
+        self.update_editor(window, cx, |_, editor, _, cx| editor_path(editor, cx))
+            .flatten()
+            .is_some_and(|path| path == mark_path)
+    }
+
+    /// Updates the positions of the uppercase marks in the current file from their anchors.
+    fn update_global_marks(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(updated) = self
+            .update_editor(window, cx, |vim, editor, _, cx| {
+                let path = editor_path(editor, cx)?;
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let globals = cx.global::<VimGlobals>();
+                let updated = vim
+                    .marks
+                    .iter()
+                    .filter(|(name, _)| {
+                        globals
+                            .global_marks
+                            .get(*name)
+                            .is_some_and(|mark| mark.path == path)
+                    })
+                    .filter_map(|(name, anchors)| {
+                        let point = anchors.first()?.to_point(&snapshot);
+                        let mark = GlobalMark {
+                            path: path.clone(),
+                            row: point.row,
+                            column: point.column,
+                            line: line_text(&snapshot, point.row),
+                        };
+                        Some((name.clone(), mark))
+                    })
+                    .collect::<Vec<_>>();
+                Some(updated)
+            })
+            .flatten()
+        else {
+            return;
+        };
+        Vim::globals(cx).global_marks.extend(updated);
+    }
+
+    /// Restores the marks saved for the current file, keeping any that were already set, and
+    /// the anchors of the uppercase marks that are in this file.
+    pub(crate) fn restore_marks(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.update_editor(window, cx, |vim, editor, _, cx| {
+            let Some(path) = editor_path(editor, cx) else {
+                return;
+            };
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            for (name, mark) in &cx.global::<VimGlobals>().global_marks {
+                if mark.path == path {
+                    let anchor = snapshot.anchor_before(mark.point(&snapshot));
+                    vim.marks.entry(name.clone()).or_insert(vec![anchor]);
+                }
+            }
+        });
+
+        if !VimSettings::get_global(cx).persist.marks {
+            return;
+        }
+        let Some(path) = self
+            .update_editor(window, cx, |_, editor, _, cx| editor_path(editor, cx))
+            .flatten()
+        else {
+            return;
+        };
+
+        cx.spawn_in(window, |vim, mut cx| async move {
+            let marks = cx
+                .background_executor()
+                .spawn(async move { VIM_STATE_DB.marks(path) })
+                .await?;
+            if marks.is_empty() {
+                return anyhow::Ok(());
+            }
+            vim.update_in(&mut cx, |vim, window, cx| {
+                vim.update_editor(window, cx, |vim, editor, _, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let mut restored: HashMap<String, Vec<_>> = HashMap::default();
+                    for (name, _, row, column, line) in marks {
+                        let point = mark_point(&snapshot, row, column, &line);
+                        restored
+                            .entry(name)
+                            .or_default()
+                            .push(snapshot.anchor_before(point));
+                    }
+                    for (name, anchors) in restored {
+                        vim.marks.entry(name).or_insert(anchors);
+                    }
+                });
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+fn push_history(history: &mut Vec<String>, entry: &str, max_entries: usize) {
+    history.retain(|existing| existing != entry);
+    history.push(entry.to_string());
+    if history.len() > max_entries {
+        history.drain(..history.len() - max_entries);
+    }
+}
+
+pub(crate) fn editor_path(editor: &Editor, cx: &App) -> Option<PathBuf> {
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let file = buffer.read(cx).file()?.as_local()?;
+    Some(file.abs_path(cx))
+}
+
+/// Letters are marks set by the user, everything else (`<`, `>`, `[`, `]`, ...) is maintained
+/// automatically and only meaningful within a session.
+pub(crate) fn is_persisted_mark(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic()) && chars.next().is_none()
+}
+
+/// Uppercase marks can be jumped to from any file, rather than only within the file they
+/// were set in.
+pub(crate) fn is_global_mark(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase()) && chars.next().is_none()
+}
+
+/// The unnamed, numbered and named registers; the clipboard and read-only registers
+/// are excluded.
+fn is_persisted_register(name: char) -> bool {
+    name == '"' || name == '-' || name == '/' || name.is_ascii_alphanumeric()
+}
+
+fn persisted_actions(actions: &[ReplayableAction], cx: &App) -> Option<Vec<PersistedAction>> {
+    actions
+        .iter()
+        .map(|action| match action {
+            ReplayableAction::Action(action) => {
+                let rebuilt = cx.build_action(action.name(), None).ok()?;
+                rebuilt
+                    .partial_eq(action.as_ref())
+                    .then(|| PersistedAction::Action(action.name().to_string()))
+            }
+            ReplayableAction::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Some(PersistedAction::Insertion {
+                text: text.to_string(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            }),
+        })
+        .collect()
+}
+
+/// Finds where a mark saved at the given position should be restored to. If the saved line
+/// no longer matches, the file was changed while closed, so the nearest line with the same
+/// text is used instead. Marks are only kept for singleton buffers, whose rows are those of
+/// their buffer.
+fn mark_point(snapshot: &MultiBufferSnapshot, row: u32, column: u32, line: &str) -> Point {
+    let row = snapshot
+        .as_singleton()
+        .and_then(|(_, _, buffer)| buffer.find_line_near(row, line))
+        .unwrap_or(row);
+    snapshot.clip_point(Point::new(row, column), Bias::Left)
+}
+
+define_connection!(
+    pub static ref VIM_STATE_DB: VimStateDb<()> =
+        &[sql!(
+            CREATE TABLE vim_registers (
+                name TEXT NOT NULL PRIMARY KEY,
+                contents TEXT NOT NULL,
+                clipboard_selections TEXT
+            ) STRICT;
+
+            CREATE TABLE vim_recordings (
+                name TEXT NOT NULL PRIMARY KEY,
+                actions TEXT NOT NULL
+            ) STRICT;
+
+            CREATE TABLE vim_history (
+                kind TEXT NOT NULL,
+                ix INTEGER NOT NULL,
+                entry TEXT NOT NULL,
+                PRIMARY KEY(kind, ix)
+            ) STRICT;
+
+            CREATE TABLE vim_marks (
+                path BLOB NOT NULL,
+                name TEXT NOT NULL,
+                ix INTEGER NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                line TEXT NOT NULL,
+                PRIMARY KEY(path, name, ix)
+            ) STRICT;
+
+            CREATE TABLE vim_global_marks (
+                name TEXT NOT NULL PRIMARY KEY,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                line TEXT NOT NULL
+            ) STRICT;
+        )];
+);
+
+impl VimStateDb {
+    query! {
+        fn registers() -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT name, contents, clipboard_selections
+            FROM vim_registers
+        }
+    }
+
+    query! {
+        fn recordings() -> Result<Vec<(String, String)>> {
+            SELECT name, actions
+            FROM vim_recordings
+        }
+    }
+
+    query! {
+        fn history(kind: String) -> Result<Vec<String>> {
+            SELECT entry
+            FROM vim_history
+            WHERE kind = ?
+            ORDER BY ix
+        }
+    }
+
+    query! {
+        fn global_marks() -> Result<Vec<(String, PathBuf, u32, u32, String)>> {
+            SELECT name, path, row, column, line
+            FROM vim_global_marks
+        }
+    }
+
+    query! {
+        fn marks(path: PathBuf) -> Result<Vec<(String, u32, u32, u32, String)>> {
+            SELECT name, ix, row, column, line
+            FROM vim_marks
+            WHERE path = ?
+            ORDER BY name, ix
+        }
+    }
+
+    async fn save_globals(&self, state: PersistedGlobals) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_vim_globals", || {
+                conn.exec(sql!(
+                    DELETE FROM vim_registers;
+                    DELETE FROM vim_recordings;
+                    DELETE FROM vim_history;
+                    DELETE FROM vim_global_marks;
+                ))?()?;
+                for register in state.registers {
+                    conn.exec_bound(sql!(
+                        INSERT INTO vim_registers (name, contents, clipboard_selections)
+                        VALUES (?, ?, ?)
+                    ))?(register)?;
+                }
+                for recording in state.recordings {
+                    conn.exec_bound(sql!(
+                        INSERT INTO vim_recordings (name, actions)
+                        VALUES (?, ?)
+                    ))?(recording)?;
+                }
+                for (kind, history) in [
+                    (COMMAND_HISTORY, state.command_history),
+                    (SEARCH_HISTORY, state.search_history),
+                ] {
+                    for (ix, entry) in history.into_iter().enumerate() {
+                        conn.exec_bound(sql!(
+                            INSERT INTO vim_history (kind, ix, entry)
+                            VALUES (?, ?, ?)
+                        ))?((kind, ix as u32, entry))?;
+                    }
+                }
+                for global_mark in state.global_marks {
+                    conn.exec_bound(sql!(
+                        INSERT INTO vim_global_marks (name, path, row, column, line)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))?(global_mark)?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn save_marks(&self, path: PathBuf, marks: Vec<PersistedMark>) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_vim_marks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM vim_marks WHERE path = ?
+                ))?(path.as_path())?;
+                for mark in marks {
+                    conn.exec_bound(sql!(
+                        INSERT INTO vim_marks (path, name, ix, row, column, line)
+                        VALUES (?, ?, ?, ?, ?, ?)
+                    ))?((
+                        path.as_path(),
+                        mark.name,
+                        mark.ix,
+                        mark.row,
+                        mark.column,
+                        mark.line,
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_mark_point(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state(
+            indoc! {"
+            ˇa new first line
+            fn one() {}
+            fn two() {}
+            "},
+            Mode::Normal,
+        );
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            // unchanged
+            assert_eq!(mark_point(&snapshot, 1, 3, "fn one() {}"), Point::new(1, 3));
+            // moved down by a line inserted above
+            assert_eq!(mark_point(&snapshot, 1, 3, "fn two() {}"), Point::new(2, 3));
+            // the line is gone, so the mark stays on its row
+            assert_eq!(
+                mark_point(&snapshot, 1, 3, "fn three() {}"),
+                Point::new(1, 3)
+            );
+            // clamped to the end of the file
+            assert_eq!(mark_point(&snapshot, 10, 3, ""), Point::new(3, 0));
+        });
+    }
+
+    #[test]
+    fn test_is_persisted_mark() {
+        assert!(is_persisted_mark("a"));
+        assert!(is_persisted_mark("Z"));
+        assert!(!is_persisted_mark("<"));
+        assert!(!is_persisted_mark("["));
+        assert!(!is_persisted_mark("ab"));
+    }
+
+    #[test]
+    fn test_is_global_mark() {
+        assert!(is_global_mark("A"));
+        assert!(!is_global_mark("a"));
+        assert!(!is_global_mark("<"));
+        assert!(!is_global_mark("AB"));
+    }
+
+    #[gpui::test]
+    async fn test_matching_command_history(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|_, cx| {
+            Vim::globals(cx).command_history =
+                vec!["s/a/b".to_string(), "w".to_string(), "set wrap".to_string()];
+            assert_eq!(
+                VimGlobals::matching_command_history(":s", cx),
+                ["s/a/b", "set wrap"]
+            );
+            assert_eq!(VimGlobals::matching_command_history("", cx).len(), 3);
+        });
+    }
+
+    #[test]
+    fn test_push_history() {
+        let mut history = Vec::new();
+        for ix in 0..5 {
+            push_history(&mut history, &ix.to_string(), 3);
+        }
+        assert_eq!(history, ["2", "3", "4"]);
+
+        // repeated entries move to the end instead of being duplicated
+        push_history(&mut history, "2", 3);
+        assert_eq!(history, ["3", "4", "2"]);
+    }
+}
@@ -1,6 +1,10 @@
 use crate::command::command_interceptor;
+use crate::helix::RegexSelection;
+use crate::mapping::Mapping;
 use crate::normal::repeat::Replayer;
+use crate::persistence::GlobalMark;
 use crate::surrounds::SurroundsType;
+use crate::vimrc::VimOptions;
 use crate::{motion::Motion, object::Object};
 use crate::{UseSystemClipboard, Vim, VimSettings};
 use collections::HashMap;
@@ -110,6 +114,9 @@ pub enum Operator {
     ReplayRegister,
     ToggleComments,
     ReplaceWithRegister,
+    JumpLabel {
+        first_char: Option<char>,
+    },
 }
 
 #[derive(Default, Clone, Debug)]
@@ -199,6 +206,18 @@ pub struct VimGlobals {
     pub registers: HashMap<char, Register>,
     pub recordings: HashMap<char, Vec<ReplayableAction>>,
 
+    pub command_history: Vec<String>,
+    pub search_history: Vec<String>,
+    pub(crate) global_marks: HashMap<String, GlobalMark>,
+    pub(crate) loaded_persisted_state: bool,
+
+    pub mappings: Vec<Mapping>,
+    pub mapleader: Option<String>,
+    pub maplocalleader: Option<String>,
+    pub options: VimOptions,
+    pub(crate) replaying_keys: usize,
+    pub(crate) sourced_vimrc: bool,
+
     pub focused_vim: Option<WeakEntity<Vim>>,
 }
 impl Global for VimGlobals {}
@@ -222,7 +241,10 @@ impl VimGlobals {
                 });
                 CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
                     interceptor.set(Box::new(command_interceptor));
+                    interceptor.set_on_run(Box::new(VimGlobals::push_command_history));
+                    interceptor.set_history(Box::new(VimGlobals::matching_command_history));
                 });
+                VimGlobals::load_persisted_state(cx);
             } else {
                 *Vim::globals(cx) = VimGlobals::default();
                 CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
@@ -234,6 +256,8 @@ impl VimGlobals {
             }
         })
         .detach();
+
+        cx.on_app_quit(VimGlobals::save_persisted_state).detach();
     }
 
     pub(crate) fn write_registers(
@@ -469,6 +493,7 @@ pub struct SearchState {
     pub prior_selections: Vec<Range<Anchor>>,
     pub prior_operator: Option<Operator>,
     pub prior_mode: Mode,
+    pub helix_select: Option<RegexSelection>,
 }
 
 impl Operator {
@@ -507,6 +532,7 @@ impl Operator {
             Operator::RecordRegister => "q",
             Operator::ReplayRegister => "@",
             Operator::ToggleComments => "gc",
+            Operator::JumpLabel { .. } => "gw",
         }
     }
 
@@ -526,7 +552,9 @@ impl Operator {
 
     pub fn is_waiting(&self, mode: Mode) -> bool {
         match self {
-            Operator::AddSurrounds { target } => target.is_some() || mode.is_visual(),
+            Operator::AddSurrounds { target } => {
+                target.is_some() || mode.is_visual() || mode == Mode::HelixNormal
+            }
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
@@ -540,7 +568,8 @@ impl Operator {
             | Operator::Digraph { .. }
             | Operator::Literal { .. }
             | Operator::ChangeSurrounds { target: Some(_) }
-            | Operator::DeleteSurrounds => true,
+            | Operator::DeleteSurrounds
+            | Operator::JumpLabel { .. } => true,
             Operator::Change
             | Operator::Delete
             | Operator::Yank
@@ -101,7 +101,13 @@ impl Vim {
 
                         edits.push((start..start, start_cursor_str));
                         edits.push((end..end, end_cursor_str));
-                        anchors.push(start_anchor..start_anchor);
+                        if mode == Mode::HelixNormal {
+                            // Helix keeps the surrounded text, delimiters included, selected.
+                            let end_anchor = display_map.buffer_snapshot.anchor_after(end);
+                            anchors.push(start_anchor..end_anchor);
+                        } else {
+                            anchors.push(start_anchor..start_anchor);
+                        }
                     } else {
                         let start_anchor = display_map
                             .buffer_snapshot
@@ -121,7 +127,9 @@ impl Vim {
                 });
             });
         });
-        self.switch_mode(Mode::Normal, false, window, cx);
+        if mode != Mode::HelixNormal {
+            self.switch_mode(Mode::Normal, false, window, cx);
+        }
     }
 
     pub fn delete_surrounds(
@@ -9,14 +9,17 @@ mod digraph;
 mod helix;
 mod indent;
 mod insert;
+mod mapping;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod replace;
 mod rewrap;
 mod state;
 mod surrounds;
+mod vimrc;
 mod visual;
 
 use anyhow::Result;
@@ -30,12 +33,15 @@ use gpui::{
     actions, impl_actions, Action, App, AppContext as _, Axis, Context, Entity, EventEmitter,
     KeyContext, KeystrokeEvent, Render, Subscription, Task, WeakEntity, Window,
 };
+use helix::JumpLabel;
 use insert::{NormalBefore, TemporaryNormal};
 use language::{CursorShape, Point, Selection, SelectionGoal, TransactionId};
+pub use mapping::bind_mappings;
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
 use normal::search::SearchSubmit;
 use object::Object;
+use persistence::PersistSettings;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
@@ -316,6 +322,7 @@ pub(crate) struct Vim {
     pub(crate) stored_visual_mode: Option<(Mode, Vec<bool>)>,
     pub(crate) change_list: Vec<Vec<Anchor>>,
     pub(crate) change_list_position: Option<usize>,
+    pub(crate) jump_labels: Vec<JumpLabel>,
 
     pub(crate) current_tx: Option<TransactionId>,
     pub(crate) current_anchor: Option<Selection<Anchor>>,
@@ -364,6 +371,7 @@ impl Vim {
             stored_visual_mode: None,
             change_list: Vec::new(),
             change_list_position: None,
+            jump_labels: Vec::new(),
             current_tx: None,
             current_anchor: None,
             undo_modes: HashMap::default(),
@@ -679,9 +687,12 @@ impl Vim {
             visual::register(editor, cx);
             change_list::register(editor, cx);
             digraph::register(editor, cx);
+            mapping::register(editor, cx);
 
             cx.defer_in(window, |vim, window, cx| {
                 vim.focused(false, window, cx);
+                vim.restore_marks(window, cx);
+                vim.init_from_vimrc(window, cx);
             })
         })
     }
@@ -803,6 +814,7 @@ impl Vim {
                 self.transaction_undone(transaction_id, window, cx)
             }
             EditorEvent::Edited { .. } => self.push_to_change_list(window, cx),
+            EditorEvent::Saved => self.persist_marks(window, cx),
             EditorEvent::FocusedIn => self.sync_vim_settings(window, cx),
             EditorEvent::CursorShapeChanged => self.cursor_shape_changed(window, cx),
             _ => {}
@@ -1079,6 +1091,7 @@ impl Vim {
         }
         context.set("vim_mode", mode);
         context.set("vim_operator", operator_id);
+        context.set("vim_mappings", mapping::mappings_key_context(cx));
     }
 
     fn focused(&mut self, preserve_selection: bool, window: &mut Window, cx: &mut Context<Self>) {
@@ -1314,6 +1327,7 @@ impl Vim {
         Vim::take_count(cx);
         self.selected_register.take();
         self.operator_stack.clear();
+        self.clear_jump_labels(window, cx);
         self.sync_vim_settings(window, cx);
     }
 
@@ -1525,10 +1539,14 @@ impl Vim {
                     self.add_surrounds(text, SurroundsType::Selection, window, cx);
                     self.clear_operator(window, cx);
                 }
+                Mode::HelixNormal => {
+                    self.helix_add_surrounds(text, window, cx);
+                    self.clear_operator(window, cx);
+                }
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::ChangeSurrounds { target }) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     if let Some(target) = target {
                         self.change_surrounds(text, target, window, cx);
                         self.clear_operator(window, cx);
@@ -1537,7 +1555,7 @@ impl Vim {
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::DeleteSurrounds) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     self.delete_surrounds(text, window, cx);
                     self.clear_operator(window, cx);
                 }
@@ -1572,6 +1590,9 @@ impl Vim {
                 }
             },
             Some(Operator::Jump { line }) => self.jump(text, line, window, cx),
+            Some(Operator::JumpLabel { first_char }) => {
+                self.helix_jump_label_input(text, first_char, window, cx)
+            }
             _ => {
                 if self.mode == Mode::Replace {
                     self.multi_replace(text, window, cx)
@@ -1629,6 +1650,7 @@ struct VimSettings {
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
+    pub persist: PersistSettings,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1639,6 +1661,7 @@ struct VimSettingsContent {
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
+    pub persist: Option<PersistSettings>,
 }
 
 impl Settings for VimSettings {
@@ -0,0 +1,269 @@
+use std::{mem, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use gpui::{App, Context, Window};
+use language::language_settings::SoftWrap;
+use workspace::notifications::NotifyResultExt;
+
+use crate::{
+    command::{LetCommand, MapCommand, SetCommand},
+    mapping::bind_mappings,
+    Vim,
+};
+
+/// The options `:set` understands. Options that were never set leave Zed's settings alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VimOptions {
+    pub number: Option<bool>,
+    pub relative_number: Option<bool>,
+    pub wrap: Option<bool>,
+    pub scroll_off: Option<usize>,
+}
+
+impl VimOptions {
+    /// Applies the arguments of `:set`, returning the values of the queried options.
+    pub(crate) fn set(&mut self, args: &str) -> Result<Option<String>> {
+        if args.is_empty() || args == "all" {
+            return Ok(Some(self.listing()));
+        }
+        let mut queried = Vec::new();
+        for arg in args.split_whitespace() {
+            if let Some((name, value)) = arg.split_once(['=', ':']) {
+                match canonical_name(name) {
+                    Some("scrolloff") => {
+                        let lines = value
+                            .parse()
+                            .map_err(|_| anyhow!("E521: Number required after =: {arg}"))?;
+                        self.scroll_off = Some(lines);
+                    }
+                    Some(_) => return Err(anyhow!("E474: Invalid argument: {arg}")),
+                    None => return Err(anyhow!("E518: Unknown option: {name}")),
+                }
+            } else if let Some(name) = arg.strip_suffix('?') {
+                let name =
+                    canonical_name(name).ok_or_else(|| anyhow!("E518: Unknown option: {name}"))?;
+                queried.push(self.describe(name));
+            } else if canonical_name(arg) == Some("scrolloff") {
+                queried.push(self.describe("scrolloff"));
+            } else {
+                let (name, value) =
+                    if let Some(name) = arg.strip_suffix('!').or_else(|| arg.strip_prefix("inv")) {
+                        (name, None)
+                    } else if let Some(name) = arg
+                        .strip_prefix("no")
+                        .filter(|name| canonical_name(name).is_some())
+                    {
+                        (name, Some(false))
+                    } else {
+                        (arg, Some(true))
+                    };
+                let option = self
+                    .flag(name)
+                    .ok_or_else(|| anyhow!("E518: Unknown option: {arg}"))?;
+                let current = option.unwrap_or(false);
+                *option = Some(value.unwrap_or(!current));
+            }
+        }
+        Ok((!queried.is_empty()).then(|| queried.join("\n") + "\n"))
+    }
+
+    fn flag(&mut self, name: &str) -> Option<&mut Option<bool>> {
+        match canonical_name(name)? {
+            "number" => Some(&mut self.number),
+            "relativenumber" => Some(&mut self.relative_number),
+            "wrap" => Some(&mut self.wrap),
+            _ => None,
+        }
+    }
+
+    fn describe(&self, name: &str) -> String {
+        let flag = |name: &str, value: Option<bool>| {
+            if value.unwrap_or(false) {
+                format!("  {name}")
+            } else {
+                format!("no{name}")
+            }
+        };
+        match name {
+            "number" => flag(name, self.number),
+            "relativenumber" => flag(name, self.relative_number),
+            "wrap" => flag(name, self.wrap),
+            _ => format!("  scrolloff={}", self.scroll_off.unwrap_or(0)),
+        }
+    }
+
+    fn listing(&self) -> String {
+        let mut listing = String::from("--- Options ---\n");
+        for (name, is_set) in [
+            ("number", self.number.is_some()),
+            ("relativenumber", self.relative_number.is_some()),
+            ("wrap", self.wrap.is_some()),
+            ("scrolloff", self.scroll_off.is_some()),
+        ] {
+            if is_set {
+                listing.push_str(&self.describe(name));
+                listing.push('\n');
+            }
+        }
+        listing
+    }
+
+    pub(crate) fn apply(&self, editor: &mut Editor, cx: &mut Context<Editor>) {
+        if let Some(number) = self.number {
+            editor.set_show_line_numbers(number, cx);
+        }
+        if let Some(relative) = self.relative_number {
+            editor.set_relative_line_number(Some(relative), cx);
+        }
+        if let Some(wrap) = self.wrap {
+            let mode = if wrap {
+                SoftWrap::EditorWidth
+            } else {
+                SoftWrap::None
+            };
+            editor.set_soft_wrap_mode(mode, cx);
+        }
+        if let Some(lines) = self.scroll_off {
+            editor.set_vertical_scroll_margin(lines, cx);
+        }
+    }
+}
+
+fn canonical_name(name: &str) -> Option<&'static str> {
+    match name {
+        "number" | "nu" => Some("number"),
+        "relativenumber" | "rnu" => Some("relativenumber"),
+        "wrap" => Some("wrap"),
+        "scrolloff" | "so" => Some("scrolloff"),
+        _ => None,
+    }
+}
+
+impl Vim {
+    /// Sources the user's vimrc the first time vim is activated, and applies the options
+    /// set so far to this editor.
+    pub(crate) fn init_from_vimrc(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let options = Vim::globals(cx).options;
+        self.update_editor(window, cx, |_, editor, _, cx| options.apply(editor, cx));
+        if mem::replace(&mut Vim::globals(cx).sourced_vimrc, true) {
+            return;
+        }
+        self.source_file(paths::vimrc_file().clone(), true, window, cx);
+    }
+
+    /// Runs the `set`, `let` and `map` commands in the file at `path`. Errors are reported
+    /// together once the whole file has been read.
+    pub(crate) fn source_file(
+        &mut self,
+        path: PathBuf,
+        ignore_missing: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace(window) else {
+            return;
+        };
+        let fs = workspace.read(cx).app_state().fs.clone();
+        cx.spawn_in(window, |vim, mut cx| async move {
+            if ignore_missing && !fs.is_file(&path).await {
+                return anyhow::Ok(());
+            }
+            let text = fs
+                .load(&path)
+                .await
+                .map_err(|error| anyhow!("E484: Can't open file {}: {error}", path.display()));
+            vim.update_in(&mut cx, |vim, window, cx| {
+                let result = text
+                    .and_then(|text| vim.source_lines(&text, window, cx))
+                    .map_err(|error| anyhow!("Error sourcing {}: {error}", path.display()));
+                let Some(workspace) = vim.workspace(window) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    result.notify_err(workspace, cx);
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn source_lines(
+        &mut self,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let mut errors = Vec::new();
+        for (ix, line) in text.lines().enumerate() {
+            let line = line.trim().trim_start_matches(':').trim_start();
+            if line.is_empty() || line.starts_with('"') {
+                continue;
+            }
+            if let Err(error) = source_line(line, cx) {
+                errors.push(format!("line {}: {error}", ix + 1));
+            }
+        }
+        bind_mappings(cx);
+        self.apply_options(window, cx);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(errors.join("\n")))
+        }
+    }
+
+    /// Applies the `:set` options to every editor in the workspace.
+    pub(crate) fn apply_options(&self, window: &mut Window, cx: &mut Context<Self>) {
+        let options = Vim::globals(cx).options;
+        let Some(workspace) = self.workspace(window) else {
+            return;
+        };
+        let editors = workspace
+            .read(cx)
+            .items_of_type::<Editor>(cx)
+            .collect::<Vec<_>>();
+        for editor in editors {
+            editor.update(cx, |editor, cx| options.apply(editor, cx));
+        }
+    }
+}
+
+fn source_line(line: &str, cx: &mut App) -> Result<()> {
+    if let Some(command) = MapCommand::parse(line) {
+        command.run(cx)?;
+    } else if let Some(command) = SetCommand::parse(line) {
+        command.run(cx)?;
+    } else if let Some(command) = LetCommand::parse(line) {
+        command.run(cx)?;
+    } else {
+        return Err(anyhow!("Not supported in a vimrc: {line}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::VimOptions;
+
+    #[test]
+    fn test_set_options() {
+        let mut options = VimOptions::default();
+        assert_eq!(options.set("nu rnu so=5").unwrap(), None);
+        assert_eq!(options.number, Some(true));
+        assert_eq!(options.relative_number, Some(true));
+        assert_eq!(options.scroll_off, Some(5));
+
+        options.set("nonumber invrnu wrap!").unwrap();
+        assert_eq!(options.number, Some(false));
+        assert_eq!(options.relative_number, Some(false));
+        assert_eq!(options.wrap, Some(true));
+
+        assert_eq!(
+            options.set("wrap? so").unwrap().as_deref(),
+            Some("  wrap\n  scrolloff=5\n")
+        );
+        assert!(options.set("spell").is_err());
+        assert!(options.set("wrap=1").is_err());
+    }
+}
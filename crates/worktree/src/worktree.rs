@@ -7,7 +7,7 @@ use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
 use anyhow::{anyhow, Context as _, Result};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet, VecDeque};
-use fs::{copy_recursive, Fs, MTime, PathEvent, RemoveOptions, Watcher};
+use fs::{copy_recursive, Encoding, Fs, MTime, PathEvent, RemoveOptions, Watcher};
 use futures::{
     channel::{
         mpsc::{self, UnboundedSender},
@@ -107,6 +107,15 @@ pub enum CreatedEntry {
 pub struct LoadedFile {
     pub file: Arc<File>,
     pub text: String,
+    pub encoding: Encoding,
+}
+
+/// A file loaded in large-file mode, read a chunk at a time rather than as one string.
+pub struct LoadedLargeFile {
+    pub file: Arc<File>,
+    pub text: Rope,
+    pub line_ending: LineEnding,
+    pub encoding: Encoding,
 }
 
 pub struct LoadedBinaryFile {
@@ -925,6 +934,29 @@ impl Worktree {
         }
     }
 
+    pub fn load_large_file(
+        &self,
+        path: &Path,
+        cx: &Context<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        match self {
+            Worktree::Local(this) => this.load_large_file(path, cx),
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load files")))
+            }
+        }
+    }
+
+    /// Whether the file at the given path should be opened in large-file mode.
+    pub fn is_large_file(&self, path: &Path) -> bool {
+        match self {
+            Worktree::Local(this) => this
+                .entry_for_path(path)
+                .is_some_and(|entry| this.settings.is_large_file(entry.size)),
+            Worktree::Remote(_) => false,
+        }
+    }
+
     pub fn load_staged_file(&self, path: &Path, cx: &App) -> Task<Result<Option<String>>> {
         match self {
             Worktree::Local(this) => {
@@ -991,10 +1023,11 @@ impl Worktree {
         path: &Path,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &Context<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         match self {
-            Worktree::Local(this) => this.write_file(path, text, line_ending, cx),
+            Worktree::Local(this) => this.write_file(path, text, line_ending, encoding, cx),
             Worktree::Remote(_) => {
                 Task::ready(Err(anyhow!("remote worktree can't yet write files")))
             }
@@ -1601,61 +1634,72 @@ impl LocalWorktree {
         path: &Path,
         cx: &Context<Worktree>,
     ) -> Task<Result<LoadedBinaryFile>> {
-        let path = Arc::from(path);
-        let abs_path = self.absolutize(&path);
-        let fs = self.fs.clone();
-        let entry = self.refresh_entry(path.clone(), None, cx);
-        let is_private = self.is_path_private(path.as_ref());
-
-        let worktree = cx.weak_entity();
+        let load = self.load_file_with(path, cx, |fs, abs_path| async move {
+            fs.load_bytes(&abs_path).await
+        });
         cx.background_executor().spawn(async move {
-            let abs_path = abs_path?;
-            let content = fs.load_bytes(&abs_path).await?;
-
-            let worktree = worktree
-                .upgrade()
-                .ok_or_else(|| anyhow!("worktree was dropped"))?;
-            let file = match entry.await? {
-                Some(entry) => File::for_entry(entry, worktree),
-                None => {
-                    let metadata = fs
-                        .metadata(&abs_path)
-                        .await
-                        .with_context(|| {
-                            format!("Loading metadata for excluded file {abs_path:?}")
-                        })?
-                        .with_context(|| {
-                            format!("Excluded file {abs_path:?} got removed during loading")
-                        })?;
-                    Arc::new(File {
-                        entry_id: None,
-                        worktree,
-                        path,
-                        disk_state: DiskState::Present {
-                            mtime: metadata.mtime,
-                        },
-                        is_local: true,
-                        is_private,
-                    })
-                }
-            };
-
+            let (content, file) = load.await?;
             Ok(LoadedBinaryFile { file, content })
         })
     }
 
     fn load_file(&self, path: &Path, cx: &Context<Worktree>) -> Task<Result<LoadedFile>> {
+        let load = self.load_file_with(path, cx, |fs, abs_path| async move {
+            fs.load_with_encoding(&abs_path, None).await
+        });
+        cx.spawn(|_, _| async move {
+            let ((text, encoding), file) = load.await?;
+            Ok(LoadedFile {
+                file,
+                text,
+                encoding,
+            })
+        })
+    }
+
+    fn load_large_file(
+        &self,
+        path: &Path,
+        cx: &Context<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        let load = self.load_file_with(path, cx, |fs, abs_path| async move {
+            fs.load_rope(&abs_path).await
+        });
+        cx.background_executor().spawn(async move {
+            let ((text, line_ending, encoding), file) = load.await?;
+            Ok(LoadedLargeFile {
+                file,
+                text,
+                line_ending,
+                encoding,
+            })
+        })
+    }
+
+    /// Loads the file at the given path with `load`, along with its `File`, refreshing its
+    /// entry first. Files excluded from the worktree have no entry.
+    fn load_file_with<T, F>(
+        &self,
+        path: &Path,
+        cx: &Context<Worktree>,
+        load: impl 'static + Send + FnOnce(Arc<dyn Fs>, PathBuf) -> F,
+    ) -> impl 'static + Send + Future<Output = Result<(T, Arc<File>)>>
+    where
+        T: 'static + Send,
+        F: 'static + Send + Future<Output = Result<T>>,
+    {
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
         let is_private = self.is_path_private(path.as_ref());
 
-        cx.spawn(|this, _cx| async move {
+        let worktree = cx.weak_entity();
+        async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let loaded = load(fs.clone(), abs_path.clone()).await?;
 
-            let worktree = this
+            let worktree = worktree
                 .upgrade()
                 .ok_or_else(|| anyhow!("worktree was dropped"))?;
             let file = match entry.await? {
@@ -1682,9 +1726,8 @@ impl LocalWorktree {
                     })
                 }
             };
-
-            Ok(LoadedFile { file, text })
-        })
+            Ok((loaded, file))
+        }
     }
 
     /// Find the lowest path in the worktree's datastructures that is an ancestor
@@ -1769,6 +1812,7 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &Context<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         let path = path.into();
@@ -1781,7 +1825,10 @@ impl LocalWorktree {
         let write = cx.background_executor().spawn({
             let fs = fs.clone();
             let abs_path = abs_path.clone();
-            async move { fs.save(&abs_path, &text, line_ending).await }
+            async move {
+                fs.save_with_encoding(&abs_path, &text, line_ending, encoding)
+                    .await
+            }
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -3664,6 +3711,26 @@ impl language::LocalFile for File {
             .spawn(async move { fs.load(&abs_path?).await })
     }
 
+    fn load_with_encoding(&self, encoding: Encoding, cx: &App) -> Task<Result<String>> {
+        let worktree = self.worktree.read(cx).as_local().unwrap();
+        let abs_path = worktree.absolutize(&self.path);
+        let fs = worktree.fs.clone();
+        cx.background_executor().spawn(async move {
+            let (text, _) = fs.load_with_encoding(&abs_path?, Some(encoding)).await?;
+            Ok(text)
+        })
+    }
+
+    fn load_rope(&self, cx: &App) -> Task<Result<(Rope, LineEnding)>> {
+        let worktree = self.worktree.read(cx).as_local().unwrap();
+        let abs_path = worktree.absolutize(&self.path);
+        let fs = worktree.fs.clone();
+        cx.background_executor().spawn(async move {
+            let (text, line_ending, _) = fs.load_rope(&abs_path?).await?;
+            Ok((text, line_ending))
+        })
+    }
+
     fn load_bytes(&self, cx: &App) -> Task<Result<Vec<u8>>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
@@ -12,6 +12,8 @@ pub struct WorktreeSettings {
     pub file_scan_inclusions: PathMatcher,
     pub file_scan_exclusions: PathMatcher,
     pub private_files: PathMatcher,
+    /// Files of at least this many bytes are opened in large-file mode.
+    pub large_file_threshold: Option<u64>,
 }
 
 impl WorktreeSettings {
@@ -29,6 +31,11 @@ impl WorktreeSettings {
         path.ancestors()
             .any(|ancestor| self.file_scan_inclusions.is_match(&ancestor))
     }
+
+    pub fn is_large_file(&self, size: u64) -> bool {
+        self.large_file_threshold
+            .is_some_and(|threshold| size >= threshold)
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -62,6 +69,13 @@ pub struct WorktreeSettingsContent {
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
+
+    /// Open files of at least this many megabytes in large-file mode: read-only, without
+    /// syntax highlighting, language servers, git diffs or inlay hints. Set to 0 to never
+    /// use large-file mode.
+    ///
+    /// Default: 64
+    pub large_file_threshold_mb: Option<u64>,
 }
 
 impl Settings for WorktreeSettings {
@@ -94,6 +108,10 @@ impl Settings for WorktreeSettings {
                 &parsed_file_scan_inclusions,
                 "file_scan_inclusions",
             )?,
+            large_file_threshold: result
+                .large_file_threshold_mb
+                .filter(|megabytes| *megabytes > 0)
+                .map(|megabytes| megabytes * 1024 * 1024),
         })
     }
 }
@@ -875,6 +875,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("tracked-dir/file.txt"),
             "hello".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -885,6 +886,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("ignored-dir/file.txt"),
             "world".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -1973,8 +1975,13 @@ fn randomly_mutate_worktree(
                 })
             } else {
                 log::info!("overwriting file {:?} ({})", entry.path, entry.id.0);
-                let task =
-                    worktree.write_file(entry.path.clone(), "".into(), Default::default(), cx);
+                let task = worktree.write_file(
+                    entry.path.clone(),
+                    "".into(),
+                    Default::default(),
+                    Default::default(),
+                    cx,
+                );
                 cx.background_executor().spawn(async move {
                     task.await?;
                     Ok(())
@@ -486,6 +486,8 @@ fn main() {
         file_finder::init(cx);
         tab_switcher::init(cx);
         outline::init(cx);
+        bookmarks::init(cx);
+        local_history::init(cx);
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
         git_ui::git_panel::init(cx);
@@ -499,6 +501,7 @@ fn main() {
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
+        encoding_selector::init(cx);
         toolchain_selector::init(cx);
         theme_selector::init(cx);
         language_tools::init(cx);